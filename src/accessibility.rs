@@ -0,0 +1,130 @@
+use eframe::egui::{self, ecolor, Color32};
+
+use crate::diagnostics;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorblindMode {
+    None,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    Monochrome,
+}
+
+impl ColorblindMode {
+    pub const ALL: [ColorblindMode; 5] = [
+        ColorblindMode::None,
+        ColorblindMode::Deuteranopia,
+        ColorblindMode::Protanopia,
+        ColorblindMode::Tritanopia,
+        ColorblindMode::Monochrome,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorblindMode::None => "None",
+            ColorblindMode::Deuteranopia => "Deuteranopia",
+            ColorblindMode::Protanopia => "Protanopia",
+            ColorblindMode::Tritanopia => "Tritanopia",
+            ColorblindMode::Monochrome => "Monochrome",
+        }
+    }
+}
+
+// Matrices de Machado, Oliveira y Fernandes (2009) con severidad 1.0, en RGB lineal.
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+/// Cómo vería `color` una persona con la deficiencia de `mode`; `Monochrome` usa la luminancia relativa.
+pub fn simulate_cvd(color: Color32, mode: ColorblindMode) -> Color32 {
+    let matrix = match mode {
+        ColorblindMode::None => return color,
+        ColorblindMode::Deuteranopia => DEUTERANOPIA,
+        ColorblindMode::Protanopia => PROTANOPIA,
+        ColorblindMode::Tritanopia => TRITANOPIA,
+        ColorblindMode::Monochrome => {
+            let [r, g, b] = [color.r(), color.g(), color.b()].map(ecolor::linear_f32_from_gamma_u8);
+            let luminance = ecolor::gamma_u8_from_linear_f32(0.2126 * r + 0.7152 * g + 0.0722 * b);
+            return Color32::from_rgba_premultiplied(luminance, luminance, luminance, color.a());
+        }
+    };
+    let linear = [color.r(), color.g(), color.b()].map(ecolor::linear_f32_from_gamma_u8);
+    let channel = |row: [f32; 3]| {
+        ecolor::gamma_u8_from_linear_f32((row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2]).clamp(0.0, 1.0))
+    };
+    Color32::from_rgba_premultiplied(channel(matrix[0]), channel(matrix[1]), channel(matrix[2]), color.a())
+}
+
+/// Colores de estado (errores, diffs, cambios). Con un modo activo se usa la paleta de Okabe-Ito (azul/naranja),
+/// que no depende del contraste rojo/verde; en monocromo solo quedan las formas (subrayado, tachado, `+`/`-`).
+#[derive(Clone, Copy)]
+pub struct StatusPalette {
+    pub error: Color32,
+    pub warning: Color32,
+    pub added: Color32,
+    pub removed: Color32,
+}
+
+impl StatusPalette {
+    pub fn for_mode(mode: ColorblindMode) -> Self {
+        match mode {
+            ColorblindMode::None => Self {
+                error: diagnostics::ERROR_COLOR,
+                warning: Color32::from_rgb(230, 160, 30),
+                added: Color32::from_rgb(40, 160, 70),
+                removed: Color32::from_rgb(210, 60, 60),
+            },
+            ColorblindMode::Monochrome => Self {
+                error: Color32::from_gray(40),
+                warning: Color32::from_gray(110),
+                added: Color32::from_gray(80),
+                removed: Color32::from_gray(150),
+            },
+            _ => Self {
+                error: Color32::from_rgb(213, 94, 0),
+                warning: Color32::from_rgb(230, 159, 0),
+                added: Color32::from_rgb(0, 114, 178),
+                removed: Color32::from_rgb(213, 94, 0),
+            },
+        }
+    }
+}
+
+/// Pasa los colores de los widgets de egui por la simulación para que toda la interfaz sea coherente.
+pub fn apply_to_visuals(visuals: &mut egui::Visuals, mode: ColorblindMode) {
+    let convert = |color: &mut Color32| *color = simulate_cvd(*color, mode);
+    convert(&mut visuals.hyperlink_color);
+    convert(&mut visuals.faint_bg_color);
+    convert(&mut visuals.extreme_bg_color);
+    convert(&mut visuals.code_bg_color);
+    convert(&mut visuals.warn_fg_color);
+    convert(&mut visuals.error_fg_color);
+    convert(&mut visuals.window_fill);
+    convert(&mut visuals.panel_fill);
+    convert(&mut visuals.selection.bg_fill);
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        convert(&mut widget.bg_fill);
+        convert(&mut widget.weak_bg_fill);
+        convert(&mut widget.fg_stroke.color);
+        convert(&mut widget.bg_stroke.color);
+    }
+}
@@ -0,0 +1,114 @@
+use rodio::buffer::SamplesBuffer;
+use rodio::{OutputStream, Sink, Source};
+
+const SAMPLE_RATE: u32 = 44_100;
+// Duración del fragmento que se repite, en segundos.
+const CLIP_SECONDS: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AmbientSound {
+    Rain,
+    CafeNoise,
+    WhiteNoise,
+    KeyboardClicks,
+}
+
+impl AmbientSound {
+    pub const ALL: [AmbientSound; 4] =
+        [AmbientSound::Rain, AmbientSound::CafeNoise, AmbientSound::WhiteNoise, AmbientSound::KeyboardClicks];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AmbientSound::Rain => "Rain",
+            AmbientSound::CafeNoise => "Café",
+            AmbientSound::WhiteNoise => "White Noise",
+            AmbientSound::KeyboardClicks => "Keyboard Clicks",
+        }
+    }
+
+    /// Muestras mono del fragmento, entre -1 y 1. Se generan en vez de leerlas de un archivo para no llevar audio
+    /// dentro del ejecutable; la semilla fija hace que suene igual cada vez.
+    fn clip(self) -> Vec<f32> {
+        let len = SAMPLE_RATE as usize * CLIP_SECONDS;
+        let mut noise = Noise(0x9e37_79b9);
+        let mut samples = Vec::with_capacity(len);
+        match self {
+            AmbientSound::WhiteNoise => samples.extend((0..len).map(|_| noise.next() * 0.3)),
+            AmbientSound::Rain => {
+                // Ruido suavizado de fondo con gotas sueltas que se apagan enseguida.
+                let (mut low, mut drop) = (0.0, 0.0);
+                for _ in 0..len {
+                    low += (noise.next() - low) * 0.25;
+                    if noise.next() > 0.9993 {
+                        drop = 0.6;
+                    }
+                    drop *= 0.995;
+                    samples.push(low * 0.35 + noise.next() * drop * 0.3);
+                }
+            }
+            AmbientSound::CafeNoise => {
+                // Ruido marrón con un vaivén lento, como un murmullo de fondo.
+                let mut brown = 0.0;
+                for index in 0..len {
+                    brown = (brown + noise.next() * 0.02) * 0.998;
+                    let swell = 0.75 + 0.25 * (index as f32 / SAMPLE_RATE as f32 * std::f32::consts::PI).sin();
+                    samples.push(brown * swell * 2.0);
+                }
+            }
+            AmbientSound::KeyboardClicks => {
+                // Chasquidos de unos milisegundos separados entre 80 y 250 ms.
+                let mut next_click = 0;
+                let mut click = 0.0;
+                for index in 0..len {
+                    if index == next_click {
+                        click = 0.8;
+                        next_click += (SAMPLE_RATE as f32 * (0.08 + 0.17 * (noise.next() + 1.0) / 2.0)) as usize;
+                    }
+                    click *= 0.985;
+                    samples.push(noise.next() * click * 0.5);
+                }
+            }
+        }
+        samples
+    }
+}
+
+// Generador xorshift; basta para ruido y evita otra dependencia.
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+/// Salida de audio abierta al elegir el primer sonido. `_stream` tiene que vivir mientras suene el `Sink`.
+pub struct AmbientPlayer {
+    _stream: OutputStream,
+    sink: Sink,
+}
+
+impl AmbientPlayer {
+    pub fn new() -> Result<Self, String> {
+        let (stream, handle) = OutputStream::try_default().map_err(|error| error.to_string())?;
+        let sink = Sink::try_new(&handle).map_err(|error| error.to_string())?;
+        Ok(Self { _stream: stream, sink })
+    }
+
+    /// Cambia a `sound` en bucle, o calla con `None`.
+    pub fn play(&self, sound: Option<AmbientSound>, volume: f32) {
+        self.sink.stop();
+        if let Some(sound) = sound {
+            self.sink.append(SamplesBuffer::new(1, SAMPLE_RATE, sound.clip()).repeat_infinite());
+            self.sink.set_volume(volume);
+            self.sink.play();
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.sink.set_volume(volume);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::json;
+use crate::text;
+
+pub const ANNOTATIONS_DIR: &str = ".rbeditor_annotations";
+
+/// Índice de línea (desde 0) → texto de la anotación.
+pub type LineAnnotations = HashMap<usize, String>;
+
+fn annotations_path(dir: &Path, file: &Path) -> Option<PathBuf> {
+    let name = file.file_name()?.to_string_lossy();
+    Some(dir.join(ANNOTATIONS_DIR).join(format!("{}.json", name)))
+}
+
+pub fn load(dir: &Path, file: &Path) -> LineAnnotations {
+    let Some(content) = annotations_path(dir, file).and_then(|path| fs::read_to_string(path).ok()) else {
+        return LineAnnotations::new();
+    };
+    let Ok(json::Value::Object(entries)) = json::parse(&content) else {
+        return LineAnnotations::new();
+    };
+    entries
+        .into_iter()
+        .filter_map(|(line, value)| match value {
+            json::Value::String(note) => Some((line.parse::<usize>().ok()?.checked_sub(1)?, note)),
+            _ => None,
+        })
+        .collect()
+}
+
+// En el archivo las líneas se numeran desde 1, como en la barra de estado. Sin anotaciones se borra el archivo.
+pub fn save(dir: &Path, file: &Path, annotations: &LineAnnotations) -> io::Result<()> {
+    let Some(path) = annotations_path(dir, file) else {
+        return Ok(());
+    };
+    if annotations.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let mut lines: Vec<_> = annotations.iter().collect();
+    lines.sort();
+    let value = json::Value::Object(
+        lines
+            .into_iter()
+            .map(|(line, note)| ((line + 1).to_string(), json::Value::String(note.clone())))
+            .collect(),
+    );
+    fs::create_dir_all(dir.join(ANNOTATIONS_DIR))?;
+    fs::write(path, value.to_pretty_string("  ") + "\n")
+}
+
+/// Mueve las anotaciones de las líneas posteriores a una edición que añadió o quitó líneas.
+/// Las anotaciones de líneas borradas por completo se descartan. Devuelve `true` si algo cambió.
+pub fn shift_for_edit(annotations: &mut LineAnnotations, before: &str, after: &str) -> bool {
+    let (start, removed_end, inserted_end) = text::changed_range(before, after);
+    let removed_lines = before[start..removed_end].matches('\n').count();
+    let inserted_lines = after[start..inserted_end].matches('\n').count();
+    if removed_lines == 0 && inserted_lines == 0 {
+        return false;
+    }
+    let first_line = before[..start].matches('\n').count();
+    let old = std::mem::take(annotations);
+    for (line, note) in old {
+        if line <= first_line {
+            annotations.insert(line, note);
+        } else if line > first_line + removed_lines {
+            annotations.insert(line - removed_lines + inserted_lines, note);
+        }
+    }
+    true
+}
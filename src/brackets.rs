@@ -0,0 +1,105 @@
+use eframe::egui;
+use std::ops::Range;
+use tree_sitter::Tree;
+
+// Un color por nivel, que se repite a partir del séptimo.
+const RAINBOW: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(230, 160, 30),
+    egui::Color32::from_rgb(200, 90, 200),
+    egui::Color32::from_rgb(40, 150, 230),
+    egui::Color32::from_rgb(60, 170, 90),
+    egui::Color32::from_rgb(220, 80, 80),
+    egui::Color32::from_rgb(40, 170, 170),
+];
+/// A partir de esta profundidad se pinta el número junto al paréntesis, y a partir de `BLINK_DEPTH` parpadea.
+pub const DEPTH_LABEL_MIN: usize = 4;
+pub const BLINK_DEPTH: usize = 8;
+
+/// Un paréntesis, corchete o llave y su profundidad: 1 para los de más fuera; el que cierra tiene la del que abre.
+pub struct Bracket {
+    pub byte: usize,
+    pub depth: usize,
+}
+
+pub fn color(depth: usize) -> egui::Color32 {
+    RAINBOW[depth.saturating_sub(1) % RAINBOW.len()]
+}
+
+/// Con árbol solo cuentan los tokens de puntuación, así que no entran los de cadenas ni comentarios; sin árbol se
+/// recorre el texto saltando las cadenas entre comillas dobles.
+pub fn find_brackets(tree: Option<&Tree>, content: &str) -> Vec<Bracket> {
+    let mut brackets = Vec::new();
+    let mut depth = 0;
+    let mut visit = |byte: usize, c: u8| match c {
+        b'(' | b'[' | b'{' => {
+            depth += 1;
+            brackets.push(Bracket { byte, depth });
+        }
+        b')' | b']' | b'}' => {
+            brackets.push(Bracket { byte, depth: depth.max(1) });
+            depth = depth.saturating_sub(1);
+        }
+        _ => {}
+    };
+    match tree {
+        Some(tree) => {
+            let mut cursor = tree.walk();
+            loop {
+                let node = cursor.node();
+                if node.child_count() == 0 && !node.is_named() && node.end_byte() == node.start_byte() + 1 {
+                    if let Some(&c) = content.as_bytes().get(node.start_byte()) {
+                        visit(node.start_byte(), c);
+                    }
+                }
+                if cursor.goto_first_child() || cursor.goto_next_sibling() {
+                    continue;
+                }
+                while cursor.goto_parent() {
+                    if cursor.goto_next_sibling() {
+                        break;
+                    }
+                }
+                if cursor.node() == tree.root_node() {
+                    break;
+                }
+            }
+        }
+        None => {
+            let mut in_string = false;
+            let mut escaped = false;
+            for (byte, c) in content.bytes().enumerate() {
+                match c {
+                    _ if escaped => escaped = false,
+                    b'\\' if in_string => escaped = true,
+                    b'"' => in_string = !in_string,
+                    _ if !in_string => visit(byte, c),
+                    _ => {}
+                }
+            }
+        }
+    }
+    brackets
+}
+
+/// Parte los trozos de `spans` para que cada paréntesis vaya en el color de su nivel.
+pub fn colorize(spans: Vec<(egui::Color32, Range<usize>)>, brackets: &[Bracket]) -> Vec<(egui::Color32, Range<usize>)> {
+    let mut colored = Vec::with_capacity(spans.len() + brackets.len() * 2);
+    let mut brackets = brackets.iter().peekable();
+    for (span_color, span) in spans {
+        let mut position = span.start;
+        while let Some(bracket) = brackets.next_if(|bracket| bracket.byte < span.end) {
+            if bracket.byte < position {
+                continue;
+            }
+            if bracket.byte > position {
+                colored.push((span_color, position..bracket.byte));
+            }
+            colored.push((color(bracket.depth), bracket.byte..bracket.byte + 1));
+            position = bracket.byte + 1;
+        }
+        if position < span.end {
+            colored.push((span_color, position..span.end));
+        }
+    }
+    colored
+}
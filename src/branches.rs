@@ -0,0 +1,108 @@
+use eframe::egui;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::git;
+
+pub enum BranchAction {
+    Checkout(String),
+    Create(String),
+}
+
+/// Ventana con las ramas locales; el checkout se hace en segundo plano para no congelar el editor.
+pub struct BranchSwitcher {
+    pub branches: Vec<String>,
+    pub current: String,
+    // Nombre que se escribe tras "New Branch...".
+    new_branch: Option<String>,
+    checkout: Option<Receiver<Result<Vec<PathBuf>, String>>>,
+    pub error: Option<String>,
+}
+
+impl BranchSwitcher {
+    pub fn new(branches: Vec<String>, current: String) -> Self {
+        Self { branches, current, new_branch: None, checkout: None, error: None }
+    }
+
+    pub fn start_checkout(&mut self, dir: PathBuf, action: &BranchAction) {
+        let (branch, create) = match action {
+            BranchAction::Checkout(branch) => (branch.clone(), false),
+            BranchAction::Create(branch) => (branch.clone(), true),
+        };
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(git::checkout(&dir, &branch, create).map_err(|e| e.to_string()));
+        });
+        self.error = None;
+        self.checkout = Some(receiver);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.checkout.is_some()
+    }
+
+    /// Archivos que cambiaron, cuando termina un checkout que ha ido bien; si falla, el error queda en la ventana.
+    pub fn poll(&mut self) -> Option<Vec<PathBuf>> {
+        let result = match self.checkout.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("git checkout stopped unexpectedly".to_string()),
+        };
+        self.checkout = None;
+        match result {
+            Ok(changed) => Some(changed),
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    /// Devuelve `false` cuando se cierra la ventana.
+    pub fn show(&mut self, ctx: &egui::Context, action: &mut Option<BranchAction>) -> bool {
+        let mut open = true;
+        egui::Window::new("Branches").collapsible(false).open(&mut open).show(ctx, |ui| {
+            if self.is_running() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Checking out…");
+                });
+                return;
+            }
+            match &mut self.new_branch {
+                Some(name) => {
+                    let mut create = false;
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(name);
+                        response.request_focus();
+                        create = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        create |= ui.add_enabled(!name.trim().is_empty(), egui::Button::new("Create")).clicked();
+                    });
+                    if create && !name.trim().is_empty() {
+                        *action = Some(BranchAction::Create(name.trim().to_string()));
+                        self.new_branch = None;
+                    }
+                }
+                None => {
+                    if ui.selectable_label(false, "New Branch...").clicked() {
+                        self.new_branch = Some(String::new());
+                    }
+                }
+            }
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for branch in &self.branches {
+                    let current = *branch == self.current;
+                    if ui.selectable_label(current, egui::RichText::new(branch).monospace()).clicked() && !current {
+                        *action = Some(BranchAction::Checkout(branch.clone()));
+                    }
+                }
+            });
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+        open
+    }
+}
@@ -0,0 +1,106 @@
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+use crate::text;
+
+/// Nodo más pequeño de tipo `kind` que contiene por completo `range`.
+pub fn enclosing<'t>(tree: &'t Tree, range: &Range<usize>, kind: &str) -> Option<Node<'t>> {
+    let mut node = tree.root_node().descendant_for_byte_range(range.start, range.end);
+    while let Some(current) = node {
+        if current.kind() == kind {
+            return Some(current);
+        }
+        node = current.parent();
+    }
+    None
+}
+
+// Ítem al nivel del archivo (o de un `mod`) que contiene `node`; la función nueva se inserta justo después.
+pub fn top_level_item(node: Node) -> Node {
+    let mut item = node;
+    while let Some(parent) = item.parent() {
+        let at_module_level = parent.kind() == "source_file"
+            || (parent.kind() == "declaration_list" && parent.parent().is_some_and(|p| p.kind() == "mod_item"));
+        if at_module_level {
+            break;
+        }
+        item = parent;
+    }
+    item
+}
+
+/// Convierte la closure que contiene `selection` en `fn name(...)` insertada tras el ítem que la contiene y la
+/// sustituye por `name`. Los parámetros sin tipo quedan como `_` para que el compilador pida completarlos,
+/// y las variables capturadas no se convierten en parámetros.
+pub fn closure_to_function(content: &str, tree: &Tree, selection: &Range<usize>, name: &str) -> Result<String, String> {
+    let closure = enclosing(tree, selection, "closure_expression").ok_or("Place the cursor inside a closure")?;
+    let text = |node: Node| &content[node.byte_range()];
+
+    let parameters = closure.child_by_field_name("parameters").ok_or("The closure has no parameter list")?;
+    let mut cursor = parameters.walk();
+    let params: Vec<String> = parameters
+        .named_children(&mut cursor)
+        .map(|param| match param.kind() {
+            "parameter" => text(param).to_string(),
+            _ => format!("{}: _", text(param)),
+        })
+        .collect();
+    let body = closure.child_by_field_name("body").ok_or("The closure has no body")?;
+    // Una closure de expresión sin tipo de retorno devuelve algo: se deja `-> _` para completarlo.
+    let return_type = match closure.child_by_field_name("return_type") {
+        Some(ty) => format!(" -> {}", text(ty)),
+        None if body.kind() != "block" => " -> _".to_string(),
+        None => String::new(),
+    };
+    let item = top_level_item(closure);
+    let indent = text::line_indent(content, item.start_byte());
+    let body = match body.kind() {
+        "block" => text::reindent(text(body), text::line_indent(content, closure.start_byte()), indent),
+        _ => format!("{{\n{}    {}\n{}}}", indent, text(body), indent),
+    };
+    let function = format!("\n\n{}fn {}({}){} {}", indent, name, params.join(", "), return_type, body);
+
+    let mut result = String::with_capacity(content.len() + function.len());
+    result.push_str(&content[..closure.start_byte()]);
+    result.push_str(name);
+    result.push_str(&content[closure.end_byte()..item.end_byte()]);
+    result.push_str(&function);
+    result.push_str(&content[item.end_byte()..]);
+    Ok(result)
+}
+
+/// Convierte la función anidada que contiene `selection` en `let name = |...| -> T { ... };` en el mismo sitio.
+/// Las funciones al nivel del módulo no pueden ser closures, y las genéricas o con `self` tampoco.
+pub fn function_to_closure(content: &str, tree: &Tree, selection: &Range<usize>) -> Result<String, String> {
+    let function = enclosing(tree, selection, "function_item").ok_or("Place the cursor inside a function")?;
+    if function.parent().map(|parent| parent.kind()) != Some("block") {
+        return Err("Only functions declared inside another function can become closures".to_string());
+    }
+    if function.child_by_field_name("type_parameters").is_some() {
+        return Err("Generic functions cannot be converted to closures".to_string());
+    }
+    let text = |node: Node| &content[node.byte_range()];
+
+    let name = function.child_by_field_name("name").map(text).ok_or("The function has no name")?;
+    let parameters = function.child_by_field_name("parameters").ok_or("The function has no parameters")?;
+    let mut cursor = parameters.walk();
+    let mut params = Vec::new();
+    for param in parameters.named_children(&mut cursor) {
+        if param.kind() == "self_parameter" {
+            return Err("Methods cannot be converted to closures".to_string());
+        }
+        params.push(text(param));
+    }
+    let return_type = function
+        .child_by_field_name("return_type")
+        .map(|ty| format!(" -> {}", text(ty)))
+        .unwrap_or_default();
+    let body = function.child_by_field_name("body").map(text).ok_or("The function has no body")?;
+
+    let closure = format!("let {} = |{}|{} {};", name, params.join(", "), return_type, body);
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..function.start_byte()]);
+    result.push_str(&closure);
+    result.push_str(&content[function.end_byte()..]);
+    Ok(result)
+}
@@ -0,0 +1,56 @@
+use eframe::egui;
+use std::ops::Range;
+
+use crate::text::is_identifier_char;
+
+pub struct ColorLiteral {
+    pub range: Range<usize>,
+    pub color: egui::Color32,
+}
+
+/// Literales `#RGB`, `#RGBA`, `#RRGGBB` y `#RRGGBBAA` del texto, en orden.
+pub fn find_hex_colors(content: &str) -> Vec<ColorLiteral> {
+    let bytes = content.as_bytes();
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while let Some(offset) = content[i..].find('#') {
+        let start = i + offset;
+        let digits = bytes[start + 1..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+        let end = start + 1 + digits;
+        i = end.max(start + 1);
+        let followed_by_identifier = content[end..].chars().next().is_some_and(is_identifier_char);
+        if followed_by_identifier || ![3, 4, 6, 8].contains(&digits) {
+            continue;
+        }
+        if let Some(color) = parse_hex(&content[start + 1..end]) {
+            literals.push(ColorLiteral { range: start..end, color });
+        }
+    }
+    literals
+}
+
+fn parse_hex(digits: &str) -> Option<egui::Color32> {
+    let channel = |i: usize, width: usize| -> Option<u8> {
+        let value = u8::from_str_radix(digits.get(i * width..(i + 1) * width)?, 16).ok()?;
+        Some(if width == 1 { value * 17 } else { value })
+    };
+    let (width, has_alpha) = match digits.len() {
+        3 => (1, false),
+        4 => (1, true),
+        6 => (2, false),
+        8 => (2, true),
+        _ => return None,
+    };
+    let alpha = if has_alpha { channel(3, width)? } else { 255 };
+    Some(egui::Color32::from_rgba_unmultiplied(channel(0, width)?, channel(1, width)?, channel(2, width)?, alpha))
+}
+
+/// Texto del literal para `color`; solo se añade el canal alfa si el color no es opaco.
+pub fn to_hex(color: egui::Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
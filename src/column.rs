@@ -0,0 +1,53 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::text;
+
+/// Selección rectangular (Alt+arrastre) entre dos esquinas `(línea, columna)`, con la columna en caracteres.
+/// Puede ir más allá del final de las líneas cortas.
+#[derive(Clone, Copy)]
+pub struct RectSelection {
+    pub anchor: (usize, usize),
+    pub head: (usize, usize),
+}
+
+impl RectSelection {
+    pub fn at(line: usize, column: usize) -> Self {
+        Self { anchor: (line, column), head: (line, column) }
+    }
+
+    pub fn lines(&self) -> RangeInclusive<usize> {
+        self.anchor.0.min(self.head.0)..=self.anchor.0.max(self.head.0)
+    }
+
+    pub fn columns(&self) -> Range<usize> {
+        self.anchor.1.min(self.head.1)..self.anchor.1.max(self.head.1)
+    }
+}
+
+/// Pega `clipboard` columna a columna: su línea N sustituye las columnas seleccionadas de la línea N de la selección,
+/// y si tiene menos líneas se vuelve a empezar por la primera. Las líneas más cortas que la selección se rellenan con
+/// espacios. Devuelve el texto que había en el rectángulo, una línea por cada línea seleccionada.
+pub fn column_paste(content: &mut String, rect_sel: &RectSelection, clipboard: &str) -> String {
+    let clipboard = clipboard.strip_suffix('\n').unwrap_or(clipboard);
+    let pieces: Vec<&str> = clipboard.split('\n').map(|piece| piece.strip_suffix('\r').unwrap_or(piece)).collect();
+    let columns = rect_sel.columns();
+    let offsets = text::line_offsets(content);
+    let mut replaced = Vec::new();
+    // De abajo arriba, para que los desplazamientos de las líneas que faltan sigan siendo válidos.
+    let first = *rect_sel.lines().start();
+    for line in rect_sel.lines().rev() {
+        let Some(&start) = offsets.get(line) else {
+            continue;
+        };
+        let end = content[start..].find('\n').map_or(content.len(), |i| start + i);
+        let line_text = &content[start..end];
+        let length = line_text.chars().count();
+        let from = start + text::char_to_byte(line_text, columns.start.min(length));
+        let to = start + text::char_to_byte(line_text, columns.end.min(length));
+        replaced.push(content[from..to].to_string());
+        let padding = " ".repeat(columns.start.saturating_sub(length));
+        content.replace_range(from..to, &(padding + pieces[(line - first) % pieces.len()]));
+    }
+    replaced.reverse();
+    replaced.join("\n")
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::config;
+use crate::language::EditorLanguage;
+use crate::text;
+
+pub const COMMENTS_FILE: &str = "comments.toml";
+
+/// Cómo se comenta en un lenguaje. Sin comentario de línea, "Toggle Comment" envuelve la selección en el de bloque.
+#[derive(Clone, Default, PartialEq)]
+pub struct CommentConfig {
+    pub line_comment: Option<String>,
+    pub block_comment_start: Option<String>,
+    pub block_comment_end: Option<String>,
+}
+
+impl CommentConfig {
+    fn new(line: Option<&str>, block: Option<(&str, &str)>) -> Self {
+        Self {
+            line_comment: line.map(str::to_string),
+            block_comment_start: block.map(|(start, _)| start.to_string()),
+            block_comment_end: block.map(|(_, end)| end.to_string()),
+        }
+    }
+
+    fn block(&self) -> Option<(&str, &str)> {
+        Some((self.block_comment_start.as_deref()?, self.block_comment_end.as_deref()?))
+    }
+}
+
+fn builtin(language: EditorLanguage) -> CommentConfig {
+    match language {
+        EditorLanguage::Rust | EditorLanguage::JavaScript => CommentConfig::new(Some("//"), Some(("/*", "*/"))),
+        EditorLanguage::Python | EditorLanguage::Toml | EditorLanguage::Yaml => CommentConfig::new(Some("#"), None),
+        EditorLanguage::Html | EditorLanguage::Xml | EditorLanguage::Markdown => CommentConfig::new(None, Some(("<!--", "-->"))),
+        EditorLanguage::Css => CommentConfig::new(None, Some(("/*", "*/"))),
+        EditorLanguage::Json | EditorLanguage::PlainText => CommentConfig::default(),
+    }
+}
+
+/// Comentarios de cada lenguaje: los de serie con `comments.toml` del directorio de configuración encima. Cada tabla
+/// del archivo es un lenguaje por su nombre (`[rust]`, `["plain text"]`) o, si no es ninguno, una extensión de archivo
+/// (`[dsl]`) para lenguajes que el editor no conoce.
+pub struct CommentConfigs {
+    pub comment_config: HashMap<EditorLanguage, CommentConfig>,
+    pub extensions: HashMap<String, CommentConfig>,
+}
+
+impl CommentConfigs {
+    /// Los errores al leer el archivo de comentarios se añaden a `errors`.
+    pub fn load(errors: &mut Vec<String>) -> Self {
+        let mut configs = Self {
+            comment_config: EditorLanguage::ALL.iter().map(|&language| (language, builtin(language))).collect(),
+            extensions: HashMap::new(),
+        };
+        // Que no exista el archivo es lo normal.
+        let Some(content) = config::config_dir().and_then(|dir| fs::read_to_string(dir.join(COMMENTS_FILE)).ok()) else {
+            return configs;
+        };
+        let table = match content.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                errors.push(format!("Unable to read {}: {}", COMMENTS_FILE, e));
+                return configs;
+            }
+        };
+        for (key, value) in table {
+            let Some(entry) = value.as_table() else {
+                errors.push(format!("Unable to read {}: `{}` must be a table", COMMENTS_FILE, key));
+                continue;
+            };
+            let field = |name: &str| entry.get(name).and_then(toml::Value::as_str).map(str::to_string);
+            let comment = CommentConfig {
+                line_comment: field("line_comment"),
+                block_comment_start: field("block_comment_start"),
+                block_comment_end: field("block_comment_end"),
+            };
+            match EditorLanguage::ALL.iter().find(|language| language.label().eq_ignore_ascii_case(&key)) {
+                Some(language) => {
+                    configs.comment_config.insert(*language, comment);
+                }
+                None => {
+                    configs.extensions.insert(key.trim_start_matches('.').to_ascii_lowercase(), comment);
+                }
+            }
+        }
+        configs
+    }
+
+    /// Configuración para `path`: la de su extensión si el archivo la define y si no la de `language`.
+    pub fn for_file(&self, language: EditorLanguage, path: Option<&Path>) -> Option<&CommentConfig> {
+        let extension = path.and_then(|path| path.extension()).map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        extension.and_then(|ext| self.extensions.get(&ext)).or_else(|| self.comment_config.get(&language))
+    }
+}
+
+/// Comenta o descomenta las líneas que toca `selection`. Con comentario de línea, si todas las que no están en blanco
+/// ya lo llevan se quita y si no se añade a todas a la altura de la menor sangría. Sin él, la selección (o la línea del
+/// cursor) se envuelve en el comentario de bloque o se desenvuelve. Devuelve el texto nuevo y la selección ajustada, o
+/// `None` si el lenguaje no tiene comentarios.
+pub fn toggle_comment(content: &str, selection: &Range<usize>, config: &CommentConfig) -> Option<(String, Range<usize>)> {
+    let start = text::line_start(content, selection.start);
+    // Una selección que acaba justo al principio de una línea no la incluye.
+    let last = if selection.end > selection.start && text::line_start(content, selection.end) == selection.end {
+        selection.end - 1
+    } else {
+        selection.end
+    };
+    let end = content[last..].find('\n').map_or(content.len(), |i| last + i);
+    let block = &content[start..end];
+
+    let replacement = if let Some(prefix) = &config.line_comment {
+        toggle_line_comments(block, prefix)
+    } else {
+        let (open, close) = config.block()?;
+        let target = if selection.is_empty() { block } else { &content[selection.clone()] };
+        let toggled = toggle_block_comment(target, open, close);
+        if selection.is_empty() {
+            toggled
+        } else {
+            format!("{}{}{}", &content[start..selection.start], toggled, &content[selection.end..end])
+        }
+    };
+    let result = format!("{}{}{}", &content[..start], replacement, &content[end..]);
+    Some((result, start..start + replacement.len()))
+}
+
+fn toggle_line_comments(block: &str, prefix: &str) -> String {
+    let lines: Vec<&str> = block.split('\n').collect();
+    let code = || lines.iter().filter(|line| !line.trim().is_empty());
+    let commented = code().all(|line| line.trim_start().starts_with(prefix));
+    // Sangría común como prefijo de caracteres, no como número de bytes: con espacios de varios bytes (U+3000) un
+    // corte por bytes podría caer a mitad de carácter en otra línea.
+    let indent = code()
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|common, leading| {
+            let shared = common.char_indices().zip(leading.chars()).find(|((_, a), b)| a != b);
+            shared.map_or(if common.len() <= leading.len() { common } else { leading }, |((i, _), _)| &common[..i])
+        })
+        .map_or(0, str::len);
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                return line.to_string();
+            }
+            if commented {
+                let at = line.len() - line.trim_start().len();
+                let rest = &line[at + prefix.len()..];
+                format!("{}{}", &line[..at], rest.strip_prefix(' ').unwrap_or(rest))
+            } else {
+                format!("{}{} {}", &line[..indent], prefix, &line[indent..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Los espacios de alrededor se quedan fuera del comentario.
+fn toggle_block_comment(target: &str, open: &str, close: &str) -> String {
+    let trimmed = target.trim();
+    let leading = &target[..target.len() - target.trim_start().len()];
+    let trailing = &target[leading.len() + trimmed.len()..];
+    match trimmed.strip_prefix(open).and_then(|rest| rest.strip_suffix(close)) {
+        Some(inner) => {
+            let inner = inner.strip_prefix(' ').unwrap_or(inner);
+            let inner = inner.strip_suffix(' ').unwrap_or(inner);
+            format!("{}{}{}", leading, inner, trailing)
+        }
+        None => format!("{}{} {} {}{}", leading, open, trimmed, close, trailing),
+    }
+}
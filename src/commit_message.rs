@@ -0,0 +1,125 @@
+use eframe::egui;
+use std::path::Path;
+
+use crate::git::{self, StatusEntry};
+
+pub const SUBJECT_LIMIT: usize = 72;
+
+pub fn is_commit_message(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "COMMIT_EDITMSG")
+}
+
+/// Caracteres que quedan en la primera línea antes del límite de 72 (negativo si se pasa).
+pub fn subject_chars_left(content: &str) -> isize {
+    let subject = content.lines().find(|line| !line.starts_with('#')).unwrap_or("");
+    SUBJECT_LIMIT as isize - subject.chars().count() as isize
+}
+
+/// `Nombre <email>` según `git config` del repositorio que contiene `dir`.
+pub fn git_identity(dir: &Path) -> Option<String> {
+    let config = |key: &str| {
+        git::run_git_command(&["config", key], dir)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Some(format!("{} <{}>", config("user.name")?, config("user.email")?))
+}
+
+/// Añade `trailer` al final del mensaje, antes de los comentarios de git, separado del cuerpo por una línea
+/// en blanco si hace falta. Devuelve el offset del final del trailer insertado.
+pub fn insert_trailer(content: &mut String, trailer: &str) -> usize {
+    let comments = content
+        .match_indices('\n')
+        .map(|(i, _)| i + 1)
+        .chain(std::iter::once(0))
+        .filter(|&start| content[start..].starts_with('#'))
+        .min()
+        .unwrap_or(content.len());
+    let message = content[..comments].trim_end_matches('\n');
+    let last_line = message.rsplit('\n').next().unwrap_or("");
+    let is_trailer = last_line.split_once(": ").is_some_and(|(key, _)| key == "BREAKING CHANGE" || (!key.is_empty() && !key.contains(' ')));
+
+    let separator = match (message.is_empty(), is_trailer) {
+        (true, _) => "",
+        (false, true) => "\n",
+        (false, false) => "\n\n",
+    };
+    let insertion = format!("{}{}\n", separator, trailer);
+    let at = message.len();
+    let end = at + insertion.len() - 1;
+    let rest = content[at..].trim_start_matches('\n').to_string();
+    content.truncate(at);
+    content.push_str(&insertion);
+    if !rest.is_empty() {
+        content.push('\n');
+        content.push_str(&rest);
+    }
+    end
+}
+
+pub enum CommitAction {
+    Stage(String, bool),
+    Refresh,
+    Commit,
+    CommitAndPush,
+}
+
+/// Panel de commit: archivos de `git status --short` con casillas para el índice y el mensaje.
+pub struct CommitPanel {
+    pub entries: Vec<StatusEntry>,
+    pub message: String,
+}
+
+impl CommitPanel {
+    pub fn new(entries: Vec<StatusEntry>) -> Self {
+        Self { entries, message: String::new() }
+    }
+
+    /// Devuelve `false` cuando se cierra la ventana.
+    pub fn show(&mut self, ctx: &egui::Context, action: &mut Option<CommitAction>) -> bool {
+        let mut open = true;
+        egui::Window::new("Commit").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} changed files", self.entries.len()));
+                if ui.small_button("⟳").on_hover_text("Refresh").clicked() {
+                    *action = Some(CommitAction::Refresh);
+                }
+            });
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for entry in &self.entries {
+                    let mut staged = entry.is_staged();
+                    let label = egui::RichText::new(format!("{} {}", entry.code, entry.path)).monospace();
+                    if ui.checkbox(&mut staged, label).changed() {
+                        *action = Some(CommitAction::Stage(entry.path.clone(), staged));
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.label("Message (subject, blank line, then body):");
+            ui.add(egui::TextEdit::multiline(&mut self.message).desired_rows(5).desired_width(f32::INFINITY).font(egui::TextStyle::Monospace));
+            let left = subject_chars_left(&self.message);
+            let hint = if left < 0 {
+                egui::RichText::new(format!("Subject is {} characters over {}", -left, SUBJECT_LIMIT)).color(egui::Color32::from_rgb(220, 50, 50))
+            } else {
+                egui::RichText::new(format!("{} characters left in the subject", left)).weak()
+            };
+            ui.label(hint);
+            if self.message.lines().nth(1).is_some_and(|line| !line.trim().is_empty()) {
+                ui.label(egui::RichText::new("Leave the second line blank to separate the subject from the body.").weak());
+            }
+
+            let can_commit = !self.message.trim().is_empty() && self.entries.iter().any(StatusEntry::is_staged);
+            ui.horizontal(|ui| {
+                if ui.add_enabled(can_commit, egui::Button::new("Commit")).clicked() {
+                    *action = Some(CommitAction::Commit);
+                }
+                if ui.add_enabled(can_commit, egui::Button::new("Commit & Push")).clicked() {
+                    *action = Some(CommitAction::CommitAndPush);
+                }
+            });
+        });
+        open
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::text::is_identifier_char;
+
+pub const MAX_COMPLETIONS: usize = 10;
+const MIN_WORD_LEN: usize = 3;
+
+/// Ventana de autocompletado con palabras del propio archivo (Ctrl+Space).
+pub struct Completion {
+    // Parte de la palabra ya escrita, que se sustituye al aceptar.
+    pub prefix: Range<usize>,
+    pub items: Vec<String>,
+    pub selected: usize,
+}
+
+impl Completion {
+    /// `None` si no hay nada que sugerir para la palabra que acaba en `cursor`.
+    pub fn at(content: &str, cursor: usize, hash_comments: bool) -> Option<Self> {
+        let start = content[..cursor]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_identifier_char(*c))
+            .last()
+            .map_or(cursor, |(i, _)| i);
+        let prefix = start..cursor;
+        let items = local_completions(content, prefix.clone(), hash_comments);
+        (!items.is_empty()).then_some(Self { prefix, items, selected: 0 })
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+    }
+}
+
+/// Palabras únicas de al menos tres caracteres que empiezan por el texto de `prefix`, fuera de cadenas y comentarios.
+/// Las que aparecen más abajo en el documento van primero.
+pub fn local_completions(content: &str, prefix: Range<usize>, hash_comments: bool) -> Vec<String> {
+    let typed = &content[prefix.clone()];
+    let mut last_seen: HashMap<&str, usize> = HashMap::new();
+    for (range, word) in code_words(content, hash_comments) {
+        // La palabra que se está escribiendo no se sugiere a sí misma.
+        let under_cursor = range.start <= prefix.end && prefix.end <= range.end;
+        if !under_cursor && word.len() >= MIN_WORD_LEN && word.starts_with(typed) && word != typed {
+            last_seen.insert(word, range.start);
+        }
+    }
+    let mut words: Vec<(&str, usize)> = last_seen.into_iter().collect();
+    words.sort_by_key(|&(_, last)| std::cmp::Reverse(last));
+    words.into_iter().take(MAX_COMPLETIONS).map(|(word, _)| word.to_string()).collect()
+}
+
+// Palabras (identificadores) que no están dentro de una cadena entre comillas dobles ni de un comentario
+// `//`, `/* */` o, si `hash_comments`, `#`.
+fn code_words(content: &str, hash_comments: bool) -> Vec<(Range<usize>, &str)> {
+    let bytes = content.as_bytes();
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => i = content[i..].find('\n').map_or(bytes.len(), |end| i + end),
+            b'#' if hash_comments => i = content[i..].find('\n').map_or(bytes.len(), |end| i + end),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = content[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2),
+            _ => {
+                let Some(c) = content[i..].chars().next() else {
+                    break;
+                };
+                if !is_identifier_char(c) {
+                    i += c.len_utf8();
+                    continue;
+                }
+                let end = content[i..].find(|c: char| !is_identifier_char(c)).map_or(content.len(), |end| i + end);
+                // Los identificadores no empiezan por dígito; así no se sugieren números.
+                if !c.is_ascii_digit() {
+                    words.push((i..end, &content[i..end]));
+                }
+                i = end;
+            }
+        }
+    }
+    words
+}
@@ -0,0 +1,27 @@
+use regex::Regex;
+use std::env;
+use std::path::PathBuf;
+
+/// Directorio de configuración del usuario: `%APPDATA%\rbeditor` en Windows y
+/// `$XDG_CONFIG_HOME/rbeditor` (o `~/.config/rbeditor`) en el resto.
+pub fn config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("rbeditor"));
+    }
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("rbeditor"))
+}
+
+/// Directorio de configuración del proyecto (`.rbeditor`) dentro de `root`.
+pub fn project_dir(root: &std::path::Path) -> PathBuf {
+    root.join(".rbeditor")
+}
+
+// `**` cruza directorios, `*` no, y `?` es un carácter; `**/` al principio también coincide sin directorio.
+pub fn glob_matches(pattern: &str, target: &str) -> bool {
+    let escaped = regex::escape(pattern).replace(r"\*\*/", "(.*/)?").replace(r"\*\*", ".*").replace(r"\*", "[^/]*").replace(r"\?", "[^/]");
+    let anchored = if pattern.contains('/') && !pattern.starts_with('/') { format!("(^|/){}$", escaped) } else { format!("^{}$", escaped) };
+    Regex::new(&anchored).is_ok_and(|regex| regex.is_match(target))
+}
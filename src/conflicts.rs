@@ -0,0 +1,88 @@
+use eframe::egui;
+use std::ops::Range;
+
+pub const MARKER_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(120, 120, 120, 60);
+pub const OURS_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(40, 110, 60, 50);
+pub const THEIRS_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(40, 80, 140, 50);
+
+/// Un bloque en conflicto que dejó git en el archivo. Los rangos son en bytes: `range` abarca desde la línea
+/// `<<<<<<<` hasta el final de la `>>>>>>>`, y `ours`/`theirs` son solo el texto de cada lado, sin marcadores. Con
+/// `merge.conflictstyle = diff3` la parte común entre `|||||||` y `=======` no entra en ninguno de los dos.
+pub struct ConflictHunk {
+    pub range: Range<usize>,
+    pub ours: Range<usize>,
+    pub theirs: Range<usize>,
+    pub markers: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 3] = [Resolution::Ours, Resolution::Theirs, Resolution::Both];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Resolution::Ours => "Accept Ours",
+            Resolution::Theirs => "Accept Theirs",
+            Resolution::Both => "Accept Both",
+        }
+    }
+}
+
+pub fn find_conflicts(content: &str) -> Vec<ConflictHunk> {
+    let mut hunks = Vec::new();
+    // Inicio del bloque que se está leyendo y dónde empieza y acaba cada lado.
+    let mut start = None;
+    let mut ours_start = 0;
+    let mut ours_end = None;
+    let mut theirs_start = None;
+    let mut markers = Vec::new();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        if line.starts_with("<<<<<<<") {
+            start = Some(line_start);
+            ours_start = line_end;
+            (ours_end, theirs_start) = (None, None);
+            markers.clear();
+            markers.push(line_start..line_end);
+        } else if start.is_some() && theirs_start.is_none() && line.starts_with("|||||||") {
+            ours_end.get_or_insert(line_start);
+            markers.push(line_start..line_end);
+        } else if start.is_some() && theirs_start.is_none() && line.starts_with("=======") {
+            ours_end.get_or_insert(line_start);
+            theirs_start = Some(line_end);
+            markers.push(line_start..line_end);
+        } else if line.starts_with(">>>>>>>") {
+            if let (Some(start), Some(ours_end), Some(theirs_start)) = (start, ours_end, theirs_start) {
+                markers.push(line_start..line_end);
+                hunks.push(ConflictHunk {
+                    range: start..line_end,
+                    ours: ours_start..ours_end,
+                    theirs: theirs_start..line_start,
+                    markers: std::mem::take(&mut markers),
+                });
+            }
+            start = None;
+        }
+        line_start = line_end;
+    }
+    hunks
+}
+
+/// `content` con el bloque sustituido por el lado elegido; con `Both` va primero el nuestro.
+pub fn resolve(content: &str, hunk: &ConflictHunk, resolution: Resolution) -> String {
+    let ours = &content[hunk.ours.clone()];
+    let theirs = &content[hunk.theirs.clone()];
+    let replacement = match resolution {
+        Resolution::Ours => ours.to_string(),
+        Resolution::Theirs => theirs.to_string(),
+        Resolution::Both => format!("{}{}", ours, theirs),
+    };
+    format!("{}{}{}", &content[..hunk.range.start], replacement, &content[hunk.range.end..])
+}
@@ -0,0 +1,279 @@
+// Acciones del menú contextual del editor según los nodos de tree-sitter bajo el puntero.
+
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+use crate::closures;
+use crate::imports;
+use crate::literals;
+use crate::text;
+
+// Nombre provisional de la función extraída; después se abre el cuadro de renombrar.
+pub const EXTRACTED_FUNCTION_NAME: &str = "extracted";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ContextAction {
+    ExtractFunction,
+    AddDocComment,
+    TogglePub,
+    SortImports,
+    ToggleEscapes,
+    ConvertToRawString,
+    CycleNumber,
+}
+
+impl ContextAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            ContextAction::ExtractFunction => "Extract Function",
+            ContextAction::AddDocComment => "Add Documentation Comment",
+            ContextAction::TogglePub => "Toggle pub Visibility",
+            ContextAction::SortImports => "Sort Imports",
+            ContextAction::ToggleEscapes => "Escape / Unescape",
+            ContextAction::ConvertToRawString => "Convert to Raw String",
+            ContextAction::CycleNumber => "Cycle Number Representation",
+        }
+    }
+
+    // Tipo de nodo sobre el que trabaja la acción.
+    fn node_kind(self) -> &'static str {
+        match self {
+            ContextAction::ExtractFunction | ContextAction::AddDocComment | ContextAction::TogglePub => "function_item",
+            ContextAction::SortImports => "use_declaration",
+            ContextAction::ToggleEscapes | ContextAction::ConvertToRawString => "string_literal",
+            ContextAction::CycleNumber => "integer_literal",
+        }
+    }
+}
+
+/// Acciones que se ofrecen para un nodo de tipo `kind`.
+pub fn context_actions_for_node(kind: &str) -> Vec<ContextAction> {
+    match kind {
+        "function_item" => vec![ContextAction::ExtractFunction, ContextAction::AddDocComment, ContextAction::TogglePub],
+        "use_declaration" => vec![ContextAction::SortImports],
+        "string_literal" => vec![ContextAction::ToggleEscapes, ContextAction::ConvertToRawString],
+        "integer_literal" => vec![ContextAction::CycleNumber],
+        _ => Vec::new(),
+    }
+}
+
+/// Acciones del nodo en `byte` y de los que lo contienen, de dentro afuera: sobre un número dentro de una función salen
+/// las del número y después las de la función.
+pub fn actions_at(tree: &Tree, byte: usize) -> Vec<ContextAction> {
+    let mut actions = Vec::new();
+    let mut node = tree.root_node().descendant_for_byte_range(byte, byte);
+    while let Some(current) = node {
+        for action in context_actions_for_node(current.kind()) {
+            if !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+        node = current.parent();
+    }
+    actions
+}
+
+fn node_for<'t>(tree: &'t Tree, byte: usize, action: ContextAction) -> Option<Node<'t>> {
+    closures::enclosing(tree, &(byte..byte), action.node_kind())
+}
+
+/// Aplica `action` al nodo en `byte`; devuelve el texto nuevo y dónde dejar el cursor. Extraer una función usa la
+/// selección, que tiene que estar dentro del cuerpo de la función.
+pub fn apply(action: ContextAction, content: &str, tree: &Tree, byte: usize, selection: &Range<usize>) -> Result<(String, usize), String> {
+    let node = node_for(tree, byte, action).ok_or("Nothing to apply this action to at the pointer")?;
+    let (range, replacement, cursor) = match action {
+        ContextAction::ExtractFunction => return extract_function(content, node, selection),
+        ContextAction::AddDocComment => {
+            let start = text::line_start(content, doc_target(node).start_byte());
+            let comment = format!("{}/// \n", text::line_indent(content, start));
+            let cursor = start + comment.len() - 1;
+            (start..start, comment, cursor)
+        }
+        ContextAction::TogglePub => toggle_pub(content, node)?,
+        ContextAction::SortImports => return Ok((imports::sort_rust_imports(content, tree), byte)),
+        ContextAction::ToggleEscapes => {
+            let converted = toggle_escapes(&content[node.byte_range()]).ok_or("The string has nothing to escape or unescape")?;
+            (node.byte_range(), converted, node.start_byte())
+        }
+        ContextAction::ConvertToRawString => {
+            let raw = raw_string(&content[node.byte_range()]).ok_or("The string has an escape that a raw string cannot hold")?;
+            (node.byte_range(), raw, node.start_byte())
+        }
+        ContextAction::CycleNumber => {
+            let cycled = cycle_number(&content[node.byte_range()]).ok_or("The number is too large or not an integer")?;
+            (node.byte_range(), cycled, node.start_byte())
+        }
+    };
+    let mut result = String::with_capacity(content.len() + replacement.len());
+    result.push_str(&content[..range.start]);
+    result.push_str(&replacement);
+    result.push_str(&content[range.end..]);
+    Ok((result, cursor))
+}
+
+// El comentario va encima de los atributos de la función, no entre ellos y el `fn`.
+fn doc_target(function: Node) -> Node {
+    let mut target = function;
+    while let Some(previous) = target.prev_named_sibling().filter(|previous| previous.kind() == "attribute_item") {
+        target = previous;
+    }
+    target
+}
+
+/// Mueve lo seleccionado dentro de la función a `fn extracted()` justo después del ítem que la contiene y deja una
+/// llamada en su lugar. Una expresión (sin `;` ni varias líneas) se devuelve con `-> _`; las variables que use no se
+/// convierten en parámetros.
+fn extract_function(content: &str, function: Node, selection: &Range<usize>) -> Result<(String, usize), String> {
+    let body = function.child_by_field_name("body").ok_or("The function has no body")?;
+    let inner = body.start_byte() + 1..body.end_byte().saturating_sub(1);
+    if selection.is_empty() || selection.start < inner.start || selection.end > inner.end {
+        return Err("Select code inside the function body first".to_string());
+    }
+    let selected = &content[selection.clone()];
+    let trimmed = selected.trim();
+    if trimmed.is_empty() {
+        return Err("Select code inside the function body first".to_string());
+    }
+    let item = closures::top_level_item(function);
+    let indent = text::line_indent(content, item.start_byte());
+    let body_indent = format!("{}    ", indent);
+    let is_expression = !trimmed.ends_with(';') && !trimmed.ends_with('}') && !trimmed.contains('\n');
+    let (call, return_type) = if is_expression {
+        (format!("{}()", EXTRACTED_FUNCTION_NAME), " -> _")
+    } else {
+        (format!("{}();", EXTRACTED_FUNCTION_NAME), "")
+    };
+    let from = text::line_indent(content, selection.start);
+    let statements = text::reindent(trimmed, from, &body_indent);
+    let function_text = format!("\n\n{}fn {}(){} {{\n{}{}\n{}}}", indent, EXTRACTED_FUNCTION_NAME, return_type, body_indent, statements, indent);
+
+    // Se conservan los espacios que rodeaban la selección para no juntar líneas.
+    let leading = selected.len() - selected.trim_start().len();
+    let trailing = selected.len() - selected.trim_end().len();
+    let mut result = String::with_capacity(content.len() + function_text.len());
+    result.push_str(&content[..selection.start + leading]);
+    let cursor = result.len();
+    result.push_str(&call);
+    result.push_str(&content[selection.end - trailing..item.end_byte()]);
+    result.push_str(&function_text);
+    result.push_str(&content[item.end_byte()..]);
+    Ok((result, cursor))
+}
+
+fn toggle_pub(content: &str, function: Node) -> Result<(Range<usize>, String, usize), String> {
+    let container = function.parent().and_then(|list| list.parent());
+    let in_trait = container.is_some_and(|item| item.kind() == "trait_item" || (item.kind() == "impl_item" && item.child_by_field_name("trait").is_some()));
+    if in_trait {
+        return Err("Trait items cannot have a visibility".to_string());
+    }
+    let mut cursor = function.walk();
+    let visibility = function.children(&mut cursor).find(|child| child.kind() == "visibility_modifier");
+    Ok(match visibility {
+        Some(visibility) => {
+            let spaces = content[visibility.end_byte()..].len() - content[visibility.end_byte()..].trim_start().len();
+            (visibility.start_byte()..visibility.end_byte() + spaces, String::new(), function.start_byte())
+        }
+        None => (function.start_byte()..function.start_byte(), "pub ".to_string(), function.start_byte()),
+    })
+}
+
+// Las secuencias `\u{…}` y `\xHH` pasan a ser el carácter; si no hay ninguna, los caracteres que no son ASCII pasan a
+// `\u{…}`. `None` si no hay nada que cambiar.
+fn toggle_escapes(literal: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(literal.len());
+    let mut changed = false;
+    let mut index = 0;
+    while index < literal.len() {
+        let escape = (literal.as_bytes()[index] == b'\\').then(|| literals::escape_at(literal, index + 1)).flatten();
+        if let Some(range) = escape.filter(|range| range.start == index) {
+            // `\x22` o `\u{5C}` escritos tal cual cerrarían la cadena o escaparían lo siguiente.
+            match literals::unescape_sequence(&literal[range.clone()]) {
+                Ok(c) if c != '"' && c != '\\' => {
+                    unescaped.push(c);
+                    changed = true;
+                }
+                _ => unescaped.push_str(&literal[range.clone()]),
+            }
+            index = range.end;
+            continue;
+        }
+        let c = literal[index..].chars().next()?;
+        // `\\` y `\"` se copian enteros para no confundir su segundo carácter con el inicio de otra secuencia.
+        if c == '\\' {
+            let next = literal[index + 1..].chars().next().map_or(0, char::len_utf8);
+            unescaped.push_str(&literal[index..index + 1 + next]);
+            index += 1 + next;
+            continue;
+        }
+        unescaped.push(c);
+        index += c.len_utf8();
+    }
+    if changed {
+        return Some(unescaped);
+    }
+    let escaped: String = literal.chars().map(|c| if c.is_ascii() { c.to_string() } else { literals::escape_char_to_unicode(c) }).collect();
+    (escaped != literal).then_some(escaped)
+}
+
+// `"a\tb"` → `r"a	b"`, con el prefijo `b` si lo había. `None` para `\0` y los demás caracteres de control que una cadena
+// cruda no puede contener de forma legible, y para literales que ya son crudos.
+fn raw_string(literal: &str) -> Option<String> {
+    let quote = literal.find('"')?;
+    let prefix = &literal[..quote];
+    let inner = literal[quote + 1..].strip_suffix('"')?;
+    let mut value = String::with_capacity(inner.len());
+    let mut index = 0;
+    while let Some(c) = inner[index..].chars().next() {
+        if c != '\\' {
+            value.push(c);
+            index += c.len_utf8();
+            continue;
+        }
+        let next = inner[index + 1..].chars().next()?;
+        match next {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            '\\' | '"' | '\'' => value.push(next),
+            // `\` al final de la línea se salta el salto y la sangría de la siguiente.
+            '\n' => {
+                let rest = &inner[index + 2..];
+                index = inner.len() - rest.trim_start().len();
+                continue;
+            }
+            'x' | 'u' => {
+                let range = literals::escape_at(inner, index + 1).filter(|range| range.start == index)?;
+                value.push(literals::unescape_sequence(&inner[range.clone()]).ok()?);
+                index = range.end;
+                continue;
+            }
+            _ => return None,
+        }
+        index += 1 + next.len_utf8();
+    }
+    Some(format!("{}{}", prefix, literals::raw_string_literal(&value)))
+}
+
+// Sufijos de tipo que puede llevar un literal entero.
+const INTEGER_SUFFIXES: [&str; 12] = ["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"];
+
+// Decimal → hexadecimal → binario → octal → decimal, conservando el sufijo de tipo.
+fn cycle_number(literal: &str) -> Option<String> {
+    let suffix = INTEGER_SUFFIXES.iter().find(|suffix| literal.strip_suffix(**suffix).is_some_and(|digits| !digits.is_empty()));
+    let suffix = suffix.copied().unwrap_or("");
+    let digits = literal[..literal.len() - suffix.len()].replace('_', "");
+    let (radix, body) = match digits.get(..2) {
+        Some("0x") => (16, &digits[2..]),
+        Some("0b") => (2, &digits[2..]),
+        Some("0o") => (8, &digits[2..]),
+        _ => (10, digits.as_str()),
+    };
+    let value = u128::from_str_radix(body, radix).ok()?;
+    let number = match radix {
+        10 => format!("0x{:X}", value),
+        16 => format!("0b{:b}", value),
+        2 => format!("0o{:o}", value),
+        _ => value.to_string(),
+    };
+    Some(format!("{}{}", number, suffix))
+}
@@ -0,0 +1,151 @@
+use eframe::egui;
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+// Se ignoran los errores a partir de este número para no saturar la vista con un archivo muy roto.
+const MAX_DIAGNOSTICS: usize = 100;
+
+// Caracteres máximos del mensaje que se dibuja al final de la línea.
+pub const INLINE_MESSAGE_CHARS: usize = 60;
+
+pub const ERROR_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 50, 50);
+
+// Medio periodo y amplitud de la línea ondulada bajo los diagnósticos.
+const SQUIGGLE_STEP: f32 = 2.0;
+const SQUIGGLE_HEIGHT: f32 = 1.5;
+
+pub struct Diagnostic {
+    pub range: Range<usize>,
+    pub line: usize,
+    pub message: String,
+    pub repair: Option<RepairSuggestion>,
+}
+
+/// Sustituye `range` de `content` por `replacement`.
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Arreglo propuesto para un error de sintaxis, con el texto que se muestra en el botón de la ayuda.
+pub struct RepairSuggestion {
+    pub description: String,
+    pub edit: TextEdit,
+}
+
+impl RepairSuggestion {
+    fn insert(text: &str, at: usize) -> Self {
+        Self { description: format!("Insert `{}`", text.trim()), edit: TextEdit { range: at..at, replacement: text.to_string() } }
+    }
+}
+
+impl Diagnostic {
+    /// Primera línea del mensaje recortada a `INLINE_MESSAGE_CHARS`.
+    pub fn inline_message(&self) -> String {
+        let first_line = self.message.lines().next().unwrap_or("");
+        let mut message: String = first_line.chars().take(INLINE_MESSAGE_CHARS).collect();
+        if first_line.chars().count() > INLINE_MESSAGE_CHARS {
+            message.push('…');
+        }
+        message
+    }
+}
+
+/// Puntos de una línea en zigzag de `left` a `right` justo por encima de `bottom`.
+pub fn squiggle(left: f32, right: f32, bottom: f32) -> Vec<egui::Pos2> {
+    let steps = ((right - left) / SQUIGGLE_STEP).ceil().max(1.0) as usize;
+    (0..=steps)
+        .map(|i| {
+            let x = (left + i as f32 * SQUIGGLE_STEP).min(right);
+            let y = if i % 2 == 0 { bottom } else { bottom - SQUIGGLE_HEIGHT };
+            egui::pos2(x, y)
+        })
+        .collect()
+}
+
+/// Errores de sintaxis según tree-sitter: nodos `ERROR` y nodos que faltan (`MISSING`).
+pub fn syntax_diagnostics(tree: &Tree, source: &[u8]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    collect(tree.root_node(), source, &mut diagnostics);
+    diagnostics
+}
+
+fn collect(node: Node, source: &[u8], diagnostics: &mut Vec<Diagnostic>) {
+    if diagnostics.len() >= MAX_DIAGNOSTICS || !node.has_error() {
+        return;
+    }
+    let context = std::str::from_utf8(source).unwrap_or_default();
+    if node.is_missing() {
+        diagnostics.push(Diagnostic {
+            range: node.byte_range(),
+            line: node.start_position().row,
+            message: format!("missing `{}`", node.kind()),
+            repair: suggest_repair(&node, context),
+        });
+        return;
+    }
+    if node.is_error() {
+        let text = node.utf8_text(source).unwrap_or("");
+        let snippet: String = text.split_whitespace().next().unwrap_or("").chars().take(20).collect();
+        diagnostics.push(Diagnostic {
+            range: node.byte_range(),
+            line: node.start_position().row,
+            message: if snippet.is_empty() {
+                "syntax error".to_string()
+            } else {
+                format!("syntax error near `{}`", snippet)
+            },
+            repair: suggest_repair(&node, context),
+        });
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(child, source, diagnostics);
+    }
+}
+
+/// Arreglo probable para un nodo `ERROR` o `MISSING` de Rust, deducido de la forma del árbol y del texto de alrededor:
+/// falta `;` al final de la línea, falta `->` entre los parámetros y el tipo de retorno, o hay llaves desparejadas.
+pub fn suggest_repair(error_node: &Node, context: &str) -> Option<RepairSuggestion> {
+    let range = error_node.byte_range();
+    if error_node.is_missing() {
+        let kind = error_node.kind();
+        // Un `;` delante de algo que abre un bloque en la misma línea no arregla nada; suele ser el `ERROR` de al lado.
+        let rest = context[range.start..].lines().next().unwrap_or("");
+        if kind == ";" && rest.contains('{') {
+            return None;
+        }
+        let text = if kind == "}" && !context[..range.start].ends_with('\n') { "\n}" } else { kind };
+        return Some(RepairSuggestion::insert(text, range.start));
+    }
+    // `fn f() u8 { .. }`: o el tipo queda como `ERROR` tras los parámetros, o la firma se cierra sin cuerpo y el
+    // resto queda como un `ERROR` aparte.
+    match error_node.prev_sibling() {
+        Some(previous) if previous.kind() == "parameters" => return Some(RepairSuggestion::insert("-> ", range.start)),
+        Some(previous) if previous.kind() == "function_signature_item" => {
+            return Some(RepairSuggestion::insert(" ->", previous.end_byte()));
+        }
+        _ => {}
+    }
+    let text = context.get(range.clone())?;
+    if text.trim() == "}" {
+        let edit = TextEdit { range, replacement: String::new() };
+        return Some(RepairSuggestion { description: "Remove unmatched `}`".to_string(), edit });
+    }
+    let unclosed = text.matches('{').count().saturating_sub(text.matches('}').count());
+    if unclosed > 0 {
+        let closing = "}\n".repeat(unclosed);
+        let at = range.end;
+        let text = if context[..at].ends_with('\n') { closing } else { format!("\n{}", closing) };
+        return Some(RepairSuggestion::insert(&text, at));
+    }
+    // Un `ERROR` que empieza en la línea siguiente a una instrucción sin terminar: falta `;` al final de esa línea.
+    let before = &context[..range.start];
+    let line_end = before.trim_end().len();
+    let ends_statement = before[..line_end].ends_with(|c: char| !";{},".contains(c));
+    if line_end > 0 && before[line_end..].contains('\n') && ends_statement {
+        return Some(RepairSuggestion::insert(";", line_end));
+    }
+    None
+}
@@ -0,0 +1,198 @@
+use eframe::egui;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::accessibility::StatusPalette;
+
+// Por encima de este número de celdas (líneas antiguas × nuevas) no se calcula la LCS completa.
+const MAX_LCS_CELLS: usize = 4_000_000;
+pub const HUNK_HEADER_COLOR: egui::Color32 = egui::Color32::from_rgb(40, 170, 190);
+
+/// Trozos de texto en bytes con su color, como los del resaltado del editor.
+pub type ColorSpans = Vec<(egui::Color32, Range<usize>)>;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// Diff por líneas de `old` a `new` basado en la subsecuencia común más larga.
+/// Con archivos enormes la zona central distinta se muestra entera como borrada y añadida.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let line = |kind, text: &str| DiffLine { kind, text: text.to_string() };
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(|text| line(DiffKind::Same, text)).collect();
+
+    if old_mid.len() * new_mid.len() > MAX_LCS_CELLS {
+        lines.extend(old_mid.iter().map(|text| line(DiffKind::Removed, text)));
+        lines.extend(new_mid.iter().map(|text| line(DiffKind::Added, text)));
+    } else {
+        // lcs[i][j] = longitud de la LCS de old_mid[i..] y new_mid[j..]
+        let width = new_mid.len() + 1;
+        let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() || j < new_mid.len() {
+            if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+                lines.push(line(DiffKind::Same, old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if j < new_mid.len() && (i == old_mid.len() || lcs[i * width + j + 1] >= lcs[(i + 1) * width + j]) {
+                lines.push(line(DiffKind::Added, new_mid[j]));
+                j += 1;
+            } else {
+                lines.push(line(DiffKind::Removed, old_mid[i]));
+                i += 1;
+            }
+        }
+    }
+
+    lines.extend(old[old.len() - suffix..].iter().map(|text| line(DiffKind::Same, text)));
+    lines
+}
+
+/// Dibuja el diff con `+`/`-` y fondo verde/rojo. Devuelve `false` cuando el usuario cierra la ventana.
+pub fn show_diff_window(ctx: &egui::Context, title: &str, lines: &[DiffLine], palette: &StatusPalette) -> bool {
+    let mut open = true;
+    egui::Window::new(title)
+        .id(egui::Id::new("diff_viewer"))
+        .open(&mut open)
+        .default_width(700.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::both().max_height(500.0).show(ui, |ui| {
+                for diff_line in lines {
+                    let (sign, color) = match diff_line.kind {
+                        DiffKind::Same => (' ', None),
+                        DiffKind::Added => ('+', Some(palette.added)),
+                        DiffKind::Removed => ('-', Some(palette.removed)),
+                    };
+                    let mut text = egui::RichText::new(format!("{} {}", sign, diff_line.text)).monospace();
+                    if let Some(color) = color {
+                        text = text.background_color(color.gamma_multiply(0.25));
+                    }
+                    ui.label(text);
+                }
+            });
+        });
+    open
+}
+
+/// Si `text` parece la salida de `git diff` (o de `git stash show -p`): alguna cabecera de archivo o de bloque.
+pub fn is_unified_diff(text: &str) -> bool {
+    text.lines().any(|line| line.starts_with("diff --git ") || line.starts_with("@@ "))
+}
+
+/// Colores de sintaxis para el código de cada archivo del diff, como rangos en bytes de `diff_text`. El código de un
+/// archivo (sin el `+`, `-` o espacio inicial) se resalta junto con `highlight`, que recibe la ruta de `+++ b/...` y
+/// devuelve `None` para los lenguajes sin gramática.
+pub fn diff_code_spans(
+    diff_text: &str,
+    mut highlight: impl FnMut(&Path, &str) -> Option<ColorSpans>,
+) -> ColorSpans {
+    let mut spans = Vec::new();
+    let mut path: Option<&str> = None;
+    let mut in_hunk = false;
+    // Código del archivo en curso y, por cada línea, dónde empieza en ese código y en `diff_text`.
+    let mut source = String::new();
+    let mut lines: Vec<(usize, usize, usize)> = Vec::new();
+    let mut flush = |path: Option<&str>, source: &mut String, lines: &mut Vec<(usize, usize, usize)>| {
+        if let Some(colored) = path.and_then(|path| highlight(Path::new(path), source)) {
+            for (color, span) in colored {
+                for &(code_start, diff_start, len) in lines.iter().filter(|(start, _, len)| span.start < start + len && *start < span.end) {
+                    let start = span.start.max(code_start) - code_start;
+                    let end = span.end.min(code_start + len) - code_start;
+                    spans.push((color, diff_start + start..diff_start + end));
+                }
+            }
+        }
+        source.clear();
+        lines.clear();
+    };
+
+    let mut offset = 0;
+    for line in diff_text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if line.starts_with("diff --git ") {
+            flush(path, &mut source, &mut lines);
+            path = None;
+            in_hunk = false;
+        } else if let Some(new_path) = line.strip_prefix("+++ ").filter(|_| !in_hunk) {
+            path = Some(new_path.trim_end().strip_prefix("b/").unwrap_or(new_path.trim_end()));
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+        } else if in_hunk && line.starts_with([' ', '+', '-']) {
+            let code = line[1..].trim_end_matches('\n');
+            lines.push((source.len(), start + 1, code.len()));
+            source.push_str(code);
+            source.push('\n');
+        }
+    }
+    flush(path, &mut source, &mut lines);
+    spans
+}
+
+/// Salida de `git diff` línea a línea: `+` en verde, `-` en rojo y las cabeceras `@@` en cian. Si hay `code_spans`
+/// (de `diff_code_spans`), el código va con sus colores de sintaxis y el verde o el rojo pasan al fondo.
+pub fn render_diff_output(ui: &mut egui::Ui, diff_text: &str, code_spans: &[(egui::Color32, Range<usize>)], palette: &StatusPalette) {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let mut spans = code_spans.iter().peekable();
+    let mut offset = 0;
+    for line in diff_text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches('\n');
+        let header = line.starts_with("+++") || line.starts_with("---");
+        let (color, background) = match line.chars().next() {
+            Some('@') if line.starts_with("@@") => (HUNK_HEADER_COLOR, egui::Color32::TRANSPARENT),
+            Some('+') if !header => (palette.added, palette.added.gamma_multiply(0.15)),
+            Some('-') if !header => (palette.removed, palette.removed.gamma_multiply(0.15)),
+            _ => (text_color, egui::Color32::TRANSPARENT),
+        };
+        let format = |color| egui::TextFormat { font_id: font.clone(), color, background, ..Default::default() };
+
+        let mut job = egui::text::LayoutJob::default();
+        let mut position = start;
+        let end = start + line.len();
+        while let Some((span_color, span)) = spans.next_if(|(_, span)| span.start < end) {
+            if span.start > position {
+                job.append(&diff_text[position..span.start], 0.0, format(color));
+            }
+            job.append(&diff_text[span.start.max(position)..span.end.min(end)], 0.0, format(*span_color));
+            position = span.end.min(end);
+        }
+        if position < end {
+            job.append(&diff_text[position..end], 0.0, format(color));
+        }
+        if job.sections.is_empty() {
+            job.append(" ", 0.0, format(color));
+        }
+        ui.label(job);
+    }
+}
@@ -0,0 +1,101 @@
+// Enlaces que se pueden abrir desde el editor: URLs escritas en el texto y la documentación de crates.
+
+use tree_sitter::{Node, Tree};
+
+const STD_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+const LOCAL_ROOTS: &[&str] = &["crate", "self", "super"];
+const URL_SCHEMES: &[&str] = &["https://", "http://"];
+
+/// URL `http(s)://` que contiene `cursor`, sin la puntuación final típica de una frase o un literal.
+pub fn url_at(content: &str, cursor: usize) -> Option<String> {
+    let cursor = cursor.min(content.len());
+    let is_delimiter = |c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '`' | '(' | ')');
+    let start = content[..cursor].rfind(is_delimiter).map_or(0, |i| i + 1);
+    let end = content[cursor..].find(is_delimiter).map_or(content.len(), |i| cursor + i);
+    let candidate = content[start..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    URL_SCHEMES
+        .iter()
+        .find_map(|scheme| candidate.find(scheme).map(|i| &candidate[i..]))
+        .filter(|url| !url.ends_with("://"))
+        .map(str::to_string)
+}
+
+/// Documentación del crate (o del ítem de la biblioteca estándar) nombrado en el `use` o `extern crate` bajo el cursor.
+pub fn rust_docs_url(tree: &Tree, source: &[u8], cursor: usize) -> Option<String> {
+    let node = tree.root_node().descendant_for_byte_range(cursor, cursor)?;
+    let identifier = if node.kind() == "identifier" {
+        node
+    } else {
+        // El cursor puede estar justo después del identificador.
+        tree.root_node()
+            .descendant_for_byte_range(cursor.saturating_sub(1), cursor.saturating_sub(1))
+            .filter(|node| node.kind() == "identifier")?
+    };
+    let text = |node: Node| String::from_utf8_lossy(&source[node.byte_range()]).into_owned();
+
+    let mut segments = vec![text(identifier)];
+    let mut current = identifier;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "extern_crate_declaration" => {
+                let name = parent.child_by_field_name("name")?;
+                return Some(docs_url(&[text(name)]));
+            }
+            "use_declaration" => return (!LOCAL_ROOTS.contains(&segments[0].as_str())).then(|| docs_url(&segments)),
+            // Solo el nombre (o la lista) añade el prefijo; si el nodo es el propio prefijo ya está completo.
+            "scoped_identifier" | "scoped_use_list" if parent.child_by_field_name("path") != Some(current) => {
+                if let Some(path) = parent.child_by_field_name("path") {
+                    let prefix = text(path);
+                    segments.splice(0..0, prefix.split("::").map(|segment| segment.trim().to_string()));
+                }
+            }
+            "scoped_identifier" | "scoped_use_list" | "use_list" | "use_as_clause" | "use_wildcard" => {}
+            _ => return None,
+        }
+        current = parent;
+    }
+    None
+}
+
+/// Nombre del crate de la dependencia declarada en la línea del cursor de un `Cargo.toml`.
+pub fn cargo_dependency_at(content: &str, cursor: usize) -> Option<String> {
+    let cursor = cursor.min(content.len());
+    let line_start = content[..cursor].rfind('\n').map_or(0, |i| i + 1);
+    let line = content[line_start..].lines().next().unwrap_or("").trim();
+    let is_dependency_table = |table: &str| table.ends_with("dependencies");
+
+    if let Some(table) = line.strip_prefix('[') {
+        let (table, name) = table.trim_end_matches(']').rsplit_once('.')?;
+        return is_dependency_table(table).then(|| name.trim_matches('"').to_string());
+    }
+    let key = line.split_once('=')?.0.trim().trim_matches('"');
+    let header = content[..line_start].lines().rev().map(str::trim).find(|line| line.starts_with('['))?;
+    let table = header.trim_start_matches('[').trim_end_matches(']');
+    (is_dependency_table(table) && !key.is_empty()).then(|| key.to_string())
+}
+
+/// `https://docs.rs/{crate}` para crates externos y `https://doc.rust-lang.org/std/...` para la biblioteca estándar.
+/// El último segmento apunta a su módulo si empieza en minúscula y, si no, a una búsqueda dentro del módulo padre.
+pub fn docs_url(segments: &[String]) -> String {
+    let root = segments[0].as_str();
+    let base = if STD_CRATES.contains(&root) {
+        format!("https://doc.rust-lang.org/{}", root)
+    } else if segments.len() == 1 {
+        return format!("https://docs.rs/{}", root);
+    } else {
+        format!("https://docs.rs/{}/latest/{}", root, root.replace('-', "_"))
+    };
+    let Some((last, modules)) = segments[1..].split_last() else {
+        return format!("{}/", base);
+    };
+    let mut url = base;
+    for module in modules {
+        url.push('/');
+        url.push_str(module);
+    }
+    if last.starts_with(|c: char| c.is_lowercase()) {
+        format!("{}/{}/", url, last)
+    } else {
+        format!("{}/?search={}", url, last)
+    }
+}
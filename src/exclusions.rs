@@ -0,0 +1,91 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+pub const PROJECT_FILE: &str = ".rbeditor.toml";
+const PATTERNS_KEY: &str = "excluded_patterns";
+
+/// Rutas que no se muestran en el panel lateral ni entran en las búsquedas del proyecto, guardadas como
+/// `excluded_patterns` en `.rbeditor.toml` de la raíz. Los patrones son globs como los de `schema_associations.toml`
+/// y una carpeta excluye también lo que hay dentro; con `/` delante se cuentan desde la raíz y sin ninguna `/` valen
+/// para ese nombre en cualquier carpeta.
+#[derive(Clone, Default)]
+pub struct Exclusions {
+    root: Option<PathBuf>,
+    pub patterns: Vec<String>,
+}
+
+impl Exclusions {
+    /// Los errores al leer el archivo del proyecto se añaden a `errors`.
+    pub fn load(root: &Path, errors: &mut Vec<String>) -> Self {
+        let mut exclusions = Self { root: Some(root.to_path_buf()), patterns: Vec::new() };
+        // Que no exista el archivo es lo normal en la mayoría de proyectos.
+        let Ok(content) = fs::read_to_string(root.join(PROJECT_FILE)) else {
+            return exclusions;
+        };
+        match content.parse::<toml::Table>() {
+            Ok(table) => {
+                if let Some(toml::Value::Array(patterns)) = table.get(PATTERNS_KEY) {
+                    exclusions.patterns = patterns.iter().filter_map(|pattern| pattern.as_str().map(str::to_string)).collect();
+                }
+            }
+            Err(e) => errors.push(format!("Unable to read {}: {}", PROJECT_FILE, e)),
+        }
+        exclusions
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let Some(relative) = self.root.as_deref().and_then(|root| path.strip_prefix(root).ok()) else {
+            return false;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.patterns.iter().any(|pattern| {
+            let pattern = pattern.trim_end_matches('/');
+            match pattern.strip_prefix('/') {
+                Some(anchored) => anchored_match(anchored, &relative),
+                None if pattern.contains('/') => anchored_match(pattern, &relative),
+                // Sin `/` el patrón se compara con cada nombre de la ruta, como en `.gitignore`.
+                None => relative.split('/').any(|name| config::glob_matches(pattern, name)),
+            }
+        })
+    }
+
+    /// Excluye `path` (relativa a la raíz) y guarda la lista.
+    pub fn add(&mut self, path: &Path) -> io::Result<()> {
+        let Some(relative) = self.root.as_deref().and_then(|root| path.strip_prefix(root).ok()) else {
+            return Ok(());
+        };
+        // Con `/` delante solo excluye esa ruta, no otras con el mismo nombre en subcarpetas.
+        let pattern = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+        if !self.patterns.contains(&pattern) {
+            self.patterns.push(pattern);
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        if index < self.patterns.len() {
+            self.patterns.remove(index);
+        }
+        self.save()
+    }
+
+    // Se conservan las demás claves que tuviera el archivo.
+    fn save(&self) -> io::Result<()> {
+        let Some(root) = &self.root else {
+            return Ok(());
+        };
+        let path = root.join(PROJECT_FILE);
+        let mut table = fs::read_to_string(&path).ok().and_then(|content| content.parse::<toml::Table>().ok()).unwrap_or_default();
+        let patterns = self.patterns.iter().cloned().map(toml::Value::String).collect();
+        table.insert(PATTERNS_KEY.to_string(), toml::Value::Array(patterns));
+        fs::write(path, toml::to_string_pretty(&table).map_err(io::Error::other)?)
+    }
+}
+
+// La ruta misma, todo lo que hay dentro si es una carpeta, o lo que coincida con el glob desde la raíz.
+fn anchored_match(pattern: &str, relative: &str) -> bool {
+    relative == pattern || relative.starts_with(&format!("{}/", pattern)) || config::glob_matches(&format!("/{}", pattern), &format!("/{}", relative))
+}
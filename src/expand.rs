@@ -0,0 +1,180 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use tree_sitter::{Node, Tree};
+use tree_sitter_highlight::Highlighter;
+
+use crate::folding;
+use crate::highlight;
+use crate::language::{EditorLanguage, LanguageProfile};
+
+#[derive(Debug)]
+pub enum ExpandError {
+    // `cargo expand` es un subcomando aparte (`cargo install cargo-expand`).
+    NotInstalled,
+    Io(io::Error),
+    Failed(String),
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::NotInstalled => write!(f, "cargo-expand is not installed (cargo install cargo-expand)"),
+            ExpandError::Io(e) => write!(f, "unable to run cargo: {}", e),
+            ExpandError::Failed(stderr) => write!(f, "cargo expand failed: {}", stderr.trim()),
+        }
+    }
+}
+
+impl From<io::Error> for ExpandError {
+    fn from(e: io::Error) -> Self {
+        ExpandError::Io(e)
+    }
+}
+
+/// Ejecuta `cargo expand --ugly item_name` en `project_root` y devuelve el código expandido de ese elemento.
+/// Con `item_name` vacío se expande el crate entero.
+pub fn cargo_expand_item(project_root: &Path, item_name: &str) -> Result<String, ExpandError> {
+    let mut command = Command::new("cargo");
+    command.args(["expand", "--ugly"]).current_dir(project_root);
+    if !item_name.is_empty() {
+        command.arg(item_name);
+    }
+    let output = command.output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        if stderr.contains("no such command") {
+            return Err(ExpandError::NotInstalled);
+        }
+        return Err(ExpandError::Failed(stderr.into_owned()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Directorio más cercano a `file` que tiene un `Cargo.toml`.
+pub fn project_root(file: &Path) -> Option<PathBuf> {
+    file.ancestors().skip(1).find(|dir| dir.join("Cargo.toml").is_file()).map(Path::to_path_buf)
+}
+
+/// Ruta (`modulo::elemento`) del elemento de nivel superior que contiene la invocación de macro en `byte`,
+/// o `None` si el cursor no está en una macro. Los elementos sin nombre (como `impl`) expanden su módulo entero.
+pub fn item_path_at(tree: &Tree, content: &str, file: &Path, project_root: &Path, byte: usize) -> Option<String> {
+    let node = tree.root_node().descendant_for_byte_range(byte, byte)?;
+    let invocation = ancestors(node).find(|node| node.kind() == "macro_invocation")?;
+
+    let mut segments = module_segments(file, project_root);
+    let mut inline_modules = Vec::new();
+    let mut item_name = None;
+    for node in ancestors(invocation) {
+        let top_level = node.parent().is_some_and(|parent| {
+            parent.kind() == "source_file" || (parent.kind() == "declaration_list" && parent.parent().is_some_and(|p| p.kind() == "mod_item"))
+        });
+        if !top_level {
+            continue;
+        }
+        let name = node.child_by_field_name("name").map(|name| content[name.byte_range()].to_string());
+        if node.kind() == "mod_item" {
+            inline_modules.extend(name);
+        } else if item_name.is_none() && inline_modules.is_empty() {
+            item_name = Some(name);
+        }
+    }
+    segments.extend(inline_modules.into_iter().rev());
+    segments.extend(item_name.flatten());
+    Some(segments.join("::"))
+}
+
+fn ancestors(node: Node) -> impl Iterator<Item = Node> {
+    std::iter::successors(Some(node), Node::parent)
+}
+
+// `src/main.rs` y `src/lib.rs` son la raíz del crate; `src/a/mod.rs` es `a` y `src/a/b.rs`, `a::b`.
+fn module_segments(file: &Path, project_root: &Path) -> Vec<String> {
+    let Ok(relative) = file.strip_prefix(project_root.join("src")) else {
+        return Vec::new();
+    };
+    let mut segments: Vec<String> = relative.iter().map(|part| part.to_string_lossy().into_owned()).collect();
+    if let Some(last) = segments.pop() {
+        let stem = last.trim_end_matches(".rs");
+        if !["main", "lib", "mod"].contains(&stem) {
+            segments.push(stem.to_string());
+        }
+    }
+    segments
+}
+
+/// Ventana con el resultado de `cargo expand`, que se ejecuta en segundo plano.
+pub struct MacroExpansion {
+    item: String,
+    receiver: Option<Receiver<Result<String, ExpandError>>>,
+    result: Option<Result<String, String>>,
+    spans: Vec<(egui::Color32, Range<usize>)>,
+}
+
+impl MacroExpansion {
+    pub fn start(project_root: PathBuf, item: String) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let item_name = item.clone();
+        thread::spawn(move || {
+            let _ = sender.send(cargo_expand_item(&project_root, &item_name));
+        });
+        Self { item, receiver: Some(receiver), result: None, spans: Vec::new() }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Recoge el resultado cuando termina y lo resalta con la gramática de Rust.
+    pub fn poll(&mut self, text_color: egui::Color32, syntax_colors: &HashMap<String, egui::Color32>) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("cargo expand stopped unexpectedly".to_string()),
+        };
+        self.receiver = None;
+        if let Ok(code) = &result {
+            if let Some(config) = LanguageProfile::for_language(EditorLanguage::Rust).and_then(|profile| profile.highlight_config()) {
+                let colors = highlight::capture_colors(config.names(), syntax_colors, text_color);
+                let spans = highlight::highlight_spans(&mut Highlighter::new(), &config, &colors, text_color, code);
+                self.spans = highlight::span_ranges(code, spans);
+            }
+        }
+        self.result = Some(result);
+    }
+
+    /// Devuelve `false` cuando el usuario cierra la ventana.
+    pub fn show(&self, ctx: &egui::Context, font: egui::FontId) -> bool {
+        let mut open = true;
+        let title = if self.item.is_empty() { "Expanded: crate".to_string() } else { format!("Expanded: {}", self.item) };
+        egui::Window::new(title).id(egui::Id::new("macro_expansion")).open(&mut open).default_width(600.0).show(ctx, |ui| {
+            match &self.result {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Running cargo expand…");
+                    });
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                }
+                Some(Ok(code)) => {
+                    egui::ScrollArea::both().max_height(500.0).show(ui, |ui| {
+                        let color = ui.visuals().text_color();
+                        ui.label(folding::layout_job(code, &self.spans, &[], &[], font, color, f32::INFINITY));
+                    });
+                }
+            }
+        });
+        open
+    }
+}
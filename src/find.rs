@@ -0,0 +1,224 @@
+use eframe::egui;
+use regex::Regex;
+use std::ops::Range;
+
+pub const MATCH_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(200, 180, 40, 50);
+pub const ACTIVE_MATCH_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(255, 160, 0, 140);
+
+pub enum FindAction {
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+}
+
+/// Búsqueda con expresión regular (o literal, con "Plain text") en el archivo abierto; `matches` se recalcula cada
+/// vez que cambian la consulta o el texto mientras la barra está abierta.
+pub struct FindReplace {
+    pub query: String,
+    pub replacement: String,
+    pub plain_text: bool,
+    pub matches: Vec<Range<usize>>,
+    pub active: Option<usize>,
+    pub open: bool,
+    // Error de compilación de la expresión regular, que se muestra bajo la barra.
+    pub error: Option<String>,
+    focus_query: bool,
+}
+
+impl FindReplace {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            replacement: String::new(),
+            plain_text: true,
+            matches: Vec::new(),
+            active: None,
+            open: false,
+            error: None,
+            focus_query: false,
+        }
+    }
+
+    pub fn open(&mut self, content: &str) {
+        self.open = true;
+        self.focus_query = true;
+        self.update(content);
+    }
+
+    /// Cierra la barra y deja de marcar coincidencias.
+    pub fn close(&mut self) {
+        self.open = false;
+        self.matches.clear();
+        self.active = None;
+        self.error = None;
+    }
+
+    pub fn set_query(&mut self, query: &str, content: &str) {
+        self.query = query.to_string();
+        self.active = None;
+        self.search(content);
+    }
+
+    pub fn update(&mut self, content: &str) {
+        if self.open {
+            self.search(content);
+        } else {
+            self.matches.clear();
+            self.active = None;
+        }
+    }
+
+    fn regex(&self) -> Result<Option<Regex>, regex::Error> {
+        if self.query.is_empty() {
+            return Ok(None);
+        }
+        let pattern = if self.plain_text { regex::escape(&self.query) } else { self.query.clone() };
+        Regex::new(&pattern).map(Some)
+    }
+
+    fn search(&mut self, content: &str) {
+        self.error = None;
+        self.matches = match self.regex() {
+            // Las coincidencias vacías (p. ej. `a*`) no se pueden marcar ni sustituir una a una.
+            Ok(Some(regex)) => regex.find_iter(content).filter(|m| !m.is_empty()).map(|m| m.range()).collect(),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Vec::new()
+            }
+        };
+        self.active = self.active.filter(|&i| i < self.matches.len());
+    }
+
+    /// Texto que sustituye a la coincidencia `range`; con expresión regular se expanden `$1`, `${name}`…
+    pub fn replacement_for(&self, content: &str, range: Range<usize>) -> Option<String> {
+        let regex = if self.plain_text { None } else { Some(self.regex().ok()??) };
+        self.expand(regex.as_ref(), content, range)
+    }
+
+    /// Contenido con todas las coincidencias marcadas sustituidas, o `None` si no hay ninguna.
+    pub fn replace_all(&self, content: &str) -> Option<String> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let regex = if self.plain_text { None } else { Some(self.regex().ok()??) };
+        let mut replaced = String::with_capacity(content.len());
+        let mut last = 0;
+        for range in &self.matches {
+            replaced.push_str(content.get(last..range.start)?);
+            replaced.push_str(&self.expand(regex.as_ref(), content, range.clone())?);
+            last = range.end;
+        }
+        replaced.push_str(&content[last..]);
+        Some(replaced)
+    }
+
+    fn expand(&self, regex: Option<&Regex>, content: &str, range: Range<usize>) -> Option<String> {
+        let Some(regex) = regex else {
+            return Some(self.replacement.clone());
+        };
+        let captures = regex.captures_at(content, range.start)?;
+        if captures.get(0)?.range() != range {
+            return None;
+        }
+        let mut replaced = String::new();
+        captures.expand(&self.replacement, &mut replaced);
+        Some(replaced)
+    }
+
+    /// Avanza a la siguiente coincidencia (o a la primera tras `cursor` si no hay ninguna activa).
+    /// Devuelve la coincidencia y si la búsqueda dio la vuelta al documento.
+    pub fn next(&mut self, cursor: usize) -> Option<(Range<usize>, bool)> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let (index, wrapped) = match self.active {
+            Some(i) if i + 1 < self.matches.len() => (i + 1, false),
+            Some(_) => (0, true),
+            None => match self.matches.iter().position(|m| m.start >= cursor) {
+                Some(i) => (i, false),
+                None => (0, true),
+            },
+        };
+        self.active = Some(index);
+        Some((self.matches[index].clone(), wrapped))
+    }
+
+    pub fn previous(&mut self, cursor: usize) -> Option<(Range<usize>, bool)> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let last = self.matches.len() - 1;
+        let (index, wrapped) = match self.active {
+            Some(i) if i > 0 => (i - 1, false),
+            Some(_) => (last, true),
+            None => match self.matches.iter().rposition(|m| m.end <= cursor) {
+                Some(i) => (i, false),
+                None => (last, true),
+            },
+        };
+        self.active = Some(index);
+        Some((self.matches[index].clone(), wrapped))
+    }
+
+    /// Texto del indicador de la barra de estado, p. ej. "Match 3 of 12".
+    pub fn status(&self) -> Option<String> {
+        if self.query.is_empty() {
+            return None;
+        }
+        Some(match self.active {
+            Some(i) => format!("Match {} of {}", i + 1, self.matches.len()),
+            None => format!("{} matches", self.matches.len()),
+        })
+    }
+
+    /// Barra entre la barra de herramientas y el editor; el botón `×` la cierra.
+    pub fn show(&mut self, ui: &mut egui::Ui, content: &str) -> Option<FindAction> {
+        let mut action = None;
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Find:");
+            let response = ui.text_edit_singleline(&mut self.query);
+            if std::mem::take(&mut self.focus_query) {
+                response.request_focus();
+            }
+            changed |= response.changed();
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = Some(if ui.input(|i| i.modifiers.shift) { FindAction::Previous } else { FindAction::Next });
+                response.request_focus();
+            }
+            changed |= ui.checkbox(&mut self.plain_text, "Plain text").changed();
+            if ui.button("⏶").on_hover_text("Find Previous (Shift+F3)").clicked() {
+                action = Some(FindAction::Previous);
+            }
+            if ui.button("⏷").on_hover_text("Find Next (F3)").clicked() {
+                action = Some(FindAction::Next);
+            }
+            if let Some(status) = self.status() {
+                ui.label(status);
+            }
+            if ui.small_button("×").on_hover_text("Close (Ctrl+F)").clicked() {
+                self.close();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Replace:");
+            ui.text_edit_singleline(&mut self.replacement);
+            if ui.add_enabled(!self.matches.is_empty(), egui::Button::new("Replace")).clicked() {
+                action = Some(FindAction::Replace);
+            }
+            if ui.add_enabled(!self.matches.is_empty(), egui::Button::new("Replace All")).clicked() {
+                action = Some(FindAction::ReplaceAll);
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        if changed && self.open {
+            self.active = None;
+            self.search(content);
+        }
+        action
+    }
+}
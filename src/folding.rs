@@ -0,0 +1,219 @@
+use eframe::egui;
+use regex::Regex;
+use std::ops::Range;
+use tree_sitter::Tree;
+
+// Tamaño de letra de las líneas ocultas: sus filas quedan prácticamente sin altura, pero el texto sigue en la galería
+// y los índices de carácter del TextEdit no cambian.
+const HIDDEN_FONT_SIZE: f32 = 0.1;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FoldKind {
+    Imports,
+    // Entre una línea que casa con el patrón de inicio de región de los ajustes y la de fin.
+    Region,
+}
+
+/// Región plegable. `range` abarca líneas completas (sin el último salto de línea); al plegarla, la primera
+/// línea se sustituye por `placeholder` y las demás se ocultan.
+pub struct Fold {
+    pub kind: FoldKind,
+    pub range: Range<usize>,
+    pub start_line: usize,
+    pub placeholder: String,
+    pub collapsed: bool,
+}
+
+/// Un pliegue por cada grupo de `use` consecutivos de nivel superior que ocupe más de una línea.
+pub fn import_folds(tree: &Tree, content: &str, collapsed: bool) -> Vec<Fold> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let mut blocks: Vec<(Range<usize>, usize)> = Vec::new();
+    let mut in_block = false;
+    for child in root.named_children(&mut cursor) {
+        if child.kind() != "use_declaration" {
+            in_block = false;
+            continue;
+        }
+        match blocks.last_mut() {
+            Some((range, count)) if in_block => {
+                range.end = child.end_byte();
+                *count += 1;
+            }
+            _ => blocks.push((child.start_byte()..child.end_byte(), 1)),
+        }
+        in_block = true;
+    }
+    blocks
+        .into_iter()
+        .filter(|(range, _)| content[range.clone()].contains('\n'))
+        .map(|(range, count)| {
+            let start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+            let end = content[range.end..].find('\n').map_or(content.len(), |i| range.end + i);
+            Fold {
+                kind: FoldKind::Imports,
+                range: start..end,
+                start_line: content[..start].matches('\n').count(),
+                placeholder: format!("use ... ({} imports)", count),
+                collapsed,
+            }
+        })
+        .collect()
+}
+
+/// Pares de líneas que casan con `start_pattern` y `end_pattern`, anidados como paréntesis. Cada rango en bytes va
+/// del principio de la línea de inicio al final (sin el salto de línea) de la de fin.
+pub fn find_regex_fold_regions(content: &str, start_pattern: &Regex, end_pattern: &Regex) -> Vec<(usize, usize)> {
+    let mut open = Vec::new();
+    let mut regions = Vec::new();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let line_end = line_start + line.trim_end_matches('\n').len();
+        let text = &content[line_start..line_end];
+        if end_pattern.is_match(text) {
+            if let Some(start) = open.pop() {
+                regions.push((start, line_end));
+            }
+        } else if start_pattern.is_match(text) {
+            open.push(line_start);
+        }
+        line_start += line.len();
+    }
+    regions.sort_unstable();
+    regions
+}
+
+/// Un pliegue por región; el texto sustituto es la propia línea de inicio.
+pub fn region_folds(content: &str, start_pattern: &Regex, end_pattern: &Regex) -> Vec<Fold> {
+    find_regex_fold_regions(content, start_pattern, end_pattern)
+        .into_iter()
+        .map(|(start, end)| {
+            let first_line = content[start..end].lines().next().unwrap_or_default();
+            Fold {
+                kind: FoldKind::Region,
+                range: start..end,
+                start_line: content[..start].matches('\n').count(),
+                placeholder: format!("{} ...", first_line.trim_end()),
+                collapsed: false,
+            }
+        })
+        .collect()
+}
+
+/// Conserva el estado plegado de los pliegues que siguen empezando en la misma línea tras una edición.
+pub fn carry_over(old: &[Fold], new: &mut [Fold]) {
+    for fold in new {
+        if let Some(previous) = old.iter().find(|previous| previous.kind == fold.kind && previous.start_line == fold.start_line) {
+            fold.collapsed = previous.collapsed;
+        }
+    }
+}
+
+// Altura máxima de la vista previa de un pliegue; lo que no quepa se desplaza.
+const PREVIEW_MAX_HEIGHT: f32 = 300.0;
+
+/// Contenido de la ayuda emergente de un pliegue: el texto de `range` con los colores de `highlights`.
+pub fn render_folded_preview(
+    ui: &mut egui::Ui,
+    content: &str,
+    range: Range<usize>,
+    highlights: &[(egui::Color32, Range<usize>)],
+    font: egui::FontId,
+) {
+    if content.get(range.clone()).is_none() {
+        return;
+    }
+    let default = ui.visuals().text_color();
+    let mut job = egui::text::LayoutJob::default();
+    let mut position = range.start;
+    for (color, span) in highlights.iter().filter(|(_, span)| span.end > range.start && span.start < range.end) {
+        let (start, end) = (span.start.max(position), span.end.min(range.end));
+        let Some(piece) = content.get(start..end) else {
+            continue;
+        };
+        if start > position {
+            job.append(&content[position..start], 0.0, egui::TextFormat::simple(font.clone(), default));
+        }
+        job.append(piece, 0.0, egui::TextFormat::simple(font.clone(), *color));
+        position = end;
+    }
+    if let Some(rest) = content.get(position..range.end).filter(|rest| !rest.is_empty()) {
+        job.append(rest, 0.0, egui::TextFormat::simple(font, default));
+    }
+    egui::ScrollArea::vertical().max_height(PREVIEW_MAX_HEIGHT).show(ui, |ui| {
+        ui.label(job);
+    });
+}
+
+/// Trabajo de maquetación del editor: cada trozo de `spans` con su color, las líneas plegadas ocultas y la primera
+/// de cada pliegue transparente, para dibujar encima el texto sustituto. `gaps` (byte y anchura, en orden) deja un
+/// hueco antes de esos bytes sin añadir caracteres, donde se dibujan las pistas en línea.
+pub fn layout_job(
+    content: &str,
+    spans: &[(egui::Color32, Range<usize>)],
+    folds: &[Fold],
+    gaps: &[(usize, f32)],
+    font: egui::FontId,
+    color: egui::Color32,
+    wrap_width: f32,
+) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let transparent = egui::TextFormat::simple(font.clone(), egui::Color32::TRANSPARENT);
+    let hidden = egui::TextFormat::simple(egui::FontId::new(HIDDEN_FONT_SIZE, font.family.clone()), egui::Color32::TRANSPARENT);
+
+    // Pliegues y colores son del último análisis; si el texto acaba de cambiar pueden no encajar y se dejan para el siguiente fotograma.
+    let mut collapsed: Vec<&Fold> = folds.iter().filter(|fold| fold.collapsed).collect();
+    collapsed.sort_by_key(|fold| fold.range.start);
+    let mut masks: Vec<(Range<usize>, egui::TextFormat)> = Vec::new();
+    let mut last = 0;
+    for fold in collapsed {
+        let Some(region) = content.get(fold.range.clone()).filter(|_| fold.range.start >= last) else {
+            continue;
+        };
+        let first_line_end = fold.range.start + region.find('\n').unwrap_or(region.len());
+        masks.push((fold.range.start..first_line_end, transparent.clone()));
+        masks.push((first_line_end..fold.range.end, hidden.clone()));
+        last = fold.range.end;
+    }
+    let spans_fit = spans.last().map(|(_, span)| span.end) == Some(content.len())
+        && spans.iter().all(|(_, span)| content.is_char_boundary(span.start) && content.is_char_boundary(span.end));
+    let fallback = [(color, 0..content.len())];
+    let spans = if spans_fit { spans } else { &fallback[..] };
+
+    let mut masks = masks.into_iter().peekable();
+    let mut gaps = gaps.iter().filter(|(offset, _)| content.is_char_boundary(*offset)).peekable();
+    for (span_color, span) in spans {
+        let mut position = span.start;
+        while position < span.end {
+            while masks.peek().is_some_and(|(mask, _)| mask.end <= position) {
+                masks.next();
+            }
+            while gaps.peek().is_some_and(|(offset, _)| *offset < position) {
+                gaps.next();
+            }
+            let (mut end, format, masked) = match masks.peek() {
+                Some((mask, format)) if mask.start <= position => (mask.end.min(span.end), format.clone(), true),
+                Some((mask, _)) => (mask.start.min(span.end), egui::TextFormat::simple(font.clone(), *span_color), false),
+                None => (span.end, egui::TextFormat::simple(font.clone(), *span_color), false),
+            };
+            // El texto plegado no deja huecos.
+            let mut leading_space = 0.0;
+            if let Some(&&(offset, width)) = gaps.peek() {
+                if offset == position {
+                    leading_space = if masked { 0.0 } else { width };
+                    gaps.next();
+                }
+            }
+            if let Some(&&(offset, _)) = gaps.peek() {
+                end = end.min(offset.max(position + 1));
+            }
+            while !content.is_char_boundary(end) {
+                end += 1;
+            }
+            job.append(&content[position..end], leading_space, format);
+            position = end;
+        }
+    }
+    job.wrap.max_width = wrap_width;
+    job
+}
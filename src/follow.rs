@@ -0,0 +1,65 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const FOLLOW_EXTENSIONS: &[&str] = &["log", "out", "txt"];
+
+pub enum FollowUpdate {
+    Appended(String),
+    Reloaded(String),
+}
+
+pub fn is_followable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| FOLLOW_EXTENSIONS.contains(&extension))
+}
+
+/// Estado de `tail -f` del archivo abierto: tamaño leído hasta ahora y si el seguimiento está activo.
+pub struct FollowState {
+    pub last_size: u64,
+    pub polling: bool,
+    last_poll: Instant,
+}
+
+impl FollowState {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            last_size: fs::metadata(path).map_or(0, |metadata| metadata.len()),
+            polling: false,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Comprueba el tamaño cada `POLL_INTERVAL`: si creció lee solo los bytes nuevos y si encogió relee el archivo entero.
+    pub fn poll(&mut self, path: &Path) -> Option<FollowUpdate> {
+        if !self.polling || self.last_poll.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+        let size = fs::metadata(path).ok()?.len();
+        if size == self.last_size {
+            return None;
+        }
+        if size < self.last_size {
+            let content = fs::read_to_string(path).ok()?;
+            self.last_size = size;
+            return Some(FollowUpdate::Reloaded(content));
+        }
+
+        let mut file = File::open(path).ok()?;
+        file.seek(SeekFrom::Start(self.last_size)).ok()?;
+        let mut delta = Vec::new();
+        file.take(size - self.last_size).read_to_end(&mut delta).ok()?;
+        // No se corta un carácter UTF-8 a medias: lo que falte llegará en la siguiente lectura.
+        let complete = match std::str::from_utf8(&delta) {
+            Ok(_) => delta.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => delta.len(),
+        };
+        self.last_size += complete as u64;
+        Some(FollowUpdate::Appended(String::from_utf8_lossy(&delta[..complete]).into_owned()))
+    }
+}
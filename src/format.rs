@@ -0,0 +1,205 @@
+use std::fmt;
+use std::io::Write;
+use std::ops::Range;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use tree_sitter::Tree;
+
+use crate::text::line_indent;
+
+// No se formatea mientras se escribe en archivos más grandes que esto.
+pub const FORMAT_ON_TYPE_MAX_BYTES: usize = 500 * 1024;
+
+// Comentario que se inserta en la posición del cursor para poder recuperarla tras pasar por rustfmt.
+const CURSOR_MARKER: &str = "/*rbeditor_cursor*/";
+
+const WRAPPER_HEADER: &str = "fn __rbeditor_format__() {\n";
+const WRAPPER_FOOTER: &str = "\n}\n";
+
+// Nodos que se formatean como una unidad al cerrar una sentencia o abrir un bloque.
+const ITEM_KINDS: &[&str] = &[
+    "function_item",
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "impl_item",
+    "trait_item",
+    "mod_item",
+    "const_item",
+    "static_item",
+    "type_item",
+    "use_declaration",
+    "extern_crate_declaration",
+    "macro_definition",
+];
+
+const STATEMENT_KINDS: &[&str] = &["let_declaration", "expression_statement"];
+
+#[derive(Debug)]
+pub enum FormatError {
+    TooLarge(usize),
+    Io(std::io::Error),
+    Rustfmt(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::TooLarge(size) => write!(f, "file too large to format on type ({} bytes)", size),
+            FormatError::Io(e) => write!(f, "unable to run rustfmt: {}", e),
+            FormatError::Rustfmt(stderr) => write!(f, "rustfmt failed: {}", stderr.trim()),
+        }
+    }
+}
+
+impl From<std::io::Error> for FormatError {
+    fn from(e: std::io::Error) -> Self {
+        FormatError::Io(e)
+    }
+}
+
+/// Formatea el texto de `node_range` con rustfmt y devuelve `content` con el resultado insertado en su lugar.
+pub fn format_node(content: &str, node_range: Range<usize>) -> Result<String, FormatError> {
+    if content.len() > FORMAT_ON_TYPE_MAX_BYTES {
+        return Err(FormatError::TooLarge(content.len()));
+    }
+
+    // Primero como item de nivel superior; si rustfmt lo rechaza, se envuelve en una función.
+    let node_text = &content[node_range.clone()];
+    let body = match run_rustfmt(node_text) {
+        Ok(formatted) => formatted.trim_end().to_string(),
+        Err(FormatError::Rustfmt(_)) => {
+            let snippet = format!("{}{}{}", WRAPPER_HEADER, node_text, WRAPPER_FOOTER);
+            unwrap_statement(&run_rustfmt(&snippet)?)
+        }
+        Err(e) => return Err(e),
+    };
+
+    let indent = line_indent(content, node_range.start);
+    let reindented = body
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", indent, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..node_range.start]);
+    result.push_str(&reindented);
+    result.push_str(&content[node_range.end..]);
+    Ok(result)
+}
+
+/// `format_node` de lo recién cerrado en un hilo, para que rustfmt no pare el editor mientras se escribe. Guarda el
+/// texto y el cursor con que se lanzó: si al acabar ya no coinciden con los del editor, el resultado se descarta.
+pub struct OnTypeFormat {
+    pub source: String,
+    pub cursor: usize,
+    receiver: Receiver<Result<Option<(String, usize)>, FormatError>>,
+}
+
+impl OnTypeFormat {
+    pub fn start(source: String, cursor: usize, node_range: Range<usize>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut marked = source.clone();
+        thread::spawn(move || {
+            marked.insert_str(cursor, CURSOR_MARKER);
+            let range = node_range.start..node_range.end + CURSOR_MARKER.len();
+            let _ = sender.send(format_node(&marked, range.clone()).map(|formatted| take_cursor_marker(formatted, range.start)));
+        });
+        Self { source, cursor, receiver }
+    }
+
+    /// El texto formateado y la nueva posición del cursor; `None` mientras rustfmt sigue en marcha.
+    pub fn poll(&self) -> Option<Result<Option<(String, usize)>, FormatError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(FormatError::Rustfmt("formatting stopped unexpectedly".to_string()))),
+        }
+    }
+}
+
+// Quita el marcador del cursor y devuelve dónde estaba; si quedó al final de la línea, se lleva el espacio de antes.
+fn take_cursor_marker(mut formatted: String, from: usize) -> Option<(String, usize)> {
+    let mut marker = from + formatted[from..].find(CURSOR_MARKER)?;
+    formatted.replace_range(marker..marker + CURSOR_MARKER.len(), "");
+    let at_line_end = formatted[marker..].starts_with('\n') || marker == formatted.len();
+    if at_line_end && formatted[..marker].ends_with(' ') {
+        marker -= 1;
+        formatted.remove(marker);
+    }
+    Some((formatted, marker))
+}
+
+/// Busca el nodo a formatear tras teclear `;` o `{` seguido de salto de línea, con el cursor en `cursor`.
+pub fn on_type_target(tree: &Tree, content: &str, cursor: usize) -> Option<Range<usize>> {
+    let before = &content[..cursor];
+    let trigger = if before.ends_with(';') {
+        cursor - 1
+    } else {
+        let line = before.trim_end_matches([' ', '\t']);
+        let line = line.strip_suffix('\n')?.trim_end_matches([' ', '\t', '\r']);
+        if !line.ends_with('{') {
+            return None;
+        }
+        line.len() - 1
+    };
+
+    let mut node = tree.root_node().descendant_for_byte_range(trigger, trigger + 1)?;
+    loop {
+        if ITEM_KINDS.contains(&node.kind()) || STATEMENT_KINDS.contains(&node.kind()) {
+            break;
+        }
+        node = node.parent()?;
+    }
+
+    if node.has_error() || !(node.start_byte()..=node.end_byte()).contains(&cursor) {
+        return None;
+    }
+    Some(node.byte_range())
+}
+
+fn unwrap_statement(formatted: &str) -> String {
+    let mut lines: Vec<&str> = formatted.trim_end().lines().collect();
+    if lines.len() >= 2 {
+        lines.remove(0);
+        lines.pop();
+    }
+    lines
+        .iter()
+        .map(|line| line.strip_prefix("    ").unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formatea un archivo completo con rustfmt.
+pub fn format_file(content: &str) -> Result<String, FormatError> {
+    run_rustfmt(content)
+}
+
+fn run_rustfmt(source: &str) -> Result<String, FormatError> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(source.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(FormatError::Rustfmt(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug)]
+pub enum GitError {
+    Io(std::io::Error),
+    Failed { code: Option<i32>, stderr: String },
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::Io(e) => write!(f, "unable to run git: {}", e),
+            GitError::Failed { code: Some(code), stderr } => write!(f, "git exited with {}: {}", code, stderr.trim()),
+            GitError::Failed { code: None, stderr } => write!(f, "git was terminated: {}", stderr.trim()),
+        }
+    }
+}
+
+impl From<std::io::Error> for GitError {
+    fn from(e: std::io::Error) -> Self {
+        GitError::Io(e)
+    }
+}
+
+/// Ejecuta `git` con `args` en `cwd` y devuelve su stdout.
+pub fn run_git_command(args: &[&str], cwd: &Path) -> Result<String, GitError> {
+    let output = Command::new("git").args(args).current_dir(cwd).output()?;
+    if !output.status.success() {
+        return Err(GitError::Failed {
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn is_git_repo(dir: &Path) -> bool {
+    run_git_command(&["rev-parse", "--is-inside-work-tree"], dir).is_ok_and(|out| out.trim() == "true")
+}
+
+pub struct StatusEntry {
+    // Las dos columnas de `git status --short` (índice y árbol de trabajo), p. ej. "M ", " M" o "??".
+    pub code: String,
+    pub path: String,
+}
+
+impl StatusEntry {
+    pub fn is_staged(&self) -> bool {
+        !matches!(self.code.chars().next(), Some(' ' | '?') | None)
+    }
+}
+
+/// Archivos modificados según `git status --short`, con rutas relativas a `dir`.
+pub fn status_short(dir: &Path) -> Result<Vec<StatusEntry>, GitError> {
+    let output = run_git_command(&["status", "--short"], dir)?;
+    Ok(output
+        .lines()
+        .filter(|line| line.len() > 3)
+        .map(|line| {
+            let path = &line[3..];
+            // Los renombrados aparecen como "antiguo -> nuevo".
+            let path = path.rsplit_once(" -> ").map_or(path, |(_, new)| new);
+            StatusEntry {
+                code: line[..2].to_string(),
+                path: path.trim_matches('"').to_string(),
+            }
+        })
+        .collect())
+}
+
+pub struct LogEntry {
+    pub hash: String,
+    pub date: String,
+    pub author: String,
+    pub message: String,
+    // Ruta del archivo en ese commit, relativa a la raíz del repositorio (cambia si el archivo se renombró).
+    pub path: String,
+}
+
+/// Hasta `count` commits que tocan `file`, empezando tras los `skip` más recientes (`git log --follow`).
+pub fn file_log(file: &Path, skip: usize, count: usize) -> Result<Vec<LogEntry>, GitError> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let output = run_git_command(
+        &[
+            "log",
+            "--follow",
+            "--name-only",
+            "--date=short",
+            "--format=%x1e%h%x09%ad%x09%an%x09%s",
+            &format!("--skip={}", skip),
+            "-n",
+            &count.to_string(),
+            "--",
+            &name,
+        ],
+        dir,
+    )?;
+    Ok(output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut lines = record.lines().filter(|line| !line.is_empty());
+            let mut fields = lines.next()?.splitn(4, '\t');
+            Some(LogEntry {
+                hash: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                message: fields.next().unwrap_or("").to_string(),
+                path: lines.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Contenido de `path` (relativa a la raíz del repositorio) en el commit `hash`.
+pub fn show_file_at(dir: &Path, hash: &str, path: &str) -> Result<String, GitError> {
+    let root = run_git_command(&["rev-parse", "--show-toplevel"], dir)?;
+    run_git_command(&["show", &format!("{}:{}", hash, path)], Path::new(root.trim()))
+}
+
+/// Rama actual, o el commit abreviado si HEAD no apunta a ninguna.
+pub fn current_branch(dir: &Path) -> Result<String, GitError> {
+    let branch = run_git_command(&["rev-parse", "--abbrev-ref", "HEAD"], dir)?.trim().to_string();
+    if branch != "HEAD" {
+        return Ok(branch);
+    }
+    Ok(run_git_command(&["rev-parse", "--short", "HEAD"], dir)?.trim().to_string())
+}
+
+/// Resultado de `git cherry-pick`: la salida de git, las rutas absolutas de los archivos que cambió en el árbol de
+/// trabajo y, si se paró por conflictos, las de los que quedaron con marcadores.
+pub struct CherryPick {
+    pub output: String,
+    pub changed: Vec<PathBuf>,
+    pub conflicts: Vec<PathBuf>,
+}
+
+pub fn cherry_pick(dir: &Path, hash: &str) -> Result<CherryPick, GitError> {
+    match run_git_command(&["cherry-pick", hash], dir) {
+        Ok(output) => {
+            let changed = repo_paths(dir, &["diff", "--name-only", "HEAD~1", "HEAD"])?;
+            Ok(CherryPick { output, changed, conflicts: Vec::new() })
+        }
+        Err(GitError::Failed { code, stderr }) => {
+            let conflicts = repo_paths(dir, &["diff", "--name-only", "--diff-filter=U"])?;
+            if conflicts.is_empty() {
+                return Err(GitError::Failed { code, stderr });
+            }
+            // A medias, lo aplicado sin conflictos está en el índice y el resto en el árbol: todo difiere de HEAD.
+            let changed = repo_paths(dir, &["diff", "--name-only", "HEAD"])?;
+            Ok(CherryPick { output: stderr, changed, conflicts })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Rutas que lista `git diff --name-only …`, absolutas desde la raíz del repositorio.
+fn repo_paths(dir: &Path, args: &[&str]) -> Result<Vec<PathBuf>, GitError> {
+    let root = run_git_command(&["rev-parse", "--show-toplevel"], dir)?;
+    let listed = run_git_command(args, dir)?;
+    Ok(listed.lines().filter(|line| !line.is_empty()).map(|line| Path::new(root.trim()).join(line)).collect())
+}
+
+pub fn local_branches(dir: &Path) -> Result<Vec<String>, GitError> {
+    let output = run_git_command(&["branch", "--format=%(refname:short)"], dir)?;
+    Ok(output.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+}
+
+/// `git checkout branch` (o `-b branch` si `create`) y las rutas absolutas de los archivos que difieren entre el HEAD
+/// anterior y el nuevo, que son los que hay que volver a leer si están abiertos.
+pub fn checkout(dir: &Path, branch: &str, create: bool) -> Result<Vec<PathBuf>, GitError> {
+    let previous = run_git_command(&["rev-parse", "HEAD"], dir)?;
+    let args: &[&str] = if create { &["checkout", "-b", branch] } else { &["checkout", branch] };
+    run_git_command(args, dir)?;
+    repo_paths(dir, &["diff", "--name-only", previous.trim(), "HEAD"])
+}
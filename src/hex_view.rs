@@ -0,0 +1,58 @@
+use eframe::egui;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+// Bytes del principio del archivo en los que se busca un NUL para decidir si es binario.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+const BYTES_PER_ROW: usize = 16;
+
+/// `true` si en los primeros 8 KB hay algún byte nulo, que no aparece en texto UTF-8 normal.
+pub fn looks_binary(path: &Path) -> bool {
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    match File::open(path).and_then(|file| file.take(BINARY_SNIFF_BYTES as u64).read_to_end(&mut head)) {
+        Ok(_) => head.contains(&0),
+        Err(_) => false,
+    }
+}
+
+/// Vista de solo lectura de un archivo binario: desplazamiento, bytes en hexadecimal y su lectura ASCII.
+pub struct HexView {
+    path: PathBuf,
+    bytes: Vec<u8>,
+}
+
+impl HexView {
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let bytes = std::fs::read(&path)?;
+        Ok(Self { path, bytes })
+    }
+
+    /// Devuelve `false` cuando el usuario cierra la ventana.
+    pub fn show(&self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        let name = self.path.file_name().map_or_else(|| self.path.to_string_lossy(), |name| name.to_string_lossy());
+        egui::Window::new(format!("Hex View: {}", name)).open(&mut open).default_width(620.0).show(ctx, |ui| {
+            ui.weak(format!("{} bytes", self.bytes.len()));
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            let rows = self.bytes.len().div_ceil(BYTES_PER_ROW);
+            // Solo se maquetan las filas visibles, así que el tamaño del archivo no afecta al fotograma.
+            egui::ScrollArea::vertical().max_height(500.0).auto_shrink([false, true]).show_rows(ui, row_height, rows, |ui, range| {
+                for row in range {
+                    let start = row * BYTES_PER_ROW;
+                    let chunk = &self.bytes[start..(start + BYTES_PER_ROW).min(self.bytes.len())];
+                    ui.monospace(format_row(start, chunk));
+                }
+            });
+        });
+        open
+    }
+}
+
+fn format_row(offset: usize, chunk: &[u8]) -> String {
+    let hex: Vec<String> = (0..BYTES_PER_ROW)
+        .map(|i| chunk.get(i).map_or_else(|| "  ".to_string(), |byte| format!("{:02x}", byte)))
+        .collect();
+    let ascii: String = chunk.iter().map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' }).collect();
+    format!("{:08x}  {}  {}", offset, hex.join(" "), ascii)
+}
@@ -0,0 +1,99 @@
+use eframe::egui::Color32;
+use std::collections::HashMap;
+use std::ops::Range;
+use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent, Highlighter};
+
+/// Reconoce todas las capturas de la consulta, para que el índice de cada `Highlight` sea su posición en `names()`.
+pub fn configure(config: &mut HighlightConfiguration) {
+    let names = config.names().to_vec();
+    config.configure(&names);
+}
+
+/// Un color por captura de `names()`, buscado en `syntax_colors` por el primer segmento del nombre
+/// (`function.method` usa el de `function`); las que no tienen uno propio (puntuación, variables…) usan `default`.
+pub fn capture_colors(names: &[String], syntax_colors: &HashMap<String, Color32>, default: Color32) -> Vec<Color32> {
+    names
+        .iter()
+        .map(|name| {
+            let key = match name.split('.').next().unwrap_or(name) {
+                "escape" => "string",
+                "constructor" => "type",
+                "label" => "property",
+                key => key,
+            };
+            syntax_colors.get(key).copied().unwrap_or(default)
+        })
+        .collect()
+}
+
+/// Trozos consecutivos de `source` con el color de la captura más interna que los cubre.
+/// Si el resaltado falla, devuelve el archivo entero en `default`.
+pub fn highlight_spans<'a>(
+    highlighter: &mut Highlighter,
+    config: &HighlightConfiguration,
+    colors: &[Color32],
+    default: Color32,
+    source: &'a str,
+) -> Vec<(Color32, &'a str)> {
+    let events = match highlighter.highlight(config, source.as_bytes(), None, |_| None) {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("Unable to highlight: {:?}", e);
+            return vec![(default, source)];
+        }
+    };
+    let mut stack: Vec<Highlight> = Vec::new();
+    let mut spans = Vec::new();
+    for event in events {
+        match event {
+            Ok(HighlightEvent::Source { start, end }) => {
+                let color = stack.last().and_then(|highlight| colors.get(highlight.0)).copied().unwrap_or(default);
+                if let Some(text) = source.get(start..end) {
+                    spans.push((color, text));
+                }
+            }
+            Ok(HighlightEvent::HighlightStart(highlight)) => stack.push(highlight),
+            Ok(HighlightEvent::HighlightEnd) => {
+                stack.pop();
+            }
+            Err(e) => {
+                eprintln!("Unable to highlight: {:?}", e);
+                return vec![(default, source)];
+            }
+        }
+    }
+    spans
+}
+
+/// Los trozos de `highlight_spans` como rangos en bytes de `source`, para guardarlos sin tomar prestado el texto.
+pub fn span_ranges(source: &str, spans: Vec<(Color32, &str)>) -> Vec<(Color32, Range<usize>)> {
+    spans
+        .into_iter()
+        .map(|(color, text)| {
+            let start = text.as_ptr() as usize - source.as_ptr() as usize;
+            (color, start..start + text.len())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyword_fn_gets_its_own_color() {
+        let mut config =
+            HighlightConfiguration::new(tree_sitter_rust::language(), tree_sitter_rust::HIGHLIGHT_QUERY, "", "").unwrap();
+        configure(&mut config);
+        let default = Color32::BLACK;
+        let colors = capture_colors(config.names(), &crate::themes::syntax_colors(crate::settings::ThemeName::Light), default);
+        let source = "fn main() { let total = count; }";
+        let spans = highlight_spans(&mut Highlighter::new(), &config, &colors, default, source);
+
+        let color_of = |text: &str| spans.iter().find(|(_, span)| *span == text).map(|(color, _)| *color);
+        let keyword = color_of("fn").expect("`fn` should be its own span");
+        assert_ne!(keyword, default);
+        assert_ne!(Some(keyword), color_of("count"));
+        assert_eq!(spans.iter().map(|(_, span)| *span).collect::<String>(), source);
+    }
+}
@@ -0,0 +1,68 @@
+use eframe::egui;
+use std::ops::Range;
+
+/// Colores y letra de la página exportada, los mismos del editor.
+pub struct HtmlStyle {
+    pub background: egui::Color32,
+    pub text: egui::Color32,
+    pub font_size: f32,
+    pub line_spacing: f32,
+}
+
+/// Página HTML autónoma con `content` resaltado según `spans`: los estilos van en un `<style>` propio y los números
+/// de línea en una columna aparte que no se copia al seleccionar el código.
+pub fn export_html(title: &str, content: &str, spans: &[(egui::Color32, Range<usize>)], style: &HtmlStyle) -> String {
+    // Si el resaltado es de otro análisis y ya no encaja con el texto, se exporta sin colores.
+    let fits = spans.last().map(|(_, span)| span.end) == Some(content.len())
+        && spans.iter().all(|(_, span)| content.is_char_boundary(span.start) && content.is_char_boundary(span.end));
+    let fallback = [(style.text, 0..content.len())];
+    let spans = if fits { spans } else { &fallback };
+
+    let mut code = String::new();
+    for (color, span) in spans.iter().filter(|(_, span)| !span.is_empty()) {
+        let piece = escape_html(&content[span.clone()]);
+        if *color == style.text {
+            code.push_str(&piece);
+        } else {
+            code.push_str(&format!("<span style=\"color:{}\">{}</span>", hex(*color), piece));
+        }
+    }
+    let line_count = content.split('\n').count();
+    let numbers: Vec<String> = (1..=line_count).map(|line| line.to_string()).collect();
+
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+body {{ margin: 0; background: {background}; color: {text}; }}
+table {{ border-collapse: collapse; }}
+td {{ vertical-align: top; padding: 8px; }}
+pre {{ margin: 0; font-family: ui-monospace, Menlo, Consolas, monospace; font-size: {font_size}px; line-height: {line_spacing}; }}
+.line-numbers {{ text-align: right; opacity: 0.5; user-select: none; border-right: 1px solid; }}
+</style>
+</head>
+<body>
+<table><tr><td class=\"line-numbers\"><pre>{numbers}</pre></td><td><pre>{code}</pre></td></tr></table>
+</body>
+</html>
+",
+        title = escape_html(title),
+        background = hex(style.background),
+        text = hex(style.text),
+        font_size = style.font_size,
+        line_spacing = style.line_spacing,
+        numbers = numbers.join("\n"),
+        code = code,
+    )
+}
+
+fn hex(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+pub const LOCALES_DIR: &str = "locales";
+pub const DEFAULT_LOCALE: &str = "en";
+
+// Las claves son el propio texto en inglés, así que el inglés no necesita tabla y es el respaldo de cualquier clave
+// que falte en otro idioma.
+const SPANISH: &[(&str, &str)] = &[
+    ("File", "Archivo"),
+    ("Open File", "Abrir archivo"),
+    ("Paste as New File", "Pegar como archivo nuevo"),
+    ("Save", "Guardar"),
+    ("Save All", "Guardar todo"),
+    ("Don't Save", "No guardar"),
+    ("Cancel", "Cancelar"),
+    ("Unsaved Changes", "Cambios sin guardar"),
+    ("Save changes before quitting?", "¿Guardar los cambios antes de salir?"),
+    ("Save a Copy As...", "Guardar una copia como..."),
+    ("Export as HTML...", "Exportar como HTML..."),
+    ("View", "Ver"),
+    ("Show Line Numbers", "Mostrar números de línea"),
+    ("Show Annotations", "Mostrar anotaciones"),
+    ("Show Indent Guides", "Mostrar guías de sangría"),
+    ("Rainbow Brackets", "Paréntesis de colores"),
+    ("Show Minimap", "Mostrar minimapa"),
+    ("Minimap zoom", "Aumento del minimapa"),
+    ("Collapse Imports", "Plegar importaciones"),
+    ("Typing Statistics", "Estadísticas de escritura"),
+    ("Find (Ctrl+F)", "Buscar (Ctrl+F)"),
+    ("Go to Symbol in Project (Ctrl+T)", "Ir a símbolo del proyecto (Ctrl+T)"),
+    ("Search in Files", "Buscar en archivos"),
+    ("Search", "Buscar"),
+    ("Search in Project", "Buscar en el proyecto"),
+    ("Search Recent Files", "Buscar en archivos recientes"),
+    ("Open a directory to search its files.", "Abre una carpeta para buscar en sus archivos."),
+    ("results shown; refine the search to see the rest.", "resultados mostrados; afina la búsqueda para ver el resto."),
+    ("History", "Historial"),
+    ("Outline", "Esquema"),
+    ("Rename Symbol", "Renombrar símbolo"),
+    ("Refactor", "Refactorizar"),
+    ("Toggle Comment", "Comentar / descomentar"),
+    ("Sort Imports", "Ordenar importaciones"),
+    ("Remove Unused Imports", "Quitar importaciones sin usar"),
+    ("Unused import", "Importación sin usar"),
+    ("Best effort: names used only through macros from other files or trait methods may be missed", "Aproximado: puede no ver los nombres que solo se usan en macros de otros archivos o con métodos de traits"),
+    ("Convert Closure to Named Function", "Convertir cierre en función con nombre"),
+    ("Convert Function to Closure", "Convertir función en cierre"),
+    ("Convert to match", "Convertir en match"),
+    ("Convert to if let", "Convertir en if let"),
+    ("Convert Escape", "Convertir escape"),
+    ("Extract Function", "Extraer función"),
+    ("Add Documentation Comment", "Añadir comentario de documentación"),
+    ("Toggle pub Visibility", "Alternar visibilidad pub"),
+    ("Escape / Unescape", "Escapar / desescapar"),
+    ("Convert to Raw String", "Convertir en cadena cruda"),
+    ("Cycle Number Representation", "Cambiar la base del número"),
+    ("Escape Character", "Escapar carácter"),
+    ("Build", "Compilar"),
+    ("Build (cargo build)", "Compilar (cargo build)"),
+    ("Run (cargo run)", "Ejecutar (cargo run)"),
+    ("Format File (rustfmt)", "Formatear archivo (rustfmt)"),
+    ("Show Output", "Mostrar salida"),
+    ("Developer", "Desarrollo"),
+    ("Performance", "Rendimiento"),
+    ("Tools", "Herramientas"),
+    ("Tree-sitter Query Playground", "Pruebas de consultas de tree-sitter"),
+    ("Show File Checksums", "Mostrar sumas de comprobación"),
+    ("File Checksums", "Sumas de comprobación"),
+    ("File not yet saved", "El archivo aún no está guardado"),
+    ("Copy", "Copiar"),
+    ("Verify Checksum:", "Verificar suma:"),
+    ("matches", "coincide"),
+    ("No checksum matches", "No coincide ninguna suma"),
+    ("Settings", "Ajustes"),
+    ("Notes", "Notas"),
+    ("Follow", "Seguir"),
+    ("Append new lines as the file grows (tail -f)", "Añadir las líneas nuevas según crece el archivo (tail -f)"),
+    ("Tree View", "Vista de árbol"),
+    ("Select Schema", "Elegir esquema"),
+    ("Translation View", "Vista de traducciones"),
+    ("Switch Branch", "Cambiar de rama"),
+    ("Commit", "Confirmar"),
+    ("Show Diff", "Mostrar diferencias"),
+    ("Stash Changes", "Guardar cambios en el stash"),
+    ("Pop Stash", "Recuperar del stash"),
+    ("List Stashes", "Listar stashes"),
+    ("File History", "Historial del archivo"),
+    ("Filter files", "Filtrar archivos"),
+    ("Current Directory:", "Carpeta actual:"),
+    ("↑ Parent", "↑ Superior"),
+    ("Show hidden", "Mostrar ocultos"),
+    ("New File...", "Archivo nuevo..."),
+    ("Open Directory", "Abrir carpeta"),
+    ("Language", "Idioma"),
+    ("Theme", "Tema"),
+    ("Theme name", "Nombre del tema"),
+    ("Save as Theme", "Guardar como tema"),
+    ("Delete Theme", "Borrar tema"),
+    ("Light theme", "Tema claro"),
+    ("Dark theme", "Tema oscuro"),
+    ("Ctrl+Shift+T toggles between the light and dark themes.", "Ctrl+Mayús+T alterna entre el tema claro y el oscuro."),
+    ("Font size:", "Tamaño de letra:"),
+    ("Background color:", "Color de fondo:"),
+    ("Text color:", "Color del texto:"),
+    ("Indent guides", "Guías de sangría"),
+    ("Solid", "Continua"),
+    ("Dotted", "Punteada"),
+    ("Dashed", "Discontinua"),
+    ("Column fill", "Columna coloreada"),
+    ("Guide", "Guía"),
+    ("Current block", "Bloque actual"),
+    ("Color vision", "Visión del color"),
+    ("Font style:", "Tipo de letra:"),
+    ("Monospace", "Monoespaciada"),
+    ("Proportional", "Proporcional"),
+    ("Line spacing:", "Interlineado:"),
+    ("Cursor blink rate (Hz, 0 = solid):", "Parpadeo del cursor (Hz, 0 = fijo):"),
+    ("Undo depth:", "Niveles de deshacer:"),
+    ("Ask before opening files larger than:", "Preguntar antes de abrir archivos de más de:"),
+    ("Auto-save", "Guardado automático"),
+    ("Format on type (Rust)", "Formatear al escribir (Rust)"),
+    ("Sort imports on save (Rust)", "Ordenar importaciones al guardar (Rust)"),
+    ("Show diagnostics inline", "Mostrar diagnósticos en el texto"),
+    ("Inlay hints (rust-analyzer)", "Pistas en línea (rust-analyzer)"),
+    ("Hint size", "Tamaño de las pistas"),
+    ("Auto-pair emphasis (Markdown)", "Cerrar énfasis automáticamente (Markdown)"),
+    ("Format pasted JSON", "Formatear el JSON pegado"),
+    ("Track changes", "Control de cambios"),
+    ("Recent files to search:", "Archivos recientes en los que buscar:"),
+    ("License for {license} in file templates:", "Licencia para {license} en las plantillas:"),
+    ("Snippets (type the trigger and press Tab):", "Fragmentos (escribe el disparador y pulsa Tab):"),
+    ("Create Project Snippets File", "Crear archivo de fragmentos del proyecto"),
+    ("Outline queries (one per line, capture the symbol name as @name):", "Consultas del esquema (una por línea, captura el nombre del símbolo como @name):"),
+    ("Fold regions (regular expressions for the start and end lines):", "Regiones plegables (expresiones regulares de las líneas de inicio y fin):"),
+    ("Start:", "Inicio:"),
+    ("End:", "Fin:"),
+    ("Maximum line length", "Longitud máxima de línea"),
+    ("Long lines", "Líneas largas"),
+    ("Selection matches", "Coincidencias de la selección"),
+    ("Ambient sound", "Sonido de fondo"),
+    ("Ambient volume:", "Volumen del sonido de fondo:"),
+    ("Off", "Apagado"),
+    ("Rain", "Lluvia"),
+    ("Café", "Cafetería"),
+    ("White Noise", "Ruido blanco"),
+    ("Keyboard Clicks", "Teclado"),
+    ("Keyboard Shortcuts", "Atajos de teclado"),
+    ("Show All Files", "Mostrar todos los archivos"),
+    ("Exclude from Project", "Excluir del proyecto"),
+    ("Manage Exclusions", "Gestionar exclusiones"),
+    ("Nothing is excluded. Right-click a file in the sidebar to exclude it.", "No hay nada excluido. Haz clic derecho en un archivo del panel lateral para excluirlo."),
+    ("Delete", "Borrar"),
+    ("Invalid TOML at line", "TOML no válido en la línea"),
+    ("Go to Error", "Ir al error"),
+    ("Mark Region for Mirror Edit", "Marcar zona para edición en espejo"),
+    ("Mirror with Marked Region", "Enlazar con la zona marcada"),
+    ("Stop Mirror Edit", "Detener la edición en espejo"),
+    ("Accept Ours", "Aceptar lo nuestro"),
+    ("Accept Theirs", "Aceptar lo suyo"),
+    ("Accept Both", "Aceptar ambos"),
+    ("Conflict", "Conflicto"),
+];
+
+// Idiomas incluidos en el editor, con su etiqueta BCP 47 y su nombre en ese idioma.
+const BUILTIN_LOCALES: [(&str, &str); 2] = [("en", "English"), ("es", "Español")];
+
+/// Textos de la interfaz en el idioma elegido: la tabla incluida para el idioma (o su subetiqueta principal, `es` para
+/// `es-MX`) con `locales/<etiqueta>.toml` del directorio de configuración encima.
+pub struct Localizations {
+    strings: HashMap<String, String>,
+}
+
+impl Localizations {
+    pub fn load(locale: &str) -> Self {
+        let primary = locale.split(['-', '_']).next().unwrap_or(locale);
+        let builtin: &[(&str, &str)] = match primary {
+            "es" => SPANISH,
+            _ => &[],
+        };
+        let mut strings: HashMap<String, String> = builtin.iter().map(|(key, text)| (key.to_string(), text.to_string())).collect();
+        // Que no haya archivo propio es lo normal; las claves que no sean texto se ignoran.
+        if let Some(content) = locale_path(locale).and_then(|path| fs::read_to_string(path).ok()) {
+            match content.parse::<toml::Table>() {
+                Ok(table) => {
+                    for (key, value) in table {
+                        if let toml::Value::String(text) = value {
+                            strings.insert(key, text);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Unable to read locale {}: {}", locale, e),
+            }
+        }
+        Self { strings }
+    }
+
+    /// `key` traducido, o el propio `key` (el texto en inglés) si el idioma no lo tiene.
+    pub fn tr(&self, key: &str) -> String {
+        self.strings.get(key).map_or_else(|| key.to_string(), String::clone)
+    }
+}
+
+/// Idiomas que se pueden elegir: los incluidos y los de los archivos en `locales/`, como `(etiqueta, nombre)`.
+pub fn available_locales() -> Vec<(String, String)> {
+    let mut locales: Vec<(String, String)> = BUILTIN_LOCALES.iter().map(|(tag, name)| (tag.to_string(), name.to_string())).collect();
+    let entries = config::config_dir().and_then(|dir| fs::read_dir(dir.join(LOCALES_DIR)).ok());
+    for path in entries.into_iter().flatten().filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let Some(tag) = path.file_stem().filter(|_| path.extension().is_some_and(|ext| ext == "toml")) else {
+            continue;
+        };
+        let tag = tag.to_string_lossy().to_string();
+        if !locales.iter().any(|(known, _)| *known == tag) {
+            locales.push((tag.clone(), tag));
+        }
+    }
+    locales
+}
+
+fn locale_path(locale: &str) -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(LOCALES_DIR).join(format!("{}.toml", locale)))
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+const STD_CRATES: &[&str] = &["std", "core", "alloc"];
+const LOCAL_ROOTS: &[&str] = &["crate", "self", "super"];
+// Traits de std que se importan para llamar a sus métodos sin nombrarlos; sin análisis de tipos no se sabe si se
+// usan, así que nunca se dan por sobrantes.
+const METHOD_TRAITS: &[&str] = &[
+    "Write", "Read", "BufRead", "Seek", "FromStr", "Hash", "Hasher", "Borrow", "BorrowMut", "Iterator", "IntoIterator", "FromIterator",
+    "Extend", "Deref", "DerefMut", "AsRef", "AsMut", "Any", "Error", "FromRawFd", "AsRawFd", "OsStrExt", "OsStringExt", "PermissionsExt",
+    "CommandExt", "MetadataExt", "FileExt", "Digest",
+];
+
+/// Ordena y agrupa el primer bloque de `use` de nivel superior: std, luego crates externos y luego rutas locales.
+/// Las rutas con el mismo padre se fusionan en `use padre::{a, b};`. Los `use` con atributos no se mueven.
+pub fn sort_rust_imports(content: &str, tree: &Tree) -> String {
+    let uses = leading_use_block(tree);
+    let (Some(first), Some(last)) = (uses.first(), uses.last()) else {
+        return content.to_string();
+    };
+
+    // (visibilidad, padre) -> hojas
+    let mut merged: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    for node in &uses {
+        let Some(argument) = node.child_by_field_name("argument") else {
+            return content.to_string();
+        };
+        let visibility = node
+            .child(0)
+            .filter(|child| child.kind() == "visibility_modifier")
+            .map(|child| format!("{} ", &content[child.byte_range()]))
+            .unwrap_or_default();
+        let compact: String = content[argument.byte_range()].split_whitespace().collect::<Vec<_>>().join(" ");
+        for path in flatten_use_tree(&compact) {
+            let (parent, leaf) = match path.rfind("::") {
+                Some(i) if !path[i + 2..].is_empty() => (path[..i].to_string(), path[i + 2..].to_string()),
+                _ => (String::new(), path),
+            };
+            let leaves = merged.entry((visibility.clone(), parent)).or_default();
+            if !leaves.contains(&leaf) {
+                leaves.push(leaf);
+            }
+        }
+    }
+
+    let mut groups: [Vec<String>; 3] = Default::default();
+    for ((visibility, parent), mut leaves) in merged {
+        leaves.sort_by_key(|leaf| (leaf != "self", leaf.to_lowercase()));
+        // Sin padre cada hoja es una ruta completa (`use regex;`) y va al grupo de su propio crate.
+        if parent.is_empty() {
+            for leaf in leaves {
+                groups[import_group(&leaf)].push(format!("{}use {};", visibility, leaf));
+            }
+            continue;
+        }
+        let body = match leaves.len() {
+            1 if leaves[0] == "self" => parent.clone(),
+            1 => format!("{}::{}", parent, leaves[0]),
+            _ => format!("{}::{{{}}}", parent, leaves.join(", ")),
+        };
+        groups[import_group(&parent)].push(format!("{}use {};", visibility, body));
+    }
+
+    let block = groups
+        .iter_mut()
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            group.sort_by_key(|line| line.split_once("use ").map_or_else(|| line.to_lowercase(), |(_, path)| path.to_lowercase()));
+            group.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..first.start_byte()]);
+    result.push_str(&block);
+    result.push_str(&content[last.end_byte()..]);
+    result
+}
+
+// 0 para std, 1 para los crates externos y 2 para las rutas locales, según la primera parte de `path`.
+fn import_group(path: &str) -> usize {
+    let root = path.trim_start_matches("::").split("::").next().unwrap_or("");
+    if STD_CRATES.contains(&root) {
+        0
+    } else if LOCAL_ROOTS.contains(&root) {
+        2
+    } else {
+        1
+    }
+}
+
+/// `use` cuyos nombres no aparecen en ningún otro sitio del archivo, con el rango que hay que borrar para quitarlos
+/// (la línea entera si no hay nada más en ella). Es una aproximación: los `pub use`, los globs, los `use` con atributos
+/// (`#[cfg(...)]`, `#[allow(unused_imports)]`…) y los traits de `traits` (los del proyecto) o de `METHOD_TRAITS` se dan
+/// siempre por usados.
+pub fn unused_imports(tree: &Tree, content: &str, traits: &HashSet<String>) -> Vec<Range<usize>> {
+    let mut uses = Vec::new();
+    let mut used = HashSet::new();
+    collect_uses(tree.root_node(), content, &mut uses, &mut used);
+    uses.into_iter()
+        .filter(|node| {
+            let public = node.child(0).is_some_and(|child| child.kind() == "visibility_modifier");
+            let kept = public || has_attributes(*node) || node.has_error();
+            let Some(argument) = node.child_by_field_name("argument").filter(|_| !kept) else {
+                return false;
+            };
+            let compact: String = content[argument.byte_range()].split_whitespace().collect::<Vec<_>>().join(" ");
+            flatten_use_tree(&compact).iter().all(|path| match imported_name(path) {
+                Some(name) => !used.contains(name) && !traits.contains(name) && !METHOD_TRAITS.contains(&name),
+                None => false,
+            })
+        })
+        .map(|node| removal_range(content, node.byte_range()))
+        .collect()
+}
+
+// Separa los `use` del resto y apunta cada identificador que aparece fuera de ellos, también dentro de macros.
+fn collect_uses<'a>(node: Node<'a>, content: &'a str, uses: &mut Vec<Node<'a>>, used: &mut HashSet<&'a str>) {
+    if node.kind() == "use_declaration" {
+        uses.push(node);
+        return;
+    }
+    if node.child_count() == 0 {
+        if matches!(node.kind(), "identifier" | "type_identifier" | "field_identifier") {
+            used.insert(&content[node.byte_range()]);
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_uses(child, content, uses, used);
+    }
+}
+
+// Nombre que un `use` deja en el ámbito: el alias, la última parte de la ruta o el módulo de `modulo::self`.
+// `None` para los globs y para `as _`, que no se pueden comprobar por nombre.
+fn imported_name(path: &str) -> Option<&str> {
+    if let Some((_, alias)) = path.split_once(" as ") {
+        return (alias != "_").then_some(alias);
+    }
+    let mut segments = path.rsplit("::");
+    match segments.next()? {
+        "*" => None,
+        "self" => segments.next(),
+        name => Some(name),
+    }
+}
+
+// Los atributos son nodos hermanos anteriores: si se borrara solo el `use`, se quedarían pegados al ítem siguiente.
+fn has_attributes(node: Node) -> bool {
+    node.prev_sibling().is_some_and(|sibling| sibling.kind() == "attribute_item")
+}
+
+fn removal_range(content: &str, range: Range<usize>) -> Range<usize> {
+    let line_start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[range.end..].find('\n').map_or(content.len(), |i| range.end + i + 1);
+    let alone = content[line_start..range.start].trim().is_empty() && content[range.end..line_end].trim().is_empty();
+    if alone {
+        line_start..line_end
+    } else {
+        range
+    }
+}
+
+/// `content` sin los rangos de `ranges`, que no se solapan y van en orden.
+pub fn remove_ranges(content: &str, ranges: &[Range<usize>]) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for range in ranges {
+        result.push_str(&content[last..range.start]);
+        last = range.end;
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+// Primer grupo contiguo de `use_declaration` sin atributos en la raíz del archivo; uno con atributo corta el grupo, porque
+// al reordenar su atributo acabaría encima de otro `use`.
+fn leading_use_block(tree: &Tree) -> Vec<Node<'_>> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let mut uses = Vec::new();
+    for child in root.named_children(&mut cursor) {
+        match child.kind() {
+            "use_declaration" if !child.has_error() && !has_attributes(child) => uses.push(child),
+            _ if uses.is_empty() => {}
+            _ => break,
+        }
+    }
+    uses
+}
+
+/// Expande un árbol de `use` (`a::{b, c::{d, e}}`) en rutas completas (`a::b`, `a::c::d`, `a::c::e`).
+fn flatten_use_tree(tree: &str) -> Vec<String> {
+    let tree = tree.trim();
+    let Some(open) = tree.find('{') else {
+        return vec![match tree.split_once(" as ") {
+            Some((path, alias)) => format!("{} as {}", path.replace(' ', ""), alias.trim()),
+            None => tree.replace(' ', ""),
+        }];
+    };
+    let prefix = tree[..open].trim().trim_end_matches("::").replace(' ', "");
+    let inner = tree[open + 1..].trim_end().strip_suffix('}').unwrap_or(&tree[open + 1..]);
+
+    let mut paths = Vec::new();
+    for item in split_top_level(inner) {
+        for path in flatten_use_tree(item) {
+            paths.push(if prefix.is_empty() { path } else { format!("{}::{}", prefix, path) });
+        }
+    }
+    paths
+}
+
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&list[start..]);
+    items.into_iter().map(str::trim).filter(|item| !item.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(source: &str) -> String {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        sort_rust_imports(source, &tree)
+    }
+
+    #[test]
+    fn single_segment_paths_go_to_their_own_crate_group() {
+        assert_eq!(sorted("use regex;\nuse std;\nuse crate::text;\n"), "use std;\n\nuse regex;\n\nuse crate::text;\n");
+    }
+
+    #[test]
+    fn attributed_use_stays_in_place() {
+        let source = "#[cfg(test)]\nuse std::fs;\nuse regex::Regex;\nuse std::io;\n";
+        assert_eq!(sorted(source), "#[cfg(test)]\nuse std::fs;\nuse std::io;\n\nuse regex::Regex;\n");
+    }
+}
@@ -0,0 +1,102 @@
+use eframe::egui;
+use std::ops::RangeInclusive;
+use tree_sitter::{Node, Tree};
+
+// Nodos cuyo contenido va entre delimitadores y suele ir sangrado un nivel.
+const BLOCK_KINDS: &[&str] = &[
+    "block",
+    "declaration_list",
+    "field_declaration_list",
+    "ordered_field_declaration_list",
+    "enum_variant_list",
+    "field_initializer_list",
+    "use_list",
+    "match_block",
+    "token_tree",
+];
+
+/// Pares `(primera línea, última línea)` (base 0, inclusivos) del interior de cada bloque de varias líneas.
+/// La guía de un bloque se alinea con la sangría de la línea donde se abre, es decir, `primera línea - 1`.
+pub fn compute_indent_guide_offsets(tree: &Tree, source: &[u8]) -> Vec<(usize, usize)> {
+    let mut guides = Vec::new();
+    collect_guides(tree.root_node(), source, &mut guides);
+    guides.sort_unstable();
+    guides.dedup();
+    guides
+}
+
+// Trazos de las guías punteadas y discontinuas, en píxeles.
+const DOT_SIZE: f32 = 2.0;
+const DOT_SPACING: f32 = 4.0;
+const DASH_LENGTH: f32 = 6.0;
+const DASH_GAP: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum IndentGuideStyle {
+    Solid,
+    Dotted,
+    Dashed,
+    // La columna de sangría entera coloreada en vez de una línea.
+    Fill,
+}
+
+impl IndentGuideStyle {
+    pub const ALL: [IndentGuideStyle; 4] = [IndentGuideStyle::Solid, IndentGuideStyle::Dotted, IndentGuideStyle::Dashed, IndentGuideStyle::Fill];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IndentGuideStyle::Solid => "Solid",
+            IndentGuideStyle::Dotted => "Dotted",
+            IndentGuideStyle::Dashed => "Dashed",
+            IndentGuideStyle::Fill => "Column fill",
+        }
+    }
+}
+
+/// Última guía (la más interior, porque van ordenadas) que contiene `line`.
+pub fn active_guide(guides: &[(usize, usize)], line: usize) -> Option<usize> {
+    guides.iter().rposition(|&(first, last)| first <= line && line <= last)
+}
+
+/// Dibuja una guía en `x` de `rows`; `width` es lo que mide un nivel de sangría y solo lo usa `Fill`.
+pub fn paint_guide(painter: &egui::Painter, style: IndentGuideStyle, x: f32, rows: RangeInclusive<f32>, width: f32, color: egui::Color32) {
+    let x = x.round() + 0.5;
+    let (top, bottom) = (*rows.start(), *rows.end());
+    match style {
+        IndentGuideStyle::Solid => painter.vline(x, rows, egui::Stroke::new(1.0, color)),
+        IndentGuideStyle::Dotted => {
+            let stroke = egui::Stroke::new(DOT_SIZE, color);
+            let mut y = top;
+            while y < bottom {
+                painter.line_segment([egui::pos2(x, y), egui::pos2(x, (y + DOT_SIZE).min(bottom))], stroke);
+                y += DOT_SPACING;
+            }
+        }
+        IndentGuideStyle::Dashed => {
+            let points = [egui::pos2(x, top), egui::pos2(x, bottom)];
+            painter.extend(egui::Shape::dashed_line(&points, egui::Stroke::new(1.0, color), DASH_LENGTH, DASH_GAP));
+        }
+        IndentGuideStyle::Fill => {
+            let column = egui::Rect::from_x_y_ranges(x - 0.5..=x - 0.5 + width, rows);
+            painter.rect_filled(column, 0.0, color.gamma_multiply(0.5));
+        }
+    }
+}
+
+fn collect_guides(node: Node, source: &[u8], guides: &mut Vec<(usize, usize)>) {
+    if BLOCK_KINDS.contains(&node.kind()) && !node.is_missing() {
+        let start_line = node.start_position().row + 1;
+        let end = node.end_position();
+        // Si el delimitador de cierre comparte línea con código, esa línea también queda dentro de la guía.
+        let closing_line_start = node.end_byte() - end.column;
+        let before_close = &source[closing_line_start..node.end_byte().saturating_sub(1).max(closing_line_start)];
+        let end_line = if before_close.iter().all(u8::is_ascii_whitespace) { end.row.saturating_sub(1) } else { end.row };
+        if end.row > node.start_position().row && end_line >= start_line {
+            guides.push((start_line, end_line));
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_guides(child, source, guides);
+    }
+}
@@ -0,0 +1,73 @@
+// Niveles de 0 a 5 y un último cubo para 6 o más.
+pub const HISTOGRAM_LEVELS: usize = 7;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum IndentStyle {
+    Spaces(usize),
+    Tabs,
+}
+
+impl IndentStyle {
+    /// Tabuladores si predominan al inicio de las líneas; si no, espacios con la menor sangría encontrada (4 por defecto).
+    pub fn detect(content: &str) -> Self {
+        let mut tab_lines = 0;
+        let mut space_lines = 0;
+        let mut smallest = None;
+        for line in content.lines() {
+            if line.starts_with('\t') {
+                tab_lines += 1;
+            } else if line.starts_with(' ') && !line.trim().is_empty() {
+                space_lines += 1;
+                let width = line.len() - line.trim_start_matches(' ').len();
+                smallest = Some(smallest.map_or(width, |s: usize| s.min(width)));
+            }
+        }
+        if tab_lines > space_lines {
+            IndentStyle::Tabs
+        } else {
+            IndentStyle::Spaces(smallest.unwrap_or(4).clamp(2, 8))
+        }
+    }
+
+    /// Texto de un nivel de sangría.
+    pub fn unit(self) -> String {
+        match self {
+            IndentStyle::Spaces(width) => " ".repeat(width),
+            IndentStyle::Tabs => "\t".to_string(),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            IndentStyle::Spaces(width) => format!("Spaces: {}", width),
+            IndentStyle::Tabs => "Tabs".to_string(),
+        }
+    }
+}
+
+/// Número de líneas no vacías en cada nivel de sangría.
+pub fn indentation_counts(content: &str, indent_style: IndentStyle) -> [usize; HISTOGRAM_LEVELS] {
+    let mut counts = [0; HISTOGRAM_LEVELS];
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let level = match indent_style {
+            IndentStyle::Tabs => line.len() - line.trim_start_matches('\t').len(),
+            IndentStyle::Spaces(width) => {
+                let columns: usize = line
+                    .chars()
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .map(|c| if c == '\t' { width } else { 1 })
+                    .sum();
+                columns / width.max(1)
+            }
+        };
+        counts[level.min(HISTOGRAM_LEVELS - 1)] += 1;
+    }
+    counts
+}
+
+/// Fracción de líneas no vacías en cada nivel de sangría; suma 1 salvo en archivos vacíos.
+pub fn indentation_histogram(content: &str, indent_style: IndentStyle) -> [f32; HISTOGRAM_LEVELS] {
+    let counts = indentation_counts(content, indent_style);
+    let total = counts.iter().sum::<usize>().max(1) as f32;
+    counts.map(|count| count as f32 / total)
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use tree_sitter::{Language, Parser};
+
+use crate::exclusions::Exclusions;
+use crate::outline::{self, Symbol};
+use crate::rename;
+
+pub type ProjectIndex = Arc<RwLock<HashMap<PathBuf, Vec<Symbol>>>>;
+
+/// Hilo que indexa los símbolos de los archivos Rust de un directorio y reindexa los que se le envían.
+/// La interfaz solo lee el índice con `try_read`, así que nunca se bloquea esperando al hilo.
+pub struct IndexerThread {
+    pub index: ProjectIndex,
+    sender: Sender<PathBuf>,
+}
+
+impl IndexerThread {
+    pub fn start(root: PathBuf, language: Language, exclusions: Exclusions) -> Self {
+        let index = ProjectIndex::default();
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+        let shared = Arc::clone(&index);
+        thread::spawn(move || {
+            let mut parser = Parser::new();
+            if parser.set_language(language).is_err() {
+                return;
+            }
+            let mut files = Vec::new();
+            rename::collect_files(&root, &exclusions, &mut files);
+            for path in files {
+                index_file(&mut parser, &shared, &path);
+            }
+            // Termina cuando se suelta el `IndexerThread` (por ejemplo, al abrir otro directorio).
+            for path in receiver {
+                index_file(&mut parser, &shared, &path);
+            }
+        });
+        Self { index, sender }
+    }
+
+    pub fn reindex(&self, path: &Path) {
+        // Si el hilo ya terminó no hay nada que actualizar.
+        let _ = self.sender.send(path.to_path_buf());
+    }
+}
+
+fn index_file(parser: &mut Parser, index: &ProjectIndex, path: &Path) {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("rs") {
+        return;
+    }
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+    let Some(tree) = parser.parse(&content, None) else {
+        return;
+    };
+    let symbols = outline::extract_symbols(&tree, content.as_bytes());
+    if let Ok(mut index) = index.write() {
+        index.insert(path.to_path_buf(), symbols);
+    }
+}
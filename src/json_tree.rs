@@ -0,0 +1,156 @@
+use eframe::egui;
+
+use crate::json::{self, Value};
+
+// Prefijo de las claves que se añaden con "Add sibling"; se numeran si ya existe.
+const NEW_KEY: &str = "new_key";
+
+/// Operación sobre el nodo de la ruta indicada (índices de entrada de objeto o de elemento de array desde la raíz).
+pub enum JsonEdit {
+    AddSibling(Vec<usize>),
+    InsertBefore(Vec<usize>),
+    InsertAfter(Vec<usize>),
+    Remove(Vec<usize>),
+}
+
+impl JsonEdit {
+    fn path(&self) -> &[usize] {
+        match self {
+            JsonEdit::AddSibling(path) | JsonEdit::InsertBefore(path) | JsonEdit::InsertAfter(path) | JsonEdit::Remove(path) => path,
+        }
+    }
+}
+
+/// Aplica `edit` sobre una copia de `root` y devuelve el documento reescrito con `indent`, ya validado.
+pub fn apply_edit(root: &Value, edit: &JsonEdit, indent: &str) -> Result<String, String> {
+    let mut root = root.clone();
+    let (&index, parent_path) = edit.path().split_last().ok_or("The root value cannot be edited")?;
+    let mut parent = &mut root;
+    for &step in parent_path {
+        parent = match parent {
+            Value::Object(entries) => entries.get_mut(step).map(|(_, value)| value),
+            Value::Array(items) => items.get_mut(step),
+            _ => None,
+        }
+        .ok_or("The tree is out of date")?;
+    }
+
+    match (parent, edit) {
+        (Value::Object(entries), JsonEdit::AddSibling(_)) if index < entries.len() => {
+            let key = unique_key(entries);
+            entries.insert(index + 1, (key, Value::Null));
+        }
+        (Value::Object(entries), JsonEdit::Remove(_)) if index < entries.len() => {
+            entries.remove(index);
+        }
+        (Value::Array(items), JsonEdit::InsertBefore(_)) if index < items.len() => items.insert(index, Value::Null),
+        (Value::Array(items), JsonEdit::InsertAfter(_)) if index < items.len() => items.insert(index + 1, Value::Null),
+        (Value::Array(items), JsonEdit::Remove(_)) if index < items.len() => {
+            items.remove(index);
+        }
+        _ => return Err("This operation does not apply to the selected node".to_string()),
+    }
+
+    let text = format!("{}\n", root.to_pretty_string(indent));
+    json::parse(&text).map_err(|e| format!("The edit produced invalid JSON: {}", e))?;
+    Ok(text)
+}
+
+fn unique_key(entries: &[(String, Value)]) -> String {
+    let taken = |key: &str| entries.iter().any(|(existing, _)| existing == key);
+    if !taken(NEW_KEY) {
+        return NEW_KEY.to_string();
+    }
+    (2..).map(|n| format!("{}_{}", NEW_KEY, n)).find(|key| !taken(key)).unwrap_or_default()
+}
+
+/// Ventana con el documento como árbol y botones para añadir o quitar entradas.
+/// Devuelve `false` cuando el usuario la cierra, y la operación pulsada, si la hay.
+pub fn show(ctx: &egui::Context, root: Result<&Value, &json::ParseError>, error: Option<&str>) -> (bool, Option<JsonEdit>) {
+    let mut open = true;
+    let mut edit = None;
+    egui::Window::new("JSON Tree View").open(&mut open).default_width(360.0).show(ctx, |ui| {
+        if let Some(error) = error {
+            ui.colored_label(egui::Color32::from_rgb(220, 50, 50), error);
+        }
+        match root {
+            Ok(root) => {
+                egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                    let mut path = Vec::new();
+                    match root {
+                        Value::Object(_) | Value::Array(_) => render_children(ui, root, &mut path, &mut edit),
+                        scalar => {
+                            ui.monospace(scalar.to_compact_string());
+                        }
+                    }
+                });
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::from_rgb(220, 50, 50), format!("Invalid JSON: {}", e));
+            }
+        }
+    });
+    (open, edit)
+}
+
+fn render_children(ui: &mut egui::Ui, value: &Value, path: &mut Vec<usize>, edit: &mut Option<JsonEdit>) {
+    match value {
+        Value::Object(entries) => {
+            for (index, (key, child)) in entries.iter().enumerate() {
+                path.push(index);
+                render_node(ui, &json::escape_string(key), child, path, false, edit);
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                path.push(index);
+                render_node(ui, &format!("[{}]", index), child, path, true, edit);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn render_node(ui: &mut egui::Ui, label: &str, value: &Value, path: &mut Vec<usize>, in_array: bool, edit: &mut Option<JsonEdit>) {
+    let buttons = |ui: &mut egui::Ui, path: &[usize], edit: &mut Option<JsonEdit>| {
+        if in_array {
+            if ui.small_button("Insert Before").clicked() {
+                *edit = Some(JsonEdit::InsertBefore(path.to_vec()));
+            }
+            if ui.small_button("Insert After").clicked() {
+                *edit = Some(JsonEdit::InsertAfter(path.to_vec()));
+            }
+        } else if ui.small_button("Add sibling").clicked() {
+            *edit = Some(JsonEdit::AddSibling(path.to_vec()));
+        }
+        if ui.small_button("Remove").clicked() {
+            *edit = Some(JsonEdit::Remove(path.to_vec()));
+        }
+    };
+
+    match value {
+        Value::Object(_) | Value::Array(_) => {
+            let summary = match value {
+                Value::Object(entries) => format!("{{{}}}", entries.len()),
+                Value::Array(items) => format!("[{}]", items.len()),
+                _ => String::new(),
+            };
+            let id = ui.make_persistent_id(("json_tree", path.clone()));
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, path.len() < 2)
+                .show_header(ui, |ui| {
+                    ui.monospace(label);
+                    ui.weak(summary);
+                    buttons(ui, path, edit);
+                })
+                .body(|ui| render_children(ui, value, path, edit));
+        }
+        scalar => {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{}: {}", label, scalar.to_compact_string()));
+                buttons(ui, path, edit);
+            });
+        }
+    }
+}
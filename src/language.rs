@@ -0,0 +1,164 @@
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EditorLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    Html,
+    Xml,
+    Css,
+    Json,
+    Toml,
+    Markdown,
+    Yaml,
+    PlainText,
+}
+
+impl EditorLanguage {
+    pub const ALL: [EditorLanguage; 11] = [
+        EditorLanguage::Rust,
+        EditorLanguage::Python,
+        EditorLanguage::JavaScript,
+        EditorLanguage::Html,
+        EditorLanguage::Xml,
+        EditorLanguage::Css,
+        EditorLanguage::Json,
+        EditorLanguage::Toml,
+        EditorLanguage::Markdown,
+        EditorLanguage::Yaml,
+        EditorLanguage::PlainText,
+    ];
+
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        match extension.as_str() {
+            "rs" => EditorLanguage::Rust,
+            "py" => EditorLanguage::Python,
+            "js" | "mjs" | "ts" => EditorLanguage::JavaScript,
+            "html" | "htm" => EditorLanguage::Html,
+            "xml" | "svg" | "xhtml" => EditorLanguage::Xml,
+            "css" | "scss" => EditorLanguage::Css,
+            "json" => EditorLanguage::Json,
+            "toml" => EditorLanguage::Toml,
+            "md" | "markdown" => EditorLanguage::Markdown,
+            "yaml" | "yml" => EditorLanguage::Yaml,
+            _ => EditorLanguage::PlainText,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorLanguage::Rust => "Rust",
+            EditorLanguage::Python => "Python",
+            EditorLanguage::JavaScript => "JavaScript",
+            EditorLanguage::Html => "HTML",
+            EditorLanguage::Xml => "XML",
+            EditorLanguage::Css => "CSS",
+            EditorLanguage::Json => "JSON",
+            EditorLanguage::Toml => "TOML",
+            EditorLanguage::Markdown => "Markdown",
+            EditorLanguage::Yaml => "YAML",
+            EditorLanguage::PlainText => "Plain Text",
+        }
+    }
+
+    pub fn is_markup(self) -> bool {
+        matches!(self, EditorLanguage::Html | EditorLanguage::Xml)
+    }
+}
+
+// Solo se miran las primeras líneas; bastan para reconocer un lenguaje y el coste no crece con el archivo.
+const DETECTION_LINES: usize = 20;
+
+// Prefijos de línea que delatan un lenguaje sin lugar a dudas.
+const RUST_HINTS: &[&str] = &["fn main()", "use std::", "pub fn ", "impl ", "#[derive(", "extern crate ", "let mut "];
+const JAVASCRIPT_HINTS: &[&str] = &["function ", "'use strict'", "\"use strict\"", "console.log(", "export default ", "module.exports"];
+const PYTHON_IMPORT_HINTS: &[&str] = &["import ", "from "];
+
+/// Archivos cuya extensión no dice nada (`.txt` o sin extensión), en los que vale la pena adivinar el lenguaje.
+pub fn allows_content_detection(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(extension) => extension.eq_ignore_ascii_case("txt"),
+        None => true,
+    }
+}
+
+/// Adivina el lenguaje a partir de las primeras líneas: shebang, cabeceras de marcado y construcciones típicas.
+pub fn detect_language_from_content(content: &str) -> Option<EditorLanguage> {
+    let lines: Vec<&str> = content.lines().map(str::trim).filter(|line| !line.is_empty()).take(DETECTION_LINES).collect();
+    let first = lines.first()?;
+
+    if let Some(shebang) = first.strip_prefix("#!") {
+        if shebang.contains("python") {
+            return Some(EditorLanguage::Python);
+        }
+        if shebang.contains("node") {
+            return Some(EditorLanguage::JavaScript);
+        }
+    }
+    let lowercase = first.to_ascii_lowercase();
+    if lowercase.starts_with("<!doctype html") || lowercase.starts_with("<html") {
+        return Some(EditorLanguage::Html);
+    }
+    if lowercase.starts_with("<?xml") {
+        return Some(EditorLanguage::Xml);
+    }
+    if (first.starts_with('{') || first.starts_with('[')) && crate::json::parse(content).is_ok() {
+        return Some(EditorLanguage::Json);
+    }
+
+    let has = |hints: &[&str]| lines.iter().any(|line| hints.iter().any(|hint| line.starts_with(hint)));
+    if has(RUST_HINTS) {
+        return Some(EditorLanguage::Rust);
+    }
+    if has(&["def ", "class "]) && (has(PYTHON_IMPORT_HINTS) || lines.iter().any(|line| line.ends_with(':'))) {
+        return Some(EditorLanguage::Python);
+    }
+    if has(JAVASCRIPT_HINTS) || lines.iter().any(|line| line.contains("require(") && line.starts_with("const ")) {
+        return Some(EditorLanguage::JavaScript);
+    }
+    if has(&["[package]", "[dependencies]"]) {
+        return Some(EditorLanguage::Toml);
+    }
+    None
+}
+
+/// Gramática de tree-sitter con su consulta de resaltado. Solo los lenguajes cuya gramática está entre las
+/// dependencias tienen perfil; el resto se muestra como texto plano por el mismo camino.
+pub struct LanguageProfile {
+    pub language: tree_sitter::Language,
+    pub highlight_query: &'static str,
+    pub name: &'static str,
+}
+
+impl LanguageProfile {
+    // Python, JavaScript, TOML, Markdown y YAML necesitan sus crates `tree-sitter-*`; cada uno es un brazo más aquí.
+    pub fn for_language(language: EditorLanguage) -> Option<Self> {
+        match language {
+            EditorLanguage::Rust => Some(Self {
+                language: tree_sitter_rust::language(),
+                highlight_query: tree_sitter_rust::HIGHLIGHT_QUERY,
+                name: "Rust",
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn highlight_config(&self) -> Option<tree_sitter_highlight::HighlightConfiguration> {
+        match tree_sitter_highlight::HighlightConfiguration::new(self.language, self.highlight_query, "", "") {
+            Ok(mut config) => {
+                crate::highlight::configure(&mut config);
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("Unable to load {} highlighting: {}", self.name, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::text;
+
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 120;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineLengthMode {
+    WarnOnly,
+    HardWrap,
+    Disabled,
+}
+
+impl LineLengthMode {
+    pub const ALL: [LineLengthMode; 3] = [LineLengthMode::WarnOnly, LineLengthMode::HardWrap, LineLengthMode::Disabled];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LineLengthMode::WarnOnly => "Warn only",
+            LineLengthMode::HardWrap => "Hard wrap",
+            LineLengthMode::Disabled => "Disabled",
+        }
+    }
+}
+
+/// Índices (desde 0) de las líneas con más de `max` caracteres.
+pub fn long_lines(content: &str, max: usize) -> Vec<usize> {
+    content
+        .split('\n')
+        .enumerate()
+        .filter(|(_, line)| line.chars().count() > max)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Parte la línea que contiene `cursor` en el último espacio antes de la columna `max`, conservando la sangría.
+/// Devuelve el nuevo cursor, o `None` si la línea cabe o no tiene dónde partirse.
+pub fn hard_wrap(content: &mut String, cursor: usize, max: usize) -> Option<usize> {
+    let start = text::line_start(content, cursor);
+    let end = content[start..].find('\n').map_or(content.len(), |i| start + i);
+    let line = &content[start..end];
+    if line.chars().count() <= max {
+        return None;
+    }
+    let indent = text::line_indent(content, start).to_string();
+    let limit = start + text::char_to_byte(line, max);
+    let break_at = content.get(start + indent.len()..limit)?.rfind(' ')? + start + indent.len();
+
+    content.replace_range(break_at..break_at + 1, &format!("\n{}", indent));
+    Some(if cursor > break_at { cursor + indent.len() } else { cursor })
+}
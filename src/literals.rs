@@ -0,0 +1,115 @@
+// Utilidades para literales de cadena de Rust.
+
+/// Envuelve `content` en el literal de cadena cruda más corto que no sea ambiguo: `r"..."`, `r#"..."#`, etc.
+pub fn raw_string_literal(content: &str) -> String {
+    let hashes = if content.contains('"') {
+        let longest_run = content
+            .match_indices('"')
+            .map(|(i, _)| content[i + 1..].chars().take_while(|&c| c == '#').count())
+            .max()
+            .unwrap_or(0);
+        longest_run + 1
+    } else {
+        0
+    };
+    let delimiter = "#".repeat(hashes);
+    format!("r{0}\"{1}\"{0}", delimiter, content)
+}
+
+#[derive(Debug)]
+pub enum UnescapeError {
+    Malformed,
+    InvalidCodePoint(u32),
+    Unprintable(char),
+}
+
+impl std::fmt::Display for UnescapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnescapeError::Malformed => write!(f, "not a \\u{{…}}, \\uXXXX or \\xHH escape"),
+            UnescapeError::InvalidCodePoint(code) => write!(f, "U+{:04X} is not a valid character", code),
+            UnescapeError::Unprintable(c) => write!(f, "U+{:04X} is a control character", *c as u32),
+        }
+    }
+}
+
+/// Decodifica `\u{1F600}` (Rust), `\u00e9` (JavaScript/JSON, también pares suplentes) o `\x41`.
+/// Los caracteres de control se rechazan: insertarlos tal cual dejaría el texto ilegible.
+pub fn unescape_sequence(src: &str) -> Result<char, UnescapeError> {
+    let code = if let Some(hex) = src.strip_prefix("\\u{").and_then(|rest| rest.strip_suffix('}')) {
+        parse_hex(hex, 1..=6)?
+    } else if let Some(hex) = src.strip_prefix("\\x") {
+        parse_hex(hex, 2..=2)?
+    } else if let Some(rest) = src.strip_prefix("\\u") {
+        let (high, low) = match rest.split_once("\\u") {
+            Some((high, low)) => (parse_hex(high, 4..=4)?, Some(parse_hex(low, 4..=4)?)),
+            None => (parse_hex(rest, 4..=4)?, None),
+        };
+        match low {
+            Some(low) if (0xD800..0xDC00).contains(&high) && (0xDC00..0xE000).contains(&low) => {
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            Some(_) => return Err(UnescapeError::Malformed),
+            None => high,
+        }
+    } else {
+        return Err(UnescapeError::Malformed);
+    };
+    let c = char::from_u32(code).ok_or(UnescapeError::InvalidCodePoint(code))?;
+    if c.is_control() {
+        return Err(UnescapeError::Unprintable(c));
+    }
+    Ok(c)
+}
+
+fn parse_hex(hex: &str, digits: std::ops::RangeInclusive<usize>) -> Result<u32, UnescapeError> {
+    if !digits.contains(&hex.len()) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(UnescapeError::Malformed);
+    }
+    u32::from_str_radix(hex, 16).map_err(|_| UnescapeError::Malformed)
+}
+
+/// Forma de escape de Rust: `\u{E9}`.
+pub fn escape_char_to_unicode(c: char) -> String {
+    format!("\\u{{{:X}}}", c as u32)
+}
+
+/// Forma de escape de JavaScript/JSON: `\u00E9`, con par suplente fuera del plano básico.
+pub fn escape_char_to_utf16(c: char) -> String {
+    let mut units = [0; 2];
+    c.encode_utf16(&mut units).iter().map(|unit| format!("\\u{:04X}", unit)).collect()
+}
+
+/// Rango de la secuencia de escape que contiene o toca `cursor`.
+pub fn escape_at(content: &str, cursor: usize) -> Option<std::ops::Range<usize>> {
+    let bytes = content.as_bytes();
+    let cursor = cursor.min(bytes.len());
+    // Ninguna secuencia admitida ocupa más de 12 bytes (`\uD83D\uDE00`).
+    (cursor.saturating_sub(12)..=cursor)
+        .filter(|&i| bytes.get(i) == Some(&b'\\'))
+        .filter_map(|begin| escape_end(bytes, begin).map(|end| begin..end))
+        .find(|range| range.start <= cursor && cursor <= range.end)
+}
+
+fn escape_end(bytes: &[u8], begin: usize) -> Option<usize> {
+    let hex_run = |from: usize, max: usize| bytes[from.min(bytes.len())..].iter().take(max).take_while(|b| b.is_ascii_hexdigit()).count();
+    match bytes.get(begin + 1)? {
+        b'x' if hex_run(begin + 2, 2) == 2 => Some(begin + 4),
+        b'u' if bytes.get(begin + 2) == Some(&b'{') => {
+            let digits = hex_run(begin + 3, 6);
+            (bytes.get(begin + 3 + digits) == Some(&b'}') && digits > 0).then_some(begin + 4 + digits)
+        }
+        b'u' if hex_run(begin + 2, 4) == 4 => {
+            // Par suplente de JavaScript/JSON: se toman las dos mitades juntas.
+            let second = begin + 6;
+            if bytes.get(second..second + 2) == Some(b"\\u") && hex_run(second + 2, 4) == 4 {
+                let high = std::str::from_utf8(&bytes[begin + 2..begin + 6]).ok().and_then(|h| u32::from_str_radix(h, 16).ok())?;
+                if (0xD800..0xDC00).contains(&high) {
+                    return Some(second + 6);
+                }
+            }
+            Some(begin + 6)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,274 @@
+// Cliente mínimo del protocolo de servidores de lenguaje (JSON-RPC por stdin/stdout) para las pistas en línea.
+// Solo sincroniza el documento activo entero y pide `textDocument/inlayHint`; el resto de mensajes se ignora.
+
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::json::{self, Value};
+use crate::text;
+
+pub const RUST_ANALYZER: &str = "rust-analyzer";
+
+/// Pista devuelta por el servidor, ya convertida a posición en bytes del documento.
+pub struct InlayHint {
+    pub offset: usize,
+    pub label: String,
+}
+
+pub struct LspClient {
+    child: Child,
+    stdin: ChildStdin,
+    receiver: Receiver<Value>,
+    next_id: u64,
+    initialized: bool,
+    opened: HashSet<String>,
+    version: i64,
+    // Identificador de la última petición de pistas; las respuestas a peticiones anteriores se descartan.
+    hints_request: Option<u64>,
+    // El servidor ha terminado de indexar desde la última petición, así que las pistas pueden haber cambiado.
+    pub hints_stale: bool,
+}
+
+impl LspClient {
+    /// Lanza `command` con `root` como carpeta del proyecto y envía `initialize`.
+    pub fn start(command: &str, root: &Path) -> io::Result<Self> {
+        let mut child = Command::new(command)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| io::Error::other("no stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("no stdout"))?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || read_messages(BufReader::new(stdout), &sender));
+
+        let mut client = Self {
+            child,
+            stdin,
+            receiver,
+            next_id: 1,
+            initialized: false,
+            opened: HashSet::new(),
+            version: 0,
+            hints_request: None,
+            hints_stale: false,
+        };
+        let capabilities = object(vec![("textDocument", object(vec![("inlayHint", object(vec![]))]))]);
+        let root_uri = Value::String(path_to_uri(root));
+        let params = object(vec![("processId", Value::Number(std::process::id().to_string())), ("rootUri", root_uri), ("capabilities", capabilities)]);
+        client.request("initialize", params)?;
+        Ok(client)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.initialized
+    }
+
+    /// Envía el texto completo de `path` (`didOpen` la primera vez, `didChange` después).
+    pub fn sync_document(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        let uri = path_to_uri(path);
+        self.version += 1;
+        let version = Value::Number(self.version.to_string());
+        if self.opened.insert(uri.clone()) {
+            let document = object(vec![
+                ("uri", Value::String(uri)),
+                ("languageId", Value::String("rust".to_string())),
+                ("version", version),
+                ("text", Value::String(content.to_string())),
+            ]);
+            self.notify("textDocument/didOpen", object(vec![("textDocument", document)]))
+        } else {
+            let document = object(vec![("uri", Value::String(uri)), ("version", version)]);
+            let change = object(vec![("text", Value::String(content.to_string()))]);
+            self.notify("textDocument/didChange", object(vec![("textDocument", document), ("contentChanges", Value::Array(vec![change]))]))
+        }
+    }
+
+    /// Pide las pistas de todo el documento; llegan más tarde por `poll`.
+    pub fn request_inlay_hints(&mut self, path: &Path, line_count: usize) -> io::Result<()> {
+        let range = object(vec![("start", position(0, 0)), ("end", position(line_count, 0))]);
+        let params = object(vec![("textDocument", object(vec![("uri", Value::String(path_to_uri(path)))])), ("range", range)]);
+        self.hints_stale = false;
+        self.hints_request = Some(self.request("textDocument/inlayHint", params)?);
+        Ok(())
+    }
+
+    /// Procesa los mensajes recibidos y devuelve las pistas de la última petición si ya han llegado.
+    pub fn poll(&mut self, content: &str) -> Option<Vec<InlayHint>> {
+        let mut hints = None;
+        while let Ok(message) = self.receiver.try_recv() {
+            let id = field(&message, "id").and_then(|id| match id {
+                Value::Number(id) => id.parse::<u64>().ok(),
+                _ => None,
+            });
+            match (field(&message, "method"), id) {
+                // Petición del servidor (`workspace/configuration`, `window/workDoneProgress/create`…): se responde
+                // con `null` para que no se quede esperando.
+                (Some(_), Some(_)) => {
+                    let response = object(vec![("jsonrpc", Value::String("2.0".to_string())), ("id", field(&message, "id").cloned().unwrap_or(Value::Null)), ("result", Value::Null)]);
+                    if let Err(e) = self.send(&response) {
+                        eprintln!("Unable to reply to the language server: {}", e);
+                    }
+                }
+                (Some(Value::String(method)), None) => {
+                    let done = field(&message, "params").and_then(|params| field(params, "value")).and_then(|value| field(value, "kind"));
+                    if method == "$/progress" && done == Some(&Value::String("end".to_string())) {
+                        self.hints_stale = true;
+                    }
+                }
+                (None, Some(1)) if !self.initialized => {
+                    self.initialized = true;
+                    if let Err(e) = self.notify("initialized", object(vec![])) {
+                        eprintln!("Unable to initialize the language server: {}", e);
+                    }
+                }
+                (None, Some(id)) if Some(id) == self.hints_request => {
+                    self.hints_request = None;
+                    hints = Some(match field(&message, "result") {
+                        Some(Value::Array(items)) => items.iter().filter_map(|item| inlay_hint(item, content)).collect(),
+                        _ => Vec::new(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        hints
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> io::Result<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&object(vec![
+            ("jsonrpc", Value::String("2.0".to_string())),
+            ("id", Value::Number(id.to_string())),
+            ("method", Value::String(method.to_string())),
+            ("params", params),
+        ]))?;
+        Ok(id)
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> io::Result<()> {
+        self.send(&object(vec![
+            ("jsonrpc", Value::String("2.0".to_string())),
+            ("method", Value::String(method.to_string())),
+            ("params", params),
+        ]))
+    }
+
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        let body = message.to_compact_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.stdin.flush()
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+// Cada mensaje va precedido de cabeceras `Content-Length: N` y una línea vacía.
+fn read_messages(mut reader: impl BufRead, sender: &Sender<Value>) {
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            match reader.read_line(&mut header) {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {}
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+        let Some(length) = length else {
+            continue;
+        };
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+        match json::parse(&String::from_utf8_lossy(&body)) {
+            Ok(message) => {
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+            Err(e) => eprintln!("Unable to parse language server message: {}", e),
+        }
+    }
+}
+
+// La etiqueta es un texto o una lista de partes con `value`; `paddingLeft`/`paddingRight` piden un espacio.
+fn inlay_hint(item: &Value, content: &str) -> Option<InlayHint> {
+    let position = field(item, "position")?;
+    let number = |key: &str| match field(position, key)? {
+        Value::Number(number) => number.parse::<usize>().ok(),
+        _ => None,
+    };
+    let mut label = match field(item, "label")? {
+        Value::String(label) => label.clone(),
+        Value::Array(parts) => parts.iter().filter_map(|part| match field(part, "value")? {
+            Value::String(value) => Some(value.as_str()),
+            _ => None,
+        }).collect(),
+        _ => return None,
+    };
+    if field(item, "paddingLeft") == Some(&Value::Bool(true)) {
+        label.insert(0, ' ');
+    }
+    if field(item, "paddingRight") == Some(&Value::Bool(true)) {
+        label.push(' ');
+    }
+    Some(InlayHint { offset: byte_offset(content, number("line")?, number("character")?), label })
+}
+
+/// Posición LSP (línea y columna en unidades UTF-16) a byte de `content`, recortada al final de la línea.
+pub fn byte_offset(content: &str, line: usize, character: usize) -> usize {
+    let Some(&start) = text::line_offsets(content).get(line) else {
+        return content.len();
+    };
+    let mut units = 0;
+    for (index, c) in content[start..].char_indices() {
+        if c == '\n' || units >= character {
+            return start + index;
+        }
+        units += c.len_utf16();
+    }
+    content.len()
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path = path.to_string_lossy().replace('\\', "/").trim_start_matches("//?/").replace('%', "%25").replace(' ', "%20");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+fn position(line: usize, character: usize) -> Value {
+    object(vec![("line", Value::Number(line.to_string())), ("character", Value::Number(character.to_string()))])
+}
+
+fn object(entries: Vec<(&str, Value)>) -> Value {
+    Value::Object(entries.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+        _ => None,
+    }
+}
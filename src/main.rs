@@ -1,190 +1,4604 @@
+mod accessibility;
+#[cfg(feature = "ambient-sound")]
+mod ambient;
+mod annotations;
+mod branches;
+mod brackets;
+mod checksums;
+mod closures;
+mod colors;
+mod column;
+mod comments;
+mod commit_message;
+mod completion;
+mod config;
+mod conflicts;
+mod context_actions;
+mod diagnostics;
+mod diff;
+mod docs_links;
+mod exclusions;
+mod expand;
+mod find;
+mod folding;
+mod follow;
+mod format;
+mod git;
+mod hex_view;
+mod highlight;
+mod html_export;
+mod i18n;
+mod imports;
+mod indent_guides;
+mod indentation;
+mod index;
+mod json;
+mod json_tree;
+mod language;
+mod line_length;
+mod literals;
+mod lsp;
+mod markdown;
+mod markup;
+mod minimap;
+mod mirror;
+mod notes;
+mod notifications;
+mod outline;
+mod output;
+mod pattern_match;
+mod performance;
+mod playground;
+mod references;
+mod rename;
+mod schema;
+mod settings;
+mod shortcuts;
+mod snippets;
+mod status_bar;
+mod tabs;
+mod text;
+mod text_search;
+mod templates;
+mod themes;
+mod toml_check;
+mod track_changes;
+mod translation;
+mod typing_stats;
+mod undo;
+mod view;
+mod virtual_text;
+mod wizard;
+mod yaml;
+
 use eframe::egui;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use tree_sitter::{Parser, Language};
-use tree_sitter_highlight::{Highlighter, HighlightConfiguration, HighlightEvent};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tree_sitter::{Parser, Language, Query, Tree};
+use tree_sitter_highlight::{Highlighter, HighlightConfiguration};
+use accessibility::{ColorblindMode, StatusPalette};
+#[cfg(feature = "ambient-sound")]
+use ambient::{AmbientPlayer, AmbientSound};
+use annotations::LineAnnotations;
+use brackets::Bracket;
+use branches::BranchSwitcher;
+use checksums::ChecksumPanel;
+use commit_message::{CommitAction, CommitPanel};
+use comments::CommentConfigs;
+use completion::Completion;
+use conflicts::{ConflictHunk, Resolution};
+use virtual_text::{VirtualText, VtPosition};
+use context_actions::ContextAction;
+use colors::ColorLiteral;
+use column::RectSelection;
+use diagnostics::Diagnostic;
+use lsp::{InlayHint, LspClient};
+use diff::DiffLine;
+use exclusions::Exclusions;
+use expand::MacroExpansion;
+use find::{FindAction, FindReplace};
+use folding::{Fold, FoldKind};
+use i18n::Localizations;
+use follow::{FollowState, FollowUpdate};
+use format::{FormatError, OnTypeFormat};
+use index::IndexerThread;
+use indent_guides::IndentGuideStyle;
+use indentation::IndentStyle;
+use language::{EditorLanguage, LanguageProfile};
+use line_length::LineLengthMode;
+use mirror::MirrorPair;
+use notifications::{NotifLevel, NotificationQueue};
+use performance::PerformanceStats;
+use playground::QueryPlayground;
+use outline::Symbol;
+use output::{OutputLevel, OutputPanel};
+use references::{LocalReference, SelectionHighlight};
+use rename::{LiveRename, RenamePreview, RenamePreviewAction};
+use schema::LoadedSchema;
+use settings::{Settings, ThemeName};
+use shortcuts::{Action, ShortcutMap};
+use themes::Theme;
+use snippets::SnippetRegistry;
+use status_bar::StatusBarItem;
+use track_changes::{ChangeKind, ChangeSet};
+use translation::{LocaleFile, TranslationView};
+use typing_stats::TypingStats;
+use text::DuplicateDir;
+use text_search::{SearchScope, TextSearch};
+use hex_view::HexView;
+use tabs::{Tab, TabAction};
+use toml_check::TomlError;
+use undo::{UndoEntry, UndoTree};
+use wizard::{InitialContent, NewFileWizard, WizardAction};
+
+// Máximo de líneas borradas que se recuerdan en el historial.
+const LINE_UNDO_LIMIT: usize = 100;
+
+// Pausa al escribir a partir de la cual lo siguiente que se teclee va en otra entrada de deshacer.
+const UNDO_PAUSE: Duration = Duration::from_millis(500);
+
+// Pausa al escribir antes de mandar el documento al servidor de lenguaje y pedir las pistas.
+const INLAY_HINT_DELAY: Duration = Duration::from_millis(300);
+
+// Commits que se cargan cada vez en el historial del archivo.
+const FILE_HISTORY_PAGE: usize = 20;
+
+// Resultados como máximo en "Go to Symbol in Project".
+const SYMBOL_SEARCH_LIMIT: usize = 100;
+
+const MEGABYTE: u64 = 1024 * 1024;
+
+// Ancho del margen izquierdo del editor donde se dibujan los indicadores por línea.
+const GUTTER_WIDTH: f32 = 16.0;
 
 extern "C" {
     fn tree_sitter_rust() -> Language;
 }
 
+// Archivo que `load` no abrió directamente y espera la decisión del usuario.
+enum OpenPrompt {
+    TooLarge { path: PathBuf, size: u64 },
+    Binary(PathBuf),
+}
+
 struct TextEditor {
     content: String,
     file_path: Option<PathBuf>,
+    // Modo «tail -f»; solo existe para archivos .log, .out o .txt.
+    scroll_to_end: bool,
+    language: EditorLanguage,
+    cursor: usize,
+    selection: std::ops::Range<usize>,
+    // Archivos abiertos; el activo se edita en `content` y `file_path` (ver `Tab`).
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    // Pestaña con cambios sin guardar cuyo cierre espera confirmación.
+    close_prompt: Option<usize>,
+    // Con el guardado automático apagado, salir con cambios pendientes pregunta antes; `quit_confirmed` deja
+    // cerrar la ventana una vez respondido.
+    quit_prompt: bool,
+    quit_confirmed: bool,
+    open_prompt: Option<OpenPrompt>,
+    hex_view: Option<HexView>,
+    macro_expansion: Option<MacroExpansion>,
+    format_on_type_job: Option<OnTypeFormat>,
+    // Byte bajo el puntero al abrir el menú contextual del editor; sus acciones dependen de los nodos de ahí.
+    context_menu_byte: Option<usize>,
+    // Temas de serie y propios; `theme_name` es el último aplicado y `new_theme_name`, el nombre para guardar uno.
+    themes: Vec<Theme>,
+    shortcuts: ShortcutMap,
+    comment_configs: CommentConfigs,
+    // Nombre del proyecto abierto, para el título de la ventana.
+    project_name: Option<String>,
+    localizations: Localizations,
+    theme_name: String,
+    new_theme_name: String,
+    // Índice del indicador de la barra de estado que se está arrastrando en los ajustes.
+    status_bar_drag: Option<usize>,
     current_dir: Option<PathBuf>,
     dir_contents: Vec<PathBuf>,
-    new_file_name: String,
+    // Los nombres que empiezan por `.` no se listan salvo que se marque "Show hidden".
+    show_hidden_files: bool,
+    // Filtro por nombre del panel lateral. Con texto se buscan todos los archivos bajo la carpeta, que se recorren la
+    // primera vez y se guardan hasta que cambie la carpeta.
+    file_filter: String,
+    project_files: Option<Vec<PathBuf>>,
+    // Rutas excluidas del proyecto abierto; "Show All Files" las vuelve a listar, atenuadas.
+    exclusions: Exclusions,
+    show_all_files: bool,
+    new_file_wizard: Option<NewFileWizard>,
     show_settings: bool,
-    font_size: f32,
-    background_color: egui::Color32,
-    text_color: egui::Color32,
-    font_family: egui::FontFamily,
-    line_spacing: f32,
+    settings: Settings,
+    snippets: SnippetRegistry,
+    commit_message_mode: bool,
+    // Lenguaje adivinado por el contenido, a la espera de que el usuario lo acepte o lo descarte.
+    detected_language: Option<EditorLanguage>,
+    language_detection_done: bool,
+    // `Nombre <email>` de git config, leído al abrir el mensaje de commit.
+    commit_identity: Option<String>,
+    changes: ChangeSet,
+    show_changes: bool,
+    line_offsets: Vec<usize>,
+    indent_guides: Vec<(usize, usize)>,
+    folds: Vec<Fold>,
+    indent_style: IndentStyle,
+    indent_histogram: [f32; indentation::HISTOGRAM_LEVELS],
+    // Línea donde empezó un arrastre sobre los números de línea.
+    line_drag_anchor: Option<usize>,
+    long_lines: Vec<usize>,
+    // Sin perfil (lenguaje sin gramática) no hay árbol ni resaltado: el archivo se muestra como texto plano.
+    profile: Option<LanguageProfile>,
     parser: Parser,
+    tree: Option<Tree>,
     highlighter: Highlighter,
-    highlight_config: HighlightConfiguration,
+    highlight_config: Option<HighlightConfiguration>,
+    // Colores del último resaltado y el tema con que se calcularon, para repetirlo si cambia.
+    highlights: Vec<(egui::Color32, Range<usize>)>,
+    // Paréntesis con su profundidad, solo con los paréntesis de colores activados.
+    brackets: Vec<Bracket>,
+    // `use` de Rust que parecen sobrar, con el rango que se borra al quitarlos.
+    unused_imports: Vec<Range<usize>>,
+    conflicts: Vec<ConflictHunk>,
+    virtual_texts: Vec<VirtualText>,
+    // Error de sintaxis de un archivo TOML y si hay una edición sin validar (se valida tras `VALIDATION_DELAY`).
+    toml_error: Option<TomlError>,
+    toml_validation_due: bool,
+    highlight_palette: (egui::Color32, HashMap<String, egui::Color32>),
+    rename_prompt: Option<(String, String)>,
+    live_rename: Option<LiveRename>,
+    // Zonas enlazadas en las que lo escrito se repite, y la primera zona marcada mientras se elige la segunda.
+    mirror: Option<MirrorPair>,
+    mirror_pending: Option<Range<usize>>,
+    completion: Option<Completion>,
+    // Nombre para "Convert to Named Function" y el último error de la conversión.
+    closure_prompt: Option<(String, Option<String>)>,
+    rename_preview: Option<RenamePreview>,
+    line_undo: Vec<UndoEntry>,
+    // Instantáneas del contenido para Ctrl+Z y Ctrl+Shift+Z / Ctrl+Y.
+    undo_tree: UndoTree,
+    last_edit_time: Instant,
+    // Último guardado, para el guardado automático, y el título que se dio a la ventana.
+    last_save_time: Instant,
+    window_title: String,
+    // Contenido antes de la racha de edición en curso, que aún no está en `undo_tree`, y si la racha es de
+    // Enter/Backspace/Delete en vez de caracteres.
+    pending_snapshot: Option<(String, bool)>,
+    show_undo_history: bool,
+    in_git_repo: bool,
+    git_output: Option<(String, String)>,
+    // Colores de sintaxis del diff de `git_output`, calculados una vez por salida: (título, longitud, trozos).
+    git_diff_spans: Option<(String, usize, diff::ColorSpans)>,
+    output: OutputPanel,
+    show_output: bool,
+    commit_panel: Option<CommitPanel>,
+    // Rama del repositorio, para el botón de la barra de herramientas.
+    current_branch: Option<String>,
+    branch_switcher: Option<BranchSwitcher>,
+    stash_list: Option<Vec<String>>,
+    file_history: Option<Vec<git::LogEntry>>,
+    file_history_exhausted: bool,
+    // Archivos que dejó con conflictos el último cherry-pick.
+    cherry_pick_conflicts: Vec<PathBuf>,
+    diff_view: Option<(String, Vec<DiffLine>)>,
+    typing_stats: TypingStats,
+    show_typing_stats: bool,
+    // Sonido de fondo elegido; la salida de audio se abre la primera vez que suena algo.
+    #[cfg(feature = "ambient-sound")]
+    ambient_sound: Option<AmbientSound>,
+    #[cfg(feature = "ambient-sound")]
+    ambient_player: Option<AmbientPlayer>,
+    performance: PerformanceStats,
+    show_performance: bool,
+    outline: Vec<Symbol>,
+    indexer: Option<IndexerThread>,
+    symbol_search: Option<String>,
+    text_search: Option<TextSearch>,
+    checksum_panel: Option<ChecksumPanel>,
+    find: FindReplace,
+    query_playground: Option<QueryPlayground>,
+    notifications: NotificationQueue,
+    show_outline: bool,
+    editor_rect: egui::Rect,
+    cursor_screen_y: f32,
+    diagnostics: Vec<Diagnostic>,
+    // Diagnóstico cuyo arreglo se ofrece y dónde se abre la ventanita; sigue abierta mientras el puntero esté encima.
+    repair_popup: Option<(usize, egui::Pos2)>,
+    // Selección rectangular hecha con Alt+arrastre; se pinta aparte porque el TextEdit solo tiene una selección.
+    rect_selection: Option<RectSelection>,
+    // Ancla y cursor de la pestaña recién activada; se aplican al estado del TextEdit en el siguiente fotograma.
+    pending_selection: Option<(usize, usize)>,
+    // Ruta de tipos de nodo bajo el cursor y rango en bytes del más interno, para la barra de estado.
+    syntax_node: Option<(String, Range<usize>)>,
+    // rust-analyzer, si se pudo lanzar; `lsp_synced` es el archivo y la versión del texto que tiene el servidor.
+    lsp: Option<LspClient>,
+    lsp_failed: bool,
+    lsp_synced: Option<(PathBuf, u64)>,
+    document_version: u64,
+    inlay_hints: Vec<InlayHint>,
+    translation_view: Option<TranslationView>,
+    // Vista de árbol de JSON: documento analizado en el último `reparse` y error de la última operación.
+    json_tree: Option<Result<json::Value, json::ParseError>>,
+    json_tree_error: Option<String>,
+    // Último esquema (JSON o YAML) cargado y los elegidos a mano con "Select Schema", que tienen prioridad sobre `$schema`
+    // y las asociaciones.
+    json_schema: Option<LoadedSchema>,
+    // Alias de YAML unidos a su ancla, para dibujar la línea que los une.
+    yaml_links: Vec<yaml::YamlLink>,
+    schema_overrides: HashMap<PathBuf, String>,
+    color_literals: Vec<ColorLiteral>,
+    // Literal que se está editando con el selector de color, su color y dónde se abrió.
+    color_picker: Option<(Range<usize>, egui::Color32, egui::Pos2)>,
+    annotations: LineAnnotations,
+    // Línea cuya anotación se está escribiendo y el texto en edición.
+    annotation_input: Option<(usize, String)>,
+    file_notes: HashMap<String, String>,
+    // Archivo cuya nota se edita en el panel inferior y el texto en edición.
+    note_editor: Option<(PathBuf, String)>,
+    identifier_query: Query,
+    local_references: Vec<LocalReference>,
+    selection_matches: Vec<Range<usize>>,
 }
 
 impl TextEditor {
     fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut parser = Parser::new();
-        parser.set_language(unsafe { tree_sitter_rust() }).expect("Error loading Rust grammar");
-
         let highlighter = Highlighter::new();
+        // Lo que no se pudo leer al arrancar se avisa en cuanto existe la cola de avisos.
+        let mut load_errors = Vec::new();
+        let settings = Settings::load(&mut load_errors);
+
+        let mut editor = Self {
+            content: String::new(),
+            file_path: None,
+            scroll_to_end: false,
+            language: EditorLanguage::PlainText,
+            cursor: 0,
+            selection: 0..0,
+            tabs: vec![Tab::default()],
+            active_tab: 0,
+            close_prompt: None,
+            quit_prompt: false,
+            quit_confirmed: false,
+            open_prompt: None,
+            hex_view: None,
+            macro_expansion: None,
+            format_on_type_job: None,
+            context_menu_byte: None,
+            themes: themes::all_themes(&mut load_errors),
+            shortcuts: ShortcutMap::load(&mut load_errors),
+            comment_configs: CommentConfigs::load(&mut load_errors),
+            project_name: None,
+            localizations: Localizations::load(&settings.locale),
+            theme_name: ThemeName::Light.label().to_string(),
+            new_theme_name: String::new(),
+            status_bar_drag: None,
+            current_dir: None,
+            dir_contents: Vec::new(),
+            show_hidden_files: false,
+            file_filter: String::new(),
+            project_files: None,
+            exclusions: Exclusions::default(),
+            show_all_files: false,
+            new_file_wizard: None,
+            show_settings: false,
+            settings,
+            snippets: SnippetRegistry::new(),
+            commit_message_mode: false,
+            detected_language: None,
+            language_detection_done: false,
+            commit_identity: None,
+            changes: ChangeSet::default(),
+            show_changes: false,
+            line_offsets: vec![0],
+            indent_guides: Vec::new(),
+            folds: Vec::new(),
+            indent_style: IndentStyle::Spaces(4),
+            indent_histogram: [0.0; indentation::HISTOGRAM_LEVELS],
+            line_drag_anchor: None,
+            long_lines: Vec::new(),
+            profile: None,
+            parser: Parser::new(),
+            tree: None,
+            highlighter,
+            highlight_config: None,
+            highlights: Vec::new(),
+            brackets: Vec::new(),
+            unused_imports: Vec::new(),
+            conflicts: Vec::new(),
+            virtual_texts: Vec::new(),
+            toml_error: None,
+            toml_validation_due: false,
+            highlight_palette: (egui::Color32::TRANSPARENT, HashMap::new()),
+            rename_prompt: None,
+            live_rename: None,
+            mirror: None,
+            mirror_pending: None,
+            completion: None,
+            closure_prompt: None,
+            rename_preview: None,
+            line_undo: Vec::new(),
+            undo_tree: UndoTree::default(),
+            last_edit_time: Instant::now(),
+            last_save_time: Instant::now(),
+            window_title: String::new(),
+            pending_snapshot: None,
+            show_undo_history: false,
+            in_git_repo: false,
+            git_output: None,
+            git_diff_spans: None,
+            output: OutputPanel::default(),
+            show_output: false,
+            commit_panel: None,
+            current_branch: None,
+            branch_switcher: None,
+            stash_list: None,
+            file_history: None,
+            file_history_exhausted: false,
+            cherry_pick_conflicts: Vec::new(),
+            diff_view: None,
+            typing_stats: TypingStats::new(),
+            #[cfg(feature = "ambient-sound")]
+            ambient_sound: None,
+            #[cfg(feature = "ambient-sound")]
+            ambient_player: None,
+            show_typing_stats: false,
+            performance: PerformanceStats::new(),
+            show_performance: false,
+            outline: Vec::new(),
+            indexer: None,
+            symbol_search: None,
+            text_search: None,
+            checksum_panel: None,
+            find: FindReplace::new(),
+            query_playground: None,
+            notifications: NotificationQueue::default(),
+            show_outline: false,
+            editor_rect: egui::Rect::NOTHING,
+            cursor_screen_y: 0.0,
+            diagnostics: Vec::new(),
+            repair_popup: None,
+            rect_selection: None,
+            pending_selection: None,
+            syntax_node: None,
+            lsp: None,
+            lsp_failed: false,
+            lsp_synced: None,
+            document_version: 0,
+            inlay_hints: Vec::new(),
+            translation_view: None,
+            json_tree: None,
+            json_tree_error: None,
+            json_schema: None,
+            yaml_links: Vec::new(),
+            schema_overrides: HashMap::new(),
+            color_literals: Vec::new(),
+            color_picker: None,
+            annotations: LineAnnotations::new(),
+            annotation_input: None,
+            file_notes: HashMap::new(),
+            note_editor: None,
+            identifier_query: references::identifier_query(unsafe { tree_sitter_rust() }),
+            local_references: Vec::new(),
+            selection_matches: Vec::new(),
+        };
+        for error in load_errors {
+            editor.report_error(error);
+        }
+        editor
+    }
+
+    fn editor_id() -> egui::Id {
+        egui::Id::new("editor_text")
+    }
+
+    // Mueve el cursor del TextEdit a un offset en bytes de `content`.
+    fn set_cursor(&mut self, ctx: &egui::Context, byte_index: usize) {
+        self.set_selection(ctx, byte_index..byte_index);
+    }
+
+    // Selecciona `range` (en bytes) con el cursor al final.
+    fn set_selection(&mut self, ctx: &egui::Context, range: Range<usize>) {
+        let start = range.start.min(self.content.len());
+        self.select_from(ctx, start, range.end.clamp(start, self.content.len()));
+    }
+
+    // Selecciona de `anchor` a `cursor` (en bytes), en cualquier orden; el cursor queda en `cursor`.
+    fn select_from(&mut self, ctx: &egui::Context, anchor: usize, cursor: usize) {
+        let (anchor, cursor) = (anchor.min(self.content.len()), cursor.min(self.content.len()));
+        self.cursor = cursor;
+        self.selection = anchor.min(cursor)..anchor.max(cursor);
+        let mut state = egui::TextEdit::load_state(ctx, Self::editor_id()).unwrap_or_default();
+        let anchor = egui::text::CCursor::new(text::byte_to_char(&self.content, anchor));
+        let cursor = egui::text::CCursor::new(text::byte_to_char(&self.content, cursor));
+        state.set_ccursor_range(Some(egui::text::CCursorRange::two(anchor, cursor)));
+        egui::TextEdit::store_state(ctx, Self::editor_id(), state);
+        self.update_selection_matches();
+    }
+
+    // egui mueve el cursor y borra de carácter en carácter. Cuando el grupo de grafemas junto al cursor tiene varios
+    // (una letra con acento combinante, un emoji con tono de piel, una bandera), las flechas, Backspace y Supr se
+    // aplican aquí al grupo entero; el resto de casos los sigue haciendo egui.
+    fn move_by_graphemes(&mut self, ctx: &egui::Context) {
+        let cursor = self.cursor.min(self.content.len());
+        let anchor = if self.selection.start == cursor { self.selection.end } else { self.selection.start };
+        let previous = text::previous_grapheme_boundary(&self.content, cursor);
+        let next = text::next_grapheme_boundary(&self.content, cursor);
+        let (wide_before, wide_after) = (text::char_len(&self.content, previous..cursor) > 1, text::char_len(&self.content, cursor..next) > 1);
+        let collapsed = self.selection.is_empty();
+        let (left, select_left, right, select_right, backspace, delete) = ctx.input_mut(|i| {
+            let mut take = |wanted: bool, modifiers: egui::Modifiers, key: egui::Key| wanted && i.consume_key(modifiers, key);
+            (
+                take(wide_before && collapsed, egui::Modifiers::NONE, egui::Key::ArrowLeft),
+                take(wide_before, egui::Modifiers::SHIFT, egui::Key::ArrowLeft),
+                take(wide_after && collapsed, egui::Modifiers::NONE, egui::Key::ArrowRight),
+                take(wide_after, egui::Modifiers::SHIFT, egui::Key::ArrowRight),
+                take(wide_before && collapsed, egui::Modifiers::NONE, egui::Key::Backspace),
+                take(wide_after && collapsed, egui::Modifiers::NONE, egui::Key::Delete),
+            )
+        });
+        if left {
+            self.set_cursor(ctx, previous);
+        } else if right {
+            self.set_cursor(ctx, next);
+        } else if select_left {
+            self.select_from(ctx, anchor, previous);
+        } else if select_right {
+            self.select_from(ctx, anchor, next);
+        } else if backspace || delete {
+            let range = if backspace { previous..cursor } else { cursor..next };
+            let before = self.content.clone();
+            self.content.replace_range(range.clone(), "");
+            self.record_edit(before, true);
+            self.reparse();
+            self.set_cursor(ctx, range.start);
+            self.active_tab_mut().is_modified = true;
+        }
+    }
+
+    // Vista del archivo entero en miniatura; mientras se mantiene pulsado, una lupa amplía la zona bajo el puntero sin
+    // mover el editor.
+    fn show_minimap(&self, ui: &mut egui::Ui) {
+        let (bounds, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
+        let color = self.theme_color(self.settings.text_color);
+        minimap::paint(ui.painter(), bounds, &self.content, &self.highlights, color);
+        if !response.is_pointer_button_down_on() {
+            return;
+        }
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let zoom = self.settings.minimap_zoom;
+            let zoom_rect = minimap::minimap_zoom_rect(pointer.y, bounds, zoom);
+            ui.painter().rect_stroke(zoom_rect, 0.0, ui.visuals().selection.stroke);
+            minimap::paint_zoom(ui.ctx(), bounds, zoom_rect, zoom, &self.content, &self.highlights, color);
+        }
+    }
+
+    fn palette(&self) -> StatusPalette {
+        StatusPalette::for_mode(self.settings.colorblind_mode)
+    }
+
+    // Colores del tema vistos a través de la simulación del modo daltónico elegido.
+    fn theme_color(&self, color: egui::Color32) -> egui::Color32 {
+        accessibility::simulate_cvd(color, self.settings.colorblind_mode)
+    }
+
+    fn line_number_font(&self) -> egui::FontId {
+        egui::FontId::new(self.settings.font_size * 0.85, egui::FontFamily::Monospace)
+    }
+
+    // Se mide con la fuente real en cada fotograma, así que sigue al tamaño de letra y al número de líneas.
+    fn gutter_width(&self, ctx: &egui::Context) -> f32 {
+        if self.settings.show_line_numbers {
+            let digits = self.line_offsets.len().max(1).to_string().len() as f32;
+            let digit_width = ctx.fonts(|fonts| fonts.glyph_width(&self.line_number_font(), '0'));
+            GUTTER_WIDTH + digits * digit_width + 8.0
+        } else {
+            GUTTER_WIDTH
+        }
+    }
+
+    // Bytes de las líneas `first..=last`, incluido el salto de línea final.
+    fn lines_range(&self, first: usize, last: usize) -> Range<usize> {
+        let start = self.line_offsets.get(first).copied().unwrap_or(self.content.len());
+        let end = self.line_offsets.get(last + 1).copied().unwrap_or(self.content.len());
+        start..end
+    }
+
+    // Números de línea en el margen; pulsar uno selecciona la línea y arrastrar extiende la selección.
+    fn show_line_numbers(&mut self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2, editor: egui::Rect) {
+        let font = self.line_number_font();
+        let color = self.theme_color(self.settings.text_color).gamma_multiply(0.5);
+        let number_right = editor.left() + self.gutter_width(ui.ctx()) - 4.0;
+        let clip = ui.clip_rect();
+
+        let mut line = 0;
+        let mut starts_line = true;
+        let mut pointer_line = None;
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        for row in &galley.rows {
+            let y_range = (row.rect.min.y + origin.y)..=(row.rect.max.y + origin.y);
+            if pointer.is_some_and(|pos| y_range.contains(&pos.y)) {
+                pointer_line = Some(line);
+            }
+            // Las filas de las líneas plegadas no tienen altura y no llevan número.
+            let visible = row.rect.height() >= 1.0;
+            if starts_line && visible && *y_range.end() >= clip.top() && *y_range.start() <= clip.bottom() {
+                let rect = egui::Rect::from_x_y_ranges(editor.left() + GUTTER_WIDTH..=number_right, y_range.clone());
+                let response = ui.interact(rect, egui::Id::new(("line_number", line)), egui::Sense::click_and_drag());
+                if response.drag_started() || response.clicked() {
+                    self.line_drag_anchor = Some(line);
+                    // La selección solo se dibuja si el editor tiene el foco.
+                    ui.memory_mut(|memory| memory.request_focus(Self::editor_id()));
+                }
+                ui.painter().text(egui::pos2(number_right, rect.center().y), egui::Align2::RIGHT_CENTER, (line + 1).to_string(), font.clone(), color);
+            }
+            starts_line = row.ends_with_newline;
+            if row.ends_with_newline {
+                line += 1;
+            }
+        }
+
+        if let Some(anchor) = self.line_drag_anchor {
+            let current = pointer_line.unwrap_or(anchor);
+            self.set_selection(ui.ctx(), self.lines_range(anchor.min(current), anchor.max(current)));
+            if !ui.input(|i| i.pointer.primary_down()) {
+                self.line_drag_anchor = None;
+            }
+        }
+    }
+
+    // Sustituye la selección actual (o inserta en el cursor) y deja el cursor al final del texto insertado.
+    fn replace_selection(&mut self, ctx: &egui::Context, replacement: &str) {
+        let range = self.selection.start.min(self.content.len())..self.selection.end.min(self.content.len());
+        self.content.replace_range(range.clone(), replacement);
+        self.reparse();
+        self.set_cursor(ctx, range.start + replacement.len());
+        self.active_tab_mut().is_modified = true;
+    }
+
+    // Sustituye la pulsación de `*`, `_` o `~` por el par de delimitadores o por saltar el de cierre.
+    fn auto_pair_emphasis(&mut self, ctx: &egui::Context) {
+        let cursor = self.cursor.min(self.content.len());
+        // egui-winit manda el `Key` de la tecla junto al `Text`; solo se quita el segundo.
+        let edit = ctx.input_mut(|i| {
+            let index = i.events.iter().position(|event| matches!(event, egui::Event::Text(_)))?;
+            let egui::Event::Text(typed) = &i.events[index] else {
+                return None;
+            };
+            let mut chars = typed.chars();
+            let edit = match (chars.next(), chars.next()) {
+                (Some(c), None) => markdown::emphasis_edit(&self.content, cursor, c)?,
+                _ => return None,
+            };
+            i.events.remove(index);
+            Some(edit)
+        });
+        match edit {
+            Some(markdown::EmphasisEdit::Skip) => self.set_cursor(ctx, cursor + 1),
+            Some(markdown::EmphasisEdit::Pair { before: opening, after }) => {
+                let before = self.content.clone();
+                self.content.insert_str(cursor, &(opening.clone() + &after));
+                self.record_edit(before, false);
+                self.reparse();
+                self.set_cursor(ctx, cursor + opening.len());
+                self.active_tab_mut().is_modified = true;
+            }
+            None => {}
+        }
+    }
+
+    // Pega JSON válido formateado con la sangría del archivo; si no es válido, se pega tal cual y se avisa.
+    fn smart_paste_json(&mut self, ctx: &egui::Context) {
+        let paste = ctx.input_mut(|i| {
+            let index = i.events.iter().position(|event| matches!(event, egui::Event::Paste(_)))?;
+            match i.events.remove(index) {
+                egui::Event::Paste(clipboard) => Some(clipboard),
+                _ => None,
+            }
+        });
+        let Some(clipboard) = paste else {
+            return;
+        };
+        let start = self.selection.start.min(self.content.len());
+        let line_start = self.content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_indent: String = self.content[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+        let file_compact = !self.content.trim().contains('\n');
+        self.record_edit(self.content.clone(), true);
+        match json::format_paste(&clipboard, &self.indent_style.unit(), &line_indent, file_compact) {
+            Ok(text) => self.replace_selection(ctx, &text),
+            Err(_) => {
+                self.replace_selection(ctx, &clipboard);
+                self.notifications.info("Pasted as raw text (invalid JSON)");
+            }
+        }
+    }
+
+    // Agrupa las pulsaciones seguidas en una sola instantánea: la racha se cierra tras una pausa o cuando se pasa
+    // de escribir caracteres a Enter/Backspace/Delete (o al revés).
+    fn record_edit(&mut self, before: String, boundary_key: bool) {
+        let pause = self.last_edit_time.elapsed() >= UNDO_PAUSE;
+        if matches!(&self.pending_snapshot, Some((_, kind)) if pause || *kind != boundary_key) {
+            self.commit_snapshot();
+        }
+        if self.pending_snapshot.is_none() {
+            self.pending_snapshot = Some((before, boundary_key));
+        }
+        self.last_edit_time = Instant::now();
+    }
+
+    fn commit_snapshot(&mut self) {
+        if let Some((snapshot, _)) = self.pending_snapshot.take() {
+            self.push_undo_snapshot(snapshot);
+        }
+    }
+
+    fn push_undo_snapshot(&mut self, snapshot: String) {
+        self.undo_tree.record(snapshot);
+        self.undo_tree.truncate(self.settings.undo_depth);
+    }
+
+    fn clear_undo_stacks(&mut self) {
+        self.undo_tree.clear();
+        self.pending_snapshot = None;
+    }
+
+    fn undo(&mut self, ctx: &egui::Context) {
+        self.commit_snapshot();
+        if let Some(snapshot) = self.undo_tree.undo(&self.content) {
+            self.restore_snapshot(ctx, snapshot);
+            self.undo_tree.truncate(self.settings.undo_depth);
+        }
+    }
+
+    fn redo(&mut self, ctx: &egui::Context) {
+        if let Some(snapshot) = self.undo_tree.redo(&self.content) {
+            self.restore_snapshot(ctx, snapshot);
+        }
+    }
+
+    // Salta a un estado de cualquier rama desde el historial.
+    fn jump_to_undo_state(&mut self, ctx: &egui::Context, index: usize) {
+        self.commit_snapshot();
+        if let Some(snapshot) = self.undo_tree.jump(index, &self.content) {
+            self.restore_snapshot(ctx, snapshot);
+            self.undo_tree.truncate(self.settings.undo_depth);
+        }
+    }
+
+    // Pone `snapshot` como contenido con el cursor al final de lo que cambia.
+    fn restore_snapshot(&mut self, ctx: &egui::Context, snapshot: String) {
+        let (_, _, end) = text::changed_range(&self.content, &snapshot);
+        self.content = snapshot;
+        // Los rangos enlazados ya no encajan con el texto restaurado.
+        self.stop_mirroring();
+        self.reparse();
+        self.set_cursor(ctx, end);
+        self.active_tab_mut().is_modified = true;
+    }
+
+    fn push_line_undo(&mut self, entry: UndoEntry) {
+        self.line_undo.push(entry);
+        if self.line_undo.len() > LINE_UNDO_LIMIT {
+            self.line_undo.remove(0);
+        }
+    }
+
+    fn delete_current_line(&mut self, ctx: &egui::Context) {
+        let offset = text::line_start(&self.content, self.cursor.min(self.content.len()));
+        if let Some(entry) = undo::delete_line(&mut self.content, self.cursor) {
+            self.push_line_undo(entry);
+            self.reparse();
+            self.set_cursor(ctx, offset);
+            self.active_tab_mut().is_modified = true;
+        }
+    }
+
+    fn duplicate_lines(&mut self, ctx: &egui::Context, direction: DuplicateDir) {
+        let before = self.content.clone();
+        let selection = text::duplicate_selection(&mut self.content, self.selection.clone(), direction);
+        if let Some(entry) = undo::duplicated_lines(&before, &self.content) {
+            self.push_line_undo(entry);
+        }
+        self.reparse();
+        self.set_selection(ctx, selection);
+        self.active_tab_mut().is_modified = true;
+    }
+
+    fn show_undo_history_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_undo_history;
+        let mut restore = None;
+        let mut jump = None;
+        egui::Window::new("Undo History").open(&mut open).show(ctx, |ui| {
+            if self.line_undo.is_empty() {
+                ui.label("No line edits yet.");
+            }
+            for (index, entry) in self.line_undo.iter().enumerate().rev() {
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = Some(index);
+                    }
+                    ui.label(entry.label());
+                });
+            }
+
+            ui.separator();
+            ui.heading("Undo Tree");
+            if self.undo_tree.nodes.is_empty() {
+                ui.label("No edits yet.");
+            }
+            // Cada rama anterior a la más reciente se sangra un nivel bajo el estado del que sale.
+            egui::ScrollArea::vertical().id_source("undo_tree").max_height(300.0).show(ui, |ui| {
+                for (depth, index) in self.undo_tree.rows() {
+                    ui.horizontal(|ui| {
+                        if depth > 0 {
+                            ui.label("│  ".repeat(depth));
+                        }
+                        let current = index == self.undo_tree.current;
+                        let marker = if current { "●" } else { "○" };
+                        if ui.selectable_label(current, format!("{} {}", marker, self.undo_tree.label(index))).clicked() {
+                            jump = Some(index);
+                        }
+                    });
+                }
+            });
+        });
+        self.show_undo_history = open;
+
+        if let Some(index) = jump {
+            self.jump_to_undo_state(ctx, index);
+        }
+
+        if let Some(index) = restore {
+            let entry = self.line_undo.remove(index);
+            let offset = entry.restore(&mut self.content);
+            self.reparse();
+            self.set_cursor(ctx, offset);
+            self.active_tab_mut().is_modified = true;
+        }
+    }
+
+    fn active_tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active_tab]
+    }
+
+    fn save(&mut self) {
+        if let Some(path) = &self.tabs[self.active_tab].file_path {
+            match fs::write(path, &self.content) {
+                Ok(()) => {
+                    if let Some(indexer) = &self.indexer {
+                        indexer.reindex(path);
+                    }
+                    if let Some(follow) = &mut self.tabs[self.active_tab].follow {
+                        follow.last_size = self.content.len() as u64;
+                    }
+                    self.last_save_time = Instant::now();
+                    self.active_tab_mut().is_modified = false;
+                }
+                Err(e) => {
+                    self.report_error(format!("Unable to save file: {}", e));
+                    self.active_tab_mut().is_modified = true;
+                    // El guardado automático no vuelve a intentarlo hasta el siguiente intervalo.
+                    self.last_save_time = Instant::now();
+                }
+            }
+        } else {
+            let modified = !self.content.is_empty();
+            self.active_tab_mut().is_modified = modified;
+        }
+    }
+
+    // Traits del índice del proyecto: sus métodos se llaman sin nombrarlos, así que su `use` no se da por sobrante.
+    fn project_traits(&self) -> HashSet<String> {
+        let Some(index) = self.indexer.as_ref().and_then(|indexer| indexer.index.try_read().ok()) else {
+            return HashSet::new();
+        };
+        index.values().flatten().filter(|symbol| symbol.kind == "trait").map(|symbol| symbol.name.clone()).collect()
+    }
+
+    fn remove_unused_imports(&mut self, ctx: &egui::Context) {
+        if self.unused_imports.is_empty() {
+            self.notifications.info("No unused imports found");
+            return;
+        }
+        let count = self.unused_imports.len();
+        let result = imports::remove_ranges(&self.content, &self.unused_imports);
+        self.apply_refactoring(ctx, result);
+        self.notifications.info(format!("Removed {} unused import(s)", count));
+    }
+
+    // Pista en tenue al final de cada `use` que parece sobrar.
+    fn paint_unused_imports(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        for range in &self.unused_imports {
+            let end = self.content[range.clone()].trim_end().len() + range.start;
+            let eol = view::byte_rect(galley, origin, &self.content, end);
+            painter.text(
+                eol.left_center() + egui::vec2(self.settings.font_size, 0.0),
+                egui::Align2::LEFT_CENTER,
+                self.localizations.tr("Unused import"),
+                egui::FontId::proportional(self.settings.font_size * 0.8),
+                self.theme_color(self.settings.text_color).gamma_multiply(0.45),
+            );
+        }
+    }
+
+    fn sort_imports(&mut self) {
+        if let Some(tree) = &self.tree {
+            self.content = imports::sort_rust_imports(&self.content, tree);
+            self.reparse();
+        }
+    }
+
+    // Guardado explícito (menú o atajo): aplica los pasos de formateo activados antes de escribir.
+    fn save_with_format(&mut self) {
+        if self.settings.sort_imports_on_save && self.is_rust_file() {
+            self.sort_imports();
+        }
+        self.save();
+    }
+
+    fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::Save => self.save_now(),
+            Action::OpenFile => self.open_file_dialog(),
+            Action::OpenDirectory => self.open_directory_dialog(),
+            Action::NewFile => self.new_file_wizard = Some(NewFileWizard::new(self.current_dir.clone(), self.settings.license.clone())),
+            Action::ToggleSettings => self.toggle_settings(),
+            Action::Find => self.toggle_find_bar(),
+            Action::NextTab => self.switch_tab((self.active_tab + 1) % self.tabs.len()),
+            Action::PreviousTab => self.switch_tab((self.active_tab + self.tabs.len() - 1) % self.tabs.len()),
+            Action::ToggleComment => self.toggle_comment(ctx),
+            Action::PasteAsNewFile => self.paste_as_new_file(),
+        }
+    }
+
+    fn open_file_dialog(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            self.load(path);
+        }
+    }
+
+    fn open_directory_dialog(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+            self.open_directory(path);
+        }
+    }
+
+    // Al abrir la barra de búsqueda se busca la selección si es de una sola línea.
+    fn toggle_find_bar(&mut self) {
+        if self.find.open {
+            self.find.close();
+            return;
+        }
+        let selected = self.content.get(self.selection.clone()).unwrap_or_default().to_string();
+        if !selected.is_empty() && !selected.contains('\n') {
+            self.find.query = selected;
+        }
+        self.find.open(&self.content);
+    }
+
+    // Atajo de guardar y "Save": pide una ruta si el archivo no tiene y guarda en el acto, sin esperar al guardado automático.
+    fn save_now(&mut self) {
+        if self.file_path.is_none() {
+            if let Some(path) = rfd::FileDialog::new().save_file() {
+                self.set_file_path(path);
+            }
+        }
+        if self.file_path.is_some() {
+            self.save_with_format();
+        }
+    }
+
+    // Los cambios tecleados solo marcan la pestaña como modificada; aquí se escriben cuando ha pasado el intervalo.
+    // Sin entrada egui no vuelve a pintar, así que se pide un fotograma para cuando toque.
+    fn auto_save_if_due(&mut self, ctx: &egui::Context) {
+        if !self.settings.auto_save || self.file_path.is_none() || !self.tabs[self.active_tab].is_modified {
+            return;
+        }
+        if self.last_save_time.elapsed() >= self.settings.auto_save_interval {
+            self.save();
+        }
+        if self.tabs[self.active_tab].is_modified {
+            ctx.request_repaint_after(self.settings.auto_save_interval.saturating_sub(self.last_save_time.elapsed()));
+        }
+    }
+
+    fn update_window_title(&mut self, frame: &mut eframe::Frame) {
+        let title = compute_window_title(self);
+        if title != self.window_title {
+            frame.set_window_title(&title);
+            self.window_title = title;
+        }
+    }
+
+    // Escribe el contenido en otra ruta sin cambiar el archivo activo.
+    fn save_copy_to(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, &self.content)
+    }
+
+    // Se propone el nombre del archivo con `.html`; el resaltado es el del último análisis.
+    fn export_html(&mut self) {
+        let name = self.file_path.as_ref().and_then(|path| path.file_name()).map_or_else(|| "Untitled".to_string(), |name| name.to_string_lossy().into_owned());
+        let Some(path) = rfd::FileDialog::new().set_file_name(&format!("{}.html", name)).add_filter("HTML", &["html"]).save_file() else {
+            return;
+        };
+        let style = html_export::HtmlStyle {
+            background: self.theme_color(self.settings.background_color),
+            text: self.theme_color(self.settings.text_color),
+            font_size: self.settings.font_size,
+            line_spacing: self.settings.line_spacing,
+        };
+        let html = html_export::export_html(&name, &self.content, &self.highlights, &style);
+        if let Err(e) = fs::write(&path, html) {
+            self.report_error(format!("Unable to export HTML: {}", e));
+        }
+    }
+
+    // Si `path` ya está abierto en otra pestaña, solo la activa; en la activa, lo vuelve a leer del disco.
+    fn load(&mut self, path: PathBuf) {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.file_path.as_ref() == Some(&path)) {
+            if index != self.active_tab {
+                self.switch_tab(index);
+                return;
+            }
+            // Volver a leerlo del disco tiraría lo tecleado desde el último guardado.
+            if self.tabs[index].is_modified {
+                self.save();
+            }
+        }
+        if let Ok(metadata) = fs::metadata(&path) {
+            if metadata.len() > self.settings.max_file_size_bytes as u64 {
+                self.open_prompt = Some(OpenPrompt::TooLarge { path, size: metadata.len() });
+                return;
+            }
+        }
+        self.load_within_limit(path);
+    }
+
+    fn load_within_limit(&mut self, path: PathBuf) {
+        if hex_view::looks_binary(&path) {
+            self.open_prompt = Some(OpenPrompt::Binary(path));
+            return;
+        }
+        self.read_into_tab(path);
+    }
+
+    fn read_into_tab(&mut self, path: PathBuf) {
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                // Un documento vacío sin guardar se reutiliza en vez de dejarlo como pestaña aparte.
+                let blank = self.file_path.is_none() && self.content.is_empty();
+                if self.file_path.as_ref() != Some(&path) && !blank {
+                    self.stash_active_tab();
+                    self.tabs.push(Tab::default());
+                    self.active_tab = self.tabs.len() - 1;
+                }
+                self.clear_undo_stacks();
+                self.settings.add_recent_file(&path);
+                let tab = self.active_tab_mut();
+                tab.file_path = Some(path.clone());
+                tab.is_modified = false;
+                self.open_buffer(Some(path), content);
+            }
+            Err(e) => self.report_error(format!("Unable to read file: {}", e)),
+        }
+    }
+
+    // Archivo recién abierto (o releído del disco): sin cambios registrados, zonas en espejo ni selección rectangular. El cursor se queda
+    // donde estaba si es el mismo archivo y va al principio si es otro.
+    fn open_buffer(&mut self, path: Option<PathBuf>, content: String) {
+        self.stop_mirroring();
+        self.changes = ChangeSet::default();
+        self.rect_selection = None;
+        self.active_tab_mut().follow = path.as_deref().filter(|path| follow::is_followable(path)).map(FollowState::new);
+        if path.is_none() || path != self.file_path {
+            self.pending_selection = Some((0, 0));
+        }
+        self.show_buffer(path, content);
+    }
+
+    // Prepara el editor para `content`: lenguaje, anotaciones, modo de mensaje de commit, análisis…
+    fn show_buffer(&mut self, path: Option<PathBuf>, content: String) {
+        self.content = content;
+        self.annotation_input = None;
+        self.annotations = path
+            .as_ref()
+            .and_then(|path| self.notes_dir_for(path).map(|dir| annotations::load(&dir, path)))
+            .unwrap_or_default();
+        self.set_language(path.as_deref().map_or(EditorLanguage::PlainText, EditorLanguage::from_path));
+        self.detected_language = None;
+        self.language_detection_done = path.as_deref().is_some_and(|path| !language::allows_content_detection(path));
+        self.commit_message_mode = path.as_deref().is_some_and(commit_message::is_commit_message);
+        self.commit_identity = path
+            .as_deref()
+            .and_then(Path::parent)
+            .filter(|_| self.commit_message_mode)
+            .and_then(commit_message::git_identity);
+        self.file_path = path;
+        self.reparse();
+        self.refresh_git_status();
+    }
+
+    // Devuelve a su pestaña el texto, el historial de deshacer, el cursor y el resto del estado de ese archivo.
+    fn stash_active_tab(&mut self) {
+        self.commit_snapshot();
+        let tab = &mut self.tabs[self.active_tab];
+        tab.content = std::mem::take(&mut self.content);
+        tab.undo_tree = std::mem::take(&mut self.undo_tree);
+        tab.cursor = self.cursor;
+        tab.selection = self.selection.clone();
+        tab.changes = std::mem::take(&mut self.changes);
+        tab.mirror = self.mirror.take();
+        tab.mirror_pending = self.mirror_pending.take();
+        tab.rect_selection = self.rect_selection.take();
+    }
+
+    fn restore_active_tab(&mut self) {
+        let tab = &mut self.tabs[self.active_tab];
+        let content = std::mem::take(&mut tab.content);
+        let path = tab.file_path.clone();
+        let undo_tree = std::mem::take(&mut tab.undo_tree);
+        let changes = std::mem::take(&mut tab.changes);
+        let (mirror, mirror_pending, rect_selection) = (tab.mirror.take(), tab.mirror_pending.take(), tab.rect_selection.take());
+        let anchor = if tab.cursor == tab.selection.start { tab.selection.end } else { tab.selection.start };
+        self.pending_selection = Some((anchor, tab.cursor));
+        self.clear_undo_stacks();
+        self.show_buffer(path, content);
+        self.undo_tree = undo_tree;
+        self.changes = changes;
+        (self.mirror, self.mirror_pending, self.rect_selection) = (mirror, mirror_pending, rect_selection);
+    }
+
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        if self.settings.auto_save && self.tabs[self.active_tab].is_modified {
+            self.save();
+        }
+        self.stash_active_tab();
+        self.active_tab = index;
+        self.restore_active_tab();
+    }
+
+    // Los cambios sin guardar se confirman antes; la pestaña se activa para que se vea qué se cierra.
+    fn request_close_tab(&mut self, index: usize) {
+        if self.tabs[index].is_modified {
+            self.switch_tab(index);
+            self.close_prompt = Some(self.active_tab);
+        } else {
+            self.close_tab(index);
+        }
+    }
+
+    // Pestaña nueva sin nombre con el portapapeles, resaltada según el lenguaje que delate el contenido.
+    fn paste_as_new_file(&mut self) {
+        let Some(content) = read_clipboard() else {
+            self.notifications.info("Clipboard is empty");
+            return;
+        };
+        self.stash_active_tab();
+        self.tabs.push(Tab::default());
+        self.active_tab = self.tabs.len() - 1;
+        self.clear_undo_stacks();
+        self.open_buffer(None, content);
+        self.detect_language();
+        self.active_tab_mut().is_modified = true;
+    }
+
+    // Cerrar la última pestaña deja un documento vacío sin guardar.
+    fn close_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+        self.tabs.remove(index);
+        if self.tabs.is_empty() {
+            self.tabs.push(Tab::default());
+        }
+        if index == self.active_tab {
+            self.active_tab = index.min(self.tabs.len() - 1);
+            self.restore_active_tab();
+        } else if index < self.active_tab {
+            self.active_tab -= 1;
+        }
+    }
+
+    // Escribe la pestaña activa y las demás modificadas que ya tienen ruta.
+    fn save_modified_tabs(&mut self) {
+        if self.tabs[self.active_tab].is_modified {
+            self.save();
+        }
+        let active = self.active_tab;
+        for index in (0..self.tabs.len()).filter(|&index| index != active) {
+            let tab = &self.tabs[index];
+            let Some(path) = tab.file_path.as_ref().filter(|_| tab.is_modified) else {
+                continue;
+            };
+            match fs::write(path, &tab.content) {
+                Ok(()) => self.tabs[index].is_modified = false,
+                Err(e) => self.report_error(format!("Unable to save file: {}", e)),
+            }
+        }
+    }
+
+    // Ventana de "¿Guardar antes de salir?" con las pestañas que tienen cambios.
+    fn show_quit_prompt(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if !self.quit_prompt {
+            return;
+        }
+        let mut choice = None;
+        egui::Window::new(self.localizations.tr("Unsaved Changes")).collapsible(false).resizable(false).show(ctx, |ui| {
+            ui.label(self.localizations.tr("Save changes before quitting?"));
+            for tab in self.tabs.iter().filter(|tab| tab.is_modified) {
+                ui.monospace(tab.title().trim_start_matches("● "));
+            }
+            ui.horizontal(|ui| {
+                for label in ["Save All", "Don't Save", "Cancel"] {
+                    if ui.button(self.localizations.tr(label)).clicked() {
+                        choice = Some(label);
+                    }
+                }
+            });
+        });
+        match choice {
+            Some("Save All") => {
+                self.save_modified_tabs();
+                self.quit_prompt = false;
+                // Si algo no se pudo escribir, no se sale.
+                if !self.tabs.iter().any(|tab| tab.is_modified && tab.file_path.is_some()) {
+                    self.quit_confirmed = true;
+                    frame.close();
+                }
+            }
+            Some("Don't Save") => {
+                self.quit_prompt = false;
+                self.quit_confirmed = true;
+                frame.close();
+            }
+            Some(_) => self.quit_prompt = false,
+            None => {}
+        }
+    }
+
+    // Ventana de "¿Guardar antes de cerrar?" para la pestaña de `close_prompt`.
+    fn show_close_prompt(&mut self, ctx: &egui::Context) {
+        let Some(index) = self.close_prompt else {
+            return;
+        };
+        if index != self.active_tab {
+            self.close_prompt = None;
+            return;
+        }
+        let mut choice = None;
+        egui::Window::new("Unsaved Changes").collapsible(false).resizable(false).show(ctx, |ui| {
+            ui.label(format!("Save changes to {} before closing?", self.tabs[index].title().trim_start_matches("● ")));
+            ui.horizontal(|ui| {
+                for label in ["Save", "Don't Save", "Cancel"] {
+                    if ui.button(label).clicked() {
+                        choice = Some(label);
+                    }
+                }
+            });
+        });
+        match choice {
+            Some("Save") => {
+                if self.file_path.is_none() {
+                    if let Some(path) = rfd::FileDialog::new().save_file() {
+                        self.set_file_path(path);
+                    }
+                }
+                self.save_with_format();
+                self.close_prompt = None;
+                // Si no se llegó a guardar (diálogo cancelado o error), la pestaña se queda abierta.
+                if !self.tabs[index].is_modified {
+                    self.close_tab(index);
+                }
+            }
+            Some("Don't Save") => {
+                self.close_prompt = None;
+                self.close_tab(index);
+            }
+            Some(_) => self.close_prompt = None,
+            None => {}
+        }
+    }
 
-        let highlight_config = HighlightConfiguration::new(
-            unsafe { tree_sitter_rust() },
-            tree_sitter_rust::HIGHLIGHT_QUERY,
-            "",
-            "",
-        ).expect("Error creating highlight configuration");
+    // Diálogo para archivos por encima del límite de tamaño o que parecen binarios.
+    fn show_open_prompt(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = &self.open_prompt else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("Open File").collapsible(false).resizable(false).show(ctx, |ui| {
+            let buttons: &[&str] = match prompt {
+                OpenPrompt::TooLarge { size, .. } => {
+                    ui.label(format!(
+                        "File is {} MB, which exceeds the limit of {} MB. Open anyway? (May cause slowness)",
+                        size / MEGABYTE,
+                        self.settings.max_file_size_bytes as u64 / MEGABYTE
+                    ));
+                    &["Open", "Cancel"]
+                }
+                OpenPrompt::Binary(path) => {
+                    ui.label(format!("{} looks like a binary file.", path.display()));
+                    &["Open in Hex View", "Open as Text", "Cancel"]
+                }
+            };
+            ui.horizontal(|ui| {
+                for &label in buttons {
+                    if ui.button(label).clicked() {
+                        choice = Some(label);
+                    }
+                }
+            });
+        });
+        let Some(choice) = choice else {
+            return;
+        };
+        match (self.open_prompt.take(), choice) {
+            (Some(OpenPrompt::TooLarge { path, .. }), "Open") => self.load_within_limit(path),
+            (Some(OpenPrompt::Binary(path)), "Open in Hex View") => match HexView::open(path) {
+                Ok(view) => self.hex_view = Some(view),
+                Err(e) => self.report_error(format!("Unable to read file: {}", e)),
+            },
+            (Some(OpenPrompt::Binary(path)), "Open as Text") => self.read_into_tab(path),
+            _ => {}
+        }
+    }
+
+    // Proyecto y ruta del elemento que `cargo expand` debe expandir si el cursor está en una macro de Rust.
+    fn macro_item_at_cursor(&self) -> Option<(PathBuf, String)> {
+        let (Some(tree), Some(path)) = (&self.tree, &self.file_path) else {
+            return None;
+        };
+        if !self.is_rust_file() {
+            return None;
+        }
+        let root = expand::project_root(path)?;
+        let item = expand::item_path_at(tree, &self.content, path, &root, self.cursor)?;
+        Some((root, item))
+    }
+
+    fn show_macro_expansion(&mut self, ctx: &egui::Context) {
+        let text_color = self.theme_color(self.settings.text_color);
+        let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let Some(expansion) = &mut self.macro_expansion else {
+            return;
+        };
+        expansion.poll(text_color, &self.settings.syntax_colors);
+        if expansion.is_running() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        if !expansion.show(ctx, font) {
+            self.macro_expansion = None;
+        }
+    }
+
+    fn set_file_path(&mut self, path: PathBuf) {
+        self.active_tab_mut().file_path = Some(path.clone());
+        self.file_path = Some(path);
+    }
+
+    // Añade lo que haya crecido cada archivo seguido (o lo recarga si se truncó) sin volver a guardarlo. Las pestañas
+    // de fondo también se siguen; su texto está en la propia pestaña.
+    fn poll_follow(&mut self, ctx: &egui::Context) {
+        for index in 0..self.tabs.len() {
+            let tab = &mut self.tabs[index];
+            let (Some(follow), Some(path)) = (&mut tab.follow, &tab.file_path) else {
+                continue;
+            };
+            if !follow.polling {
+                continue;
+            }
+            ctx.request_repaint_after(follow::POLL_INTERVAL);
+            let content = if index == self.active_tab { &mut self.content } else { &mut tab.content };
+            match follow.poll(path) {
+                Some(FollowUpdate::Appended(delta)) if !delta.is_empty() => content.push_str(&delta),
+                Some(FollowUpdate::Reloaded(reloaded)) => *content = reloaded,
+                _ => continue,
+            }
+            if index == self.active_tab {
+                self.reparse();
+                self.set_cursor(ctx, self.content.len());
+                self.scroll_to_end = true;
+            }
+        }
+    }
+
+    // Cambia de gramática; el siguiente `reparse` ya analiza con la nueva.
+    fn set_language(&mut self, language: EditorLanguage) {
+        self.language = language;
+        self.profile = LanguageProfile::for_language(language);
+        self.highlight_config = self.profile.as_ref().and_then(LanguageProfile::highlight_config);
+        if let Some(profile) = &self.profile {
+            if let Err(e) = self.parser.set_language(profile.language) {
+                self.report_error(format!("Unable to load {} grammar: {}", profile.name, e));
+                self.profile = None;
+            }
+        }
+        self.tree = None;
+    }
+
+    fn reparse(&mut self) {
+        self.document_version += 1;
+        self.tree = match &self.profile {
+            Some(_) => self.parser.parse(&self.content, None),
+            None => None,
+        };
+        self.outline = self
+            .tree
+            .as_ref()
+            .map(|tree| {
+                let queries = self.outline_query_list();
+                if queries.is_empty() {
+                    outline::extract_symbols(tree, self.content.as_bytes())
+                } else {
+                    outline::run_outline_queries(&queries, tree, self.content.as_bytes())
+                }
+            })
+            .unwrap_or_default();
+        self.toml_validation_due = self.language == EditorLanguage::Toml;
+        if !self.toml_validation_due {
+            self.toml_error = None;
+        }
+        self.diagnostics = match &self.tree {
+            Some(tree) if self.is_rust_file() => diagnostics::syntax_diagnostics(tree, self.content.as_bytes()),
+            _ if matches!(self.language, EditorLanguage::Json | EditorLanguage::Yaml) => self.schema_diagnostics(),
+            _ if self.language == EditorLanguage::Toml => self.toml_diagnostics(),
+            _ => Vec::new(),
+        };
+        self.find.update(&self.content);
+        if let (Some(playground), Some(tree)) = (&mut self.query_playground, &self.tree) {
+            playground.run(tree, self.content.as_bytes());
+        }
+        self.indent_style = IndentStyle::detect(&self.content);
+        self.indent_histogram = indentation::indentation_histogram(&self.content, self.indent_style);
+        self.indent_guides = match &self.tree {
+            Some(tree) if self.is_rust_file() => indent_guides::compute_indent_guide_offsets(tree, self.content.as_bytes()),
+            _ => Vec::new(),
+        };
+        let mut folds = match &self.tree {
+            Some(tree) if self.is_rust_file() => folding::import_folds(tree, &self.content, self.settings.collapse_imports),
+            _ => Vec::new(),
+        };
+        if let Some((start, end)) = self.settings.fold_region_patterns() {
+            folds.extend(folding::region_folds(&self.content, &start, &end));
+        }
+        folding::carry_over(&self.folds, &mut folds);
+        self.folds = folds;
+        self.unused_imports = match &self.tree {
+            Some(tree) if self.is_rust_file() => imports::unused_imports(tree, &self.content, &self.project_traits()),
+            _ => Vec::new(),
+        };
+        self.conflicts = conflicts::find_conflicts(&self.content);
+        self.update_virtual_texts();
+        self.yaml_links = if self.language == EditorLanguage::Yaml {
+            yaml::find_yaml_aliases(&self.content, &yaml::find_yaml_anchors(&self.content))
+        } else {
+            Vec::new()
+        };
+        self.color_literals = if self.language == EditorLanguage::Css {
+            colors::find_hex_colors(&self.content)
+        } else {
+            Vec::new()
+        };
+        self.update_long_lines();
+        self.line_offsets = text::line_offsets(&self.content);
+        self.update_node_count();
+        self.refresh_highlights();
+        if self.json_tree.is_some() {
+            self.json_tree = Some(json::parse(&self.content));
+        }
+    }
+
+    // Recorrer el árbol entero cuesta; solo se hace con la ventana de rendimiento abierta.
+    fn update_node_count(&mut self) {
+        if let (true, Some(tree)) = (self.show_performance, &self.tree) {
+            self.performance.node_count = performance::count_nodes(tree);
+        }
+    }
+
+    fn line_length_limit(&self) -> Option<usize> {
+        self.settings.max_line_length.filter(|_| self.settings.line_length_mode != LineLengthMode::Disabled)
+    }
+
+    fn update_long_lines(&mut self) {
+        self.long_lines = match self.line_length_limit() {
+            Some(max) => line_length::long_lines(&self.content, max),
+            None => Vec::new(),
+        };
+    }
+
+    // Guía vertical en la columna límite, un punto en el margen y un fondo tenue sobre la parte que sobra.
+    fn paint_long_lines(&self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2, editor: egui::Rect) {
+        let palette = self.palette();
+        let Some(max) = self.line_length_limit() else {
+            return;
+        };
+        let painter = ui.painter();
+        let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let column_x = origin.x + ui.fonts(|fonts| fonts.glyph_width(&font, 'M')) * max as f32;
+        if column_x < editor.right() {
+            painter.vline(column_x, editor.y_range(), egui::Stroke::new(1.0, palette.warning.gamma_multiply(0.3)));
+        }
+
+        for &line in &self.long_lines {
+            let start = undo::line_offset(&self.content, line);
+            let end = self.content[start..].find('\n').map_or(self.content.len(), |i| start + i);
+            let line_rect = view::byte_rect(galley, origin, &self.content, start);
+            painter.circle_filled(
+                egui::pos2(editor.left() + GUTTER_WIDTH * 0.5, line_rect.center().y),
+                GUTTER_WIDTH * 0.2,
+                palette.warning,
+            );
+
+            let overflow = start + text::char_to_byte(&self.content[start..end], max);
+            let from = view::byte_rect(galley, origin, &self.content, overflow);
+            let to = view::byte_rect(galley, origin, &self.content, end);
+            let right = if to.top() == from.top() { to.left() } else { editor.right() };
+            let rect = egui::Rect::from_min_max(from.left_top(), egui::pos2(right, from.bottom()));
+            painter.rect_filled(rect, 0.0, palette.warning.gamma_multiply(0.15));
+        }
+    }
+
+    // Una línea de 1 px por bloque, en la columna de la sangría de la línea que lo abre.
+    fn paint_indent_guides(&self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2) {
+        let clip = ui.clip_rect();
+        let color = self.theme_color(self.settings.indent_guide_color);
+        let active_color = self.theme_color(self.settings.active_indent_guide_color);
+        let cursor_line = self.content[..self.cursor.min(self.content.len())].matches('\n').count();
+        let active = indent_guides::active_guide(&self.indent_guides, cursor_line);
+        for (index, &(first, last)) in self.indent_guides.iter().enumerate() {
+            let (Some(&opening), Some(&first_offset), Some(&last_offset)) =
+                (self.line_offsets.get(first - 1), self.line_offsets.get(first), self.line_offsets.get(last))
+            else {
+                continue;
+            };
+            let indent = text::line_indent(&self.content, opening).len();
+            let x = view::byte_rect(galley, origin, &self.content, opening + indent).left();
+            let top = view::byte_rect(galley, origin, &self.content, first_offset).top();
+            let bottom = view::byte_rect(galley, origin, &self.content, last_offset).bottom();
+            if bottom >= clip.top() && top <= clip.bottom() {
+                // Un nivel de sangría mide lo que separa la guía del texto de la primera línea del bloque.
+                let inner_indent = text::line_indent(&self.content, first_offset).len();
+                let inner_x = view::byte_rect(galley, origin, &self.content, first_offset + inner_indent).left();
+                let color = if active == Some(index) { active_color } else { color };
+                indent_guides::paint_guide(ui.painter(), self.settings.indent_guide_style, x, top..=bottom, (inner_x - x).max(1.0), color);
+            }
+        }
+    }
+
+    // Fija arriba del editor la cabecera del ámbito superior que se está recorriendo.
+    fn paint_sticky_scope(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2, viewport: egui::Rect) {
+        let Some(tree) = self.tree.as_ref().filter(|_| self.is_rust_file()) else {
+            return;
+        };
+        let mut first_visible_line = 0;
+        for row in &galley.rows {
+            if origin.y + row.rect.max.y > viewport.top() {
+                break;
+            }
+            if row.ends_with_newline {
+                first_visible_line += 1;
+            }
+        }
+        let Some(header) = outline::outermost_visible_scope(tree, self.content.as_bytes(), first_visible_line) else {
+            return;
+        };
+        let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let header = painter.layout_no_wrap(header, font, self.theme_color(self.settings.text_color));
+        let rect = egui::Rect::from_min_size(viewport.left_top(), egui::vec2(viewport.width(), header.size().y + 4.0));
+        // Fondo un poco desplazado hacia el color del texto para separarlo del contenido.
+        let background = self.theme_color(self.settings.background_color);
+        let text_color = self.theme_color(self.settings.text_color);
+        let mix = |a: u8, b: u8| (a as f32 * 0.92 + b as f32 * 0.08) as u8;
+        let fill = egui::Color32::from_rgb(
+            mix(background.r(), text_color.r()),
+            mix(background.g(), text_color.g()),
+            mix(background.b(), text_color.b()),
+        );
+        painter.rect_filled(rect, 0.0, fill);
+        painter.galley(egui::pos2(origin.x, rect.top() + 2.0), header);
+        painter.hline(rect.x_range(), rect.bottom(), egui::Stroke::new(1.0, text_color.gamma_multiply(0.3)));
+    }
+
+    // Inserciones subrayadas; cada borrado se dibuja tachado justo encima del punto donde estaba.
+    fn paint_tracked_changes(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2, editor: egui::Rect) {
+        let palette = self.palette();
+        for change in &self.changes.changes {
+            match &change.kind {
+                ChangeKind::Insertion => {
+                    let mut start = change.range.start;
+                    for segment in self.content.get(change.range.clone()).unwrap_or("").split_inclusive('\n') {
+                        let end = start + segment.trim_end_matches('\n').len();
+                        let from = view::byte_rect(galley, origin, &self.content, start);
+                        let to = view::byte_rect(galley, origin, &self.content, end);
+                        let right = if to.top() == from.top() { to.left() } else { editor.right() };
+                        painter.hline(from.left()..=right, from.bottom(), egui::Stroke::new(2.0, palette.added));
+                        start += segment.len();
+                    }
+                }
+                ChangeKind::Deletion { text } => {
+                    let anchor = view::byte_rect(galley, origin, &self.content, change.range.start);
+                    let preview: String = text.lines().next().unwrap_or("⏎").chars().take(40).collect();
+                    let deleted = painter.layout_no_wrap(
+                        preview,
+                        egui::FontId::new(self.settings.font_size * 0.7, self.settings.font_family.clone()),
+                        palette.removed,
+                    );
+                    let rect = egui::Align2::LEFT_BOTTOM.anchor_rect(egui::Rect::from_min_size(anchor.left_top(), deleted.size()));
+                    painter.rect_filled(rect.expand(1.0), 2.0, self.theme_color(self.settings.background_color));
+                    painter.hline(rect.x_range(), rect.center().y, egui::Stroke::new(1.0, palette.removed));
+                    painter.galley(rect.min, deleted);
+                    painter.vline(anchor.left(), anchor.y_range(), egui::Stroke::new(1.5, palette.removed));
+                }
+            }
+        }
+    }
+
+    fn show_changes_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_changes;
+        let mut accept = None;
+        let mut reject = None;
+        let mut accept_all = false;
+        let mut reject_all = false;
+        egui::Window::new("Tracked Changes").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                accept_all = ui.button("Accept All").clicked();
+                reject_all = ui.button("Reject All").clicked();
+            });
+            ui.separator();
+            if self.changes.changes.is_empty() {
+                ui.label("No tracked changes.");
+            }
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for change in &self.changes.changes {
+                    ui.horizontal(|ui| {
+                        if ui.button("Accept").clicked() {
+                            accept = Some(change.id);
+                        }
+                        if ui.button("Reject").clicked() {
+                            reject = Some(change.id);
+                        }
+                        ui.label(change.label(&self.content));
+                    });
+                }
+            });
+        });
+        self.show_changes = open;
+
+        let before = self.content.len();
+        if let Some(id) = accept {
+            track_changes::accept_change(&mut self.changes, id);
+        }
+        if let Some(id) = reject {
+            track_changes::reject_change(&mut self.changes, &mut self.content, id);
+        }
+        if accept_all {
+            track_changes::accept_all(&mut self.changes);
+        }
+        if reject_all {
+            track_changes::reject_all(&mut self.changes, &mut self.content);
+        }
+        if self.content.len() != before {
+            self.reparse();
+            self.set_cursor(ctx, self.cursor.min(self.content.len()));
+            self.active_tab_mut().is_modified = true;
+        }
+    }
+
+    fn insert_commit_trailer(&mut self, ctx: &egui::Context, trailer: &str) {
+        let end = commit_message::insert_trailer(&mut self.content, trailer);
+        self.reparse();
+        self.set_cursor(ctx, end);
+        self.active_tab_mut().is_modified = true;
+    }
+
+    // Ayudas para mensajes de commit (`COMMIT_EDITMSG`): trailers habituales y caracteres libres del asunto.
+    fn show_commit_message_banner(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::none()
+            .fill(ui.visuals().faint_bg_color)
+            .inner_margin(egui::Margin::same(4.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Commit message:");
+                    let identity = self.commit_identity.clone();
+                    if ui.add_enabled(identity.is_some(), egui::Button::new("Insert Co-Author")).clicked() {
+                        let identity = identity.clone().unwrap_or_default();
+                        self.insert_commit_trailer(ui.ctx(), &format!("Co-authored-by: {}", identity));
+                    }
+                    if ui.add_enabled(identity.is_some(), egui::Button::new("Insert Signed-off-by")).clicked() {
+                        let identity = identity.unwrap_or_default();
+                        self.insert_commit_trailer(ui.ctx(), &format!("Signed-off-by: {}", identity));
+                    }
+                    if ui.button("Insert Breaking Change").clicked() {
+                        self.insert_commit_trailer(ui.ctx(), "BREAKING CHANGE: ");
+                    }
+                    if ui.button("Insert Date-Time").clicked() {
+                        self.replace_selection(ui.ctx(), &templates::now());
+                    }
+
+                    ui.separator();
+                    let left = commit_message::subject_chars_left(&self.content);
+                    if left >= 0 {
+                        ui.label(format!("{} characters left on the first line", left));
+                    } else {
+                        ui.colored_label(self.palette().error, format!("First line is {} characters over {}", -left, commit_message::SUBJECT_LIMIT));
+                    }
+                });
+            });
+    }
+
+    // En archivos `.txt` o sin extensión, cambia de gramática en cuanto el contenido delata un lenguaje.
+    fn detect_language(&mut self) {
+        if self.language_detection_done || self.language != EditorLanguage::PlainText {
+            return;
+        }
+        if let Some(language) = language::detect_language_from_content(&self.content) {
+            self.set_language(language);
+            self.detected_language = Some(language);
+            self.language_detection_done = true;
+            self.reparse();
+        }
+    }
+
+    fn show_language_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(language) = self.detected_language else {
+            return;
+        };
+        egui::Frame::none()
+            .fill(ui.visuals().faint_bg_color)
+            .inner_margin(egui::Margin::same(4.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    // Sin gramática para el lenguaje detectado no hay colores que anunciar.
+                    if self.profile.is_some() {
+                        ui.label(format!("Detected {} — syntax highlighting enabled.", language.label()));
+                    } else {
+                        ui.label(format!("Detected {} — no syntax highlighting available for it yet.", language.label()));
+                    }
+                    if ui.button("Keep").clicked() {
+                        self.detected_language = None;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        self.detected_language = None;
+                        self.set_language(EditorLanguage::PlainText);
+                        self.reparse();
+                    }
+                });
+            });
+    }
+
+    fn show_status_bar(&mut self, ctx: &egui::Context) {
+        let mut customize = false;
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let mut first = true;
+                for item in self.settings.status_bar_items.clone() {
+                    let Some(response) = self.show_status_bar_item(ui, item, first) else {
+                        continue;
+                    };
+                    first = false;
+                    response.context_menu(|ui| {
+                        if ui.button("Customize Status Bar…").clicked() {
+                            customize = true;
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+        });
+        if customize {
+            self.show_settings = true;
+        }
+    }
+
+    // Dibuja un indicador (con separador delante salvo el primero); `None` si no tiene nada que mostrar.
+    fn show_status_bar_item(&self, ui: &mut egui::Ui, item: StatusBarItem, first: bool) -> Option<egui::Response> {
+        let text = match item {
+            StatusBarItem::CursorPosition => {
+                let cursor = self.cursor.min(self.content.len());
+                let line = self.content[..cursor].matches('\n').count();
+                let column = self.content[text::line_start(&self.content, cursor)..cursor].chars().count();
+                egui::RichText::new(format!("Ln {}, Col {}", line + 1, column + 1))
+            }
+            StatusBarItem::Language => egui::RichText::new(self.language.label()),
+            StatusBarItem::Encoding => egui::RichText::new("UTF-8"),
+            StatusBarItem::LineEnding => egui::RichText::new(status_bar::line_ending(&self.content)),
+            StatusBarItem::FileSize => egui::RichText::new(status_bar::human_size(self.content.len())),
+            StatusBarItem::DirtyFlag if self.tabs[self.active_tab].is_modified => egui::RichText::new("● Unsaved").color(self.palette().warning),
+            StatusBarItem::DirtyFlag => egui::RichText::new("Saved"),
+            StatusBarItem::WordCount => egui::RichText::new(format!("{} words", self.content.split_whitespace().count())),
+            StatusBarItem::TypingSpeed => egui::RichText::new(format!("{:.0} WPM", self.typing_stats.displayed_wpm())),
+            StatusBarItem::LongLines if self.long_lines.is_empty() => return None,
+            StatusBarItem::LongLines => {
+                egui::RichText::new(format!("⚠ {} long lines", self.long_lines.len())).color(self.palette().warning)
+            }
+            StatusBarItem::FindMatches => egui::RichText::new(self.find.status()?),
+            StatusBarItem::LspStatus => match &self.lsp {
+                Some(client) if client.is_ready() => egui::RichText::new(lsp::RUST_ANALYZER),
+                Some(_) => egui::RichText::new(format!("{} (starting)", lsp::RUST_ANALYZER)).weak(),
+                None => egui::RichText::new("No language server").weak(),
+            },
+            StatusBarItem::SyntaxNode => {
+                let (path, range) = self.syntax_node.as_ref().filter(|(path, _)| !path.is_empty())?;
+                if !first {
+                    ui.separator();
+                }
+                let label = ui.add(egui::Label::new(format!("node: {}", path)).sense(egui::Sense::click()));
+                return Some(label.on_hover_text(format!("Bytes {}..{}", range.start, range.end)));
+            }
+            StatusBarItem::IndentHistogram => {
+                if !first {
+                    ui.separator();
+                }
+                return Some(self.show_indent_histogram(ui));
+            }
+        };
+        if !first {
+            ui.separator();
+        }
+        Some(ui.add(egui::Label::new(text).sense(egui::Sense::click())))
+    }
+
+    // Minigráfico de barras con la proporción de líneas en cada nivel de sangría; el tooltip da las cifras exactas.
+    fn show_indent_histogram(&self, ui: &mut egui::Ui) -> egui::Response {
+        const BAR_WIDTH: f32 = 4.0;
+        let height = ui.spacing().interact_size.y * 0.7;
+        let size = egui::vec2((BAR_WIDTH + 1.0) * indentation::HISTOGRAM_LEVELS as f32, height);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+        let color = ui.visuals().text_color().gamma_multiply(0.7);
+        for (level, fraction) in self.indent_histogram.iter().enumerate() {
+            let left = rect.left() + level as f32 * (BAR_WIDTH + 1.0);
+            let bar_height = (height * fraction).max(if *fraction > 0.0 { 1.0 } else { 0.0 });
+            let bar = egui::Rect::from_min_max(egui::pos2(left, rect.bottom() - bar_height), egui::pos2(left + BAR_WIDTH, rect.bottom()));
+            ui.painter().rect_filled(bar, 0.0, color);
+        }
+        response.on_hover_ui(|ui| {
+            ui.label(format!("Indentation ({})", self.indent_style.label()));
+            let counts = indentation::indentation_counts(&self.content, self.indent_style);
+            for (level, count) in counts.iter().enumerate() {
+                let name = if level == indentation::HISTOGRAM_LEVELS - 1 { format!("{}+", level) } else { level.to_string() };
+                ui.label(format!("Level {}: {} lines", name, count));
+            }
+        })
+    }
+
+    // Muestra a la derecha de cada literal hexadecimal una muestra del color; al pulsarla se abre el selector.
+    fn show_color_swatches(&mut self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2) {
+        let size = self.settings.font_size * 0.8;
+        let mut clicked = None;
+        for literal in &self.color_literals {
+            let end = view::byte_rect(galley, origin, &self.content, literal.range.end);
+            let rect = egui::Rect::from_center_size(
+                egui::pos2(end.left() + size * 0.5 + 4.0, end.center().y),
+                egui::vec2(size, size),
+            );
+            let response = ui.interact(rect, egui::Id::new(("color_swatch", literal.range.start)), egui::Sense::click());
+            ui.painter().rect_filled(rect, 2.0, literal.color);
+            ui.painter().rect_stroke(rect, 2.0, egui::Stroke::new(1.0, egui::Color32::GRAY));
+            if response.clicked() {
+                clicked = Some((literal.range.clone(), literal.color, rect.right_bottom()));
+            }
+        }
+        if clicked.is_some() {
+            self.color_picker = clicked;
+        }
+    }
+
+    fn show_color_picker(&mut self, ctx: &egui::Context) {
+        let Some((mut range, mut color, pos)) = self.color_picker.clone() else {
+            return;
+        };
+        let mut open = true;
+        let mut changed = false;
+        egui::Window::new("Color")
+            .collapsible(false)
+            .resizable(false)
+            .fixed_pos(pos)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                changed = egui::color_picker::color_picker_color32(ui, &mut color, egui::color_picker::Alpha::OnlyBlend);
+            });
+
+        if changed && self.content.get(range.clone()).is_some() {
+            let hex = colors::to_hex(color);
+            self.content.replace_range(range.clone(), &hex);
+            range = range.start..range.start + hex.len();
+            self.reparse();
+            self.active_tab_mut().is_modified = true;
+        }
+        self.color_picker = open.then_some((range, color, pos));
+    }
+
+    // Una consulta por línea; vacío significa usar el esquema predefinido.
+    fn outline_query_list(&self) -> Vec<&str> {
+        self.settings.outline_queries.lines().map(str::trim).filter(|line| !line.is_empty()).collect()
+    }
+
+    // Ctrl+clic: primero una URL escrita en el texto y, si no la hay, la documentación del crate.
+    fn link_at_cursor(&self) -> Option<String> {
+        docs_links::url_at(&self.content, self.cursor).or_else(|| self.docs_link_at_cursor())
+    }
+
+    fn docs_link_at_cursor(&self) -> Option<String> {
+        let is_manifest = self.file_path.as_ref().is_some_and(|path| path.file_name().is_some_and(|name| name == "Cargo.toml"));
+        if is_manifest {
+            return docs_links::cargo_dependency_at(&self.content, self.cursor).map(|name| docs_links::docs_url(&[name]));
+        }
+        let tree = self.tree.as_ref().filter(|_| self.is_rust_file())?;
+        docs_links::rust_docs_url(tree, self.content.as_bytes(), self.cursor)
+    }
+
+    fn update_syntax_node(&mut self) {
+        self.syntax_node = self.tree.as_ref().and_then(|tree| {
+            let cursor = self.cursor.min(self.content.len());
+            let node = tree.root_node().named_descendant_for_byte_range(cursor, cursor)?;
+            Some((status_bar::node_path_at(tree, cursor), node.byte_range()))
+        });
+    }
+
+    fn update_local_references(&mut self) {
+        self.local_references = match &self.tree {
+            Some(tree) if self.is_rust_file() && self.selection.is_empty() => {
+                references::local_references(&self.identifier_query, tree, self.content.as_bytes(), self.cursor)
+            }
+            _ => Vec::new(),
+        };
+    }
+
+    // La primera selección queda marcada; la segunda, si no se solapa con ella, completa el par.
+    fn mark_mirror_region(&mut self) {
+        let selection = self.selection.clone();
+        let Some(first) = self.mirror_pending.take() else {
+            self.mirror_pending = Some(selection);
+            self.notifications.info("Select the second region and choose Mirror with Marked Region");
+            return;
+        };
+        match MirrorPair::new(first, selection) {
+            Some(pair) => {
+                self.mirror = Some(pair);
+                self.notifications.info("Mirror edit on: edits in one region are repeated in the other");
+            }
+            None => self.notifications.push(NotifLevel::Warning, "Unable to mirror: the regions overlap"),
+        }
+    }
+
+    fn stop_mirroring(&mut self) {
+        self.mirror = None;
+        self.mirror_pending = None;
+    }
+
+    // Fondo en cada línea de las zonas enlazadas o de la zona marcada.
+    fn paint_mirror_regions(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        let regions = match (&self.mirror, &self.mirror_pending) {
+            (Some(mirror), _) => vec![mirror.region_a.clone(), mirror.region_b.clone()],
+            (None, Some(pending)) => vec![pending.clone()],
+            (None, None) => return,
+        };
+        for region in regions.into_iter().filter(|region| region.end <= self.content.len()) {
+            let mut line_start = region.start;
+            for line in self.content[region.clone()].split_inclusive('\n') {
+                let line_end = line_start + line.trim_end_matches('\n').len();
+                let start = view::byte_rect(galley, origin, &self.content, line_start);
+                let end = view::byte_rect(galley, origin, &self.content, line_end);
+                let rect = egui::Rect::from_min_max(start.left_top(), egui::pos2(end.left().max(start.left() + 2.0), start.bottom()));
+                painter.rect_filled(rect, 2.0, mirror::MIRROR_COLOR);
+                line_start += line.len();
+            }
+        }
+    }
+
+    // Fondo distinto para cada lado del conflicto y para las líneas de marcador, con los botones para resolverlo al
+    // final de la línea `<<<<<<<`.
+    fn paint_conflicts(&mut self, ui: &mut egui::Ui, galley: &egui::Galley, origin: egui::Pos2, editor_rect: egui::Rect) {
+        let mut chosen = None;
+        for (index, hunk) in self.conflicts.iter().enumerate() {
+            if hunk.range.end > self.content.len() {
+                continue;
+            }
+            let regions = hunk.markers.iter().map(|marker| (marker.clone(), conflicts::MARKER_COLOR));
+            let sides = [(hunk.ours.clone(), conflicts::OURS_COLOR), (hunk.theirs.clone(), conflicts::THEIRS_COLOR)];
+            for (region, color) in regions.chain(sides) {
+                let mut line_start = region.start;
+                for line in self.content[region].split_inclusive('\n') {
+                    let rect = view::byte_rect(galley, origin, &self.content, line_start);
+                    let rect = egui::Rect::from_x_y_ranges(editor_rect.x_range(), rect.y_range());
+                    ui.painter().rect_filled(rect, 0.0, color);
+                    line_start += line.len();
+                }
+            }
+
+            let first_marker = &hunk.markers[0];
+            let line_end = first_marker.start + self.content[first_marker.clone()].trim_end_matches('\n').len();
+            let anchor = view::byte_rect(galley, origin, &self.content, line_end);
+            if !ui.clip_rect().intersects(anchor) {
+                continue;
+            }
+            let mut position = anchor.right_top() + egui::vec2(16.0, 0.0);
+            for resolution in Resolution::ALL {
+                let button = egui::Button::new(egui::RichText::new(self.localizations.tr(resolution.label())).small());
+                let size = egui::vec2(ui.spacing().interact_size.x * 1.6, anchor.height());
+                if ui.put(egui::Rect::from_min_size(position, size), button).clicked() {
+                    chosen = Some((index, resolution));
+                }
+                position.x += size.x + 4.0;
+            }
+        }
+        if let Some((index, resolution)) = chosen {
+            let resolved = conflicts::resolve(&self.content, &self.conflicts[index], resolution);
+            self.apply_refactoring(ui.ctx(), resolved);
+        }
+    }
+
+    // Textos virtuales de las herramientas del editor: el número de cada conflicto encima de su `<<<<<<<` y el error
+    // de TOML señalado en su columna y explicado al final de la línea.
+    fn update_virtual_texts(&mut self) {
+        let mut virtual_texts = Vec::new();
+        let total = self.conflicts.len();
+        for (index, hunk) in self.conflicts.iter().enumerate() {
+            virtual_texts.push(VirtualText {
+                line: self.content[..hunk.range.start].matches('\n').count(),
+                col: None,
+                text: format!("{} {} / {}", self.localizations.tr("Conflict"), index + 1, total),
+                color: self.palette().removed,
+                position: VtPosition::AboveLine,
+            });
+        }
+        if let Some(error) = &self.toml_error {
+            let line_start = undo::line_offset(&self.content, error.line);
+            let col = self.content.get(line_start..error.range.start.max(line_start)).map(|before| before.chars().count());
+            let color = self.palette().error;
+            let pointer = VirtualText { line: error.line, col, text: "▸".to_string(), color, position: VtPosition::InlineBeforeContent };
+            let message = VirtualText { col: None, text: error.message.clone(), position: VtPosition::InlineAfterEol, ..pointer.clone() };
+            virtual_texts.extend([pointer, message]);
+        }
+        self.virtual_texts = virtual_texts;
+    }
+
+    // Se pintan encima del texto ya colocado; los de líneas plegadas no se ven.
+    fn paint_virtual_texts(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        let font = egui::FontId::new(self.settings.font_size * 0.85, self.settings.font_family.clone());
+        let small = egui::FontId::new(self.settings.font_size * 0.7, self.settings.font_family.clone());
+        for virtual_text in &self.virtual_texts {
+            let Some(anchor) = virtual_text::anchor_byte(&self.content, &self.line_offsets, virtual_text) else {
+                continue;
+            };
+            if self.folds.iter().any(|fold| fold.collapsed && fold.range.start < anchor && anchor < fold.range.end) {
+                continue;
+            }
+            let rect = view::byte_rect(galley, origin, &self.content, anchor);
+            match virtual_text.position {
+                VtPosition::InlineAfterEol => {
+                    let position = rect.left_center() + egui::vec2(self.settings.font_size, 0.0);
+                    painter.text(position, egui::Align2::LEFT_CENTER, &virtual_text.text, font.clone(), virtual_text.color);
+                }
+                VtPosition::InlineBeforeContent => {
+                    let position = rect.left_center() - egui::vec2(2.0, 0.0);
+                    painter.text(position, egui::Align2::RIGHT_CENTER, &virtual_text.text, font.clone(), virtual_text.color);
+                }
+                // Como el texto borrado de los cambios registrados: sobre un fondo propio para que se lea aunque
+                // pise la línea de arriba.
+                VtPosition::AboveLine => {
+                    let label = painter.layout_no_wrap(virtual_text.text.clone(), small.clone(), virtual_text.color);
+                    let label_rect = egui::Align2::LEFT_BOTTOM.anchor_rect(egui::Rect::from_min_size(rect.left_top(), label.size()));
+                    painter.rect_filled(label_rect.expand(1.0), 2.0, self.theme_color(self.settings.background_color));
+                    painter.galley(label_rect.min, label);
+                }
+            }
+        }
+    }
+
+    // Número de nivel en pequeño sobre los paréntesis muy anidados; desde `BLINK_DEPTH` el paréntesis parpadea en
+    // blanco una vez por segundo como aviso.
+    fn paint_bracket_depths(&self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2) {
+        let font = egui::FontId::monospace(self.settings.font_size * 0.55);
+        let editor_font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let blink_on = (ui.input(|i| i.time) * 2.0) as i64 % 2 == 0;
+        let mut blinking = false;
+        for bracket in self.brackets.iter().filter(|bracket| bracket.depth >= brackets::DEPTH_LABEL_MIN) {
+            let rect = view::byte_rect(galley, origin, &self.content, bracket.byte);
+            if !ui.clip_rect().intersects(rect) {
+                continue;
+            }
+            let mut color = self.theme_color(brackets::color(bracket.depth));
+            if bracket.depth >= brackets::BLINK_DEPTH {
+                blinking = true;
+                // El paréntesis en blanco se pinta justo encima del de color.
+                if let Some(glyph) = self.content.get(bracket.byte..bracket.byte + 1).filter(|_| blink_on) {
+                    color = egui::Color32::WHITE;
+                    ui.painter().text(rect.left_top(), egui::Align2::LEFT_TOP, glyph, editor_font.clone(), color);
+                }
+            }
+            let end = view::byte_rect(galley, origin, &self.content, bracket.byte + 1);
+            ui.painter().text(egui::pos2(end.left(), rect.top()), egui::Align2::LEFT_TOP, bracket.depth.to_string(), font.clone(), color);
+        }
+        if blinking {
+            ui.ctx().request_repaint_after(std::time::Duration::from_millis(500));
+        }
+    }
+
+    // Fondo translúcido sobre cada aparición de la variable local bajo el cursor.
+    fn paint_local_references(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        if self.local_references.len() < 2 {
+            return;
+        }
+        for reference in &self.local_references {
+            let start = view::byte_rect(galley, origin, &self.content, reference.range.start);
+            let end = view::byte_rect(galley, origin, &self.content, reference.range.end);
+            let rect = egui::Rect::from_min_max(start.left_top(), egui::pos2(end.left(), start.bottom()));
+            let color = if reference.write { references::WRITE_COLOR } else { references::READ_COLOR };
+            painter.rect_filled(rect, 2.0, color);
+        }
+    }
+
+    fn update_selection_matches(&mut self) {
+        self.selection_matches = if self.selection.is_empty() {
+            Vec::new()
+        } else {
+            references::selection_matches(&self.content, self.selection.clone(), self.settings.selection_highlight)
+        };
+    }
+
+    fn paint_selection_matches(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        for range in &self.selection_matches {
+            let start = view::byte_rect(galley, origin, &self.content, range.start);
+            let end = view::byte_rect(galley, origin, &self.content, range.end);
+            let right = if end.top() > start.top() { start.right() } else { end.left() };
+            let rect = egui::Rect::from_min_max(start.left_top(), egui::pos2(right, start.bottom()));
+            painter.rect_filled(rect, 2.0, references::SELECTION_MATCH_COLOR);
+        }
+    }
+
+    fn paint_find_matches(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        for (index, range) in self.find.matches.iter().enumerate() {
+            let start = view::byte_rect(galley, origin, &self.content, range.start);
+            let end = view::byte_rect(galley, origin, &self.content, range.end);
+            let right = if end.top() > start.top() { start.right() } else { end.left() };
+            let rect = egui::Rect::from_min_max(start.left_top(), egui::pos2(right, start.bottom()));
+            let color = if self.find.active == Some(index) { find::ACTIVE_MATCH_COLOR } else { find::MATCH_COLOR };
+            painter.rect_filled(rect, 2.0, color);
+        }
+    }
+
+    // Cambia todo el estilo de una vez con `set_visuals`, así que el siguiente fotograma ya sale entero con el tema nuevo.
+    fn toggle_dark_mode(&mut self, ctx: &egui::Context) {
+        let dark = self.settings.toggle_dark_mode();
+        self.theme_name = self.settings.theme.label().to_string();
+        ctx.set_visuals(self.settings.visuals());
+        let message = if dark { "Dark mode" } else { "Light mode" };
+        self.notifications.info(message);
+    }
+
+    // Triángulo en el margen para plegar o desplegar y, en los plegados, el texto sustituto, que también despliega al hacer clic.
+    fn paint_folds(&mut self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2, editor: egui::Rect) {
+        let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let color = ui.visuals().weak_text_color();
+        let mut toggled = None;
+        for (index, fold) in self.folds.iter().enumerate() {
+            if fold.range.end > self.content.len() {
+                continue;
+            }
+            // Los pliegues que quedan dentro de otro plegado están en filas ocultas.
+            let hidden = self.folds.iter().any(|outer| outer.collapsed && outer.range.start < fold.range.start && fold.range.start < outer.range.end);
+            if hidden {
+                continue;
+            }
+            let line = view::byte_rect(galley, origin, &self.content, fold.range.start);
+            let marker = egui::Rect::from_center_size(
+                egui::pos2(editor.left() + GUTTER_WIDTH * 0.5, line.center().y),
+                egui::Vec2::splat(GUTTER_WIDTH * 0.5),
+            );
+            match fold.kind {
+                FoldKind::Imports => {
+                    let points = if fold.collapsed {
+                        vec![marker.left_top(), marker.right_center(), marker.left_bottom()]
+                    } else {
+                        vec![marker.left_top(), marker.right_top(), marker.center_bottom()]
+                    };
+                    ui.painter().add(egui::Shape::convex_polygon(points, color, egui::Stroke::NONE));
+                }
+                // ▣ plegada y □ desplegada, para distinguir las regiones de los pliegues del árbol sintáctico.
+                FoldKind::Region => {
+                    ui.painter().rect_stroke(marker, 0.0, egui::Stroke::new(1.0, color));
+                    if fold.collapsed {
+                        ui.painter().rect_filled(marker.shrink(marker.width() * 0.25), 0.0, color);
+                    }
+                }
+            }
+            if ui.interact(marker.expand(2.0), egui::Id::new(("fold", index)), egui::Sense::click()).clicked() {
+                toggled = Some(index);
+            }
+            if fold.collapsed {
+                let rect = ui.painter().text(line.left_center(), egui::Align2::LEFT_CENTER, &fold.placeholder, font.clone(), color);
+                let response = ui
+                    .interact(rect, egui::Id::new(("fold_placeholder", index)), egui::Sense::click())
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_ui(|ui| folding::render_folded_preview(ui, &self.content, fold.range.clone(), &self.highlights, font.clone()));
+                if response.clicked() {
+                    toggled = Some(index);
+                }
+            }
+        }
+        if let Some(index) = toggled {
+            self.folds[index].collapsed = !self.folds[index].collapsed;
+        }
+    }
+
+    // Sigue saliendo por stderr, pero también se ve en la ventana.
+    fn report_error(&mut self, message: String) {
+        eprintln!("{}", message);
+        self.notifications.error(message);
+    }
+
+    fn find_step(&mut self, ctx: &egui::Context, action: FindAction) {
+        let found = match action {
+            FindAction::Next => self.find.next(self.cursor),
+            FindAction::Previous => self.find.previous(self.selection.start),
+            FindAction::Replace | FindAction::ReplaceAll => return,
+        };
+        match found {
+            Some((range, wrapped)) => {
+                if wrapped {
+                    let message = match action {
+                        FindAction::Previous => "Search wrapped to the bottom",
+                        _ => "Search wrapped to the top",
+                    };
+                    self.notifications.info(message);
+                }
+                self.set_selection(ctx, range);
+                ctx.memory_mut(|m| m.request_focus(Self::editor_id()));
+            }
+            None if !self.find.query.is_empty() => self.notifications.push(NotifLevel::Warning, format!("No matches for \"{}\"", self.find.query)),
+            None => {}
+        }
+    }
+
+    // Ctrl+F3 / Ctrl+Shift+F3: busca la palabra bajo el cursor sin abrir el diálogo.
+    fn find_word_under_cursor(&mut self, ctx: &egui::Context, action: FindAction) {
+        let Some(word) = text::word_selection_at(&self.content, self.cursor) else {
+            return;
+        };
+        let query = self.content[word.clone()].to_string();
+        self.find.set_query(&query, &self.content);
+        self.find.active = self.find.matches.iter().position(|m| *m == word);
+        self.find_step(ctx, action);
+    }
+
+    fn open_query_playground(&mut self) {
+        let mut playground = QueryPlayground::new();
+        if let Some(tree) = &self.tree {
+            playground.run(tree, self.content.as_bytes());
+        }
+        self.query_playground = Some(playground);
+    }
+
+    fn show_query_playground(&mut self, ctx: &egui::Context) {
+        let Some(playground) = &mut self.query_playground else {
+            return;
+        };
+        let (open, clicked) = playground.show(ctx, self.tree.as_ref(), self.content.as_bytes());
+        if !open {
+            self.query_playground = None;
+        }
+        if let Some(offset) = clicked {
+            self.set_cursor(ctx, offset);
+            ctx.memory_mut(|m| m.request_focus(Self::editor_id()));
+        }
+    }
+
+    fn paint_playground_matches(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        let Some(playground) = &self.query_playground else {
+            return;
+        };
+        for (range, _) in &playground.matches {
+            let start = view::byte_rect(galley, origin, &self.content, range.start);
+            let end = view::byte_rect(galley, origin, &self.content, range.end);
+            let right = if end.top() > start.top() { start.right().max(start.left() + 4.0) } else { end.left() };
+            painter.rect_filled(egui::Rect::from_min_max(start.left_top(), egui::pos2(right, start.bottom())), 2.0, playground::MATCH_COLOR);
+        }
+    }
+
+    fn show_find_bar(&mut self, ui: &mut egui::Ui) {
+        if !self.find.open {
+            return;
+        }
+        match self.find.show(ui, &self.content) {
+            Some(FindAction::Replace) => self.replace_active_match(ui.ctx()),
+            Some(FindAction::ReplaceAll) => self.replace_all_matches(ui.ctx()),
+            Some(step) => self.find_step(ui.ctx(), step),
+            None => {}
+        }
+        ui.separator();
+    }
+
+    // Sin coincidencia activa, la primera pulsación solo selecciona la siguiente para que se vea qué se sustituye.
+    fn replace_active_match(&mut self, ctx: &egui::Context) {
+        let Some(range) = self.find.active.and_then(|index| self.find.matches.get(index)).cloned() else {
+            self.find_step(ctx, FindAction::Next);
+            return;
+        };
+        let Some(replacement) = self.find.replacement_for(&self.content, range.clone()) else {
+            return;
+        };
+        let mut content = self.content.clone();
+        content.replace_range(range.clone(), &replacement);
+        self.apply_refactoring(ctx, content);
+        self.find.active = None;
+        self.set_cursor(ctx, range.start + replacement.len());
+        self.find_step(ctx, FindAction::Next);
+    }
+
+    fn replace_all_matches(&mut self, ctx: &egui::Context) {
+        let count = self.find.matches.len();
+        if let Some(content) = self.find.replace_all(&self.content) {
+            self.apply_refactoring(ctx, content);
+            self.notifications.info(format!("Replaced {} matches", count));
+        }
+    }
+
+    // Errores del documento JSON según su esquema; si el esquema no se puede cargar, el motivo va en la primera línea.
+    // Los YAML no se pueden analizar sin su gramática, así que de su esquema solo se comprueba que carga.
+    fn schema_diagnostics(&mut self) -> Vec<Diagnostic> {
+        let document = if self.language == EditorLanguage::Json {
+            let Ok(document) = json::parse(&self.content) else {
+                return Vec::new();
+            };
+            Some(document)
+        } else {
+            None
+        };
+        let detected = match &document {
+            Some(document) => schema::schema_reference(document, self.file_path.as_deref()),
+            None => match yaml::schema_reference(&self.content) {
+                Some(reference) => Ok(Some(reference)),
+                None => self.file_path.as_deref().map_or(Ok(None), schema::associated_schema),
+            },
+        };
+        let root = json::value_range(&self.content, &[]).unwrap_or(0..0);
+        // Como los esquemas que no cargan: un diagnóstico al principio del documento, salvo que se haya elegido uno.
+        let overridden = self.file_path.as_ref().is_some_and(|path| self.schema_overrides.contains_key(path));
+        let detected = match detected {
+            Ok(detected) => detected,
+            Err(_) if overridden => None,
+            Err(e) => {
+                let line = self.content[..root.start].matches('\n').count();
+                return vec![Diagnostic { range: root.start..root.start + 1, line, message: e, repair: None }];
+            }
+        };
+        let reference = self.file_path.as_ref().and_then(|path| self.schema_overrides.get(path).cloned()).or(detected);
+        let Some(reference) = reference else {
+            self.json_schema = None;
+            return Vec::new();
+        };
+        let base_dir = self.file_path.as_deref().and_then(Path::parent).map(Path::to_path_buf);
+        let stale = !self.json_schema.as_ref().is_some_and(|loaded| loaded.base_dir == base_dir && loaded.reference == reference);
+        if stale {
+            let schema = schema::load_schema(&reference, base_dir.as_deref());
+            self.json_schema = Some(LoadedSchema { base_dir, reference, schema });
+        }
+        let Some(loaded) = &self.json_schema else {
+            return Vec::new();
+        };
+        match (&loaded.schema, &document) {
+            (Ok(schema), Some(document)) => schema::validate(schema, document)
+                .into_iter()
+                .map(|error| {
+                    let range = json::value_range(&self.content, &error.path).unwrap_or_else(|| root.clone());
+                    let line = self.content[..range.start].matches('\n').count();
+                    Diagnostic { range, line, message: error.message, repair: None }
+                })
+                .collect(),
+            (Ok(_), None) => Vec::new(),
+            (Err(e), _) => {
+                let line = self.content[..root.start].matches('\n').count();
+                vec![Diagnostic { range: root.start..root.start + 1, line, message: e.clone(), repair: None }]
+            }
+        }
+    }
+
+    // Hasta la siguiente validación se sigue marcando el último error, si su línea aún cabe en el texto.
+    fn toml_diagnostics(&self) -> Vec<Diagnostic> {
+        let Some(error) = &self.toml_error else {
+            return Vec::new();
+        };
+        let range = error.range.start.min(self.content.len())..error.range.end.min(self.content.len());
+        if !self.content.is_char_boundary(range.start) || !self.content.is_char_boundary(range.end) {
+            return Vec::new();
+        }
+        vec![Diagnostic { range, line: error.line, message: format!("TOML: {}", error.message), repair: None }]
+    }
+
+    fn validate_toml(&mut self, ctx: &egui::Context) {
+        if !self.toml_validation_due {
+            return;
+        }
+        let elapsed = self.last_edit_time.elapsed();
+        if elapsed < toml_check::VALIDATION_DELAY {
+            ctx.request_repaint_after(toml_check::VALIDATION_DELAY - elapsed);
+            return;
+        }
+        self.toml_validation_due = false;
+        self.toml_error = toml_check::validate(&self.content);
+        self.diagnostics = self.toml_diagnostics();
+        self.update_virtual_texts();
+    }
+
+    fn show_toml_error_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(error) = &self.toml_error else {
+            return;
+        };
+        let line = error.line;
+        let message = format!("{} {}: {}", self.localizations.tr("Invalid TOML at line"), line + 1, error.message);
+        egui::Frame::none()
+            .fill(self.palette().error.gamma_multiply(0.15))
+            .inner_margin(egui::Margin::same(4.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(self.palette().error, message);
+                    if ui.button(self.localizations.tr("Go to Error")).clicked() {
+                        let offset = self.line_offsets.get(line).copied().unwrap_or(0);
+                        self.set_cursor(ui.ctx(), offset);
+                        ui.memory_mut(|m| m.request_focus(Self::editor_id()));
+                    }
+                });
+            });
+    }
+
+    fn select_schema(&mut self) {
+        let Some(file) = self.file_path.clone() else {
+            return;
+        };
+        if let Some(schema) = rfd::FileDialog::new().add_filter("JSON Schema", &["json"]).pick_file() {
+            self.schema_overrides.insert(file, schema.to_string_lossy().into_owned());
+            self.reparse();
+        }
+    }
+
+    // Alt+clic empieza una selección rectangular y arrastrar con Alt la amplía; un clic sin Alt la quita.
+    // Las columnas salen de la x del puntero, así que la selección puede pasar del final de las líneas cortas.
+    fn update_rect_selection(&mut self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2, editor: egui::Rect) {
+        let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let char_width = ui.fonts(|fonts| fonts.glyph_width(&font, ' ')).max(1.0);
+        let (alt, pressed, down, pointer) =
+            ui.input(|i| (i.modifiers.alt, i.pointer.primary_pressed(), i.pointer.primary_down(), i.pointer.interact_pos()));
+        if let Some(pos) = pointer.filter(|pos| editor.contains(*pos)) {
+            let byte = text::char_to_byte(&self.content, galley.cursor_from_pos(pos - origin).ccursor.index);
+            let line = self.content[..byte].matches('\n').count();
+            let column = ((pos.x - origin.x) / char_width).round().max(0.0) as usize;
+            if pressed {
+                self.rect_selection = alt.then(|| RectSelection::at(line, column));
+            } else if down && alt {
+                if let Some(rect) = &mut self.rect_selection {
+                    rect.head = (line, column);
+                }
+            }
+        }
+
+        let Some(rect) = self.rect_selection else {
+            return;
+        };
+        let columns = rect.columns();
+        let fill = ui.visuals().selection.bg_fill;
+        for line in rect.lines() {
+            let Some(&start) = self.line_offsets.get(line) else {
+                break;
+            };
+            let row = view::byte_rect(galley, origin, &self.content, start);
+            let left = origin.x + columns.start as f32 * char_width;
+            // Una selección de ancho cero se ve como un cursor en cada línea.
+            let right = (origin.x + columns.end as f32 * char_width).max(left + 1.0);
+            ui.painter().rect_filled(egui::Rect::from_x_y_ranges(left..=right, row.top()..=row.bottom()), 0.0, fill);
+        }
+    }
+
+    // Una línea desde cada alias de YAML, por el margen izquierdo, hasta su ancla.
+    fn paint_yaml_links(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2) {
+        let color = self.theme_color(self.settings.syntax_colors.get("attribute").copied().unwrap_or(self.settings.text_color));
+        let stroke = egui::Stroke::new(1.0, color.gamma_multiply(0.5));
+        for link in &self.yaml_links {
+            let anchor = view::byte_rect(galley, origin, &self.content, link.anchor.start);
+            let alias = view::byte_rect(galley, origin, &self.content, link.alias.start);
+            let margin = anchor.left().min(alias.left()) - self.settings.font_size * 0.4;
+            painter.add(egui::Shape::line(
+                vec![alias.left_center(), egui::pos2(margin, alias.center().y), egui::pos2(margin, anchor.center().y), anchor.left_center()],
+                stroke,
+            ));
+        }
+    }
+
+    // Subraya con una línea ondulada cada diagnóstico, con su mensaje completo al pasar el puntero, y dibuja el
+    // mensaje atenuado al final de la línea (uno por línea).
+    // Devuelve el diagnóstico con arreglo que está bajo el puntero y la esquina donde abrir su ventanita.
+    fn paint_inline_diagnostics(&self, ui: &egui::Ui, galley: &egui::Galley, origin: egui::Pos2) -> Option<(usize, egui::Pos2)> {
+        let painter = ui.painter();
+        let palette = self.palette();
+        let pointer = ui.input(|i| i.pointer.hover_pos());
+        let mut last_line = None;
+        let mut hovered_repair = None;
+        for (index, diagnostic) in self.diagnostics.iter().enumerate() {
+            let start = view::byte_rect(galley, origin, &self.content, diagnostic.range.start);
+            let line_end = self.content[diagnostic.range.start..]
+                .find('\n')
+                .map_or(self.content.len(), |i| diagnostic.range.start + i);
+            let end_byte = diagnostic.range.end.clamp(diagnostic.range.start, line_end);
+            let mut end = view::byte_rect(galley, origin, &self.content, end_byte);
+            if end.left() <= start.left() {
+                end = start.translate(egui::vec2(self.settings.font_size * 0.5, 0.0));
+            }
+            painter.add(egui::Shape::line(
+                diagnostics::squiggle(start.left(), end.left(), start.bottom()),
+                egui::Stroke::new(1.0, palette.error),
+            ));
+            let underlined = egui::Rect::from_x_y_ranges(start.left()..=end.left(), start.top()..=start.bottom());
+            if pointer.is_some_and(|pos| underlined.contains(pos)) {
+                if diagnostic.repair.is_some() {
+                    hovered_repair = Some((index, start.left_bottom()));
+                } else {
+                    egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("diagnostic_tooltip"), |ui| {
+                        ui.label(&diagnostic.message);
+                    });
+                }
+            }
+
+            // Una línea con texto virtual al final ya tiene su mensaje ahí; el del diagnóstico se pisaría con él.
+            let covered = self.virtual_texts.iter().any(|vt| vt.line == diagnostic.line && vt.position == VtPosition::InlineAfterEol);
+            if last_line == Some(diagnostic.line) || covered {
+                continue;
+            }
+            last_line = Some(diagnostic.line);
+            let eol = view::byte_rect(galley, origin, &self.content, line_end);
+            painter.text(
+                eol.left_center() + egui::vec2(self.settings.font_size, 0.0),
+                egui::Align2::LEFT_CENTER,
+                diagnostic.inline_message(),
+                egui::FontId::proportional(self.settings.font_size * 0.8),
+                palette.error.gamma_multiply(0.6),
+            );
+        }
+        hovered_repair
+    }
+
+    // Como la ayuda del diagnóstico, pero con el arreglo propuesto y un botón para aplicarlo. Las ayudas de egui no
+    // admiten clics, así que es un `Area` que se mantiene mientras el puntero esté sobre el subrayado o sobre ella.
+    fn show_repair_popup(&mut self, ctx: &egui::Context, hovered: Option<(usize, egui::Pos2)>) {
+        if hovered.is_some() {
+            self.repair_popup = hovered;
+        }
+        let Some((index, pos)) = self.repair_popup else {
+            return;
+        };
+        let Some(diagnostic) = self.diagnostics.get(index).filter(|diagnostic| diagnostic.repair.is_some()) else {
+            self.repair_popup = None;
+            return;
+        };
+        let mut apply = false;
+        let area = egui::Area::new("repair_popup").order(egui::Order::Tooltip).fixed_pos(pos).show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(&diagnostic.message);
+                if let Some(repair) = &diagnostic.repair {
+                    ui.weak(&repair.description);
+                }
+                apply = ui.button("Apply Suggestion").clicked();
+            });
+        });
+        let over_popup = ctx.input(|i| i.pointer.hover_pos()).is_some_and(|pointer| area.response.rect.expand(4.0).contains(pointer));
+        if apply {
+            if let Some(repair) = self.diagnostics.get(index).and_then(|diagnostic| diagnostic.repair.as_ref()) {
+                let mut updated = self.content.clone();
+                updated.replace_range(repair.edit.range.clone(), &repair.edit.replacement);
+                let cursor = repair.edit.range.start + repair.edit.replacement.len();
+                self.apply_refactoring(ctx, updated);
+                self.set_cursor(ctx, cursor);
+            }
+            self.repair_popup = None;
+        } else if hovered.is_none() && !over_popup {
+            self.repair_popup = None;
+        }
+    }
+
+    // Ventana flotante con el esquema del archivo; se coloca en la esquina contraria a la línea del cursor.
+    fn show_outline_window(&mut self, ctx: &egui::Context) {
+        let cursor_in_top_half = self.cursor_screen_y < self.editor_rect.center().y;
+        let (pivot, pos) = if cursor_in_top_half {
+            (egui::Align2::RIGHT_BOTTOM, self.editor_rect.right_bottom() + egui::vec2(-8.0, -8.0))
+        } else {
+            (egui::Align2::RIGHT_TOP, self.editor_rect.right_top() + egui::vec2(-8.0, 8.0))
+        };
+
+        let mut selected = None;
+        egui::Window::new("Outline")
+            .collapsible(false)
+            .resizable(false)
+            .pivot(pivot)
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    if self.outline.is_empty() {
+                        ui.label("No symbols.");
+                    }
+                    for symbol in &self.outline {
+                        ui.horizontal(|ui| {
+                            ui.add_space(symbol.depth as f32 * 12.0);
+                            let label = format!("{} {}", symbol.kind, symbol.name);
+                            if ui.selectable_label(false, label).on_hover_text(format!("line {}", symbol.line + 1)).clicked() {
+                                selected = Some(symbol.start_byte);
+                            }
+                        });
+                    }
+                });
+            });
+
+        if let Some(byte_index) = selected {
+            self.set_cursor(ctx, byte_index);
+            ctx.memory_mut(|memory| memory.request_focus(Self::editor_id()));
+            self.show_outline = false;
+        }
+    }
+
+    fn is_rust_file(&self) -> bool {
+        self.language == EditorLanguage::Rust
+    }
+
+    // Lanza rustfmt sobre la sentencia o bloque recién cerrado; lo aplica `poll_format_on_type` cuando acaba.
+    fn format_on_type(&mut self, cursor: usize) {
+        let Some(range) = self.tree.as_ref().and_then(|tree| format::on_type_target(tree, &self.content, cursor)) else {
+            return;
+        };
+        self.format_on_type_job = Some(OnTypeFormat::start(self.content.clone(), cursor, range));
+    }
+
+    // Si se ha seguido escribiendo o se ha movido el cursor, el formateo llega tarde y se tira.
+    fn poll_format_on_type(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.format_on_type_job else {
+            return;
+        };
+        let Some(result) = job.poll() else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(50));
+            return;
+        };
+        let stale = job.source != self.content || job.cursor != self.cursor;
+        self.format_on_type_job = None;
+        match result {
+            Ok(Some((formatted, cursor))) if !stale => {
+                self.content = formatted;
+                self.reparse();
+                self.set_cursor(ctx, cursor);
+                self.active_tab_mut().is_modified = true;
+            }
+            Ok(_) => {}
+            Err(e) => self.report_error(format!("Unable to format: {}", e)),
+        }
+    }
+
+    fn open_directory(&mut self, path: PathBuf) {
+        self.project_name = Some(templates::crate_name(&path).unwrap_or_else(|| {
+            path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+        }));
+        let mut errors = Vec::new();
+        self.exclusions = Exclusions::load(&path, &mut errors);
+        for error in errors {
+            self.report_error(error);
+        }
+        self.indexer = Some(IndexerThread::start(path.clone(), unsafe { tree_sitter_rust() }, self.exclusions.clone()));
+        self.snippets.set_project_root(Some(&path));
+        self.current_dir = Some(path);
+        self.update_dir_contents();
+        self.refresh_git_status();
+    }
+
+    fn git_dir(&self) -> Option<PathBuf> {
+        self.current_dir
+            .clone()
+            .or_else(|| self.file_path.as_ref().and_then(|p| p.parent()).map(Path::to_path_buf))
+    }
+
+    fn refresh_git_status(&mut self) {
+        self.in_git_repo = self.git_dir().is_some_and(|dir| git::is_git_repo(&dir));
+        self.current_branch = self.git_dir().filter(|_| self.in_git_repo).and_then(|dir| git::current_branch(&dir).ok());
+    }
+
+    fn open_branch_switcher(&mut self) {
+        let Some(dir) = self.git_dir() else {
+            return;
+        };
+        match git::local_branches(&dir) {
+            Ok(branches) => self.branch_switcher = Some(BranchSwitcher::new(branches, self.current_branch.clone().unwrap_or_default())),
+            Err(e) => self.git_output = Some(("Branches".to_string(), e.to_string())),
+        }
+    }
+
+    fn show_branch_switcher(&mut self, ctx: &egui::Context) {
+        let Some(switcher) = &mut self.branch_switcher else {
+            return;
+        };
+        if let Some(changed) = switcher.poll() {
+            self.reload_changed_tabs(&changed);
+            self.refresh_git_status();
+            self.open_branch_switcher();
+            return;
+        }
+        if switcher.is_running() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        let mut action = None;
+        if !switcher.show(ctx, &mut action) {
+            self.branch_switcher = None;
+            return;
+        }
+        if let (Some(action), Some(dir)) = (action, self.git_dir()) {
+            // Lo que no se ha guardado se escribe antes, para que git lo tenga en cuenta al cambiar de rama.
+            self.save();
+            if let Some(switcher) = &mut self.branch_switcher {
+                switcher.start_checkout(dir, &action);
+            }
+        }
+    }
+
+    // Vuelve a leer del disco las pestañas de los archivos que cambiaron con el checkout.
+    fn reload_changed_tabs(&mut self, changed: &[PathBuf]) {
+        let is_changed = |path: &PathBuf| changed.iter().any(|changed| fs::canonicalize(changed).ok() == fs::canonicalize(path).ok());
+        let mut skipped = Vec::new();
+        for index in 0..self.tabs.len() {
+            if index == self.active_tab {
+                continue;
+            }
+            let tab = &mut self.tabs[index];
+            let Some(path) = tab.file_path.clone().filter(is_changed) else {
+                continue;
+            };
+            // Lo que no se ha guardado no se tira: la pestaña se queda como está y se avisa.
+            if tab.is_modified {
+                skipped.push(tab.title());
+                continue;
+            }
+            match fs::read_to_string(&path) {
+                Ok(content) => {
+                    tab.content = content;
+                    tab.is_modified = false;
+                    tab.undo_tree.clear();
+                }
+                Err(e) => self.report_error(format!("Unable to read file: {}", e)),
+            }
+        }
+        if self.file_path.as_ref().is_some_and(is_changed) {
+            if self.tabs[self.active_tab].is_modified {
+                skipped.push(self.tabs[self.active_tab].title());
+            } else {
+                self.reload_from_disk();
+            }
+        }
+        if !skipped.is_empty() {
+            let names: Vec<&str> = skipped.iter().map(|title| title.trim_start_matches("● ")).collect();
+            let message = format!("Not reloaded, has unsaved changes: {}", names.join(", "));
+            self.notifications.push(NotifLevel::Warning, message);
+        }
+    }
+
+    // Ejecuta git y deja la salida (o el error) en la ventana "Git Output".
+    fn run_git(&mut self, title: &str, args: &[&str]) -> Option<String> {
+        let dir = self.git_dir()?;
+        match git::run_git_command(args, &dir) {
+            Ok(output) => {
+                let shown = if output.trim().is_empty() { "(no output)".to_string() } else { output.clone() };
+                self.git_output = Some((title.to_string(), shown));
+                Some(output)
+            }
+            Err(e) => {
+                self.git_output = Some((title.to_string(), e.to_string()));
+                None
+            }
+        }
+    }
+
+    fn reload_from_disk(&mut self) {
+        if let Some(path) = self.file_path.clone() {
+            self.load(path);
+        }
+    }
+
+    fn stash_changes(&mut self) {
+        self.save();
+        if self.run_git("Stash Changes", &["stash", "push", "-m", "rbeditor quick stash"]).is_some() {
+            self.reload_from_disk();
+        }
+    }
+
+    fn pop_stash(&mut self) {
+        self.save();
+        if self.run_git("Pop Stash", &["stash", "pop"]).is_some() {
+            self.reload_from_disk();
+        }
+    }
+
+    fn open_commit_panel(&mut self) {
+        let Some(dir) = self.git_dir() else {
+            return;
+        };
+        self.save();
+        match git::status_short(&dir) {
+            Ok(entries) => match &mut self.commit_panel {
+                Some(panel) => panel.entries = entries,
+                None => self.commit_panel = Some(CommitPanel::new(entries)),
+            },
+            Err(e) => self.git_output = Some(("Status".to_string(), e.to_string())),
+        }
+    }
+
+    fn show_commit_panel(&mut self, ctx: &egui::Context) {
+        let Some(panel) = &mut self.commit_panel else {
+            return;
+        };
+        let mut action = None;
+        if !panel.show(ctx, &mut action) {
+            self.commit_panel = None;
+            return;
+        }
+        match action {
+            Some(CommitAction::Stage(path, true)) => {
+                self.run_git(&format!("git add {}", path), &["add", "--", &path]);
+            }
+            Some(CommitAction::Stage(path, false)) => {
+                self.run_git(&format!("git reset HEAD {}", path), &["reset", "HEAD", "--", &path]);
+            }
+            Some(CommitAction::Refresh) => {}
+            Some(CommitAction::Commit | CommitAction::CommitAndPush) => {
+                let push = matches!(action, Some(CommitAction::CommitAndPush));
+                let message = panel.message.clone();
+                let Some(commit_output) = self.run_git("git commit", &["commit", "-m", &message]) else {
+                    return;
+                };
+                if let Some(panel) = &mut self.commit_panel {
+                    panel.message.clear();
+                }
+                if push {
+                    self.run_git("git push", &["push"]);
+                    if let Some((title, output)) = &mut self.git_output {
+                        *title = "git commit && git push".to_string();
+                        *output = format!("{}\n{}", commit_output.trim_end(), output);
+                    }
+                }
+            }
+            None => return,
+        }
+        self.open_commit_panel();
+    }
+
+    fn list_stashes(&mut self) {
+        let dir = self.git_dir();
+        match dir.map(|dir| git::run_git_command(&["stash", "list"], &dir)) {
+            Some(Ok(output)) => self.stash_list = Some(output.lines().map(str::to_string).collect()),
+            Some(Err(e)) => self.git_output = Some(("List Stashes".to_string(), e.to_string())),
+            None => {}
+        }
+    }
+
+    fn open_file_history(&mut self) {
+        self.file_history = Some(Vec::new());
+        self.file_history_exhausted = false;
+        self.load_more_history();
+    }
+
+    fn load_more_history(&mut self) {
+        let (Some(file), Some(history)) = (self.file_path.clone(), &mut self.file_history) else {
+            return;
+        };
+        match git::file_log(&file, history.len(), FILE_HISTORY_PAGE) {
+            Ok(entries) => {
+                self.file_history_exhausted = entries.len() < FILE_HISTORY_PAGE;
+                history.extend(entries);
+            }
+            Err(e) => {
+                self.file_history_exhausted = true;
+                self.git_output = Some(("File History".to_string(), e.to_string()));
+            }
+        }
+    }
+
+    // Diff de la versión del archivo en `entry` contra el contenido actual del editor.
+    fn show_history_diff(&mut self, entry_index: usize) {
+        let (Some(history), Some(dir)) = (&self.file_history, self.git_dir()) else {
+            return;
+        };
+        let entry = &history[entry_index];
+        match git::show_file_at(&dir, &entry.hash, &entry.path) {
+            Ok(old) => {
+                let title = format!("{} ({}) → working copy", entry.path, entry.hash);
+                self.diff_view = Some((title, diff::line_diff(&old, &self.content)));
+            }
+            Err(e) => self.git_output = Some((format!("git show {}", entry.hash), e.to_string())),
+        }
+    }
+
+    fn cherry_pick_commit(&mut self, entry_index: usize) {
+        let (Some(history), Some(dir)) = (&self.file_history, self.git_dir()) else {
+            return;
+        };
+        let hash = history[entry_index].hash.clone();
+        let title = format!("git cherry-pick {}", hash);
+        // Git tiene que ver todas las pestañas con cambios, no solo la activa.
+        self.save_modified_tabs();
+        match git::cherry_pick(&dir, &hash) {
+            Ok(result) => {
+                let shown = if result.output.trim().is_empty() { "(no output)".to_string() } else { result.output };
+                self.git_output = Some((title, shown));
+                if !result.conflicts.is_empty() {
+                    self.notifications.push(NotifLevel::Warning, "Conflicts detected");
+                }
+                self.reload_changed_tabs(&result.changed);
+                self.cherry_pick_conflicts = result.conflicts;
+                self.refresh_git_status();
+            }
+            Err(e) => self.git_output = Some((title, e.to_string())),
+        }
+    }
+
+    fn show_cherry_pick_conflicts(&mut self, ctx: &egui::Context) {
+        if self.cherry_pick_conflicts.is_empty() {
+            return;
+        }
+        let mut open = true;
+        let mut selected = None;
+        egui::Window::new("Conflicts Detected").open(&mut open).show(ctx, |ui| {
+            for (index, path) in self.cherry_pick_conflicts.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default())
+                        .on_hover_text(path.display().to_string());
+                    if ui.button("Resolve Conflicts").clicked() {
+                        selected = Some(index);
+                    }
+                });
+            }
+        });
+        if let Some(index) = selected {
+            let path = self.cherry_pick_conflicts.remove(index);
+            self.load(path);
+        }
+        if !open {
+            self.cherry_pick_conflicts.clear();
+        }
+    }
+
+    fn show_file_history_window(&mut self, ctx: &egui::Context) {
+        let Some(history) = &self.file_history else {
+            return;
+        };
+        let mut open = true;
+        let mut selected = None;
+        let mut load_more = false;
+        let mut cherry_pick = None;
+        egui::Window::new("File History").open(&mut open).show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                if history.is_empty() {
+                    ui.label("No commits for this file.");
+                }
+                egui::Grid::new("file_history_grid").striped(true).show(ui, |ui| {
+                    for (index, entry) in history.iter().enumerate() {
+                        if ui.link(egui::RichText::new(&entry.hash).monospace()).clicked() {
+                            selected = Some(index);
+                        }
+                        ui.label(&entry.date);
+                        ui.label(&entry.author);
+                        ui.label(&entry.message);
+                        if ui.small_button("Cherry-pick").clicked() {
+                            cherry_pick = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if !self.file_history_exhausted && ui.button("Load More").clicked() {
+                    load_more = true;
+                }
+            });
+        });
+
+        if let Some(index) = selected {
+            self.show_history_diff(index);
+        }
+        if load_more {
+            self.load_more_history();
+        }
+        if let Some(index) = cherry_pick {
+            self.cherry_pick_commit(index);
+        }
+        if !open {
+            self.file_history = None;
+        }
+    }
+
+    fn show_git_windows(&mut self, ctx: &egui::Context) {
+        self.show_file_history_window(ctx);
+        self.show_cherry_pick_conflicts(ctx);
+        self.show_commit_panel(ctx);
+        self.show_branch_switcher(ctx);
+        if let Some((title, lines)) = &self.diff_view {
+            if !diff::show_diff_window(ctx, title, lines, &self.palette()) {
+                self.diff_view = None;
+            }
+        }
+
+        if let Some(stashes) = &self.stash_list {
+            let mut open = true;
+            let mut selected = None;
+            egui::Window::new("Stashes").open(&mut open).show(ctx, |ui| {
+                if stashes.is_empty() {
+                    ui.label("No stashes.");
+                }
+                for line in stashes {
+                    if ui.button(line).clicked() {
+                        selected = line.split(':').next().map(str::to_string);
+                    }
+                }
+            });
+            if let Some(stash) = selected {
+                self.run_git(&format!("Diff of {}", stash), &["stash", "show", "-p", &stash]);
+            }
+            if !open {
+                self.stash_list = None;
+            }
+        }
+
+        let is_diff = self.git_output.as_ref().is_some_and(|(_, output)| diff::is_unified_diff(output));
+        if is_diff {
+            self.update_git_diff_spans();
+        }
+        if let Some((title, output)) = &self.git_output {
+            let mut open = true;
+            let palette = self.palette();
+            egui::Window::new(format!("Git Output — {}", title))
+                .id(egui::Id::new("git_output"))
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| match &self.git_diff_spans {
+                        Some((_, _, spans)) if is_diff => diff::render_diff_output(ui, output, spans, &palette),
+                        _ => {
+                            ui.label(egui::RichText::new(output).monospace());
+                        }
+                    });
+                });
+            if !open {
+                self.git_output = None;
+            }
+        }
+    }
+
+    // El código de los archivos con gramática se resalta como en el editor; el resto solo lleva los colores del diff.
+    fn update_git_diff_spans(&mut self) {
+        let Some((title, output)) = &self.git_output else {
+            return;
+        };
+        if self.git_diff_spans.as_ref().is_some_and(|(cached, len, _)| cached == title && *len == output.len()) {
+            return;
+        }
+        let default = self.theme_color(self.settings.text_color);
+        let mut highlighter = Highlighter::new();
+        let mut configs = HashMap::new();
+        let spans = diff::diff_code_spans(output, |path, source| {
+            let language = EditorLanguage::from_path(path);
+            let config = configs
+                .entry(language)
+                .or_insert_with(|| LanguageProfile::for_language(language).and_then(|profile| profile.highlight_config()))
+                .as_ref()?;
+            let colors = highlight::capture_colors(config.names(), &self.settings.syntax_colors, default);
+            let spans = highlight::highlight_spans(&mut highlighter, config, &colors, default, source);
+            Some(highlight::span_ranges(source, spans))
+        });
+        self.git_diff_spans = Some((title.clone(), output.len(), spans));
+    }
+
+    fn update_dir_contents(&mut self) {
+        self.dir_contents.clear();
+        self.project_files = None;
+        self.file_notes.clear();
+        if let Some(dir) = &self.current_dir {
+            if let Ok(entries) = fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    let hidden = !self.show_hidden_files && entry.file_name().to_string_lossy().starts_with('.');
+                    let excluded = !self.show_all_files && self.exclusions.is_excluded(&entry.path());
+                    if !hidden && !excluded && entry.file_name() != notes::NOTES_DIR && entry.file_name() != annotations::ANNOTATIONS_DIR {
+                        self.dir_contents.push(entry.path());
+                    }
+                }
+            }
+            // Primero las carpetas y, dentro de cada grupo, por nombre sin distinguir mayúsculas.
+            self.dir_contents.sort_by_cached_key(|path| {
+                let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+                (!path.is_dir(), name)
+            });
+            self.file_notes = notes::load_notes(dir);
+        }
+    }
+
+    // Las búsquedas del proyecto y el índice de símbolos se rehacen sin las rutas excluidas.
+    fn exclusions_changed(&mut self) {
+        if let Some(dir) = self.current_dir.clone() {
+            self.indexer = Some(IndexerThread::start(dir, unsafe { tree_sitter_rust() }, self.exclusions.clone()));
+        }
+        self.update_dir_contents();
+    }
+
+    // Notas y anotaciones se guardan en el directorio abierto; si no hay ninguno, junto al archivo.
+    fn notes_dir(&self) -> Option<PathBuf> {
+        self.notes_dir_for(self.file_path.as_ref()?)
+    }
+
+    fn notes_dir_for(&self, file: &Path) -> Option<PathBuf> {
+        self.current_dir.clone().or_else(|| file.parent().map(Path::to_path_buf))
+    }
+
+    fn save_annotations(&mut self) {
+        if let (Some(dir), Some(file)) = (self.notes_dir(), &self.file_path) {
+            if let Err(e) = annotations::save(&dir, file, &self.annotations) {
+                self.report_error(format!("Unable to save annotations: {}", e));
+            }
+        }
+    }
+
+    // Lanza rust-analyzer la primera vez que se edita un archivo Rust con las pistas activadas, le manda el texto
+    // tras cada pausa al escribir y recoge las pistas que devuelve.
+    fn poll_lsp(&mut self, ctx: &egui::Context) {
+        if !self.settings.inlay_hints_enabled || !self.is_rust_file() {
+            self.inlay_hints.clear();
+            return;
+        }
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        if self.lsp.is_none() && !self.lsp_failed {
+            let root = expand::project_root(&path).or_else(|| path.parent().map(Path::to_path_buf)).unwrap_or_default();
+            match LspClient::start(lsp::RUST_ANALYZER, &root) {
+                Ok(client) => self.lsp = Some(client),
+                Err(e) => {
+                    self.report_error(format!("Unable to start {}: {}", lsp::RUST_ANALYZER, e));
+                    self.lsp_failed = true;
+                }
+            }
+        }
+        let Some(client) = &mut self.lsp else {
+            return;
+        };
+        if let Some(hints) = client.poll(&self.content) {
+            self.inlay_hints = hints;
+        }
+        ctx.request_repaint_after(INLAY_HINT_DELAY);
+        if !client.is_ready() {
+            return;
+        }
+        let synced = self.lsp_synced.as_ref().is_some_and(|(synced, version)| *synced == path && *version == self.document_version);
+        if synced && !client.hints_stale || !synced && self.last_edit_time.elapsed() < INLAY_HINT_DELAY {
+            return;
+        }
+        let sent = if synced { Ok(()) } else { client.sync_document(&path, &self.content) };
+        if let Err(e) = sent.and_then(|()| client.request_inlay_hints(&path, self.line_offsets.len())) {
+            self.report_error(format!("Unable to talk to {}: {}", lsp::RUST_ANALYZER, e));
+            self.lsp = None;
+            self.lsp_failed = true;
+            return;
+        }
+        self.lsp_synced = Some((path, self.document_version));
+    }
+
+    fn inlay_hint_font(&self) -> egui::FontId {
+        egui::FontId::new(self.settings.font_size * self.settings.inlay_hint_font_size_factor, self.settings.font_family.clone())
+    }
+
+    // Byte, texto y anchura de cada pista que se ve (las de líneas plegadas no).
+    fn visible_inlay_hints(&self, ctx: &egui::Context) -> Vec<(usize, String, f32)> {
+        let font = self.inlay_hint_font();
+        self.inlay_hints
+            .iter()
+            .filter(|hint| hint.offset <= self.content.len())
+            .filter(|hint| !self.folds.iter().any(|fold| fold.collapsed && fold.range.contains(&hint.offset)))
+            .map(|hint| {
+                let width = ctx.fonts(|fonts| fonts.layout_no_wrap(hint.label.clone(), font.clone(), egui::Color32::WHITE).size().x);
+                (hint.offset, hint.label.clone(), width)
+            })
+            .collect()
+    }
+
+    // Cada pista se dibuja en el hueco que `layout_job` dejó antes de su byte; no es texto del editor, así que no
+    // se puede seleccionar ni copiar.
+    fn paint_inlay_hints(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2, hints: &[(usize, String, f32)]) {
+        let font = self.inlay_hint_font();
+        let color = self.theme_color(self.settings.text_color).gamma_multiply(0.45);
+        for (offset, label, width) in hints {
+            let rect = view::byte_rect(galley, origin, &self.content, *offset);
+            painter.text(egui::pos2(rect.left() - width, rect.center().y), egui::Align2::LEFT_CENTER, label, font.clone(), color);
+        }
+    }
+
+    // Texto atenuado en el borde derecho de cada línea anotada.
+    fn paint_annotations(&self, painter: &egui::Painter, galley: &egui::Galley, origin: egui::Pos2, editor: egui::Rect) {
+        for (&line, note) in &self.annotations {
+            let start = undo::line_offset(&self.content, line);
+            let line_rect = view::byte_rect(galley, origin, &self.content, start);
+            painter.text(
+                egui::pos2(editor.right() - GUTTER_WIDTH, line_rect.center().y),
+                egui::Align2::RIGHT_CENTER,
+                format!("📎 {}", note),
+                egui::FontId::proportional(self.settings.font_size * 0.8),
+                self.theme_color(self.settings.text_color).gamma_multiply(0.45),
+            );
+        }
+    }
+
+    // Campo de texto flotante junto a la línea del cursor; Enter guarda, Escape cancela y vacío borra la anotación.
+    fn show_annotation_input(&mut self, ctx: &egui::Context) {
+        let Some((line, text)) = &mut self.annotation_input else {
+            return;
+        };
+        let line = *line;
+        let mut done = false;
+        let mut cancel = false;
+        egui::Area::new("annotation_input")
+            .fixed_pos(egui::pos2(self.editor_rect.left() + GUTTER_WIDTH, self.cursor_screen_y + self.settings.font_size))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Annotation for line {}:", line + 1));
+                        let response = ui.add(egui::TextEdit::singleline(text).desired_width(300.0));
+                        response.request_focus();
+                        done = ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        cancel = ui.input(|i| i.key_pressed(egui::Key::Escape));
+                    });
+                });
+            });
+
+        if done {
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                self.annotations.remove(&line);
+            } else {
+                self.annotations.insert(line, text);
+            }
+            self.save_annotations();
+        }
+        if done || cancel {
+            self.annotation_input = None;
+        }
+    }
+
+    fn open_note_editor(&mut self) {
+        let (Some(dir), Some(file)) = (self.notes_dir(), self.file_path.clone()) else {
+            return;
+        };
+        let text = notes::read_note(&dir, &file);
+        self.note_editor = Some((file, text));
+    }
+
+    fn show_note_panel(&mut self, ctx: &egui::Context) {
+        let Some((file, text)) = &mut self.note_editor else {
+            return;
+        };
+        let mut save = false;
+        let mut close = false;
+        egui::TopBottomPanel::bottom("note_panel").resizable(true).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(format!("Note for {}", file.file_name().unwrap_or_default().to_string_lossy()));
+                save = ui.button("Save").clicked();
+                close = ui.button("Close").clicked();
+            });
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.add(egui::TextEdit::multiline(text).desired_width(f32::INFINITY).desired_rows(6));
+            });
+        });
+
+        if let (true, Some(dir), Some((file, text))) = (save, self.notes_dir(), &self.note_editor) {
+            match notes::write_note(&dir, file, text) {
+                Ok(()) => self.update_dir_contents(),
+                Err(e) => self.report_error(format!("Unable to save note: {}", e)),
+            }
+        }
+        if close {
+            self.note_editor = None;
+        }
+    }
+
+    fn create_new_file(&mut self, wizard: &NewFileWizard) -> Result<(), String> {
+        let dir = self.current_dir.clone().ok_or("No directory is open")?;
+        let name = wizard.name.trim();
+        wizard::validate_name(name)?;
+        let new_file_path = dir.join(name);
+        if new_file_path.exists() {
+            return Err(format!("'{}' already exists", name));
+        }
+        if let Some(parent) = new_file_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Unable to create directory: {}", e))?;
+        }
+
+        let content = match wizard.initial {
+            InitialContent::Blank => String::new(),
+            InitialContent::Template => wizard
+                .template()
+                .map(|template| templates::substitute_template_vars(template.body, &wizard.template_vars()))
+                .unwrap_or_default(),
+            InitialContent::Clipboard => read_clipboard().ok_or("The clipboard is empty")?,
+        };
+
+        fs::write(&new_file_path, content).map_err(|e| format!("Unable to create file: {}", e))?;
+        // En la barra lateral solo aparece la entrada de primer nivel (el archivo o su primer subdirectorio).
+        let top_level = name.split('/').next().map_or_else(|| new_file_path.clone(), |first| dir.join(first));
+        if !self.dir_contents.contains(&top_level) {
+            self.dir_contents.push(top_level);
+        }
+        self.load(new_file_path);
+        Ok(())
+    }
+
+    fn show_new_file_wizard(&mut self, ctx: &egui::Context) {
+        let Some(mut wizard) = self.new_file_wizard.take() else {
+            return;
+        };
+        match wizard.show(ctx) {
+            Some(WizardAction::Create) => {
+                if let Err(e) = self.create_new_file(&wizard) {
+                    wizard.error = Some(e);
+                    self.new_file_wizard = Some(wizard);
+                }
+            }
+            Some(WizardAction::Cancel) => {}
+            None => self.new_file_wizard = Some(wizard),
+        }
+    }
+
+    fn start_rename(&mut self) {
+        if let Some(range) = text::word_at(&self.content, self.cursor) {
+            let word = self.content[range].to_string();
+            self.rename_prompt = Some((word.clone(), word));
+        }
+    }
+
+    fn hash_comments(&self) -> bool {
+        let config = self.comment_configs.for_file(self.language, self.file_path.as_deref());
+        config.and_then(|config| config.line_comment.as_deref()) == Some("#")
+    }
+
+    fn toggle_comment(&mut self, ctx: &egui::Context) {
+        let Some(config) = self.comment_configs.for_file(self.language, self.file_path.as_deref()) else {
+            return;
+        };
+        match comments::toggle_comment(&self.content, &self.selection, config) {
+            Some((result, selection)) => {
+                self.apply_refactoring(ctx, result);
+                self.set_selection(ctx, selection);
+            }
+            None => self.notifications.push(NotifLevel::Warning, format!("{} files have no comment syntax; add it to {}", self.language.label(), comments::COMMENTS_FILE)),
+        }
+    }
+
+    fn open_completion(&mut self) {
+        self.completion = Completion::at(&self.content, self.cursor.min(self.content.len()), self.hash_comments());
+        if self.completion.is_none() {
+            self.notifications.info("No completions");
+        }
+    }
+
+    fn handle_completion_keys(&mut self, ctx: &egui::Context) {
+        let (down, up, accept, dismiss) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) || i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if let Some(completion) = &mut self.completion {
+            if down {
+                completion.select_next();
+            }
+            if up {
+                completion.select_previous();
+            }
+        }
+        if accept {
+            self.accept_completion(ctx);
+        } else if dismiss {
+            self.completion = None;
+        }
+    }
+
+    fn accept_completion(&mut self, ctx: &egui::Context) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let word = &completion.items[completion.selected];
+        if self.content.get(completion.prefix.clone()).is_none() {
+            return;
+        }
+        self.content.replace_range(completion.prefix.clone(), word);
+        self.reparse();
+        self.set_cursor(ctx, completion.prefix.start + word.len());
+        self.active_tab_mut().is_modified = true;
+    }
+
+    fn show_completion_popup(&mut self, ctx: &egui::Context, galley: &egui::Galley, origin: egui::Pos2) {
+        let Some(completion) = &self.completion else {
+            return;
+        };
+        let anchor = view::byte_rect(galley, origin, &self.content, completion.prefix.start).left_bottom();
+        let mut clicked = None;
+        egui::Area::new("completion_popup").fixed_pos(anchor).order(egui::Order::Foreground).show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (index, item) in completion.items.iter().enumerate() {
+                    if ui.selectable_label(index == completion.selected, egui::RichText::new(item).monospace()).clicked() {
+                        clicked = Some(index);
+                    }
+                }
+            });
+        });
+        if let Some(index) = clicked {
+            if let Some(completion) = &mut self.completion {
+                completion.selected = index;
+            }
+            self.accept_completion(ctx);
+            ctx.memory_mut(|m| m.request_focus(Self::editor_id()));
+        }
+    }
+
+    fn start_live_rename(&mut self) {
+        self.live_rename = LiveRename::start(&self.content, self.cursor);
+        if self.live_rename.is_none() {
+            self.notifications.info("No identifier under the cursor");
+        }
+    }
+
+    // Cuadro de texto sobre la aparición bajo el cursor; las demás apariciones se resaltan y cambian a la vez.
+    fn show_live_rename(&mut self, ui: &mut egui::Ui, galley: &egui::Galley, origin: egui::Pos2) {
+        let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+        let Some(live) = &mut self.live_rename else {
+            return;
+        };
+        let ranges = live.apply().1;
+        for range in &ranges {
+            let start = view::byte_rect(galley, origin, &self.content, range.start);
+            let end = view::byte_rect(galley, origin, &self.content, range.end);
+            let right = if end.top() > start.top() { start.right() } else { end.left() };
+            let rect = egui::Rect::from_min_max(start.left_top(), egui::pos2(right, start.bottom()));
+            ui.painter().rect_filled(rect, 2.0, rename::LIVE_OCCURRENCE_COLOR);
+        }
+
+        let start = view::byte_rect(galley, origin, &self.content, ranges[live.primary].start);
+        let name_width = ui.fonts(|f| f.layout_no_wrap(live.name.clone(), font.clone(), self.settings.text_color).size().x);
+        // Un poco de holgura para que el cursor quepa al final del nombre.
+        let width = name_width + font.size * 0.5;
+        let rect = egui::Rect::from_min_size(start.left_top(), egui::vec2(width, start.height()));
+        ui.painter().rect_filled(rect.expand(1.0), 2.0, self.settings.background_color);
+        let response = ui.put(
+            rect,
+            egui::TextEdit::singleline(&mut live.name)
+                .frame(false)
+                .margin(egui::Vec2::ZERO)
+                .font(font)
+                .text_color(self.settings.text_color)
+                .desired_width(width),
+        );
+        response.request_focus();
+        if response.changed() {
+            self.content = live.apply().0;
+            self.reparse();
+            ui.ctx().request_repaint();
+        }
+
+        let (enter, escape, shift) = ui.input(|i| (i.key_pressed(egui::Key::Enter), i.key_pressed(egui::Key::Escape), i.modifiers.shift));
+        if escape {
+            self.cancel_live_rename(ui.ctx());
+        } else if response.lost_focus() {
+            self.confirm_live_rename(ui.ctx(), enter && shift);
+        }
+    }
+
+    fn cancel_live_rename(&mut self, ctx: &egui::Context) {
+        if let Some(live) = self.live_rename.take() {
+            self.content = live.before;
+            self.reparse();
+            self.set_cursor(ctx, live.ranges[live.primary].start);
+            ctx.memory_mut(|m| m.request_focus(Self::editor_id()));
+        }
+    }
+
+    // Confirma el renombrado en el archivo como una sola entrada de deshacer; con `project_wide` abre además la vista previa para el resto del proyecto.
+    fn confirm_live_rename(&mut self, ctx: &egui::Context, project_wide: bool) {
+        let Some(live) = self.live_rename.take() else {
+            return;
+        };
+        if live.name.is_empty() || live.name == live.original {
+            self.live_rename = Some(live);
+            self.cancel_live_rename(ctx);
+            return;
+        }
+        let (content, ranges) = live.apply();
+        self.content = content;
+        self.reparse();
+        self.set_cursor(ctx, ranges[live.primary].end);
+        ctx.memory_mut(|m| m.request_focus(Self::editor_id()));
+        self.push_line_undo(undo::renamed(&live.original, &live.name, ranges));
+        self.active_tab_mut().is_modified = true;
+        if project_wide {
+            self.rename_preview = Some(RenamePreview::compute(
+                &live.original,
+                &live.name,
+                self.current_dir.as_deref(),
+                &self.exclusions,
+                self.file_path.as_deref(),
+                &self.content,
+            ));
+        }
+    }
+
+    fn run_in_output(&mut self, source: &str, program: &str, args: &[&str]) {
+        let Some(dir) = self.current_dir.clone() else {
+            return;
+        };
+        self.save();
+        self.show_output = true;
+        self.output.run(source, program, args, dir);
+    }
+
+    fn format_whole_file(&mut self, ctx: &egui::Context) {
+        self.show_output = true;
+        match format::format_file(&self.content) {
+            Ok(formatted) => {
+                if formatted != self.content {
+                    self.apply_refactoring(ctx, formatted);
+                }
+                self.output.push("rustfmt", OutputLevel::Info, "Formatted file");
+            }
+            Err(FormatError::Rustfmt(stderr)) => self.output.push_lines("rustfmt", &stderr),
+            Err(e) => self.output.push("rustfmt", OutputLevel::Error, &e.to_string()),
+        }
+    }
+
+    fn show_output_panel(&mut self, ctx: &egui::Context) {
+        if !self.show_output {
+            return;
+        }
+        let clicked = egui::TopBottomPanel::bottom("output_panel")
+            .resizable(true)
+            .default_height(160.0)
+            .show(ctx, |ui| self.output.show(ui))
+            .inner;
+        if let Some(reference) = clicked {
+            self.open_file_reference(ctx, &reference);
+        }
+    }
+
+    // Las rutas de cargo son relativas al directorio abierto; `<stdin>` es el archivo actual, que es lo que se pasó a rustfmt.
+    fn open_file_reference(&mut self, ctx: &egui::Context, reference: &output::FileReference) {
+        if reference.path != "<stdin>" {
+            let path = match &self.current_dir {
+                Some(dir) => dir.join(&reference.path),
+                None => PathBuf::from(&reference.path),
+            };
+            if !path.is_file() {
+                self.notifications.push(NotifLevel::Warning, format!("{} not found", reference.path));
+                return;
+            }
+            if self.file_path.as_ref() != Some(&path) {
+                self.load(path);
+            }
+        }
+        let line_start = undo::line_offset(&self.content, reference.line - 1);
+        let line_end = self.content[line_start..].find('\n').map_or(self.content.len(), |i| line_start + i);
+        let column = self.content[line_start..line_end]
+            .char_indices()
+            .nth(reference.column - 1)
+            .map_or(line_end, |(i, _)| line_start + i);
+        self.set_cursor(ctx, column);
+        ctx.memory_mut(|m| m.request_focus(Self::editor_id()));
+    }
+
+    fn apply_refactoring(&mut self, ctx: &egui::Context, result: String) {
+        // Cada refactorización es una entrada propia de deshacer.
+        self.commit_snapshot();
+        let previous = std::mem::replace(&mut self.content, result);
+        self.push_undo_snapshot(previous);
+        self.stop_mirroring();
+        self.reparse();
+        self.set_cursor(ctx, self.cursor.min(self.content.len()));
+        self.active_tab_mut().is_modified = true;
+    }
+
+    // Sustituye la secuencia de escape seleccionada (o junto al cursor) por el carácter que representa.
+    fn convert_escape(&mut self, ctx: &egui::Context) {
+        let range = if self.selection.is_empty() {
+            literals::escape_at(&self.content, self.cursor)
+        } else {
+            Some(self.selection.clone())
+        };
+        let Some(range) = range else {
+            self.notifications.push(NotifLevel::Warning, "Unable to convert escape: no escape sequence at the cursor");
+            return;
+        };
+        match literals::unescape_sequence(&self.content[range.clone()]) {
+            Ok(c) => {
+                self.set_selection(ctx, range);
+                self.replace_selection(ctx, &c.to_string());
+            }
+            Err(e) => self.report_error(format!("Unable to convert escape: {}", e)),
+        }
+    }
+
+    // Escribe el carácter seleccionado (o el anterior al cursor) como `\u{…}` en Rust y como `\uXXXX` en JavaScript/JSON.
+    fn escape_character(&mut self, ctx: &egui::Context) {
+        let range = if self.selection.is_empty() {
+            let start = self.content[..self.cursor].chars().next_back().map_or(self.cursor, |c| self.cursor - c.len_utf8());
+            start..self.cursor
+        } else {
+            self.selection.clone()
+        };
+        let mut chars = self.content[range.clone()].chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            self.notifications.push(NotifLevel::Warning, "Unable to escape character: select a single character");
+            return;
+        };
+        let escaped = match self.language {
+            EditorLanguage::JavaScript | EditorLanguage::Json => literals::escape_char_to_utf16(c),
+            _ => literals::escape_char_to_unicode(c),
+        };
+        self.set_selection(ctx, range);
+        self.replace_selection(ctx, &escaped);
+    }
+
+    // `to_match`: `if let` → `match`; si no, `match` de dos brazos con `_` → `if let`.
+    fn convert_pattern_match(&mut self, ctx: &egui::Context, to_match: bool) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        let result = if to_match {
+            pattern_match::if_let_at(tree, &self.selection).map(|node| pattern_match::if_let_to_match(&self.content, &node))
+        } else {
+            pattern_match::two_arm_match_at(tree, &self.content, &self.selection)
+                .map(|node| pattern_match::match_to_if_let(&self.content, &node))
+        };
+        match result {
+            Some(result) => self.apply_refactoring(ctx, result),
+            None if to_match => self.notifications.push(NotifLevel::Warning, "Unable to convert: place the cursor inside an `if let`"),
+            None => self.notifications.push(NotifLevel::Warning, "Unable to convert: place the cursor inside a two-arm `match` with a `_` arm"),
+        }
+    }
+
+    fn apply_context_action(&mut self, ctx: &egui::Context, action: ContextAction) {
+        let (Some(tree), Some(byte)) = (&self.tree, self.context_menu_byte) else {
+            return;
+        };
+        match context_actions::apply(action, &self.content, tree, byte.min(self.content.len()), &self.selection) {
+            Ok((result, cursor)) => {
+                self.apply_refactoring(ctx, result);
+                self.set_cursor(ctx, cursor);
+                // La función extraída tiene un nombre provisional: se pide el definitivo enseguida.
+                if action == ContextAction::ExtractFunction {
+                    self.start_rename();
+                }
+            }
+            Err(e) => self.report_error(format!("Unable to apply {}: {}", action.label(), e)),
+        }
+    }
+
+    fn function_to_closure(&mut self, ctx: &egui::Context) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        match closures::function_to_closure(&self.content, tree, &self.selection) {
+            Ok(result) => self.apply_refactoring(ctx, result),
+            Err(e) => self.report_error(format!("Unable to convert function to closure: {}", e)),
+        }
+    }
+
+    fn show_closure_prompt(&mut self, ctx: &egui::Context) {
+        let palette = self.palette();
+        let Some((name, error)) = &mut self.closure_prompt else {
+            return;
+        };
+        let mut convert = false;
+        let mut cancel = false;
+        egui::Window::new("Convert to Named Function").collapsible(false).show(ctx, |ui| {
+            ui.label("Function name:");
+            let response = ui.text_edit_singleline(name);
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                convert = true;
+            }
+            if let Some(error) = error {
+                ui.colored_label(palette.error, error.as_str());
+            }
+            ui.horizontal(|ui| {
+                convert |= ui.button("Convert").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+
+        let name = name.trim().to_string();
+        if convert && !name.is_empty() {
+            let result = match &self.tree {
+                Some(tree) => closures::closure_to_function(&self.content, tree, &self.selection, &name),
+                None => Err("The file has not been parsed".to_string()),
+            };
+            match result {
+                Ok(result) => {
+                    self.apply_refactoring(ctx, result);
+                    cancel = true;
+                }
+                Err(e) => {
+                    if let Some((_, error)) = &mut self.closure_prompt {
+                        *error = Some(e);
+                    }
+                }
+            }
+        }
+        if cancel {
+            self.closure_prompt = None;
+        }
+    }
+
+    // Busca en el índice del proyecto sin bloquear: si el indexador tiene el lock, se muestra "Indexing…".
+    fn show_symbol_search(&mut self, ctx: &egui::Context) {
+        let Some(query) = &mut self.symbol_search else {
+            return;
+        };
+        let mut open = true;
+        let mut selected = None;
+        egui::Window::new("Go to Symbol in Project")
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.text_edit_singleline(query).request_focus();
+                let Some(indexer) = &self.indexer else {
+                    ui.label("Open a directory to index its symbols.");
+                    return;
+                };
+                let Ok(index) = indexer.index.try_read() else {
+                    ui.label("Indexing…");
+                    return;
+                };
+                let needle = query.to_lowercase();
+                let mut matches: Vec<(&PathBuf, &Symbol)> = index
+                    .iter()
+                    .flat_map(|(path, symbols)| symbols.iter().map(move |symbol| (path, symbol)))
+                    .filter(|(_, symbol)| symbol.name.to_lowercase().contains(&needle))
+                    .collect();
+                matches.sort_by(|a, b| a.1.name.cmp(&b.1.name).then_with(|| a.0.cmp(b.0)));
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (path, symbol) in matches.into_iter().take(SYMBOL_SEARCH_LIMIT) {
+                        let file = path.file_name().unwrap_or_default().to_string_lossy();
+                        let label = format!("{} {}  —  {}:{}", symbol.kind, symbol.name, file, symbol.line + 1);
+                        if ui.selectable_label(false, label).clicked() {
+                            selected = Some((path.clone(), symbol.start_byte));
+                        }
+                    }
+                });
+            });
+
+        if let Some((path, offset)) = selected {
+            if self.file_path.as_ref() != Some(&path) {
+                self.load(path);
+            }
+            self.set_cursor(ctx, offset.min(self.content.len()));
+            open = false;
+        }
+        if !open {
+            self.symbol_search = None;
+        }
+    }
+
+    // Sumas del archivo guardado, con un campo para pegar el hash publicado y comprobarlo.
+    fn show_checksums(&mut self, ctx: &egui::Context) {
+        let Some(panel) = &mut self.checksum_panel else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(self.localizations.tr("File Checksums")).collapsible(false).open(&mut open).show(ctx, |ui| match &panel.checksums {
+            None => {
+                ui.label(self.localizations.tr("File not yet saved"));
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, format!("Unable to read file: {}", e));
+            }
+            Some(Ok(checksums)) => {
+                egui::Grid::new("checksums").num_columns(3).show(ui, |ui| {
+                    for (name, hash) in checksums.entries() {
+                        ui.label(name);
+                        ui.monospace(hash);
+                        if ui.small_button(self.localizations.tr("Copy")).clicked() {
+                            ui.output_mut(|output| output.copied_text = hash.to_string());
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(self.localizations.tr("Verify Checksum:"));
+                    ui.text_edit_singleline(&mut panel.expected);
+                });
+                if !panel.expected.trim().is_empty() {
+                    match checksums.verify(&panel.expected) {
+                        Some(name) => ui.colored_label(egui::Color32::from_rgb(60, 170, 80), format!("✔ {} {}", name, self.localizations.tr("matches"))),
+                        None => ui.colored_label(ui.visuals().error_fg_color, format!("✘ {}", self.localizations.tr("No checksum matches"))),
+                    };
+                }
+            }
+        });
+        if !open {
+            self.checksum_panel = None;
+        }
+    }
+
+    // Busca texto en los archivos del proyecto (sin los excluidos) o en los últimos abiertos, estén donde estén.
+    fn show_text_search(&mut self, ctx: &egui::Context) {
+        let Some(search) = &mut self.text_search else {
+            return;
+        };
+        let mut open = true;
+        let mut run = false;
+        let mut selected = None;
+        egui::Window::new(self.localizations.tr("Search")).collapsible(false).open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for scope in SearchScope::ALL {
+                    if ui.selectable_value(&mut search.scope, scope, self.localizations.tr(scope.label())).changed() {
+                        run = true;
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                let response = ui.text_edit_singleline(&mut search.query);
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    run = true;
+                }
+                if ui.button(self.localizations.tr("Search")).clicked() {
+                    run = true;
+                }
+            });
+            if search.scope == SearchScope::Project && self.current_dir.is_none() {
+                ui.weak(self.localizations.tr("Open a directory to search its files."));
+            }
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for result in &search.results {
+                    let file = result.path.file_name().unwrap_or_default().to_string_lossy();
+                    let label = format!("{}:{}  {}", file, result.line + 1, result.text);
+                    if ui.selectable_label(false, label).on_hover_text(result.path.display().to_string()).clicked() {
+                        selected = Some((result.path.clone(), result.byte));
+                    }
+                }
+            });
+            if search.truncated {
+                ui.weak(format!("{} {}", text_search::RESULT_LIMIT, self.localizations.tr("results shown; refine the search to see the rest.")));
+            }
+        });
+
+        if run {
+            let files = match search.scope {
+                SearchScope::Project => {
+                    let mut files = Vec::new();
+                    if let Some(dir) = &self.current_dir {
+                        rename::collect_files(dir, &self.exclusions, &mut files);
+                    }
+                    files
+                }
+                SearchScope::RecentFiles => self.settings.recent_files.clone(),
+            };
+            search.run(&files);
+        }
+        if let Some((path, offset)) = selected {
+            if self.file_path.as_ref() != Some(&path) {
+                self.load(path);
+            }
+            self.set_cursor(ctx, offset.min(self.content.len()));
+        }
+        if !open {
+            self.text_search = None;
+        }
+    }
+
+    fn show_rename_windows(&mut self, ctx: &egui::Context) {
+        if let Some((old_name, new_name)) = &mut self.rename_prompt {
+            let mut preview = false;
+            let mut cancel = false;
+            egui::Window::new("Rename Symbol").collapsible(false).show(ctx, |ui| {
+                if let Some(index) = self.indexer.as_ref().and_then(|indexer| indexer.index.try_read().ok()) {
+                    for (path, symbol) in index
+                        .iter()
+                        .flat_map(|(path, symbols)| symbols.iter().map(move |symbol| (path, symbol)))
+                        .filter(|(_, symbol)| symbol.name == *old_name)
+                    {
+                        let file = path.file_name().unwrap_or_default().to_string_lossy();
+                        ui.weak(format!("Defined as {} in {}:{}", symbol.kind, file, symbol.line + 1));
+                    }
+                }
+                ui.label(format!("Rename '{}' to:", old_name));
+                let response = ui.text_edit_singleline(new_name);
+                if response.lost_focus() && ui.input(|input_state| input_state.key_pressed(egui::Key::Enter)) {
+                    preview = true;
+                }
+                ui.horizontal(|ui| {
+                    preview |= ui.button("Preview").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+            if preview && !new_name.is_empty() && new_name != old_name {
+                self.rename_preview = Some(RenamePreview::compute(
+                    old_name,
+                    new_name,
+                    self.current_dir.as_deref(),
+                    &self.exclusions,
+                    self.file_path.as_deref(),
+                    &self.content,
+                ));
+                cancel = true;
+            }
+            if cancel {
+                self.rename_prompt = None;
+            }
+        }
+
+        let palette = self.palette();
+        if let Some(preview) = &mut self.rename_preview {
+            match rename::show_rename_preview(ctx, preview, &palette) {
+                Some(RenamePreviewAction::Apply) => {
+                    if let Some(preview) = self.rename_preview.take() {
+                        self.apply_rename(&preview);
+                    }
+                }
+                Some(RenamePreviewAction::Cancel) => self.rename_preview = None,
+                None => {}
+            }
+        }
+    }
+
+    fn apply_rename(&mut self, preview: &RenamePreview) {
+        for path in preview.checked_files() {
+            if self.file_path.as_ref() == Some(&path) {
+                preview.apply_to(&path, &mut self.content);
+                self.reparse();
+                self.active_tab_mut().is_modified = true;
+                continue;
+            }
+            match fs::read_to_string(&path) {
+                Ok(mut content) => {
+                    preview.apply_to(&path, &mut content);
+                    if let Err(e) = fs::write(&path, content) {
+                        self.report_error(format!("Unable to save file: {}", e));
+                    }
+                }
+                Err(e) => self.report_error(format!("Unable to read file: {}", e)),
+            }
+        }
+    }
+
+    fn show_json_tree(&mut self, ctx: &egui::Context) {
+        let Some(root) = &self.json_tree else {
+            return;
+        };
+        let (open, edit) = json_tree::show(ctx, root.as_ref(), self.json_tree_error.as_deref());
+        if let (Some(edit), Ok(root)) = (edit, root) {
+            match json_tree::apply_edit(root, &edit, &self.indent_style.unit()) {
+                Ok(text) => {
+                    self.json_tree_error = None;
+                    self.apply_refactoring(ctx, text);
+                }
+                Err(e) => self.json_tree_error = Some(e),
+            }
+        }
+        if !open || self.language != EditorLanguage::Json {
+            self.json_tree = None;
+        }
+    }
+
+    // El archivo abierto es la traducción de referencia; el destino se elige con el diálogo.
+    fn open_translation_view(&mut self) {
+        let Some(reference_path) = self.file_path.clone() else {
+            return;
+        };
+        self.save();
+        let Some(target_path) = rfd::FileDialog::new()
+            .add_filter("Locale files", &["json", "toml"])
+            .set_directory(reference_path.parent().unwrap_or(Path::new(".")))
+            .pick_file()
+        else {
+            return;
+        };
+        match (LocaleFile::load(&reference_path), LocaleFile::load(&target_path)) {
+            (Ok(reference), Ok(target)) => {
+                self.translation_view = Some(TranslationView { reference, target, error: None });
+            }
+            (Err(e), _) | (_, Err(e)) => self.report_error(format!("Unable to open translation view: {}", e)),
+        }
+    }
+
+    fn toggle_settings(&mut self) {
+        self.show_settings = !self.show_settings;
+    }
+
+    // Lista de indicadores: los visibles en su orden (se reordenan arrastrando ☰) y después los ocultos.
+    fn show_status_bar_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label("Status bar items (drag ☰ to reorder):");
+        let items = &mut self.settings.status_bar_items;
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        let mut toggled = None;
+        let mut hovered_row = None;
+        for (index, item) in items.iter().enumerate() {
+            let row = ui.horizontal(|ui| {
+                let handle = ui.add(egui::Label::new("☰").sense(egui::Sense::drag()));
+                if handle.drag_started() {
+                    self.status_bar_drag = Some(index);
+                }
+                let mut visible = true;
+                if ui.checkbox(&mut visible, item.label()).changed() {
+                    toggled = Some(*item);
+                }
+            });
+            if pointer.is_some_and(|pos| row.response.rect.y_range().contains(&pos.y)) {
+                hovered_row = Some(index);
+            }
+        }
+        for item in StatusBarItem::ALL.into_iter().filter(|item| !items.contains(item)) {
+            let mut visible = false;
+            if ui.checkbox(&mut visible, item.label()).changed() {
+                toggled = Some(item);
+            }
+        }
+
+        if let Some(from) = self.status_bar_drag {
+            match hovered_row {
+                Some(to) if to != from && from < items.len() => {
+                    let item = items.remove(from);
+                    items.insert(to, item);
+                    self.status_bar_drag = Some(to);
+                }
+                _ => {}
+            }
+            if !ui.input(|i| i.pointer.primary_down()) {
+                self.status_bar_drag = None;
+            }
+        }
+        match toggled {
+            Some(item) if items.contains(&item) => items.retain(|visible| *visible != item),
+            Some(item) => items.push(item),
+            None => {}
+        }
+    }
+
+    // Guardar la apariencia actual como tema propio, o borrar el tema propio seleccionado.
+    fn show_custom_theme_controls(&mut self, ui: &mut egui::Ui) {
+        let selected_is_custom = self.themes.iter().any(|theme| theme.name == self.theme_name && !theme.builtin);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_theme_name).hint_text(self.localizations.tr("Theme name")).desired_width(120.0));
+            if ui.button(self.localizations.tr("Save as Theme")).clicked() {
+                let name = self.new_theme_name.trim().to_string();
+                if name.is_empty() {
+                    self.notifications.info("Enter a name for the theme");
+                } else if self.themes.iter().any(|theme| theme.name == name && theme.builtin) {
+                    self.notifications.info(format!("\"{}\" is a built-in theme", name));
+                } else {
+                    self.themes.retain(|theme| theme.name != name);
+                    self.themes.push(Theme::from_settings(&name, &self.settings, false));
+                    self.theme_name = name;
+                    self.new_theme_name.clear();
+                    changed = true;
+                }
+            }
+            if ui.add_enabled(selected_is_custom, egui::Button::new(self.localizations.tr("Delete Theme"))).clicked() {
+                self.themes.retain(|theme| theme.name != self.theme_name);
+                self.theme_name = self.settings.theme.label().to_string();
+                changed = true;
+            }
+        });
+        if changed {
+            if let Err(e) = themes::save_custom(&self.themes) {
+                self.report_error(format!("Unable to save themes: {}", e));
+            }
+        }
+    }
+
+    fn show_settings_panel(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(self.localizations.tr("Settings"), |ui| {
+            let previous_locale = self.settings.locale.clone();
+            let locales = i18n::available_locales();
+            let selected_name = locales.iter().find(|(tag, _)| *tag == self.settings.locale).map_or(self.settings.locale.as_str(), |(_, name)| name);
+            egui::ComboBox::from_label(self.localizations.tr("Language")).selected_text(selected_name).show_ui(ui, |ui| {
+                for (tag, name) in &locales {
+                    ui.selectable_value(&mut self.settings.locale, tag.clone(), name);
+                }
+            });
+            if self.settings.locale != previous_locale {
+                self.localizations = Localizations::load(&self.settings.locale);
+            }
+            let mut selected = None;
+            egui::ComboBox::from_label(self.localizations.tr("Theme")).selected_text(self.theme_name.as_str()).show_ui(ui, |ui| {
+                for theme in &self.themes {
+                    if ui.selectable_label(theme.name == self.theme_name, theme.name.as_str()).clicked() {
+                        selected = Some(theme.clone());
+                    }
+                }
+            });
+            if let Some(theme) = selected {
+                self.settings.apply_theme(&theme);
+                self.theme_name = theme.name;
+                ui.ctx().set_visuals(self.settings.visuals());
+            }
+            self.show_custom_theme_controls(ui);
+            ui.horizontal(|ui| {
+                theme_combo(ui, &self.localizations.tr("Light theme"), &mut self.settings.light_theme, false);
+                theme_combo(ui, &self.localizations.tr("Dark theme"), &mut self.settings.dark_theme, true);
+            });
+            ui.weak(self.localizations.tr("Ctrl+Shift+T toggles between the light and dark themes."));
+
+            ui.separator();
+            ui.label(self.localizations.tr("Font size:"));
+            ui.add(egui::Slider::new(&mut self.settings.font_size, 10.0..=100.0).show_value(true));
+
+            ui.separator();
+            ui.label(self.localizations.tr("Background color:"));
+            ui.color_edit_button_srgba(&mut self.settings.background_color);
+
+            ui.separator();
+            ui.label(self.localizations.tr("Text color:"));
+            ui.color_edit_button_srgba(&mut self.settings.text_color);
+
+            ui.separator();
+            egui::ComboBox::from_label(self.localizations.tr("Indent guides"))
+                .selected_text(self.localizations.tr(self.settings.indent_guide_style.label()))
+                .show_ui(ui, |ui| {
+                    for style in IndentGuideStyle::ALL {
+                        ui.selectable_value(&mut self.settings.indent_guide_style, style, self.localizations.tr(style.label()));
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut self.settings.indent_guide_color);
+                ui.label(self.localizations.tr("Guide"));
+                ui.color_edit_button_srgba(&mut self.settings.active_indent_guide_color);
+                ui.label(self.localizations.tr("Current block"));
+            });
+
+            ui.separator();
+            let previous_mode = self.settings.colorblind_mode;
+            egui::ComboBox::from_label(self.localizations.tr("Color vision"))
+                .selected_text(self.settings.colorblind_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in ColorblindMode::ALL {
+                        ui.selectable_value(&mut self.settings.colorblind_mode, mode, mode.label());
+                    }
+                });
+            if self.settings.colorblind_mode != previous_mode {
+                ui.ctx().set_visuals(self.settings.visuals());
+            }
+
+            ui.separator();
+            ui.label(self.localizations.tr("Font style:"));
+            if ui.selectable_label(self.settings.font_family == egui::FontFamily::Monospace, self.localizations.tr("Monospace")).clicked() {
+                self.settings.font_family = egui::FontFamily::Monospace;
+            }
+            if ui.selectable_label(self.settings.font_family == egui::FontFamily::Proportional, self.localizations.tr("Proportional")).clicked() {
+                self.settings.font_family = egui::FontFamily::Proportional;
+            }
+
+            ui.separator();
+            ui.label(self.localizations.tr("Line spacing:"));
+            ui.add(egui::Slider::new(&mut self.settings.line_spacing, 1.0..=5.0).show_value(true));
+
+            ui.separator();
+            ui.label(self.localizations.tr("Cursor blink rate (Hz, 0 = solid):"));
+            ui.add_enabled(
+                !self.settings.is_high_contrast(),
+                egui::Slider::new(&mut self.settings.cursor_blink_rate, 0.0..=4.0).show_value(true),
+            );
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(self.localizations.tr("Undo depth:"));
+                ui.add(egui::DragValue::new(&mut self.settings.undo_depth).clamp_range(1..=10_000));
+            });
+            ui.horizontal(|ui| {
+                ui.label(self.localizations.tr("Ask before opening files larger than:"));
+                let mut megabytes = self.settings.max_file_size_bytes as u64 / MEGABYTE;
+                if ui.add(egui::DragValue::new(&mut megabytes).clamp_range(1..=4096).suffix(" MB")).changed() {
+                    self.settings.max_file_size_bytes = (megabytes * MEGABYTE) as usize;
+                }
+            });
+            ui.checkbox(&mut self.settings.auto_save, self.localizations.tr("Auto-save"));
+            let mut seconds = self.settings.auto_save_interval.as_secs();
+            let slider = egui::Slider::new(&mut seconds, settings::AUTO_SAVE_MIN_SECS..=settings::AUTO_SAVE_MAX_SECS).suffix(" s");
+            if ui.add_enabled(self.settings.auto_save, slider).changed() {
+                self.settings.auto_save_interval = Duration::from_secs(seconds);
+            }
+
+            ui.separator();
+            ui.checkbox(&mut self.settings.format_on_type, self.localizations.tr("Format on type (Rust)"));
+            ui.checkbox(&mut self.settings.sort_imports_on_save, self.localizations.tr("Sort imports on save (Rust)"));
+            ui.checkbox(&mut self.settings.inline_diagnostics, self.localizations.tr("Show diagnostics inline"));
+            ui.checkbox(&mut self.settings.inlay_hints_enabled, self.localizations.tr("Inlay hints (rust-analyzer)"));
+            ui.add_enabled(
+                self.settings.inlay_hints_enabled,
+                egui::Slider::new(&mut self.settings.inlay_hint_font_size_factor, 0.5..=1.0).text(self.localizations.tr("Hint size")),
+            );
+            ui.checkbox(&mut self.settings.markdown_emphasis_auto_pair, self.localizations.tr("Auto-pair emphasis (Markdown)"));
+            ui.checkbox(&mut self.settings.smart_paste_json, self.localizations.tr("Format pasted JSON"));
+            ui.checkbox(&mut self.settings.track_changes, self.localizations.tr("Track changes"));
+            ui.horizontal(|ui| {
+                ui.label(self.localizations.tr("Recent files to search:"));
+                let range = 1..=settings::MAX_RECENT_FILES;
+                if ui.add(egui::DragValue::new(&mut self.settings.recent_files_limit).clamp_range(range)).changed() {
+                    self.settings.recent_files.truncate(self.settings.recent_files_limit);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(self.localizations.tr("License for {license} in file templates:"));
+                ui.text_edit_singleline(&mut self.settings.license);
+            });
+
+            ui.separator();
+            ui.label(self.localizations.tr("Snippets (type the trigger and press Tab):"));
+            if let Some(path) = self.snippets.user_path() {
+                ui.weak(format!("User: {}", path.display()));
+            }
+            match self.snippets.project_path() {
+                Some(path) if path.exists() => {
+                    ui.weak(format!("Project: {}", path.display()));
+                }
+                Some(_) if ui.button(self.localizations.tr("Create Project Snippets File")).clicked() => {
+                    if let Err(e) = self.snippets.create_project_file() {
+                        self.report_error(format!("Unable to create snippets file: {}", e));
+                    }
+                }
+                _ => {}
+            }
+            let palette = self.palette();
+            for error in self.snippets.errors() {
+                ui.colored_label(palette.error, error);
+            }
+
+            ui.separator();
+            ui.label(self.localizations.tr("Outline queries (one per line, capture the symbol name as @name):"));
+            let response = ui.add(
+                egui::TextEdit::multiline(&mut self.settings.outline_queries)
+                    .code_editor()
+                    .desired_rows(3)
+                    .hint_text("(function_item name: (identifier) @name)"),
+            );
+            if let Some(tree) = &self.tree {
+                for query in self.outline_query_list() {
+                    if let Some(error) = outline::query_error(query, tree) {
+                        ui.colored_label(self.palette().error, format!("{}: {}", query, error));
+                    }
+                }
+            }
+            if response.changed() {
+                self.reparse();
+            }
+
+            ui.separator();
+            ui.label(self.localizations.tr("Fold regions (regular expressions for the start and end lines):"));
+            let mut regions_changed = false;
+            let error_color = self.palette().error;
+            for (label, pattern) in [(self.localizations.tr("Start:"), &mut self.settings.fold_region_start), (self.localizations.tr("End:"), &mut self.settings.fold_region_end)] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    regions_changed |= ui.text_edit_singleline(pattern).changed();
+                });
+                if let Err(e) = regex::Regex::new(pattern) {
+                    ui.colored_label(error_color, e.to_string());
+                }
+            }
+            if regions_changed {
+                self.reparse();
+            }
+
+            ui.separator();
+            self.show_status_bar_settings(ui);
 
-        Self {
-            content: String::new(),
-            file_path: None,
-            current_dir: None,
-            dir_contents: Vec::new(),
-            new_file_name: String::new(),
-            show_settings: false,
-            font_size: 14.0,
-            background_color: egui::Color32::from_rgb(255, 255, 255),
-            text_color: egui::Color32::from_rgb(0, 0, 0),
-            font_family: egui::FontFamily::Monospace,
-            line_spacing: 1.5,
-            parser,
-            highlighter,
-            highlight_config,
-        }
-    }
+            ui.separator();
+            let mut limit_changed = false;
+            let mut has_limit = self.settings.max_line_length.is_some();
+            if ui.checkbox(&mut has_limit, self.localizations.tr("Maximum line length")).changed() {
+                self.settings.max_line_length = has_limit.then_some(line_length::DEFAULT_MAX_LINE_LENGTH);
+                limit_changed = true;
+            }
+            if let Some(max) = &mut self.settings.max_line_length {
+                limit_changed |= ui.add(egui::DragValue::new(max).clamp_range(20..=400)).changed();
+                egui::ComboBox::from_label(self.localizations.tr("Long lines"))
+                    .selected_text(self.settings.line_length_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in LineLengthMode::ALL {
+                            limit_changed |= ui.selectable_value(&mut self.settings.line_length_mode, mode, mode.label()).changed();
+                        }
+                    });
+            }
+            if limit_changed {
+                self.update_long_lines();
+            }
 
-    fn save(&mut self) {
-        if let Some(path) = &self.file_path {
-            if let Err(e) = fs::write(path, &self.content) {
-                eprintln!("Unable to save file: {}", e);
+            ui.separator();
+            let previous_highlight = self.settings.selection_highlight;
+            egui::ComboBox::from_label(self.localizations.tr("Selection matches"))
+                .selected_text(self.settings.selection_highlight.label())
+                .show_ui(ui, |ui| {
+                    for mode in SelectionHighlight::ALL {
+                        ui.selectable_value(&mut self.settings.selection_highlight, mode, mode.label());
+                    }
+                });
+            if self.settings.selection_highlight != previous_highlight {
+                self.update_selection_matches();
             }
-        }
-    }
 
-    fn load(&mut self, path: PathBuf) {
-        match fs::read_to_string(&path) {
-            Ok(content) => {
-                self.content = content;
-                self.file_path = Some(path);
+            #[cfg(feature = "ambient-sound")]
+            {
+                ui.separator();
+                self.show_ambient_sound_settings(ui);
             }
-            Err(e) => eprintln!("Unable to read file: {}", e),
-        }
-    }
 
-    fn open_directory(&mut self, path: PathBuf) {
-        self.current_dir = Some(path);
-        self.update_dir_contents();
+            ui.separator();
+            ui.collapsing(self.localizations.tr("Keyboard Shortcuts"), |ui| self.shortcuts.show(ui));
+            if self.current_dir.is_some() {
+                ui.collapsing(self.localizations.tr("Manage Exclusions"), |ui| self.show_exclusion_settings(ui));
+            }
+        });
     }
 
-    fn update_dir_contents(&mut self) {
-        self.dir_contents.clear();
-        if let Some(dir) = &self.current_dir {
-            if let Ok(entries) = fs::read_dir(dir) {
-                for entry in entries {
-                    if let Ok(entry) = entry {
-                        self.dir_contents.push(entry.path());
+    // Selector del sonido de fondo y su volumen. Elegir "Off" para la reproducción.
+    #[cfg(feature = "ambient-sound")]
+    fn show_ambient_sound_settings(&mut self, ui: &mut egui::Ui) {
+        let previous = self.ambient_sound;
+        let selected = self.ambient_sound.map_or("Off", AmbientSound::label);
+        egui::ComboBox::from_label(self.localizations.tr("Ambient sound"))
+            .selected_text(self.localizations.tr(selected))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.ambient_sound, None, self.localizations.tr("Off"));
+                for sound in AmbientSound::ALL {
+                    ui.selectable_value(&mut self.ambient_sound, Some(sound), self.localizations.tr(sound.label()));
+                }
+            });
+        ui.label(self.localizations.tr("Ambient volume:"));
+        let volume_changed = ui.add(egui::Slider::new(&mut self.settings.ambient_volume, 0.0..=1.0).show_value(true)).changed();
+        if self.ambient_sound != previous {
+            if self.ambient_player.is_none() && self.ambient_sound.is_some() {
+                match AmbientPlayer::new() {
+                    Ok(player) => self.ambient_player = Some(player),
+                    Err(error) => {
+                        self.ambient_sound = None;
+                        self.report_error(format!("Could not open the audio output: {}", error));
                     }
                 }
             }
+            if let Some(player) = &self.ambient_player {
+                player.play(self.ambient_sound, self.settings.ambient_volume);
+            }
+        } else if volume_changed {
+            if let Some(player) = &self.ambient_player {
+                player.set_volume(self.settings.ambient_volume);
+            }
         }
     }
 
-    fn create_new_file(&mut self) {
-        if let Some(dir) = &self.current_dir {
-            let new_file_path = dir.join(&self.new_file_name.trim());
-            if !new_file_path.exists() {
-                if let Ok(_) = fs::File::create(&new_file_path) {
-                    self.dir_contents.push(new_file_path);
-                    self.new_file_name.clear();
+    // Patrones de `.rbeditor.toml` con un botón para quitar cada uno.
+    fn show_exclusion_settings(&mut self, ui: &mut egui::Ui) {
+        if self.exclusions.patterns.is_empty() {
+            ui.weak(self.localizations.tr("Nothing is excluded. Right-click a file in the sidebar to exclude it."));
+            return;
+        }
+        let mut removed = None;
+        for (index, pattern) in self.exclusions.patterns.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(pattern);
+                if ui.small_button("✕").on_hover_text(self.localizations.tr("Delete")).clicked() {
+                    removed = Some(index);
                 }
+            });
+        }
+        if let Some(index) = removed {
+            if let Err(e) = self.exclusions.remove(index) {
+                self.report_error(format!("Unable to save exclusions: {}", e));
             }
+            self.exclusions_changed();
         }
     }
 
-    fn toggle_settings(&mut self) {
-        self.show_settings = !self.show_settings;
+    fn parse_and_highlight(&mut self) -> Vec<(egui::Color32, Range<usize>)> {
+        let default = self.theme_color(self.settings.text_color);
+        let Some(highlight_config) = &self.highlight_config else {
+            return self.plain_highlights(default);
+        };
+        let colors = highlight::capture_colors(highlight_config.names(), &self.settings.syntax_colors, default);
+        let source = self.content.as_str();
+        let spans = highlight::highlight_spans(&mut self.highlighter, highlight_config, &colors, default, source);
+        highlight::span_ranges(source, spans)
     }
 
-    fn show_settings_panel(&mut self, ui: &mut egui::Ui) {
-        ui.collapsing("Settings", |ui| {
-            ui.label("Font size:");
-            ui.add(egui::Slider::new(&mut self.font_size, 10.0..=100.0).show_value(true));
-
-            ui.separator();
-            ui.label("Background color:");
-            ui.color_edit_button_srgba(&mut self.background_color);
-
-            ui.separator();
-            ui.label("Text color:");
-            ui.color_edit_button_srgba(&mut self.text_color);
-
-            ui.separator();
-            ui.label("Font style:");
-            if ui.selectable_label(self.font_family == egui::FontFamily::Monospace, "Monospace").clicked() {
-                self.font_family = egui::FontFamily::Monospace;
-            }
-            if ui.selectable_label(self.font_family == egui::FontFamily::Proportional, "Proportional").clicked() {
-                self.font_family = egui::FontFamily::Proportional;
-            }
+    // Sin gramática todo va en `default`, salvo las anclas y los alias de YAML.
+    fn plain_highlights(&self, default: egui::Color32) -> Vec<(egui::Color32, Range<usize>)> {
+        if self.language != EditorLanguage::Yaml {
+            return vec![(default, 0..self.content.len())];
+        }
+        let anchor_color = self.theme_color(self.settings.syntax_colors.get("attribute").copied().unwrap_or(default));
+        let mut spans = Vec::new();
+        let mut position = 0;
+        for (_, marker) in yaml::markers(&self.content) {
+            spans.push((default, position..marker.start));
+            position = marker.end;
+            spans.push((anchor_color, marker));
+        }
+        spans.push((default, position..self.content.len()));
+        spans
+    }
 
-            ui.separator();
-            ui.label("Line spacing:");
-            ui.add(egui::Slider::new(&mut self.line_spacing, 1.0..=5.0).show_value(true));
-        });
+    fn highlights_stale(&self) -> bool {
+        self.highlight_palette.0 != self.theme_color(self.settings.text_color) || self.highlight_palette.1 != self.settings.syntax_colors
     }
 
-    fn parse_and_highlight(&mut self) -> Vec<(egui::Color32, String)> {
-        let mut highlights = Vec::new();
-        let mut last_index = 0;
-    
-        let tree = self.parser.parse(&self.content, None).expect("Error parsing content");
-        let root_node = tree.root_node();
-        self.highlighter.highlight(
-            &self.highlight_config,
-            self.content.as_bytes(),
-            None,
-            |capture: &str| {
-                // Process the capture string
-                highlights.push((self.text_color, capture.to_string()));
-                None
-            },
-        ).expect("Error highlighting");
-    
-        highlights.push((self.text_color, self.content[last_index..].to_string()));
-    
-        highlights
+    // Se llama tras cada análisis y, desde `update`, cuando cambia el tema.
+    fn refresh_highlights(&mut self) {
+        let start = std::time::Instant::now();
+        self.highlights = self.parse_and_highlight();
+        self.brackets = if self.settings.rainbow_brackets { brackets::find_brackets(self.tree.as_ref(), &self.content) } else { Vec::new() };
+        if !self.brackets.is_empty() {
+            self.highlights = brackets::colorize(std::mem::take(&mut self.highlights), &self.brackets);
+        }
+        for (color, span) in &mut self.highlights {
+            if self.unused_imports.iter().any(|unused| unused.start <= span.start && span.end <= unused.end) {
+                *color = color.gamma_multiply(0.45);
+            }
+        }
+        self.performance.highlight_time = start.elapsed();
+        self.performance.highlight_spans = self.highlights.len();
+        self.highlight_palette = (self.theme_color(self.settings.text_color), self.settings.syntax_colors.clone());
     }
 }
 
 impl eframe::App for TextEditor {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::SidePanel::right("right_panel").show(ctx, |ui| {
-            if let Some(dir) = &self.current_dir {
-                ui.heading("Current Directory:");
-                ui.label(dir.to_string_lossy());
-                ui.separator();
+    fn on_close_event(&mut self) -> bool {
+        let pending = self.tabs.iter().any(|tab| tab.is_modified);
+        if self.settings.auto_save || self.quit_confirmed || !pending {
+            return true;
+        }
+        self.quit_prompt = true;
+        false
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Con el guardado automático, lo tecleado desde el último guardado se escribe al salir; sin él ya se preguntó.
+        if self.settings.auto_save {
+            self.save_modified_tabs();
+        }
+        if let Err(e) = self.settings.save() {
+            self.report_error(format!("Unable to save settings: {}", e));
+        }
+    }
 
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if let Some(seconds) = frame.info().cpu_usage {
+            self.performance.record_frame(seconds * 1000.0);
+        }
+        self.typing_stats.tick();
+        self.snippets.reload_if_changed();
+        // Las ediciones que no pasan por el teclado también mueven los cambios registrados; sin registro, los que
+        // quedaran ya no apuntarían a su texto y se descartan.
+        if self.settings.track_changes {
+            self.changes.sync(&self.content, &change_author());
+        } else if self.changes.is_stale(&self.content) {
+            self.changes = ChangeSet::default();
+        }
+        if let Some((anchor, cursor)) = self.pending_selection.take() {
+            self.select_from(ctx, anchor, cursor);
+        }
+        self.poll_follow(ctx);
+        self.poll_lsp(ctx);
+        self.validate_toml(ctx);
+        for action in self.shortcuts.process(ctx) {
+            self.run_action(ctx, action);
+        }
+        if let Some(error) = self.shortcuts.save_error.take() {
+            self.report_error(error);
+        }
+        self.output.poll();
+        self.poll_format_on_type(ctx);
+        if self.output.is_running() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        // La ventana de estadísticas avanza cada segundo aunque no se escriba.
+        if self.show_typing_stats {
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+        let icon_only = self.settings.sidebar_width < settings::SIDEBAR_ICON_ONLY_WIDTH;
+        let panel = egui::SidePanel::right("right_panel")
+            .resizable(true)
+            .min_width(settings::SIDEBAR_MIN_WIDTH)
+            .max_width(settings::SIDEBAR_MAX_WIDTH)
+            .default_width(self.settings.sidebar_width);
+        let sidebar = panel.show(ctx, |ui| {
+            if let Some(dir) = self.current_dir.clone() {
                 ui.horizontal(|ui| {
-                    ui.label("New file:");
-                    if ui.text_edit_singleline(&mut self.new_file_name).lost_focus() && ui.input(|input_state| input_state.key_pressed(egui::Key::Enter)) {
-                        self.create_new_file();
+                    let clear_width = if self.file_filter.is_empty() { 0.0 } else { 24.0 };
+                    let filter = egui::TextEdit::singleline(&mut self.file_filter)
+                        .hint_text(self.localizations.tr("Filter files"))
+                        .desired_width(ui.available_width() - clear_width);
+                    ui.add(filter);
+                    if !self.file_filter.is_empty() && ui.small_button("✕").clicked() {
+                        self.file_filter.clear();
                     }
-                    if ui.button("Create").clicked() {
-                        self.create_new_file();
+                });
+                ui.heading(self.localizations.tr("Current Directory:"));
+                let mut navigate_to = None;
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(dir.parent().is_some(), egui::Button::new(self.localizations.tr("↑ Parent"))).clicked() {
+                        navigate_to = dir.parent().map(PathBuf::from);
+                    }
+                    if ui.checkbox(&mut self.show_hidden_files, self.localizations.tr("Show hidden")).changed() {
+                        self.update_dir_contents();
+                    }
+                    if !self.exclusions.patterns.is_empty()
+                        && ui.toggle_value(&mut self.show_all_files, self.localizations.tr("Show All Files")).changed()
+                    {
+                        self.update_dir_contents();
                     }
                 });
+                // Las rutas largas se desplazan en horizontal y se quedan pegadas a la derecha, con la carpeta actual a la vista.
+                egui::ScrollArea::horizontal().id_source("breadcrumbs").stick_to_right(true).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let ancestors: Vec<&Path> = dir.ancestors().collect();
+                        for (depth, ancestor) in ancestors.iter().rev().enumerate() {
+                            if depth > 1 {
+                                ui.label("/");
+                            }
+                            let name = ancestor.file_name().map_or_else(|| ancestor.to_string_lossy(), |name| name.to_string_lossy());
+                            let current = *ancestor == dir.as_path();
+                            if ui.selectable_label(current, name.as_ref()).clicked() && !current {
+                                navigate_to = Some(ancestor.to_path_buf());
+                            }
+                        }
+                    });
+                });
+                if let Some(path) = navigate_to {
+                    self.open_directory(path);
+                }
+                ui.separator();
+
+                if ui.button(self.localizations.tr("New File...")).clicked() {
+                    self.new_file_wizard = Some(NewFileWizard::new(self.current_dir.clone(), self.settings.license.clone()));
+                }
 
                 ui.separator();
 
                 let mut file_to_load = None;
                 let mut dir_to_open = None;
+                let mut to_exclude = None;
 
-                for path in &self.dir_contents {
-                    if ui.button(path.file_name().unwrap().to_string_lossy()).clicked() {
+                let filter = self.file_filter.to_lowercase();
+                if !filter.is_empty() && self.project_files.is_none() {
+                    let mut files = Vec::new();
+                    rename::collect_files(&dir, &self.exclusions, &mut files);
+                    files.sort_by_cached_key(|path| path.to_string_lossy().to_lowercase());
+                    self.project_files = Some(files);
+                }
+                let matches = |path: &&PathBuf| path.file_name().is_some_and(|name| name.to_string_lossy().to_lowercase().contains(&filter));
+                let shown: Vec<&PathBuf> = match &self.project_files {
+                    // Las carpetas de primer nivel que coinciden y luego los archivos de cualquier nivel.
+                    Some(files) if !filter.is_empty() => {
+                        self.dir_contents.iter().filter(|path| path.is_dir()).filter(matches).chain(files.iter().filter(matches)).collect()
+                    }
+                    _ => self.dir_contents.iter().collect(),
+                };
+                for path in shown {
+                    // Los resultados del filtro pueden estar en subcarpetas; se muestran con su ruta relativa.
+                    let name = path.strip_prefix(&dir).unwrap_or(path).to_string_lossy();
+                    let icon = if path.is_dir() { "📁" } else { "📄" };
+                    let excluded = self.exclusions.is_excluded(path);
+                    let text = if icon_only { icon.to_string() } else { format!("{} {}", icon, name) };
+                    let text = if excluded { egui::RichText::new(text).weak() } else { egui::RichText::new(text) };
+                    // Con el panel estrecho solo caben los iconos; el nombre pasa a la ayuda emergente.
+                    let mut response = ui.button(text);
+                    if icon_only {
+                        response = response.on_hover_text(name.as_ref());
+                    }
+                    if !excluded {
+                        response = response.context_menu(|ui| {
+                            if ui.button(self.localizations.tr("Exclude from Project")).clicked() {
+                                to_exclude = Some(path.clone());
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                    if let Some(note) = self.file_notes.get(name.as_ref()) {
+                        response = response.on_hover_text(note);
+                    }
+                    if response.clicked() {
                         if path.is_file() {
                             file_to_load = Some(path.clone());
                         } else if path.is_dir() {
@@ -193,6 +4607,12 @@ impl eframe::App for TextEditor {
                     }
                 }
 
+                if let Some(path) = to_exclude {
+                    if let Err(e) = self.exclusions.add(&path) {
+                        self.report_error(format!("Unable to save exclusions: {}", e));
+                    }
+                    self.exclusions_changed();
+                }
                 if let Some(path) = file_to_load {
                     self.load(path);
                 }
@@ -200,65 +4620,741 @@ impl eframe::App for TextEditor {
                     self.open_directory(path);
                 }
             } else {
-                if ui.button("Open Directory").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                        self.open_directory(path);
-                    }
+                if ui.button(self.localizations.tr("Open Directory")).clicked() {
+                    self.open_directory_dialog();
                 }
             }
         });
+        self.settings.sidebar_width = sidebar.response.rect.width();
+
+        self.show_status_bar(ctx);
+        self.show_note_panel(ctx);
+        self.show_output_panel(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("Open File").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_file() {
-                        self.load(path);
+                ui.menu_button(self.localizations.tr("File"), |ui| {
+                    if ui.button(self.localizations.tr("Open File")).clicked() {
+                        ui.close_menu();
+                        self.open_file_dialog();
                     }
-                }
-                if ui.button("Save").clicked() {
-                    if self.file_path.is_none() {
+                    if ui.button(self.localizations.tr("Paste as New File")).clicked() {
+                        ui.close_menu();
+                        self.paste_as_new_file();
+                    }
+                    if ui.button(self.localizations.tr("Save")).clicked() {
+                        ui.close_menu();
+                        self.save_now();
+                    }
+                    if ui.button(self.localizations.tr("Save a Copy As...")).clicked() {
+                        ui.close_menu();
                         if let Some(path) = rfd::FileDialog::new().save_file() {
-                            self.file_path = Some(path);
+                            if let Err(e) = self.save_copy_to(&path) {
+                                self.report_error(format!("Unable to save copy: {}", e));
+                            }
                         }
                     }
-                    if self.file_path.is_some() {
-                        self.save();
+                    if ui.button(self.localizations.tr("Export as HTML...")).clicked() {
+                        ui.close_menu();
+                        self.export_html();
+                    }
+                });
+                if self.settings.track_changes && ui.button(format!("Changes ({})", self.changes.changes.len())).clicked() {
+                    self.show_changes = !self.show_changes;
+                }
+                ui.menu_button(self.localizations.tr("View"), |ui| {
+                    ui.checkbox(&mut self.settings.show_line_numbers, self.localizations.tr("Show Line Numbers"));
+                    ui.checkbox(&mut self.settings.show_annotations, self.localizations.tr("Show Annotations"));
+                    ui.checkbox(&mut self.settings.show_indent_guides, self.localizations.tr("Show Indent Guides"));
+                    if ui.checkbox(&mut self.settings.rainbow_brackets, self.localizations.tr("Rainbow Brackets")).changed() {
+                        self.refresh_highlights();
+                    }
+                    ui.checkbox(&mut self.settings.show_minimap, self.localizations.tr("Show Minimap"));
+                    ui.add_enabled(
+                        self.settings.show_minimap,
+                        egui::Slider::new(&mut self.settings.minimap_zoom, minimap::MIN_ZOOM..=minimap::MAX_ZOOM).text(self.localizations.tr("Minimap zoom")),
+                    );
+                    if ui.checkbox(&mut self.settings.collapse_imports, self.localizations.tr("Collapse Imports")).changed() {
+                        let collapsed = self.settings.collapse_imports;
+                        self.folds.iter_mut().filter(|fold| fold.kind == FoldKind::Imports).for_each(|fold| fold.collapsed = collapsed);
+                    }
+                    if ui.button(self.localizations.tr("Typing Statistics")).clicked() {
+                        self.show_typing_stats = true;
+                        ui.close_menu();
                     }
+                    if ui.button(self.localizations.tr("Find (Ctrl+F)")).clicked() {
+                        self.find.open(&self.content);
+                        ui.close_menu();
+                    }
+                    if ui.button(self.localizations.tr("Go to Symbol in Project (Ctrl+T)")).clicked() {
+                        self.symbol_search = Some(String::new());
+                        ui.close_menu();
+                    }
+                    if ui.button(self.localizations.tr("Search in Files")).clicked() {
+                        let selected = self.content.get(self.selection.clone()).filter(|text| !text.contains('\n')).unwrap_or_default();
+                        self.text_search = Some(TextSearch::new(selected.to_string()));
+                        ui.close_menu();
+                    }
+                });
+                if ui.button(self.localizations.tr("History")).clicked() {
+                    self.show_undo_history = !self.show_undo_history;
                 }
-                if ui.button("Settings").clicked() {
+                if ui.button(self.localizations.tr("Outline")).clicked() {
+                    self.show_outline = !self.show_outline;
+                }
+                if ui.button(self.localizations.tr("Rename Symbol")).clicked() {
+                    self.start_rename();
+                }
+                ui.menu_button(self.localizations.tr("Refactor"), |ui| {
+                    if ui.button(self.localizations.tr("Toggle Comment")).clicked() {
+                        self.toggle_comment(ui.ctx());
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.is_rust_file(), egui::Button::new(self.localizations.tr("Sort Imports"))).clicked() {
+                        self.sort_imports();
+                        self.active_tab_mut().is_modified = true;
+                        ui.close_menu();
+                    }
+                    let remove_unused = ui
+                        .add_enabled(self.is_rust_file(), egui::Button::new(self.localizations.tr("Remove Unused Imports")))
+                        .on_hover_text(self.localizations.tr("Best effort: names used only through macros from other files or trait methods may be missed"));
+                    if remove_unused.clicked() {
+                        self.remove_unused_imports(ui.ctx());
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.is_rust_file(), egui::Button::new(self.localizations.tr("Convert Closure to Named Function"))).clicked() {
+                        self.closure_prompt = Some((String::new(), None));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.is_rust_file(), egui::Button::new(self.localizations.tr("Convert Function to Closure"))).clicked() {
+                        self.function_to_closure(ui.ctx());
+                        ui.close_menu();
+                    }
+                    let (can_match, can_if_let) = match &self.tree {
+                        Some(tree) if self.is_rust_file() => (
+                            pattern_match::if_let_at(tree, &self.selection).is_some(),
+                            pattern_match::two_arm_match_at(tree, &self.content, &self.selection).is_some(),
+                        ),
+                        _ => (false, false),
+                    };
+                    if ui.add_enabled(can_match, egui::Button::new(self.localizations.tr("Convert to match"))).clicked() {
+                        self.convert_pattern_match(ui.ctx(), true);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(can_if_let, egui::Button::new(self.localizations.tr("Convert to if let"))).clicked() {
+                        self.convert_pattern_match(ui.ctx(), false);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button(self.localizations.tr("Convert Escape")).clicked() {
+                        self.convert_escape(ui.ctx());
+                        ui.close_menu();
+                    }
+                    if ui.button(self.localizations.tr("Escape Character")).clicked() {
+                        self.escape_character(ui.ctx());
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button(self.localizations.tr("Build"), |ui| {
+                    let dir = self.current_dir.clone();
+                    if ui.add_enabled(dir.is_some(), egui::Button::new(self.localizations.tr("Build (cargo build)"))).clicked() {
+                        self.run_in_output("cargo", "cargo", &["build", "--message-format", "short"]);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(dir.is_some(), egui::Button::new(self.localizations.tr("Run (cargo run)"))).clicked() {
+                        self.run_in_output("cargo", "cargo", &["run", "--message-format", "short"]);
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.is_rust_file(), egui::Button::new(self.localizations.tr("Format File (rustfmt)"))).clicked() {
+                        self.format_whole_file(ui.ctx());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.show_output, self.localizations.tr("Show Output"));
+                });
+                ui.menu_button(self.localizations.tr("Developer"), |ui| {
+                    if ui.checkbox(&mut self.show_performance, self.localizations.tr("Performance")).changed() {
+                        self.update_node_count();
+                    }
+                });
+                ui.menu_button(self.localizations.tr("Tools"), |ui| {
+                    if ui.button(self.localizations.tr("Tree-sitter Query Playground")).clicked() {
+                        self.open_query_playground();
+                        ui.close_menu();
+                    }
+                    if ui.button(self.localizations.tr("Show File Checksums")).clicked() {
+                        self.checksum_panel = Some(ChecksumPanel::for_file(self.file_path.as_deref()));
+                        ui.close_menu();
+                    }
+                });
+                if ui.button(self.localizations.tr("Settings")).clicked() {
                     self.toggle_settings();
                 }
+                if ui.add_enabled(self.file_path.is_some(), egui::Button::new(self.localizations.tr("Notes"))).clicked() {
+                    self.open_note_editor();
+                }
+                if let Some(follow) = &mut self.tabs[self.active_tab].follow {
+                    ui.toggle_value(&mut follow.polling, self.localizations.tr("Follow")).on_hover_text(self.localizations.tr("Append new lines as the file grows (tail -f)"));
+                }
+                if self.language == EditorLanguage::Json && ui.button(self.localizations.tr("Tree View")).clicked() {
+                    self.json_tree = Some(json::parse(&self.content));
+                    self.json_tree_error = None;
+                }
+                let current_schema = self.json_schema.as_ref().map_or_else(|| "No schema detected".to_string(), |loaded| loaded.reference.clone());
+                if matches!(self.language, EditorLanguage::Json | EditorLanguage::Yaml)
+                    && ui
+                        .add_enabled(self.file_path.is_some(), egui::Button::new(self.localizations.tr("Select Schema")))
+                        .on_hover_text(current_schema)
+                        .clicked()
+                {
+                    self.select_schema();
+                }
+                if matches!(self.language, EditorLanguage::Json | EditorLanguage::Toml) && ui.button(self.localizations.tr("Translation View")).clicked() {
+                    self.open_translation_view();
+                }
+                if self.in_git_repo {
+                    if let Some(branch) = &self.current_branch {
+                        if ui.button(format!("⎇ {}", branch)).on_hover_text(self.localizations.tr("Switch Branch")).clicked() {
+                            self.open_branch_switcher();
+                        }
+                    }
+                    if ui.button(self.localizations.tr("Commit")).clicked() {
+                        self.open_commit_panel();
+                    }
+                    ui.menu_button("Git", |ui| {
+                        if ui.button(self.localizations.tr("Show Diff")).clicked() {
+                            self.save();
+                            self.run_git("git diff", &["diff"]);
+                            ui.close_menu();
+                        }
+                        if ui.button(self.localizations.tr("Stash Changes")).clicked() {
+                            self.stash_changes();
+                            ui.close_menu();
+                        }
+                        if ui.button(self.localizations.tr("Pop Stash")).clicked() {
+                            self.pop_stash();
+                            ui.close_menu();
+                        }
+                        if ui.button(self.localizations.tr("List Stashes")).clicked() {
+                            self.list_stashes();
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(self.file_path.is_some(), egui::Button::new(self.localizations.tr("File History"))).clicked() {
+                            self.open_file_history();
+                            ui.close_menu();
+                        }
+                    });
+                }
             });
 
             ui.separator();
+            match tabs::show_tab_bar(ui, &self.tabs, self.active_tab) {
+                Some(TabAction::Activate(index)) => self.switch_tab(index),
+                Some(TabAction::Close(index)) => self.request_close_tab(index),
+                None => {}
+            }
+            if self.commit_message_mode {
+                self.show_commit_message_banner(ui);
+            }
+            self.show_language_banner(ui);
+            self.show_toml_error_banner(ui);
+            self.show_find_bar(ui);
+            if self.settings.show_minimap {
+                egui::SidePanel::right("minimap")
+                    .resizable(false)
+                    .exact_width(minimap::MINIMAP_WIDTH)
+                    .show_inside(ui, |ui| self.show_minimap(ui));
+            }
 
             let rect = ui.available_rect_before_wrap();
-            ui.painter().rect_filled(rect, 0.0, self.background_color);
+            ui.painter().rect_filled(rect, 0.0, self.theme_color(self.settings.background_color));
 
-            let highlighted_text = self.parse_and_highlight();
-            for (color, text) in &highlighted_text {
-                ui.label(egui::RichText::new(text).color(*color).font(egui::FontId::new(self.font_size, self.font_family.clone())));
+            if self.highlights_stale() {
+                self.refresh_highlights();
             }
 
-            let response = ui.add(
-                egui::TextEdit::multiline(&mut self.content)
-                    .desired_width(f32::INFINITY)
-                    .desired_rows(30)
-                    .font(egui::FontId::new(self.font_size, self.font_family.clone()))
+            // Las teclas de la ventana de autocompletado se consumen antes de que el TextEdit las vea.
+            if self.completion.is_some() {
+                self.handle_completion_keys(ui.ctx());
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Space)) {
+                self.open_completion();
+            }
+            let typed_tag_end = ui.input(|i| {
+                i.events.iter().any(|event| matches!(event, egui::Event::Text(t) if t.ends_with('>')))
+            });
+            let typed_trigger = ui.input(|i| {
+                i.key_pressed(egui::Key::Enter)
+                    || i.events.iter().any(|event| matches!(event, egui::Event::Text(t) if t.ends_with(';')))
+            });
+
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::ALT, egui::Key::N)) && self.file_path.is_some() {
+                let line = self.content[..self.cursor.min(self.content.len())].matches('\n').count();
+                let text = self.annotations.get(&line).cloned().unwrap_or_default();
+                self.annotation_input = Some((line, text));
+            }
+            let editor_focused = ui.memory(|memory| memory.has_focus(Self::editor_id()));
+            let tab_pressed = ui.input(|i| i.key_pressed(egui::Key::Tab) && i.modifiers.is_none());
+            if editor_focused && tab_pressed && self.selection.is_empty() {
+                if let Some(new_cursor) = snippets::expand(&self.snippets, &mut self.content, self.cursor) {
+                    ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab));
+                    self.reparse();
+                    self.set_cursor(ui.ctx(), new_cursor);
+                    self.active_tab_mut().is_modified = true;
+                }
+            }
+            // Se consumen antes de que el TextEdit aplique su propio deshacer.
+            let (redo, undo) = ui.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z) || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y),
+                    i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z),
+                )
+            });
+            if undo && self.live_rename.is_none() {
+                self.undo(ui.ctx());
+            } else if redo && self.live_rename.is_none() {
+                self.redo(ui.ctx());
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::T)) {
+                self.toggle_dark_mode(ui.ctx());
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::T)) {
+                self.symbol_search = Some(String::new());
+            }
+            let (word_previous, word_next, find_previous, find_next) = ui.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::F3),
+                    i.consume_key(egui::Modifiers::COMMAND, egui::Key::F3),
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::F3),
+                    i.consume_key(egui::Modifiers::NONE, egui::Key::F3),
+                )
+            });
+            if word_next {
+                self.find_word_under_cursor(ui.ctx(), FindAction::Next);
+            } else if word_previous {
+                self.find_word_under_cursor(ui.ctx(), FindAction::Previous);
+            } else if find_next {
+                self.find_step(ui.ctx(), FindAction::Next);
+            } else if find_previous {
+                self.find_step(ui.ctx(), FindAction::Previous);
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::O)) {
+                self.show_outline = !self.show_outline;
+            }
+            let (next_function, prev_function) = ui.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::ALT, egui::Key::PageDown),
+                    i.consume_key(egui::Modifiers::ALT, egui::Key::PageUp),
+                )
+            });
+            let function_target = match &self.tree {
+                Some(tree) if self.is_rust_file() && next_function => outline::next_function_offset(tree, self.cursor),
+                Some(tree) if self.is_rust_file() && prev_function => outline::prev_function_offset(tree, self.cursor),
+                _ => None,
+            };
+            if let Some(offset) = function_target {
+                self.set_cursor(ui.ctx(), offset);
+            }
+            if self.live_rename.is_none() && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F2)) {
+                self.start_live_rename();
+            }
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::K)) {
+                self.delete_current_line(ui.ctx());
+            }
+            let (duplicate_down, duplicate_up) = ui.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::SHIFT | egui::Modifiers::ALT, egui::Key::ArrowDown),
+                    i.consume_key(egui::Modifiers::SHIFT | egui::Modifiers::ALT, egui::Key::ArrowUp),
+                )
+            });
+            if duplicate_down {
+                self.duplicate_lines(ui.ctx(), DuplicateDir::Down);
+            } else if duplicate_up {
+                self.duplicate_lines(ui.ctx(), DuplicateDir::Up);
+            }
+            let raw_paste = ui.input_mut(|i| {
+                let modifiers = egui::Modifiers::COMMAND | egui::Modifiers::ALT | egui::Modifiers::SHIFT;
+                if !i.consume_key(modifiers, egui::Key::V) {
+                    return None;
+                }
+                let index = i.events.iter().position(|event| matches!(event, egui::Event::Paste(_)))?;
+                match i.events.remove(index) {
+                    egui::Event::Paste(clipboard) => Some(clipboard),
+                    _ => None,
+                }
+            });
+            if let Some(clipboard) = raw_paste {
+                self.replace_selection(ui.ctx(), &literals::raw_string_literal(&clipboard));
+            }
+            if editor_focused && self.language == EditorLanguage::Json && self.settings.smart_paste_json && self.rect_selection.is_none() {
+                self.smart_paste_json(ui.ctx());
+            }
+            if let Some(rect) = self.rect_selection {
+                let paste = ui.input_mut(|i| {
+                    let index = i.events.iter().position(|event| matches!(event, egui::Event::Paste(_)))?;
+                    match i.events.remove(index) {
+                        egui::Event::Paste(clipboard) => Some(clipboard),
+                        _ => None,
+                    }
+                });
+                if let Some(clipboard) = paste {
+                    let mut updated = self.content.clone();
+                    column::column_paste(&mut updated, &rect, &clipboard);
+                    self.rect_selection = None;
+                    self.apply_refactoring(ui.ctx(), updated);
+                } else if ui.input(|i| {
+                    i.key_pressed(egui::Key::Escape) || i.events.iter().any(|event| matches!(event, egui::Event::Text(_) | egui::Event::Cut))
+                }) {
+                    self.rect_selection = None;
+                }
+            }
+            if editor_focused && self.rect_selection.is_none() {
+                self.move_by_graphemes(ui.ctx());
+            }
+            if editor_focused && self.language == EditorLanguage::Markdown && self.settings.markdown_emphasis_auto_pair && self.selection.is_empty() {
+                self.auto_pair_emphasis(ui.ctx());
+            }
+            // Solo cuenta lo tecleado: un pegado o un borrado no dicen nada de la velocidad.
+            let typed_chars: usize = ui.input(|i| {
+                i.events.iter().map(|event| if let egui::Event::Text(text) = event { text.chars().count() } else { 0 }).sum()
+            });
+            let content_before_edit = (self.settings.track_changes || !self.annotations.is_empty() || self.mirror.is_some()).then(|| self.content.clone());
+            let content_before_backspace = ui
+                .input(|i| i.key_pressed(egui::Key::Backspace))
+                .then(|| self.content.clone());
+            // Solo se copia el contenido en los fotogramas en que el teclado puede haberlo cambiado.
+            let content_before_keys = ui
+                .input(|i| {
+                    i.events
+                        .iter()
+                        .any(|event| matches!(event, egui::Event::Text(_) | egui::Event::Paste(_) | egui::Event::Cut | egui::Event::Key { pressed: true, .. }))
+                })
+                .then(|| self.content.clone());
+            let boundary_key = ui.input(|i| {
+                [egui::Key::Enter, egui::Key::Backspace, egui::Key::Delete].into_iter().any(|key| i.key_pressed(key))
+            });
+
+            let gutter_width = self.gutter_width(ui.ctx());
+            // egui pinta el cursor con el trazo de la selección; se oculta en la fase apagada del parpadeo.
+            let cursor_stroke = ui.visuals().selection.stroke;
+            let blink_rate = self.settings.cursor_blink_rate;
+            if blink_rate > 0.0 {
+                let phase = ui.input(|i| i.time) * blink_rate as f64 * 2.0;
+                if phase as u64 % 2 == 1 {
+                    ui.visuals_mut().selection.stroke.color = egui::Color32::TRANSPARENT;
+                }
+                ui.ctx().request_repaint_after(std::time::Duration::from_secs_f32(0.5 / blink_rate));
+            }
+            let font = egui::FontId::new(self.settings.font_size, self.settings.font_family.clone());
+            let scroll_to_end = std::mem::take(&mut self.scroll_to_end);
+            let max_height = if self.show_settings { ui.available_height() * 0.5 } else { f32::INFINITY };
+            let hints = self.visible_inlay_hints(ui.ctx());
+            let gaps: Vec<(usize, f32)> = hints.iter().map(|(offset, _, width)| (*offset, *width)).collect();
+            let folds = &self.folds;
+            let highlights = &self.highlights;
+            let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                let color = ui.visuals().override_text_color.unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+                let job = folding::layout_job(text, highlights, folds, &gaps, font.clone(), color, wrap_width);
+                ui.fonts(|f| f.layout_job(job))
+            };
+            let scroll = egui::ScrollArea::vertical()
+                .id_source("editor_scroll")
+                .max_height(max_height)
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    let output = egui::TextEdit::multiline(&mut self.content)
+                        .id(Self::editor_id())
+                        .interactive(self.live_rename.is_none())
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(30)
+                        .font(font.clone())
+                        .margin(egui::vec2(gutter_width, 2.0))
+                        .layouter(&mut layouter)
+                        .show(ui);
+                    if scroll_to_end {
+                        let end = egui::Rect::from_min_size(output.response.rect.left_bottom(), egui::Vec2::splat(1.0));
+                        ui.scroll_to_rect(end, Some(egui::Align::BOTTOM));
+                    }
+                    output
+                });
+            let output = scroll.inner;
+            ui.visuals_mut().selection.stroke = cursor_stroke;
+            self.editor_rect = output.response.rect;
+            // Todo lo que se dibuja encima del texto se recorta a la parte visible del área de desplazamiento.
+            let panel_clip = ui.clip_rect();
+            let viewport = scroll.inner_rect.intersect(panel_clip);
+            ui.set_clip_rect(viewport);
+            // Se deja fuera el borde del TextEdit.
+            let gutter = egui::Rect::from_x_y_ranges(
+                output.response.rect.left() + 1.0..=output.response.rect.left() + gutter_width,
+                output.response.rect.top() + 1.0..=output.response.rect.bottom() - 1.0,
             );
-            if response.changed() {
-                println!("El texto ha cambiado");
-                // Aquí puedes agregar cualquier lógica adicional que necesites cuando el texto cambie
-                self.save();
+            ui.painter().rect_filled(gutter, 0.0, view::gutter_color(self.theme_color(self.settings.background_color)));
+            if self.settings.show_line_numbers {
+                self.show_line_numbers(ui, &output.galley, output.text_draw_pos, output.response.rect);
             }
+            if self.settings.inline_diagnostics {
+                let hovered_repair = self.paint_inline_diagnostics(ui, &output.galley, output.text_draw_pos);
+                self.show_repair_popup(ui.ctx(), hovered_repair);
+            }
+            self.paint_yaml_links(ui.painter(), &output.galley, output.text_draw_pos);
+            self.update_rect_selection(ui, &output.galley, output.text_draw_pos, output.response.rect);
+            self.paint_inlay_hints(ui.painter(), &output.galley, output.text_draw_pos, &hints);
+            let previous_cursor = self.cursor;
+            let previous_selection = self.selection.clone();
+            if let Some(cursor_range) = output.cursor_range {
+                self.cursor_screen_y = output.text_draw_pos.y + output.galley.pos_from_cursor(&cursor_range.primary).center().y;
+                let chars = cursor_range.as_sorted_char_range();
+                self.cursor = text::char_to_byte(&self.content, cursor_range.primary.ccursor.index);
+                self.selection = text::char_to_byte(&self.content, chars.start)..text::char_to_byte(&self.content, chars.end);
+            }
+            if output.response.changed() {
+                if let Some(before) = content_before_keys {
+                    self.record_edit(before, boundary_key);
+                }
+                if typed_chars > 0 {
+                    self.typing_stats.record(typed_chars);
+                }
+                if let (Some(mirror), Some(before)) = (&mut self.mirror, &content_before_edit) {
+                    match mirror.apply_edit(before, &mut self.content) {
+                        Some(0) => {}
+                        Some(shift) => self.set_cursor(ui.ctx(), (self.cursor as isize + shift) as usize),
+                        None => {
+                            self.mirror = None;
+                            self.notifications.info("Mirror edit stopped: the edit crossed a mirrored region");
+                        }
+                    }
+                }
+                self.reparse();
+                self.detect_language();
+                if typed_tag_end && self.language.is_markup() {
+                    let html = self.language == EditorLanguage::Html;
+                    if let Some((closing, new_cursor)) = markup::auto_close_tag(&self.content, self.cursor, html) {
+                        self.content.insert_str(self.cursor, &closing);
+                        self.reparse();
+                        self.set_cursor(ui.ctx(), new_cursor);
+                    }
+                }
+                if let Some(before) = content_before_backspace {
+                    if let Some(entry) = undo::detect_deleted_line(&before, &self.content) {
+                        self.push_line_undo(entry);
+                    }
+                }
+                if let (LineLengthMode::HardWrap, Some(max)) = (self.settings.line_length_mode, self.settings.max_line_length) {
+                    if let Some(new_cursor) = line_length::hard_wrap(&mut self.content, self.cursor, max) {
+                        self.reparse();
+                        self.set_cursor(ui.ctx(), new_cursor);
+                    }
+                }
+                if typed_trigger
+                    && self.settings.format_on_type
+                    && self.is_rust_file()
+                    && self.content.len() <= format::FORMAT_ON_TYPE_MAX_BYTES
+                {
+                    self.format_on_type(self.cursor);
+                }
+
+                if let Some(before) = &content_before_edit {
+                    if self.settings.track_changes {
+                        // Primero lo que cambió antes de la pulsación en este mismo fotograma.
+                        self.changes.sync(before, &change_author());
+                        self.changes.record_edit(before, &self.content, &change_author());
+                    }
+                    if annotations::shift_for_edit(&mut self.annotations, before, &self.content) {
+                        self.save_annotations();
+                    }
+                }
+                self.active_tab_mut().is_modified = true;
+            }
+            if output.response.changed() || self.cursor != previous_cursor {
+                self.update_local_references();
+                self.update_syntax_node();
+            }
+            if output.response.changed() || self.selection != previous_selection {
+                self.update_selection_matches();
+            }
+            if output.response.changed() && self.completion.is_some() {
+                self.completion = Completion::at(&self.content, self.cursor, self.hash_comments());
+            } else if self.cursor != previous_cursor {
+                self.completion = None;
+            }
+            if self.cursor != previous_cursor {
+                // No se edita a ciegas: al entrar con el cursor en un pliegue, se despliega.
+                let cursor = self.cursor;
+                self.folds.iter_mut().filter(|fold| fold.range.contains(&cursor)).for_each(|fold| fold.collapsed = false);
+            }
+            if output.response.clicked() && ui.input(|i| i.modifiers.command) {
+                if let Some(url) = self.link_at_cursor() {
+                    open_url(ui.ctx(), url);
+                }
+            }
+            let mut expand_macro = None;
+            let mut context_action = None;
+            if output.response.secondary_clicked() {
+                self.context_menu_byte = ui
+                    .input(|i| i.pointer.interact_pos())
+                    .map(|pos| text::char_to_byte(&self.content, output.galley.cursor_from_pos(pos - output.text_draw_pos).ccursor.index));
+            }
+            output.response.clone().context_menu(|ui| {
+                if let (Some(tree), Some(byte)) = (self.tree.as_ref().filter(|_| self.is_rust_file()), self.context_menu_byte) {
+                    let actions = context_actions::actions_at(tree, byte.min(self.content.len()));
+                    for action in &actions {
+                        if ui.button(self.localizations.tr(action.label())).clicked() {
+                            context_action = Some(*action);
+                            ui.close_menu();
+                        }
+                    }
+                    if !actions.is_empty() {
+                        ui.separator();
+                    }
+                }
+                let url = docs_links::url_at(&self.content, self.cursor);
+                if ui.add_enabled(url.is_some(), egui::Button::new("Open URL")).clicked() {
+                    open_url(ui.ctx(), url.unwrap_or_default());
+                    ui.close_menu();
+                }
+                let docs = self.docs_link_at_cursor();
+                if ui.add_enabled(docs.is_some(), egui::Button::new("Open on docs.rs")).clicked() {
+                    open_url(ui.ctx(), docs.unwrap_or_default());
+                    ui.close_menu();
+                }
+                let macro_item = self.macro_item_at_cursor();
+                if ui.add_enabled(macro_item.is_some(), egui::Button::new("Expand Macro")).clicked() {
+                    expand_macro = macro_item;
+                    ui.close_menu();
+                }
+                ui.separator();
+                if self.mirror.is_some() {
+                    if ui.button(self.localizations.tr("Stop Mirror Edit")).clicked() {
+                        self.stop_mirroring();
+                        ui.close_menu();
+                    }
+                } else {
+                    let label = if self.mirror_pending.is_some() { "Mirror with Marked Region" } else { "Mark Region for Mirror Edit" };
+                    if ui.add_enabled(!self.selection.is_empty(), egui::Button::new(self.localizations.tr(label))).clicked() {
+                        self.mark_mirror_region();
+                        ui.close_menu();
+                    }
+                }
+            });
+            if let Some((root, item)) = expand_macro {
+                self.macro_expansion = Some(MacroExpansion::start(root, item));
+            }
+            if let Some(action) = context_action {
+                self.apply_context_action(ui.ctx(), action);
+            }
+            if self.settings.show_indent_guides {
+                self.paint_indent_guides(ui, &output.galley, output.text_draw_pos);
+            }
+            self.paint_selection_matches(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_find_matches(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_playground_matches(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_local_references(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_mirror_regions(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_conflicts(ui, &output.galley, output.text_draw_pos, output.response.rect);
+            self.paint_virtual_texts(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_unused_imports(ui.painter(), &output.galley, output.text_draw_pos);
+            self.paint_bracket_depths(ui, &output.galley, output.text_draw_pos);
+            self.show_color_swatches(ui, &output.galley, output.text_draw_pos);
+            self.paint_long_lines(ui, &output.galley, output.text_draw_pos, output.response.rect);
+            self.paint_tracked_changes(ui.painter(), &output.galley, output.text_draw_pos, output.response.rect);
+            if self.settings.show_annotations {
+                self.paint_annotations(ui.painter(), &output.galley, output.text_draw_pos, output.response.rect);
+            }
+            self.paint_sticky_scope(ui.painter(), &output.galley, output.text_draw_pos, viewport);
+            self.paint_folds(ui, &output.galley, output.text_draw_pos, output.response.rect);
+            self.show_live_rename(ui, &output.galley, output.text_draw_pos);
+            self.show_completion_popup(ui.ctx(), &output.galley, output.text_draw_pos);
+            ui.set_clip_rect(panel_clip);
 
             if self.show_settings {
                 self.show_settings_panel(ui);
             }
         });
+
+        if self.show_outline {
+            self.show_outline_window(ctx);
+        }
+        self.show_color_picker(ctx);
+        self.show_annotation_input(ctx);
+        self.show_rename_windows(ctx);
+        self.show_closure_prompt(ctx);
+        self.show_symbol_search(ctx);
+        self.show_text_search(ctx);
+        self.show_checksums(ctx);
+        self.show_query_playground(ctx);
+        self.show_json_tree(ctx);
+        self.show_new_file_wizard(ctx);
+        if let Some(view) = &mut self.translation_view {
+            if !view.show(ctx) {
+                self.translation_view = None;
+            }
+        }
+        self.show_git_windows(ctx);
+        self.show_close_prompt(ctx);
+        self.show_quit_prompt(ctx, frame);
+        self.show_open_prompt(ctx);
+        self.show_macro_expansion(ctx);
+        if let Some(view) = &self.hex_view {
+            if !view.show(ctx) {
+                self.hex_view = None;
+            }
+        }
+        if self.show_undo_history {
+            self.show_undo_history_window(ctx);
+        }
+        if self.show_changes {
+            self.show_changes_window(ctx);
+        }
+        if self.show_typing_stats {
+            self.show_typing_stats = self.typing_stats.show_window(ctx);
+        }
+        self.notifications.show(ctx);
+        if self.show_performance {
+            self.show_performance = self.performance.show_window(ctx, self.content.len(), self.dir_contents.len());
+        }
+        self.auto_save_if_due(ctx);
+        self.update_window_title(frame);
     }
 }
 
+/// `● archivo — proyecto — RBeditor`: el punto si hay cambios sin guardar y el proyecto si hay una carpeta abierta.
+fn compute_window_title(editor: &TextEditor) -> String {
+    let tab = &editor.tabs[editor.active_tab];
+    let file = tab.file_path.as_ref().and_then(|path| path.file_name()).map_or_else(|| "New File".into(), |name| name.to_string_lossy());
+    let dirty = if tab.is_modified { "● " } else { "" };
+    match &editor.project_name {
+        Some(project) => format!("{}{} — {} — RBeditor", dirty, file, project),
+        None => format!("{}{} — RBeditor", dirty, file),
+    }
+}
+
+// Selector limitado a los temas claros o a los oscuros.
+fn theme_combo(ui: &mut egui::Ui, label: &str, theme: &mut ThemeName, dark: bool) {
+    egui::ComboBox::from_label(label).selected_text(theme.label()).show_ui(ui, |ui| {
+        for option in ThemeName::ALL.into_iter().filter(|option| option.is_dark() == dark) {
+            ui.selectable_value(theme, option, option.label());
+        }
+    });
+}
+
+fn open_url(ctx: &egui::Context, url: String) {
+    ctx.output_mut(|output| output.open_url = Some(egui::output::OpenUrl::new_tab(url)));
+}
+
+fn change_author() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "local user".to_string())
+}
+
+fn read_clipboard() -> Option<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()
+        .filter(|text| !text.is_empty())
+}
+
 fn main() -> eframe::Result<()> {
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
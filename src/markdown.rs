@@ -0,0 +1,49 @@
+// Edición de Markdown sin gramática de tree-sitter: el énfasis se reconoce contando delimitadores en la línea.
+
+/// Qué hacer al teclear un delimitador de énfasis (`*`, `_` o `~`) en lugar de escribirlo sin más.
+pub enum EmphasisEdit {
+    // El cursor está ante el cierre del énfasis: se salta en lugar de escribir otro.
+    Skip,
+    // Se escribe `before` antes del cursor y `after` detrás, con el cursor entre ambos.
+    Pair { before: String, after: String },
+}
+
+/// `*` abre `*|*` y otro `*` lo convierte en `**|**`; `__` y `~~` se cierran al teclear el segundo carácter.
+/// Solo se empareja en mitad del texto, al principio de una palabra y fuera de un énfasis ya abierto en la línea: al
+/// principio de la línea `* ` es una viñeta.
+pub fn emphasis_edit(content: &str, cursor: usize, typed: char) -> Option<EmphasisEdit> {
+    if !matches!(typed, '*' | '_' | '~') {
+        return None;
+    }
+    let line_start = content[..cursor].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[cursor..].find('\n').map_or(content.len(), |i| cursor + i);
+    let run_before = content[line_start..cursor].chars().rev().take_while(|c| *c == typed).count();
+    let run_after = content[cursor..line_end].chars().take_while(|c| *c == typed).count();
+    // Los delimitadores son de un byte, así que las rachas se pueden restar como bytes.
+    let prefix = &content[line_start..cursor - run_before];
+    let suffix = &content[cursor + run_after..line_end];
+    // Tras una palabra el delimitador cierra; tras un espacio o al principio de la línea, abre.
+    let opening = !prefix.chars().next_back().is_some_and(char::is_alphanumeric);
+
+    let single = typed.to_string();
+    let pair = if typed == '*' {
+        // `*|*` y `**|**` crecen un nivel hasta `***|***`.
+        (run_before == run_after && run_before < 3).then(|| (single.clone(), single.clone()))
+    } else {
+        // `_` o `~` sueltos se escriben tal cual; el segundo cierra los dos.
+        (run_before == 1 && run_after == 0).then(|| (single.clone(), single.repeat(2)))
+    };
+    if let Some((before, after)) = pair {
+        // Con un número impar de delimitadores ya en la línea, el cursor está dentro de un énfasis abierto.
+        let unclosed = prefix.matches(typed).count() % 2 == 1;
+        let next_is_word = suffix.chars().next().is_some_and(char::is_alphanumeric);
+        let mid_text = !prefix.trim().is_empty();
+        if opening && mid_text && !unclosed && !next_is_word {
+            return Some(EmphasisEdit::Pair { before, after });
+        }
+    }
+    if run_after > 0 && !opening {
+        return Some(EmphasisEdit::Skip);
+    }
+    None
+}
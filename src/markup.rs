@@ -0,0 +1,32 @@
+// Edición de HTML/XML sin gramática de tree-sitter: se analiza el texto de la etiqueta directamente.
+
+// Elementos de HTML que nunca llevan etiqueta de cierre.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// Si el carácter antes de `cursor` es el `>` de una etiqueta de apertura, devuelve la etiqueta de cierre a insertar
+/// en el cursor y la posición en la que debe quedar el cursor (entre ambas etiquetas).
+pub fn auto_close_tag(content: &str, cursor: usize, html: bool) -> Option<(String, usize)> {
+    let before = content.get(..cursor)?;
+    let tag_body = before.strip_suffix('>')?;
+    let open = tag_body.rfind('<')?;
+    let tag = &tag_body[open + 1..];
+    if tag.contains('>') || tag.ends_with('/') || tag.starts_with(['/', '!', '?']) {
+        return None;
+    }
+
+    let name: String = tag
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect();
+    if name.is_empty() || (html && VOID_ELEMENTS.contains(&name.to_ascii_lowercase().as_str())) {
+        return None;
+    }
+
+    let closing = format!("</{}>", name);
+    if content[cursor..].starts_with(&closing) {
+        return None;
+    }
+    Some((closing, cursor))
+}
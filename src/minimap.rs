@@ -0,0 +1,116 @@
+use eframe::egui;
+use std::ops::Range;
+
+use crate::text;
+
+pub const MINIMAP_WIDTH: f32 = 80.0;
+pub const MIN_ZOOM: f32 = 2.0;
+pub const MAX_ZOOM: f32 = 4.0;
+// Cada línea ocupa como mucho 3 px de alto y cada carácter 1 px de ancho; si el archivo no cabe, las líneas se estrechan.
+const MAX_LINE_HEIGHT: f32 = 3.0;
+const CHAR_WIDTH: f32 = 1.0;
+// Tamaño de la lupa; su altura entre el aumento es la franja del minimapa que se amplía.
+const ZOOM_SIZE: egui::Vec2 = egui::vec2(420.0, 240.0);
+
+fn line_height(bounds: egui::Rect, line_count: usize) -> f32 {
+    (bounds.height() / line_count.max(1) as f32).min(MAX_LINE_HEIGHT)
+}
+
+// Los colores son del último análisis; si ya no encajan con el texto, todo se pinta en `color`.
+fn fitting_spans<'a>(content: &str, spans: &'a [(egui::Color32, Range<usize>)], fallback: &'a [(egui::Color32, Range<usize>)]) -> &'a [(egui::Color32, Range<usize>)] {
+    let fits = spans.last().map(|(_, span)| span.end) == Some(content.len())
+        && spans.iter().all(|(_, span)| content.is_char_boundary(span.start) && content.is_char_boundary(span.end));
+    if fits {
+        spans
+    } else {
+        fallback
+    }
+}
+
+/// Franja del minimapa que se amplía al mantener pulsado en `click_y`: centrada en el puntero y sin salirse del minimapa.
+pub fn minimap_zoom_rect(click_y: f32, minimap_bounds: egui::Rect, zoom: f32) -> egui::Rect {
+    let height = (ZOOM_SIZE.y / zoom).min(minimap_bounds.height());
+    let top = (click_y - height / 2.0).clamp(minimap_bounds.top(), minimap_bounds.bottom() - height);
+    egui::Rect::from_x_y_ranges(minimap_bounds.x_range(), top..=top + height)
+}
+
+/// Dibuja cada trozo resaltado como barras de su color, una fila por línea; los espacios se quedan en blanco.
+pub fn paint(painter: &egui::Painter, bounds: egui::Rect, content: &str, spans: &[(egui::Color32, Range<usize>)], color: egui::Color32) {
+    let line_height = line_height(bounds, text::line_offsets(content).len());
+    let fallback = [(color, 0..content.len())];
+    let spans = fitting_spans(content, spans, &fallback);
+    let (mut line, mut column) = (0, 0);
+    for (span_color, span) in spans {
+        let piece = &content[span.clone()];
+        let mut run_start = None;
+        for c in piece.chars().chain(std::iter::once(' ')) {
+            if c.is_whitespace() {
+                if let Some(start) = run_start.take() {
+                    let top = bounds.top() + line as f32 * line_height;
+                    let x = bounds.left() + start as f32 * CHAR_WIDTH;
+                    let bar = egui::Rect::from_min_max(egui::pos2(x, top), egui::pos2(bounds.left() + column as f32 * CHAR_WIDTH, top + line_height * 0.8));
+                    painter.rect_filled(bar, 0.0, span_color.gamma_multiply(0.8));
+                }
+            } else if run_start.is_none() {
+                run_start = Some(column);
+            }
+            if c == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+        // El espacio añadido para cerrar la última barra no es del texto.
+        column -= 1;
+    }
+}
+
+/// Lupa junto al minimapa con las líneas de `zoom_rect` escritas con su resaltado, `zoom` veces más grandes que en el
+/// minimapa. Se pinta en la capa de las ventanas emergentes y no mueve el editor.
+pub fn paint_zoom(
+    ctx: &egui::Context,
+    minimap_bounds: egui::Rect,
+    zoom_rect: egui::Rect,
+    zoom: f32,
+    content: &str,
+    spans: &[(egui::Color32, Range<usize>)],
+    color: egui::Color32,
+) {
+    let offsets = text::line_offsets(content);
+    let line_height = line_height(minimap_bounds, offsets.len());
+    let first = ((zoom_rect.top() - minimap_bounds.top()) / line_height).floor().max(0.0) as usize;
+    let last = (((zoom_rect.bottom() - minimap_bounds.top()) / line_height).ceil() as usize).min(offsets.len());
+    let Some(&start) = offsets.get(first) else {
+        return;
+    };
+    let end = offsets.get(last).map_or(content.len(), |&offset| offset);
+
+    let fallback = [(color, 0..content.len())];
+    let spans = fitting_spans(content, spans, &fallback);
+    let font = egui::FontId::monospace(line_height * zoom);
+    let mut job = egui::text::LayoutJob::default();
+    let mut position = start;
+    for (span_color, span) in spans.iter().filter(|(_, span)| span.end > start && span.start < end) {
+        let piece = span.start.max(start)..span.end.min(end);
+        if piece.start > position {
+            job.append(&content[position..piece.start], 0.0, egui::TextFormat::simple(font.clone(), color));
+        }
+        job.append(&content[piece.clone()], 0.0, egui::TextFormat::simple(font.clone(), *span_color));
+        position = piece.end;
+    }
+    if position < end {
+        job.append(&content[position..end], 0.0, egui::TextFormat::simple(font.clone(), color));
+    }
+
+    let center_y = zoom_rect.center().y;
+    let inset = egui::Rect::from_min_size(egui::pos2(minimap_bounds.left() - ZOOM_SIZE.x - 8.0, center_y - ZOOM_SIZE.y / 2.0), ZOOM_SIZE);
+    let inset = inset.translate(egui::vec2(0.0, (ctx.screen_rect().top() - inset.top()).max(0.0) + (ctx.screen_rect().bottom() - inset.bottom()).min(0.0)));
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("minimap_zoom"))).with_clip_rect(inset);
+    let visuals = ctx.style().visuals.clone();
+    painter.rect(inset, visuals.window_rounding, visuals.window_fill, visuals.window_stroke);
+    let galley = ctx.fonts(|fonts| fonts.layout_job(job));
+    // La primera línea puede empezar por encima de la franja; se desplaza igual que en el minimapa.
+    let offset = (zoom_rect.top() - minimap_bounds.top() - first as f32 * line_height) * zoom;
+    painter.galley(inset.min + egui::vec2(6.0, -offset), galley);
+}
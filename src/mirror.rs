@@ -0,0 +1,71 @@
+use std::ops::Range;
+
+use crate::text;
+
+pub const MIRROR_COLOR: eframe::egui::Color32 = eframe::egui::Color32::from_rgba_premultiplied(90, 60, 140, 40);
+
+/// Dos zonas del archivo enlazadas: lo que se escribe en una se repite en la otra en la misma posición relativa.
+/// Los rangos son en bytes y no se solapan.
+pub struct MirrorPair {
+    pub region_a: Range<usize>,
+    pub region_b: Range<usize>,
+}
+
+impl MirrorPair {
+    pub fn new(first: Range<usize>, second: Range<usize>) -> Option<Self> {
+        let overlaps = first.start < second.end && second.start < first.end;
+        (!overlaps && !first.is_empty() && !second.is_empty()).then_some(Self { region_a: first, region_b: second })
+    }
+
+    /// Repite en la otra zona la edición que llevó `before` a `after` y mueve las dos zonas. Devuelve cuánto se
+    /// desplaza el cursor por lo escrito delante de él, o `None` si la edición pisa el borde de una zona y el enlace
+    /// se rompe.
+    pub fn apply_edit(&mut self, before: &str, after: &mut String) -> Option<isize> {
+        let (start, old_end, new_end) = text::changed_range(before, after);
+        let delta = new_end as isize - old_end as isize;
+        let inside = |region: &Range<usize>| start >= region.start && old_end <= region.end;
+        let (edited, other) = if inside(&self.region_a) {
+            (&mut self.region_a, &mut self.region_b)
+        } else if inside(&self.region_b) {
+            (&mut self.region_b, &mut self.region_a)
+        } else {
+            // Fuera de las dos zonas solo hay que mover las que queden detrás.
+            let straddles = |region: &Range<usize>| start < region.end && old_end > region.start;
+            if straddles(&self.region_a) || straddles(&self.region_b) {
+                return None;
+            }
+            for region in [&mut self.region_a, &mut self.region_b] {
+                if region.start >= old_end {
+                    *region = shift(region.start, delta)..shift(region.end, delta);
+                }
+            }
+            return Some(0);
+        };
+
+        // Misma posición relativa en la otra zona, recortada si la otra es más corta.
+        let inserted = after[start..new_end].to_string();
+        let other_start = (other.start + (start - edited.start)).min(other.end);
+        let other_end = (other_start + (old_end - start)).min(other.end);
+        edited.end = shift(edited.end, delta);
+        // Coordenadas de la otra zona en `after`: si va detrás, ya se movió con la edición.
+        let offset = if other.start >= old_end { delta } else { 0 };
+        let (replace_start, replace_end) = (shift(other_start, offset), shift(other_end, offset));
+        if !after.is_char_boundary(replace_start) || !after.is_char_boundary(replace_end) {
+            return None;
+        }
+        after.replace_range(replace_start..replace_end, &inserted);
+        let other_delta = inserted.len() as isize - (other_end - other_start) as isize;
+        *other = shift(other.start, offset)..shift(other.end, offset + other_delta);
+        // La otra zona va delante del cursor: todo lo que cambió en ella lo empuja.
+        if other.start < edited.start {
+            edited.start = shift(edited.start, other_delta);
+            edited.end = shift(edited.end, other_delta);
+            return Some(other_delta);
+        }
+        Some(0)
+    }
+}
+
+fn shift(position: usize, delta: isize) -> usize {
+    (position as isize + delta).max(0) as usize
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const NOTES_DIR: &str = ".rbeditor_notes";
+
+/// `{dir}/.rbeditor_notes/{nombre_del_archivo}.note`
+pub fn note_path(dir: &Path, file: &Path) -> Option<PathBuf> {
+    let name = file.file_name()?.to_string_lossy();
+    Some(dir.join(NOTES_DIR).join(format!("{}.note", name)))
+}
+
+pub fn read_note(dir: &Path, file: &Path) -> String {
+    note_path(dir, file)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+// Una nota vacía borra el archivo para no dejar restos en `.rbeditor_notes`.
+pub fn write_note(dir: &Path, file: &Path, text: &str) -> io::Result<()> {
+    let Some(path) = note_path(dir, file) else {
+        return Ok(());
+    };
+    if text.trim().is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    fs::create_dir_all(dir.join(NOTES_DIR))?;
+    fs::write(path, text)
+}
+
+/// Notas existentes en `dir`, indexadas por nombre del archivo al que pertenecen.
+pub fn load_notes(dir: &Path) -> HashMap<String, String> {
+    let Ok(entries) = fs::read_dir(dir.join(NOTES_DIR)) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let name = file_name.strip_suffix(".note")?.to_string();
+            let text = fs::read_to_string(entry.path()).ok()?;
+            Some((name, text))
+        })
+        .collect()
+}
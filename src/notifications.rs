@@ -0,0 +1,90 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Los errores se quedan más tiempo en pantalla para que dé tiempo a leerlos.
+const INFO_DURATION: Duration = Duration::from_millis(1500);
+const ERROR_DURATION: Duration = Duration::from_secs(6);
+const MAX_VISIBLE: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NotifLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl NotifLevel {
+    fn color(self, visuals: &egui::Visuals) -> egui::Color32 {
+        match self {
+            NotifLevel::Info => visuals.text_color(),
+            NotifLevel::Warning => visuals.warn_fg_color,
+            NotifLevel::Error => visuals.error_fg_color,
+        }
+    }
+}
+
+pub struct Notification {
+    pub message: String,
+    pub level: NotifLevel,
+    pub created_at: Instant,
+    pub duration: Duration,
+}
+
+impl Notification {
+    fn is_expired(&self) -> bool {
+        self.created_at.elapsed() >= self.duration
+    }
+}
+
+/// Avisos breves en la esquina inferior derecha, del más antiguo arriba al más reciente abajo. Cada uno desaparece
+/// al pasar su `duration` o al hacer clic en él.
+#[derive(Default)]
+pub struct NotificationQueue {
+    notifications: VecDeque<Notification>,
+}
+
+impl NotificationQueue {
+    pub fn push(&mut self, level: NotifLevel, message: impl Into<String>) {
+        let duration = if level == NotifLevel::Info { INFO_DURATION } else { ERROR_DURATION };
+        self.notifications.push_back(Notification { message: message.into(), level, created_at: Instant::now(), duration });
+        while self.notifications.len() > MAX_VISIBLE {
+            self.notifications.pop_front();
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(NotifLevel::Info, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(NotifLevel::Error, message);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.notifications.retain(|notification| !notification.is_expired());
+        let Some(next_expiry) = self.notifications.iter().map(|notification| notification.duration.saturating_sub(notification.created_at.elapsed())).min() else {
+            return;
+        };
+        ctx.request_repaint_after(next_expiry);
+
+        let mut dismissed = None;
+        egui::Area::new("notifications").anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -36.0)).show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                for (index, notification) in self.notifications.iter().enumerate() {
+                    let frame = egui::Frame::popup(ui.style()).rounding(6.0).show(ui, |ui| {
+                        ui.set_max_width(320.0);
+                        ui.colored_label(notification.level.color(ui.visuals()), &notification.message);
+                    });
+                    let response = ui.interact(frame.response.rect, egui::Id::new(("notification", index)), egui::Sense::click());
+                    if response.on_hover_text("Click to dismiss").clicked() {
+                        dismissed = Some(index);
+                    }
+                }
+            });
+        });
+        if let Some(index) = dismissed {
+            self.notifications.remove(index);
+        }
+    }
+}
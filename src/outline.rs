@@ -0,0 +1,169 @@
+use std::ops::Range;
+use tree_sitter::{Node, Query, QueryCursor, Tree};
+
+pub struct Symbol {
+    pub name: String,
+    pub kind: &'static str,
+    pub depth: usize,
+    pub start_byte: usize,
+    pub line: usize,
+}
+
+/// Recorre el árbol y devuelve funciones, structs, enums, traits, módulos e impls en orden de documento.
+pub fn extract_symbols(tree: &Tree, source: &[u8]) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    collect(tree.root_node(), source, 0, &mut symbols);
+    symbols
+}
+
+/// Esquema definido por el usuario: cada consulta debe capturar el nombre del símbolo como `@name`; el símbolo es
+/// el nodo padre de esa captura. Las consultas que no compilan se ignoran (ver `query_error`).
+pub fn run_outline_queries(queries: &[&str], tree: &Tree, source: &[u8]) -> Vec<Symbol> {
+    let mut found: Vec<(Range<usize>, Symbol)> = Vec::new();
+    for text in queries {
+        let Ok(query) = Query::new(tree.language(), text) else {
+            continue;
+        };
+        let Some(name_index) = query.capture_index_for_name("name") else {
+            continue;
+        };
+        let mut cursor = QueryCursor::new();
+        for m in cursor.matches(&query, tree.root_node(), source) {
+            for capture in m.captures.iter().filter(|capture| capture.index == name_index) {
+                let item = capture.node.parent().unwrap_or(capture.node);
+                if found.iter().any(|(range, _)| *range == item.byte_range()) {
+                    continue;
+                }
+                found.push((
+                    item.byte_range(),
+                    Symbol {
+                        name: capture.node.utf8_text(source).unwrap_or("").to_string(),
+                        kind: kind_label(item.kind()).unwrap_or("item"),
+                        depth: 0,
+                        start_byte: item.start_byte(),
+                        line: item.start_position().row,
+                    },
+                ));
+            }
+        }
+    }
+
+    found.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+    let ranges: Vec<Range<usize>> = found.iter().map(|(range, _)| range.clone()).collect();
+    found
+        .into_iter()
+        .enumerate()
+        .map(|(index, (range, mut symbol))| {
+            symbol.depth = ranges[..index].iter().filter(|outer| outer.start <= range.start && range.end <= outer.end).count();
+            symbol
+        })
+        .collect()
+}
+
+/// Mensaje de error si `query` no es una consulta válida para el lenguaje del árbol o no captura `@name`.
+pub fn query_error(query: &str, tree: &Tree) -> Option<String> {
+    match Query::new(tree.language(), query) {
+        Ok(query) if query.capture_index_for_name("name").is_none() => Some("missing @name capture".to_string()),
+        Ok(_) => None,
+        Err(e) => Some(format!("{:?} error at row {}, column {}", e.kind, e.row + 1, e.column + 1)),
+    }
+}
+
+fn collect(node: Node, source: &[u8], depth: usize, symbols: &mut Vec<Symbol>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let symbol = symbol_for(child, source, depth);
+        let child_depth = if symbol.is_some() { depth + 1 } else { depth };
+        if let Some(symbol) = symbol {
+            symbols.push(symbol);
+        }
+        if child.named_child_count() > 0 {
+            collect(child, source, child_depth, symbols);
+        }
+    }
+}
+
+fn kind_label(node_kind: &str) -> Option<&'static str> {
+    Some(match node_kind {
+        "function_item" | "function_signature_item" => "fn",
+        "struct_item" => "struct",
+        "enum_item" => "enum",
+        "trait_item" => "trait",
+        "mod_item" => "mod",
+        "impl_item" => "impl",
+        "macro_definition" => "macro",
+        _ => return None,
+    })
+}
+
+fn symbol_for(node: Node, source: &[u8], depth: usize) -> Option<Symbol> {
+    let kind = kind_label(node.kind())?;
+
+    let text = |field: &str| {
+        node.child_by_field_name(field)
+            .and_then(|n| n.utf8_text(source).ok())
+            .map(str::to_string)
+    };
+    let name = if kind == "impl" {
+        match (text("trait"), text("type")) {
+            (Some(tr), Some(ty)) => format!("{} for {}", tr, ty),
+            (None, Some(ty)) => ty,
+            _ => return None,
+        }
+    } else {
+        text("name")?
+    };
+
+    Some(Symbol {
+        name,
+        kind,
+        depth,
+        start_byte: node.start_byte(),
+        line: node.start_position().row,
+    })
+}
+
+// Inicio de cada `function_item` (incluidos los métodos de `impl` y `trait`) en orden de documento.
+fn function_offsets(tree: &Tree) -> Vec<usize> {
+    fn collect_functions(node: Node, offsets: &mut Vec<usize>) {
+        if node.kind() == "function_item" {
+            offsets.push(node.start_byte());
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            collect_functions(child, offsets);
+        }
+    }
+    let mut offsets = Vec::new();
+    collect_functions(tree.root_node(), &mut offsets);
+    offsets
+}
+
+/// Inicio de la primera función que empieza después de `cursor`.
+pub fn next_function_offset(tree: &Tree, cursor: usize) -> Option<usize> {
+    let offsets = function_offsets(tree);
+    offsets.get(offsets.partition_point(|&offset| offset <= cursor)).copied()
+}
+
+/// Inicio de la última función que empieza antes de `cursor`.
+pub fn prev_function_offset(tree: &Tree, cursor: usize) -> Option<usize> {
+    let offsets = function_offsets(tree);
+    offsets.partition_point(|&offset| offset < cursor).checked_sub(1).map(|index| offsets[index])
+}
+
+// Ámbitos cuya cabecera se fija arriba del editor al desplazarse por su cuerpo.
+const STICKY_SCOPE_KINDS: &[&str] = &["function_item", "impl_item", "trait_item", "mod_item", "struct_item", "enum_item"];
+
+/// Primera línea del ámbito de nivel superior que empieza antes de `first_visible_line` y sigue abierto en ella.
+pub fn outermost_visible_scope(tree: &Tree, source: &[u8], first_visible_line: usize) -> Option<String> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let scope = root.named_children(&mut cursor).find(|node| {
+        STICKY_SCOPE_KINDS.contains(&node.kind())
+            && node.start_position().row < first_visible_line
+            && node.end_position().row >= first_visible_line
+    })?;
+    let line_start = scope.start_byte() - scope.start_position().column;
+    let header = source[line_start..].split(|&b| b == b'\n').next()?;
+    Some(String::from_utf8_lossy(header).trim_end().to_string())
+}
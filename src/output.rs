@@ -0,0 +1,190 @@
+use eframe::egui;
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Instant;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl OutputLevel {
+    fn color(self) -> egui::Color32 {
+        match self {
+            OutputLevel::Info => egui::Color32::from_rgb(90, 140, 220),
+            OutputLevel::Warning => egui::Color32::from_rgb(220, 160, 40),
+            OutputLevel::Error => egui::Color32::from_rgb(220, 50, 50),
+        }
+    }
+
+    // Nivel de una línea de rustc/cargo/rustfmt (`error[E0308]: ...`, `src/main.rs:3:5: warning: ...`).
+    fn classify(line: &str) -> Self {
+        let lower = line.trim_start().to_ascii_lowercase();
+        if lower.starts_with("error") || lower.contains(": error") {
+            OutputLevel::Error
+        } else if lower.starts_with("warning") || lower.contains(": warning") {
+            OutputLevel::Warning
+        } else {
+            OutputLevel::Info
+        }
+    }
+}
+
+pub struct OutputEntry {
+    pub source: String,
+    pub level: OutputLevel,
+    pub message: String,
+    pub timestamp: Instant,
+}
+
+pub struct FileReference {
+    pub path: String,
+    // Ambas empiezan en 1, como en los mensajes del compilador.
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Busca `ruta:línea[:columna]` en un mensaje, como el `--> src/main.rs:10:5` de rustc.
+pub fn parse_file_reference(message: &str) -> Option<FileReference> {
+    message.split_whitespace().find_map(|word| {
+        let word = word.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | '\'' | '"'));
+        let mut parts = word.split(':');
+        let path = parts.next().filter(|path| path.contains('.') || path.contains('/'))?;
+        let line = parts.next()?.parse().ok().filter(|line| *line > 0)?;
+        let column = parts.next().and_then(|column| column.parse().ok()).unwrap_or(1);
+        Some(FileReference { path: path.to_string(), line, column })
+    })
+}
+
+/// Panel con la salida de las acciones Build, Run y Format. Los procesos se ejecutan en hilos aparte
+/// y envían cada línea por un canal que se vacía en cada fotograma.
+#[derive(Default)]
+pub struct OutputPanel {
+    pub entries: Vec<OutputEntry>,
+    running: Vec<Receiver<OutputEntry>>,
+}
+
+impl OutputPanel {
+    pub fn push(&mut self, source: &str, level: OutputLevel, message: &str) {
+        self.entries.push(OutputEntry {
+            source: source.to_string(),
+            level,
+            message: message.to_string(),
+            timestamp: Instant::now(),
+        });
+    }
+
+    // Una entrada por línea, con el nivel deducido de su contenido.
+    pub fn push_lines(&mut self, source: &str, text: &str) {
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            self.push(source, OutputLevel::classify(line), line);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Lanza `program` en `dir` y vuelca su stdout y stderr, línea a línea, con `source` como origen.
+    pub fn run(&mut self, source: &str, program: &str, args: &[&str], dir: PathBuf) {
+        self.push(source, OutputLevel::Info, &format!("$ {} {}", program, args.join(" ")));
+        let (sender, receiver) = mpsc::channel();
+        self.running.push(receiver);
+        let mut command = Command::new(program);
+        command.args(args).current_dir(dir).stdout(Stdio::piped()).stderr(Stdio::piped());
+        let source = source.to_string();
+        thread::spawn(move || {
+            let send = |level, message: String| {
+                let _ = sender.send(OutputEntry { source: source.clone(), level, message, timestamp: Instant::now() });
+            };
+            let mut child = match command.spawn() {
+                Ok(child) => child,
+                Err(e) => return send(OutputLevel::Error, format!("Unable to run command: {}", e)),
+            };
+            // stderr se lee en otro hilo para que ninguna de las dos tuberías se llene y bloquee al proceso.
+            let stderr = child.stderr.take().map(|stderr| {
+                let sender = sender.clone();
+                let source = source.clone();
+                thread::spawn(move || forward_lines(stderr, &source, &sender))
+            });
+            if let Some(stdout) = child.stdout.take() {
+                forward_lines(stdout, &source, &sender);
+            }
+            if let Some(handle) = stderr {
+                let _ = handle.join();
+            }
+            match child.wait() {
+                Ok(status) if status.success() => send(OutputLevel::Info, "Finished successfully".to_string()),
+                Ok(status) => send(OutputLevel::Error, format!("Finished with {}", status)),
+                Err(e) => send(OutputLevel::Error, format!("Unable to wait for command: {}", e)),
+            }
+        });
+    }
+
+    /// Recoge lo que hayan enviado los procesos en curso.
+    pub fn poll(&mut self) {
+        let entries = &mut self.entries;
+        self.running.retain(|receiver| loop {
+            match receiver.try_recv() {
+                Ok(entry) => entries.push(entry),
+                Err(TryRecvError::Empty) => break true,
+                Err(TryRecvError::Disconnected) => break false,
+            }
+        });
+    }
+
+    /// Dibuja las entradas; devuelve la referencia a archivo en la que se ha hecho clic.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<FileReference> {
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            ui.strong("Output");
+            if self.is_running() {
+                ui.spinner();
+            }
+            if ui.button("Clear Output").clicked() {
+                self.entries.clear();
+            }
+        });
+        ui.separator();
+        egui::ScrollArea::vertical().auto_shrink([false, false]).stick_to_bottom(true).show(ui, |ui| {
+            for entry in &self.entries {
+                ui.horizontal(|ui| {
+                    ui.colored_label(entry.level.color(), "●");
+                    ui.weak(format!("[{}]", entry.source))
+                        .on_hover_text(format!("{}s ago", entry.timestamp.elapsed().as_secs()));
+                    let message = egui::RichText::new(&entry.message).monospace();
+                    match parse_file_reference(&entry.message) {
+                        Some(reference) => {
+                            if ui.link(message).on_hover_text(format!("Open {}:{}", reference.path, reference.line)).clicked() {
+                                clicked = Some(reference);
+                            }
+                        }
+                        None => {
+                            ui.label(message);
+                        }
+                    }
+                });
+            }
+        });
+        clicked
+    }
+}
+
+fn forward_lines(stream: impl Read, source: &str, sender: &Sender<OutputEntry>) {
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        let entry = OutputEntry {
+            source: source.to_string(),
+            level: OutputLevel::classify(&line),
+            message: line,
+            timestamp: Instant::now(),
+        };
+        if sender.send(entry).is_err() {
+            return;
+        }
+    }
+}
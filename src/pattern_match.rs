@@ -0,0 +1,116 @@
+// Conversión entre `if let` y `match` de dos brazos. Solo se reescribe la estructura de control: los cuerpos
+// se copian tal cual, con la sangría ajustada al nuevo nivel.
+
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+use crate::text;
+
+fn ancestors<'t>(tree: &'t Tree, range: &Range<usize>) -> impl Iterator<Item = Node<'t>> {
+    std::iter::successors(tree.root_node().descendant_for_byte_range(range.start, range.end), |node| node.parent())
+}
+
+fn is_wildcard_arm(arm: &Node, content: &str) -> bool {
+    arm.child_by_field_name("pattern")
+        .is_some_and(|pattern| pattern.child_by_field_name("condition").is_none() && &content[pattern.byte_range()] == "_")
+}
+
+fn match_arms<'t>(node: &Node<'t>) -> Vec<Node<'t>> {
+    let Some(body) = node.child_by_field_name("body") else {
+        return Vec::new();
+    };
+    let mut cursor = body.walk();
+    let arms = body.named_children(&mut cursor).filter(|child| child.kind() == "match_arm").collect();
+    arms
+}
+
+/// `if let` más interno que contiene `range` (sin cadenas `let ... && ...`).
+pub fn if_let_at<'t>(tree: &'t Tree, range: &Range<usize>) -> Option<Node<'t>> {
+    ancestors(tree, range).find(|node| {
+        node.kind() == "if_expression"
+            && node.child_by_field_name("condition").is_some_and(|condition| condition.kind() == "let_condition")
+    })
+}
+
+/// `match` más interno que contiene `range` con exactamente dos brazos, uno de ellos `_`, y sin guardas.
+pub fn two_arm_match_at<'t>(tree: &'t Tree, content: &str, range: &Range<usize>) -> Option<Node<'t>> {
+    ancestors(tree, range).find(|node| {
+        if node.kind() != "match_expression" {
+            return false;
+        }
+        let arms = match_arms(node);
+        let no_guards = arms.iter().all(|arm| {
+            arm.child_by_field_name("pattern").is_some_and(|pattern| pattern.child_by_field_name("condition").is_none())
+        });
+        arms.len() == 2 && no_guards && arms.iter().filter(|arm| is_wildcard_arm(arm, content)).count() == 1
+    })
+}
+
+/// `if let P = v { a } else { b }` → `match v { P => { a } _ => { b } }`; sin `else`, el brazo `_` queda como `{}`.
+pub fn if_let_to_match(content: &str, node: &Node) -> String {
+    let text = |node: Node| &content[node.byte_range()];
+    let (Some(condition), Some(consequence)) = (node.child_by_field_name("condition"), node.child_by_field_name("consequence")) else {
+        return content.to_string();
+    };
+    let (Some(pattern), Some(value)) = (condition.child_by_field_name("pattern"), condition.child_by_field_name("value")) else {
+        return content.to_string();
+    };
+    let indent = text::line_indent(content, node.start_byte());
+    let arm_indent = format!("{}    ", indent);
+    let otherwise = match node.child_by_field_name("alternative").and_then(|clause| clause.named_child(0)) {
+        Some(alternative) => text::reindent(text(alternative), indent, &arm_indent),
+        None => "{}".to_string(),
+    };
+    let replacement = format!(
+        "match {} {{\n{}{} => {}\n{}_ => {}\n{}}}",
+        text(value),
+        arm_indent,
+        text(pattern),
+        text::reindent(text(consequence), indent, &arm_indent),
+        arm_indent,
+        otherwise,
+        indent
+    );
+    splice(content, node.byte_range(), &replacement)
+}
+
+/// `match v { P => a, _ => b }` → `if let P = v { a } else { b }`; un brazo `_` vacío (`{}` o `()`) no genera `else`.
+pub fn match_to_if_let(content: &str, node: &Node) -> String {
+    let text = |node: Node| &content[node.byte_range()];
+    let (arms, Some(value)) = (match_arms(node), node.child_by_field_name("value")) else {
+        return content.to_string();
+    };
+    let [first, second] = arms[..] else {
+        return content.to_string();
+    };
+    let (arm, wildcard) = if is_wildcard_arm(&first, content) { (second, first) } else { (first, second) };
+    let (Some(pattern), Some(arm_value), Some(wildcard_value)) = (
+        arm.child_by_field_name("pattern"),
+        arm.child_by_field_name("value"),
+        wildcard.child_by_field_name("value"),
+    ) else {
+        return content.to_string();
+    };
+
+    let indent = text::line_indent(content, node.start_byte());
+    let arm_indent = text::line_indent(content, arm.start_byte());
+    let as_block = |value: Node| match value.kind() {
+        "block" => text::reindent(text(value), arm_indent, indent),
+        _ => format!("{{\n{}    {}\n{}}}", indent, text::reindent(text(value), arm_indent, &format!("{}    ", indent)), indent),
+    };
+    let otherwise = match wildcard_value.kind() {
+        _ if matches!(text(wildcard_value), "{}" | "()") => String::new(),
+        "if_expression" => format!(" else {}", text::reindent(text(wildcard_value), arm_indent, indent)),
+        _ => format!(" else {}", as_block(wildcard_value)),
+    };
+    let replacement = format!("if let {} = {} {}{}", text(pattern), text(value), as_block(arm_value), otherwise);
+    splice(content, node.byte_range(), &replacement)
+}
+
+fn splice(content: &str, range: Range<usize>, replacement: &str) -> String {
+    let mut result = String::with_capacity(content.len() + replacement.len());
+    result.push_str(&content[..range.start]);
+    result.push_str(replacement);
+    result.push_str(&content[range.end..]);
+    result
+}
@@ -0,0 +1,86 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tree_sitter::Tree;
+
+const FRAME_HISTORY: usize = 200;
+// Referencia para la gráfica: por encima de esto un fotograma ya no llega a 60 fps.
+const FRAME_BUDGET_MS: f32 = 1000.0 / 60.0;
+
+/// Tiempos de los últimos fotogramas y del resaltado, para ver qué edición hace caer la tasa de fotogramas.
+pub struct PerformanceStats {
+    frame_times: VecDeque<f32>,
+    pub highlight_time: Duration,
+    pub highlight_spans: usize,
+    pub node_count: usize,
+}
+
+impl PerformanceStats {
+    pub fn new() -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(FRAME_HISTORY),
+            highlight_time: Duration::ZERO,
+            highlight_spans: 0,
+            node_count: 0,
+        }
+    }
+
+    pub fn record_frame(&mut self, milliseconds: f32) {
+        if self.frame_times.len() == FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(milliseconds);
+    }
+
+    /// Ventana con las métricas y una barra por fotograma; devuelve `false` cuando el usuario la cierra.
+    pub fn show_window(&self, ctx: &egui::Context, content_bytes: usize, dir_entries: usize) -> bool {
+        let mut open = true;
+        egui::Window::new("Performance").open(&mut open).resizable(false).show(ctx, |ui| {
+            let last = self.frame_times.back().copied().unwrap_or(0.0);
+            ui.label(format!("Frame time: {:.2} ms", last));
+            ui.label(format!("parse_and_highlight: {:.2} ms", self.highlight_time.as_secs_f64() * 1000.0));
+            ui.label(format!("Highlight spans: {}", self.highlight_spans));
+            ui.label(format!("Tree-sitter nodes: {}", self.node_count));
+            ui.label(format!("Content: {} bytes", content_bytes));
+            ui.label(format!("Directory entries: {}", dir_entries));
+            ui.separator();
+
+            let bar_width = 2.0;
+            let height = 80.0;
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(bar_width * FRAME_HISTORY as f32, height), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+            let max = self.frame_times.iter().copied().fold(FRAME_BUDGET_MS, f32::max);
+            let budget_y = rect.bottom() - height * FRAME_BUDGET_MS / max;
+            painter.hline(rect.x_range(), budget_y, egui::Stroke::new(1.0, ui.visuals().weak_text_color()));
+            for (i, &milliseconds) in self.frame_times.iter().enumerate() {
+                let bar_height = height * milliseconds / max;
+                let x = rect.left() + i as f32 * bar_width;
+                let bar = egui::Rect::from_min_max(egui::pos2(x, rect.bottom() - bar_height), egui::pos2(x + bar_width, rect.bottom()));
+                let color = if milliseconds > FRAME_BUDGET_MS { ui.visuals().error_fg_color } else { ui.visuals().selection.bg_fill };
+                painter.rect_filled(bar, 0.0, color);
+            }
+            ui.weak(format!("Last {} frames; the line marks 16.7 ms (60 fps)", FRAME_HISTORY));
+        });
+        open
+    }
+}
+
+pub fn count_nodes(tree: &Tree) -> usize {
+    let mut cursor = tree.walk();
+    let mut count = 0;
+    loop {
+        count += 1;
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                return count;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
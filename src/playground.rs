@@ -0,0 +1,148 @@
+use eframe::egui;
+use std::ops::Range;
+use tree_sitter::{Node, Query, QueryCursor, Tree};
+
+pub const MATCH_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(120, 60, 200, 60);
+// Texto de cada hoja que se muestra junto a su tipo en el árbol.
+const LEAF_PREVIEW_CHARS: usize = 30;
+
+/// Panel de depuración de consultas de tree-sitter: la consulta, sus capturas y el árbol sintáctico.
+pub struct QueryPlayground {
+    pub query: String,
+    pub error: Option<String>,
+    pub matches: Vec<(Range<usize>, String)>,
+}
+
+impl QueryPlayground {
+    pub fn new() -> Self {
+        Self {
+            query: "(function_item name: (identifier) @name)".to_string(),
+            error: None,
+            matches: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self, tree: &Tree, source: &[u8]) {
+        self.matches.clear();
+        let query = match Query::new(tree.language(), &self.query) {
+            Ok(query) => query,
+            Err(e) => {
+                self.error = Some(format!("{:?} error at row {}, column {}", e.kind, e.row + 1, e.column + 1));
+                return;
+            }
+        };
+        self.error = None;
+        let mut cursor = QueryCursor::new();
+        for query_match in cursor.matches(&query, tree.root_node(), source) {
+            for capture in query_match.captures {
+                let name = query.capture_names()[capture.index as usize].clone();
+                self.matches.push((capture.node.byte_range(), name));
+            }
+        }
+    }
+
+    /// Devuelve `(ventana abierta, offset pulsado)`.
+    pub fn show(&mut self, ctx: &egui::Context, tree: Option<&Tree>, source: &[u8]) -> (bool, Option<usize>) {
+        let mut open = true;
+        let mut clicked = None;
+        egui::Window::new("Tree-sitter Query Playground").open(&mut open).default_width(420.0).show(ctx, |ui| {
+            let response = ui.add(
+                egui::TextEdit::multiline(&mut self.query)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+                    .font(egui::TextStyle::Monospace),
+            );
+            if response.changed() {
+                if let Some(tree) = tree {
+                    self.run(tree, source);
+                }
+            }
+            match &self.error {
+                Some(error) => {
+                    ui.colored_label(egui::Color32::from_rgb(220, 50, 50), error);
+                }
+                None => {
+                    ui.label(format!("{} captures", self.matches.len()));
+                }
+            }
+            egui::ScrollArea::vertical().id_source("playground_captures").max_height(120.0).show(ui, |ui| {
+                for (range, name) in &self.matches {
+                    let text = String::from_utf8_lossy(&source[range.clone()]);
+                    let preview: String = text.lines().next().unwrap_or("").chars().take(LEAF_PREVIEW_CHARS).collect();
+                    if ui.selectable_label(false, egui::RichText::new(format!("@{} {}", name, preview)).monospace()).clicked() {
+                        clicked = Some(range.start);
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.label("Parse tree:");
+            egui::ScrollArea::vertical().id_source("playground_tree").max_height(400.0).show(ui, |ui| match tree {
+                Some(tree) => {
+                    if let Some(offset) = render_sexp_tree(ui, &tree.root_node(), source) {
+                        clicked = Some(offset);
+                    }
+                }
+                None => {
+                    ui.label("No parse tree.");
+                }
+            });
+        });
+        (open, clicked)
+    }
+}
+
+/// Dibuja `node` y sus hijos como árbol plegable; los hijos solo se recorren al desplegar su padre.
+/// Devuelve el offset en bytes del nodo pulsado.
+pub fn render_sexp_tree(ui: &mut egui::Ui, node: &Node, source: &[u8]) -> Option<usize> {
+    let position = node.start_position();
+    let kind = if node.is_named() { node.kind().to_string() } else { format!("\"{}\"", node.kind()) };
+    let field = node_field_name(node);
+    let label = match field {
+        Some(field) => format!("{}: {} [{}:{}]", field, kind, position.row + 1, position.column + 1),
+        None => format!("{} [{}:{}]", kind, position.row + 1, position.column + 1),
+    };
+    let color = if node.is_error() || node.is_missing() { egui::Color32::from_rgb(220, 50, 50) } else { ui.visuals().text_color() };
+
+    let mut clicked = None;
+    if node.child_count() == 0 {
+        let text = String::from_utf8_lossy(&source[node.byte_range()]);
+        let preview: String = text.chars().take(LEAF_PREVIEW_CHARS).collect();
+        let text = egui::RichText::new(format!("{} {}", label, preview)).monospace().color(color);
+        if ui.selectable_label(false, text).clicked() {
+            clicked = Some(node.start_byte());
+        }
+        return clicked;
+    }
+
+    let id = ui.make_persistent_id(("sexp_node", node.id()));
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, node.parent().is_none())
+        .show_header(ui, |ui| {
+            if ui.selectable_label(false, egui::RichText::new(label).monospace().color(color)).clicked() {
+                clicked = Some(node.start_byte());
+            }
+        })
+        .body(|ui| {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                if let Some(offset) = render_sexp_tree(ui, &child, source) {
+                    clicked = Some(offset);
+                }
+            }
+        });
+    clicked
+}
+
+fn node_field_name(node: &Node) -> Option<&'static str> {
+    let parent = node.parent()?;
+    let mut cursor = parent.walk();
+    cursor.goto_first_child();
+    loop {
+        if cursor.node() == *node {
+            return cursor.field_name();
+        }
+        if !cursor.goto_next_sibling() {
+            return None;
+        }
+    }
+}
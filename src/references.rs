@@ -0,0 +1,129 @@
+use eframe::egui;
+use std::ops::Range;
+use tree_sitter::{Language, Node, Query, QueryCursor, Tree};
+
+use crate::rename;
+use crate::text::is_identifier_char;
+
+pub const READ_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(60, 110, 200, 50);
+pub const WRITE_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(200, 120, 40, 60);
+// Más tenue que los resultados de búsqueda: solo acompaña a la selección.
+pub const SELECTION_MATCH_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(120, 120, 120, 35);
+
+// Sin límite, seleccionar un carácter frecuente en un archivo grande dibujaría miles de rectángulos.
+const MAX_SELECTION_MATCHES: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SelectionHighlight {
+    Matches,
+    WholeWords,
+    Disabled,
+}
+
+impl SelectionHighlight {
+    pub const ALL: [SelectionHighlight; 3] = [SelectionHighlight::Matches, SelectionHighlight::WholeWords, SelectionHighlight::Disabled];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SelectionHighlight::Matches => "Highlight selection matches",
+            SelectionHighlight::WholeWords => "Only when selecting whole words",
+            SelectionHighlight::Disabled => "Disabled",
+        }
+    }
+}
+
+pub struct LocalReference {
+    pub range: Range<usize>,
+    pub write: bool,
+}
+
+/// Otras apariciones exactas (byte a byte) del texto seleccionado, sin contar la propia selección.
+/// En modo `WholeWords` solo se buscan si la selección es una palabra completa, y solo como palabra completa.
+pub fn selection_matches(content: &str, selection: Range<usize>, mode: SelectionHighlight) -> Vec<Range<usize>> {
+    let Some(selected) = content.get(selection.clone()).filter(|selected| !selected.trim().is_empty()) else {
+        return Vec::new();
+    };
+    let matches = match mode {
+        SelectionHighlight::Disabled => return Vec::new(),
+        SelectionHighlight::Matches => content.match_indices(selected).map(|(i, _)| i..i + selected.len()).collect(),
+        SelectionHighlight::WholeWords => {
+            let whole_word = selected.chars().all(is_identifier_char)
+                && !content[..selection.start].ends_with(is_identifier_char)
+                && !content[selection.end..].starts_with(is_identifier_char);
+            if !whole_word {
+                return Vec::new();
+            }
+            rename::find_word(content, selected)
+        }
+    };
+    let mut matches: Vec<Range<usize>> = matches.into_iter().filter(|range| *range != selection).collect();
+    matches.truncate(MAX_SELECTION_MATCHES);
+    matches
+}
+
+pub fn identifier_query(language: Language) -> Query {
+    Query::new(language, "(identifier) @var").expect("Error compiling identifier query")
+}
+
+/// Apariciones del identificador bajo el cursor dentro de la `function_item` que lo contiene.
+/// Fuera de una función no se resalta nada, para no marcar nombres de ítems de todo el archivo.
+pub fn local_references(query: &Query, tree: &Tree, source: &[u8], cursor: usize) -> Vec<LocalReference> {
+    let Some(identifier) = identifier_at(tree, cursor) else {
+        return Vec::new();
+    };
+    let Some(function) = enclosing_function(identifier) else {
+        return Vec::new();
+    };
+    let name = &source[identifier.byte_range()];
+
+    let mut query_cursor = QueryCursor::new();
+    query_cursor.set_byte_range(function.byte_range());
+    query_cursor
+        .captures(query, function, source)
+        .filter_map(|(m, index)| m.captures.get(index).map(|capture| capture.node))
+        .filter(|node| &source[node.byte_range()] == name)
+        .map(|node| LocalReference {
+            range: node.byte_range(),
+            write: is_write(node),
+        })
+        .collect()
+}
+
+// El cursor puede estar justo después del identificador (por ejemplo, al terminar de escribirlo).
+fn identifier_at(tree: &Tree, cursor: usize) -> Option<Node<'_>> {
+    let root = tree.root_node();
+    [Some(cursor), cursor.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .filter_map(|byte| root.descendant_for_byte_range(byte, byte))
+        .find(|node| node.kind() == "identifier")
+}
+
+fn enclosing_function(node: Node) -> Option<Node> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == "function_item" {
+            return Some(parent);
+        }
+        current = parent.parent();
+    }
+    None
+}
+
+// Escritura: lado izquierdo de una asignación o nombre ligado por un `let`, un parámetro o un patrón de `for`.
+fn is_write(node: Node) -> bool {
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        match parent.kind() {
+            "assignment_expression" | "compound_assignment_expr" => {
+                return parent.child_by_field_name("left") == Some(child);
+            }
+            "let_declaration" | "parameter" | "for_expression" | "closure_parameters" => {
+                return parent.child_by_field_name("pattern") == Some(child) || parent.kind() == "closure_parameters";
+            }
+            kind if kind.ends_with("_pattern") => child = parent,
+            _ => return false,
+        }
+    }
+    false
+}
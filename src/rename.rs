@@ -0,0 +1,260 @@
+use eframe::egui;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::accessibility::StatusPalette;
+use crate::exclusions::Exclusions;
+use crate::text::is_identifier_char;
+
+pub const LIVE_OCCURRENCE_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(80, 160, 90, 60);
+
+// Directorios que no se recorren al buscar ocurrencias en el proyecto.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+pub struct RenameOccurrence {
+    pub path: PathBuf,
+    pub range: Range<usize>,
+    pub line: usize,
+    pub context: String,
+    pub checked: bool,
+}
+
+pub struct RenamePreview {
+    pub old_name: String,
+    pub new_name: String,
+    pub occurrences: Vec<RenameOccurrence>,
+}
+
+pub enum RenamePreviewAction {
+    Apply,
+    Cancel,
+}
+
+impl RenamePreview {
+    /// Busca `old_name` como palabra completa en el archivo abierto y en todos los archivos de texto bajo `root`.
+    pub fn compute(
+        old_name: &str,
+        new_name: &str,
+        root: Option<&Path>,
+        exclusions: &Exclusions,
+        open_file: Option<&Path>,
+        open_content: &str,
+    ) -> Self {
+        let mut occurrences = Vec::new();
+        let mut files = Vec::new();
+        if let Some(root) = root {
+            collect_files(root, exclusions, &mut files);
+        }
+        if let Some(open) = open_file {
+            if !files.iter().any(|path| path == open) {
+                files.push(open.to_path_buf());
+            }
+        }
+        files.sort();
+
+        for path in files {
+            let content = if Some(path.as_path()) == open_file {
+                open_content.to_string()
+            } else {
+                match fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                }
+            };
+            for range in find_word(&content, old_name) {
+                let line_start = content[..range.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+                let line_end = content[range.end..].find('\n').map(|i| range.end + i).unwrap_or(content.len());
+                occurrences.push(RenameOccurrence {
+                    path: path.clone(),
+                    line: content[..range.start].matches('\n').count() + 1,
+                    context: content[line_start..line_end].to_string(),
+                    range,
+                    checked: true,
+                });
+            }
+        }
+
+        Self {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+            occurrences,
+        }
+    }
+
+    /// Aplica las ocurrencias marcadas sobre `content`, de atrás hacia delante para no invalidar los rangos.
+    pub fn apply_to(&self, path: &Path, content: &mut String) -> usize {
+        let mut ranges: Vec<&Range<usize>> = self
+            .occurrences
+            .iter()
+            .filter(|occurrence| occurrence.checked && occurrence.path == path)
+            .map(|occurrence| &occurrence.range)
+            .collect();
+        ranges.sort_by_key(|range| std::cmp::Reverse(range.start));
+
+        let mut applied = 0;
+        for range in ranges {
+            if content.get(range.clone()) == Some(self.old_name.as_str()) {
+                content.replace_range(range.clone(), &self.new_name);
+                applied += 1;
+            }
+        }
+        applied
+    }
+
+    pub fn checked_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .occurrences
+            .iter()
+            .filter(|occurrence| occurrence.checked)
+            .map(|occurrence| occurrence.path.clone())
+            .collect();
+        files.dedup();
+        files
+    }
+}
+
+/// Renombrado en línea (F2): cada tecla reescribe a la vez todas las apariciones de la palabra en el archivo.
+pub struct LiveRename {
+    pub original: String,
+    pub name: String,
+    // Contenido al empezar; el texto actual siempre se calcula a partir de él.
+    pub before: String,
+    pub ranges: Vec<Range<usize>>,
+    // Aparición bajo el cursor, donde se dibuja el cuadro de texto.
+    pub primary: usize,
+}
+
+impl LiveRename {
+    pub fn start(content: &str, cursor: usize) -> Option<Self> {
+        let word = crate::text::word_at(content, cursor)?;
+        let original = content[word.clone()].to_string();
+        let ranges = find_word(content, &original);
+        let primary = ranges.iter().position(|range| *range == word)?;
+        Some(Self {
+            name: original.clone(),
+            original,
+            before: content.to_string(),
+            ranges,
+            primary,
+        })
+    }
+
+    /// Contenido con `name` en lugar de cada aparición y los rangos resultantes.
+    pub fn apply(&self) -> (String, Vec<Range<usize>>) {
+        let mut content = String::with_capacity(self.before.len());
+        let mut ranges = Vec::with_capacity(self.ranges.len());
+        let mut last = 0;
+        for range in &self.ranges {
+            content.push_str(&self.before[last..range.start]);
+            ranges.push(content.len()..content.len() + self.name.len());
+            content.push_str(&self.name);
+            last = range.end;
+        }
+        content.push_str(&self.before[last..]);
+        (content, ranges)
+    }
+}
+
+pub fn find_word(content: &str, word: &str) -> Vec<Range<usize>> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+    content
+        .match_indices(word)
+        .map(|(start, _)| start..start + word.len())
+        .filter(|range| {
+            let before = content[..range.start].chars().next_back();
+            let after = content[range.end..].chars().next();
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+        })
+        .collect()
+}
+
+/// Archivos bajo `dir`, sin entrar en directorios ocultos, en `SKIPPED_DIRS` ni en las rutas excluidas del proyecto.
+pub fn collect_files(dir: &Path, exclusions: &Exclusions, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || exclusions.is_excluded(&path) {
+            continue;
+        }
+        if path.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_files(&path, exclusions, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Ventana con el diff propuesto agrupado por archivo; cada ocurrencia puede desmarcarse antes de aplicar.
+pub fn show_rename_preview(
+    ctx: &egui::Context,
+    preview: &mut RenamePreview,
+    palette: &StatusPalette,
+) -> Option<RenamePreviewAction> {
+    let mut action = None;
+    let title = format!("Rename '{}' to '{}'", preview.old_name, preview.new_name);
+
+    egui::Window::new(title).collapsible(false).show(ctx, |ui| {
+        let checked = preview.occurrences.iter().filter(|o| o.checked).count();
+        ui.label(format!("{} of {} occurrences selected", checked, preview.occurrences.len()));
+        ui.separator();
+
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            let mut index = 0;
+            while index < preview.occurrences.len() {
+                let path = preview.occurrences[index].path.clone();
+                let end = preview.occurrences[index..]
+                    .iter()
+                    .position(|o| o.path != path)
+                    .map_or(preview.occurrences.len(), |n| index + n);
+
+                egui::CollapsingHeader::new(path.to_string_lossy())
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for occurrence in &mut preview.occurrences[index..end] {
+                            ui.checkbox(&mut occurrence.checked, format!("line {}", occurrence.line));
+                            let replaced = replace_words(&occurrence.context, &preview.old_name, &preview.new_name);
+                            ui.label(
+                                egui::RichText::new(format!("- {}", occurrence.context.trim()))
+                                    .monospace()
+                                    .color(palette.removed),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!("+ {}", replaced.trim()))
+                                    .monospace()
+                                    .color(palette.added),
+                            );
+                        }
+                    });
+                index = end;
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(checked > 0, egui::Button::new("Apply")).clicked() {
+                action = Some(RenamePreviewAction::Apply);
+            }
+            if ui.button("Cancel").clicked() {
+                action = Some(RenamePreviewAction::Cancel);
+            }
+        });
+    });
+
+    action
+}
+
+fn replace_words(line: &str, old_name: &str, new_name: &str) -> String {
+    let mut result = line.to_string();
+    for range in find_word(line, old_name).into_iter().rev() {
+        result.replace_range(range, new_name);
+    }
+    result
+}
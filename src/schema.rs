@@ -0,0 +1,308 @@
+// Detección y validación de esquemas JSON (y detección para YAML). No hay cliente HTTP ni crate de JSON Schema entre las dependencias,
+// así que los esquemas remotos se leen de una caché local y se valida un subconjunto de las palabras clave.
+
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::json::{self, Value};
+
+pub const ASSOCIATIONS_FILE: &str = "schema_associations.toml";
+// Subdirectorio de `config_dir` donde se buscan las copias de los esquemas `http(s)://`.
+const CACHE_DIR: &str = "schemas";
+// Evita bucles infinitos con `$ref` recursivos.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Esquema cargado para los archivos de `base_dir` que lo referencian como `reference`, o el motivo por el que no
+/// se pudo cargar.
+pub struct LoadedSchema {
+    pub base_dir: Option<PathBuf>,
+    pub reference: String,
+    pub schema: Result<Value, String>,
+}
+
+/// Error de validación en el valor de `path` (índices desde la raíz, como en `json::value_range`).
+pub struct SchemaError {
+    pub path: Vec<usize>,
+    pub message: String,
+}
+
+/// Esquema del documento: el campo `"$schema"` del objeto raíz o, si no lo tiene, la primera asociación de
+/// `schema_associations.toml` (`"patrón glob" = "url"`) que coincide con `file`.
+pub fn schema_reference(document: &Value, file: Option<&Path>) -> Result<Option<String>, String> {
+    if let Some(Value::String(reference)) = field(document, "$schema") {
+        // Los metaesquemas de json-schema.org solo declaran el dialecto, no describen el documento.
+        if !reference.contains("json-schema.org") {
+            return Ok(Some(reference.clone()));
+        }
+    }
+    file.map_or(Ok(None), associated_schema)
+}
+
+/// Primera asociación de `schema_associations.toml` cuyo patrón coincide con `file`. Que no haya archivo no es un
+/// error; que no se pueda leer, sí.
+pub fn associated_schema(file: &Path) -> Result<Option<String>, String> {
+    let Some(content) = config::config_dir().and_then(|dir| fs::read_to_string(dir.join(ASSOCIATIONS_FILE)).ok()) else {
+        return Ok(None);
+    };
+    let table = content.parse::<toml::Table>().map_err(|e| format!("Unable to read schema associations: {}", e))?;
+    let Some(name) = file.file_name().map(|name| name.to_string_lossy()) else {
+        return Ok(None);
+    };
+    let full = file.to_string_lossy().replace('\\', "/");
+    Ok(table.iter().find_map(|(pattern, url)| {
+        // Los patrones sin `/` se comparan solo con el nombre del archivo.
+        let target = if pattern.contains('/') { full.as_str() } else { &name };
+        (config::glob_matches(pattern, target)).then(|| url.as_str().map(str::to_string)).flatten()
+    }))
+}
+
+/// Lee el esquema de `reference`: una ruta (relativa a `base_dir`), una URL `file://` o una URL `http(s)://`,
+/// que se busca en la caché con el nombre que da `cache_path`.
+pub fn load_schema(reference: &str, base_dir: Option<&Path>) -> Result<Value, String> {
+    let path = if reference.starts_with("http://") || reference.starts_with("https://") {
+        let path = cache_path(reference).ok_or("No configuration directory for the schema cache")?;
+        if !path.is_file() {
+            return Err(format!("Schemas are not downloaded automatically; save {} as {}", reference, path.display()));
+        }
+        path
+    } else {
+        let local = PathBuf::from(reference.strip_prefix("file://").unwrap_or(reference));
+        match base_dir {
+            Some(dir) if local.is_relative() => dir.join(local),
+            _ => local,
+        }
+    };
+    let content = fs::read_to_string(&path).map_err(|e| format!("Unable to read schema {}: {}", path.display(), e))?;
+    json::parse(&content).map_err(|e| format!("Invalid schema {}: {}", path.display(), e))
+}
+
+/// Archivo de la caché para una URL: la URL sin el esquema y con los caracteres no alfanuméricos como `_`.
+pub fn cache_path(url: &str) -> Option<PathBuf> {
+    let name: String = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    config::config_dir().map(|dir| dir.join(CACHE_DIR).join(name))
+}
+
+/// Valida `document` con `schema`. Se admiten `$ref` locales (`#/...`), `type`, `enum`, `const`, `required`,
+/// `properties`, `additionalProperties`, `items`, `minItems`/`maxItems`, `minimum`/`maximum` (y sus variantes
+/// exclusivas), `minLength`/`maxLength`, `pattern`, `allOf`, `anyOf` y `oneOf`; el resto de palabras clave se ignora.
+pub fn validate(schema: &Value, document: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    let mut path = Vec::new();
+    check(schema, schema, document, &mut path, 0, &mut errors);
+    errors
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &mut Vec<usize>, depth: usize, errors: &mut Vec<SchemaError>) {
+    let schema = match schema {
+        Value::Bool(false) => return push(errors, path, "no value is allowed here".to_string()),
+        Value::Object(_) => schema,
+        _ => return,
+    };
+    if let Some(Value::String(reference)) = field(schema, "$ref") {
+        match resolve_ref(root, reference) {
+            Some(target) if depth < MAX_REF_DEPTH => check(root, target, value, path, depth + 1, errors),
+            Some(_) => {}
+            None => push(errors, path, format!("unresolved $ref `{}`", reference)),
+        }
+        return;
+    }
+
+    if let Some(expected) = field(schema, "type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return push(errors, path, format!("expected {}, found {}", types.join(" or "), type_name(value)));
+        }
+    }
+    if let Some(Value::Array(options)) = field(schema, "enum") {
+        if !options.iter().any(|option| values_equal(option, value)) {
+            let allowed: Vec<String> = options.iter().map(Value::to_compact_string).collect();
+            push(errors, path, format!("value must be one of {}", allowed.join(", ")));
+        }
+    }
+    if let Some(expected) = field(schema, "const") {
+        if !values_equal(expected, value) {
+            push(errors, path, format!("value must be {}", expected.to_compact_string()));
+        }
+    }
+
+    match value {
+        Value::Object(entries) => {
+            if let Some(Value::Array(required)) = field(schema, "required") {
+                for name in required.iter().filter_map(as_str) {
+                    if !entries.iter().any(|(key, _)| key == name) {
+                        push(errors, path, format!("missing required property `{}`", name));
+                    }
+                }
+            }
+            let properties = match field(schema, "properties") {
+                Some(Value::Object(properties)) => properties.as_slice(),
+                _ => &[],
+            };
+            for (index, (key, item)) in entries.iter().enumerate() {
+                path.push(index);
+                match properties.iter().find(|(name, _)| name == key) {
+                    Some((_, property)) => check(root, property, item, path, depth, errors),
+                    None => match field(schema, "additionalProperties") {
+                        Some(Value::Bool(false)) => push(errors, path, format!("unexpected property `{}`", key)),
+                        Some(additional) => check(root, additional, item, path, depth, errors),
+                        None => {}
+                    },
+                }
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            check_length(schema, "minItems", "maxItems", items.len(), "items", path, errors);
+            if let Some(item_schema) = field(schema, "items") {
+                for (index, item) in items.iter().enumerate() {
+                    path.push(index);
+                    check(root, item_schema, item, path, depth, errors);
+                    path.pop();
+                }
+            }
+        }
+        Value::String(text) => {
+            check_length(schema, "minLength", "maxLength", text.chars().count(), "characters", path, errors);
+            if let Some(Value::String(pattern)) = field(schema, "pattern") {
+                if Regex::new(pattern).is_ok_and(|regex| !regex.is_match(text)) {
+                    push(errors, path, format!("value does not match `{}`", pattern));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number: f64 = number.parse().unwrap_or(0.0);
+            for (keyword, description) in [
+                ("minimum", "at least"),
+                ("maximum", "at most"),
+                ("exclusiveMinimum", "greater than"),
+                ("exclusiveMaximum", "less than"),
+            ] {
+                let Some(bound) = field(schema, keyword).and_then(as_number) else {
+                    continue;
+                };
+                let holds = match keyword {
+                    "minimum" => number >= bound,
+                    "maximum" => number <= bound,
+                    "exclusiveMinimum" => number > bound,
+                    _ => number < bound,
+                };
+                if !holds {
+                    push(errors, path, format!("value must be {} {}", description, bound));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(schemas)) = field(schema, "allOf") {
+        for sub in schemas {
+            check(root, sub, value, path, depth, errors);
+        }
+    }
+    for (keyword, accept) in [("anyOf", (|n| n >= 1) as fn(usize) -> bool), ("oneOf", |n| n == 1)] {
+        if let Some(Value::Array(schemas)) = field(schema, keyword) {
+            let matching = schemas.iter().filter(|sub| {
+                let mut sub_errors = Vec::new();
+                check(root, sub, value, &mut path.clone(), depth, &mut sub_errors);
+                sub_errors.is_empty()
+            });
+            if !accept(matching.count()) {
+                let count = if keyword == "oneOf" { "exactly one" } else { "at least one" };
+                push(errors, path, format!("value must match {} of the {} schemas", count, keyword));
+            }
+        }
+    }
+}
+
+fn push(errors: &mut Vec<SchemaError>, path: &[usize], message: String) {
+    errors.push(SchemaError { path: path.to_vec(), message });
+}
+
+fn check_length(schema: &Value, min: &str, max: &str, length: usize, unit: &str, path: &[usize], errors: &mut Vec<SchemaError>) {
+    if let Some(bound) = field(schema, min).and_then(as_number) {
+        if (length as f64) < bound {
+            push(errors, path, format!("expected at least {} {}", bound, unit));
+        }
+    }
+    if let Some(bound) = field(schema, max).and_then(as_number) {
+        if (length as f64) > bound {
+            push(errors, path, format!("expected at most {} {}", bound, unit));
+        }
+    }
+}
+
+// Solo punteros JSON dentro del mismo esquema (`#/definitions/x`, `#/$defs/x`, `#`).
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    pointer.split('/').skip(1).try_fold(root, |node, segment| {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        match node {
+            Value::Object(_) => field(node, &segment),
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        }
+    })
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+        _ => None,
+    }
+}
+
+fn as_str(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(text) => Some(text),
+        _ => None,
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (value, name) {
+        (Value::Number(number), "integer") => number.parse::<f64>().is_ok_and(|n| n.fract() == 0.0),
+        (Value::Number(_), "number") => true,
+        _ => type_name(value) == name,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+// Los números se guardan con su texto original, así que `1` y `1.0` se comparan por valor.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.parse::<f64>().ok() == y.parse::<f64>().ok(),
+        (Value::Array(xs), Value::Array(ys)) => xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| values_equal(x, y)),
+        (Value::Object(xs), Value::Object(ys)) => {
+            xs.len() == ys.len() && xs.iter().all(|(key, x)| ys.iter().any(|(other, y)| other == key && values_equal(x, y)))
+        }
+        _ => a == b,
+    }
+}
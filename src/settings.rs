@@ -0,0 +1,379 @@
+use eframe::egui;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::accessibility::{self, ColorblindMode};
+use crate::config;
+use crate::i18n;
+use crate::indent_guides::IndentGuideStyle;
+use crate::line_length::{self, LineLengthMode};
+use crate::references::SelectionHighlight;
+use crate::status_bar::{self, StatusBarItem};
+use crate::themes::{self, Theme};
+
+// Valores mínimos del modo de alto contraste.
+const HIGH_CONTRAST_FONT_SIZE: f32 = 16.0;
+const HIGH_CONTRAST_LINE_SPACING: f32 = 2.0;
+
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+// Límites del panel lateral; por debajo de `SIDEBAR_ICON_ONLY_WIDTH` solo se muestran los iconos.
+pub const SIDEBAR_MIN_WIDTH: f32 = 120.0;
+pub const SIDEBAR_MAX_WIDTH: f32 = 500.0;
+pub const SIDEBAR_ICON_ONLY_WIDTH: f32 = 150.0;
+const DEFAULT_SIDEBAR_WIDTH: f32 = 200.0;
+
+pub const DEFAULT_UNDO_DEPTH: usize = 200;
+const DEFAULT_MAX_FILE_SIZE_BYTES: usize = 50 * 1024 * 1024;
+// Límites en segundos del intervalo de guardado automático.
+pub const AUTO_SAVE_MIN_SECS: u64 = 5;
+pub const AUTO_SAVE_MAX_SECS: u64 = 300;
+const DEFAULT_AUTO_SAVE_SECS: u64 = 30;
+// Archivos abiertos que se recuerdan para "Search Recent Files".
+pub const MAX_RECENT_FILES: usize = 200;
+const DEFAULT_RECENT_FILES: usize = 20;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ThemeName {
+    Light,
+    Dark,
+    SolarizedLight,
+    SolarizedDark,
+    Monokai,
+    HighContrast,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 6] = [
+        ThemeName::Light,
+        ThemeName::Dark,
+        ThemeName::SolarizedLight,
+        ThemeName::SolarizedDark,
+        ThemeName::Monokai,
+        ThemeName::HighContrast,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeName::Light => "Light",
+            ThemeName::Dark => "Dark",
+            ThemeName::SolarizedLight => "Solarized Light",
+            ThemeName::SolarizedDark => "Solarized Dark",
+            ThemeName::Monokai => "Monokai",
+            ThemeName::HighContrast => "High Contrast",
+        }
+    }
+
+    pub fn is_dark(self) -> bool {
+        !matches!(self, ThemeName::Light | ThemeName::SolarizedLight)
+    }
+
+    // (fondo del editor, texto, fondo de paneles y ventanas)
+    fn colors(self) -> (egui::Color32, egui::Color32, egui::Color32) {
+        match self {
+            ThemeName::Light => (egui::Color32::WHITE, egui::Color32::BLACK, egui::Color32::from_gray(248)),
+            ThemeName::Dark => (egui::Color32::from_gray(30), egui::Color32::from_gray(220), egui::Color32::from_gray(27)),
+            ThemeName::SolarizedLight => (
+                egui::Color32::from_rgb(253, 246, 227),
+                egui::Color32::from_rgb(101, 123, 131),
+                egui::Color32::from_rgb(238, 232, 213),
+            ),
+            ThemeName::SolarizedDark => (
+                egui::Color32::from_rgb(0, 43, 54),
+                egui::Color32::from_rgb(131, 148, 150),
+                egui::Color32::from_rgb(7, 54, 66),
+            ),
+            ThemeName::Monokai => (
+                egui::Color32::from_rgb(39, 40, 34),
+                egui::Color32::from_rgb(248, 248, 242),
+                egui::Color32::from_rgb(30, 31, 28),
+            ),
+            ThemeName::HighContrast => (egui::Color32::BLACK, egui::Color32::WHITE, egui::Color32::BLACK),
+        }
+    }
+}
+
+pub struct Settings {
+    pub font_size: f32,
+    pub background_color: egui::Color32,
+    pub text_color: egui::Color32,
+    // Colores del resaltado de sintaxis por captura; los fija el tema.
+    pub syntax_colors: HashMap<String, egui::Color32>,
+    pub colorblind_mode: ColorblindMode,
+    pub theme: ThemeName,
+    // Temas entre los que alterna Ctrl+Shift+T.
+    pub light_theme: ThemeName,
+    pub dark_theme: ThemeName,
+    pub font_family: egui::FontFamily,
+    pub line_spacing: f32,
+    // Parpadeos por segundo del cursor; 0 lo deja fijo.
+    pub cursor_blink_rate: f32,
+    pub format_on_type: bool,
+    pub sort_imports_on_save: bool,
+    pub inline_diagnostics: bool,
+    pub track_changes: bool,
+    pub show_line_numbers: bool,
+    pub show_annotations: bool,
+    pub show_indent_guides: bool,
+    // Cada nivel de paréntesis en su color, con el número de nivel en los muy anidados.
+    pub rainbow_brackets: bool,
+    // La guía del bloque donde está el cursor se pinta con `active_indent_guide_color`.
+    pub indent_guide_style: IndentGuideStyle,
+    pub indent_guide_color: egui::Color32,
+    pub active_indent_guide_color: egui::Color32,
+    // Aumento de la lupa que aparece al mantener pulsado sobre el minimapa.
+    pub show_minimap: bool,
+    pub minimap_zoom: f32,
+    // Pliega los bloques de `use` de nivel superior al abrir o editar un archivo Rust.
+    pub collapse_imports: bool,
+    pub max_line_length: Option<usize>,
+    pub line_length_mode: LineLengthMode,
+    pub selection_highlight: SelectionHighlight,
+    // Indicadores visibles de la barra de estado, de izquierda a derecha.
+    pub status_bar_items: Vec<StatusBarItem>,
+    // Consultas de tree-sitter para el esquema, una por línea.
+    pub outline_queries: String,
+    pub sidebar_width: f32,
+    // Expresiones regulares de las líneas que abren y cierran una región plegable; vacías, no hay regiones.
+    pub fold_region_start: String,
+    pub fold_region_end: String,
+    // Instantáneas que guarda Ctrl+Z; las más antiguas se descartan.
+    pub undo_depth: usize,
+    // Por encima de este tamaño se pregunta antes de abrir un archivo.
+    pub max_file_size_bytes: usize,
+    // Los cambios se escriben como mucho una vez por intervalo, no con cada pulsación.
+    pub auto_save: bool,
+    pub auto_save_interval: Duration,
+    // Pistas de tipos y parámetros de rust-analyzer, con letra más pequeña que la del editor.
+    pub inlay_hints_enabled: bool,
+    pub inlay_hint_font_size_factor: f32,
+    // `*`, `**`, `__` y `~~` se cierran solos en los archivos Markdown.
+    pub markdown_emphasis_auto_pair: bool,
+    // JSON válido pegado en un archivo JSON se formatea con la sangría del archivo.
+    pub smart_paste_json: bool,
+    // Valor de `{license}` en las plantillas de archivo nuevo, como `MIT`; vacío lo deja sin sustituir.
+    pub license: String,
+    // Etiqueta BCP 47 del idioma de la interfaz (`en`, `es`, `es-MX`…).
+    pub locale: String,
+    // Últimos archivos abiertos, el más reciente primero; como mucho `recent_files_limit`.
+    pub recent_files: Vec<PathBuf>,
+    pub recent_files_limit: usize,
+    // Volumen del sonido de fondo, de 0 a 1.
+    #[cfg(feature = "ambient-sound")]
+    pub ambient_volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font_size: 14.0,
+            background_color: egui::Color32::from_rgb(255, 255, 255),
+            text_color: egui::Color32::from_rgb(0, 0, 0),
+            syntax_colors: themes::syntax_colors(ThemeName::Light),
+            colorblind_mode: ColorblindMode::None,
+            theme: ThemeName::Light,
+            light_theme: ThemeName::Light,
+            dark_theme: ThemeName::Dark,
+            font_family: egui::FontFamily::Monospace,
+            line_spacing: 1.5,
+            cursor_blink_rate: 0.0,
+            format_on_type: false,
+            sort_imports_on_save: false,
+            inline_diagnostics: true,
+            track_changes: false,
+            show_line_numbers: true,
+            show_annotations: true,
+            show_indent_guides: true,
+            rainbow_brackets: false,
+            indent_guide_style: IndentGuideStyle::Solid,
+            // Gris semitransparente: se ve tanto en los temas claros como en los oscuros.
+            indent_guide_color: egui::Color32::from_rgba_unmultiplied(128, 128, 128, 60),
+            active_indent_guide_color: egui::Color32::from_rgba_unmultiplied(128, 128, 128, 170),
+            show_minimap: false,
+            minimap_zoom: 3.0,
+            collapse_imports: false,
+            max_line_length: Some(line_length::DEFAULT_MAX_LINE_LENGTH),
+            line_length_mode: LineLengthMode::WarnOnly,
+            selection_highlight: SelectionHighlight::Matches,
+            status_bar_items: status_bar::default_items(),
+            outline_queries: String::new(),
+            sidebar_width: DEFAULT_SIDEBAR_WIDTH,
+            fold_region_start: "// region:".to_string(),
+            fold_region_end: "// endregion".to_string(),
+            undo_depth: DEFAULT_UNDO_DEPTH,
+            max_file_size_bytes: DEFAULT_MAX_FILE_SIZE_BYTES,
+            auto_save: true,
+            auto_save_interval: Duration::from_secs(DEFAULT_AUTO_SAVE_SECS),
+            inlay_hints_enabled: true,
+            inlay_hint_font_size_factor: 0.85,
+            markdown_emphasis_auto_pair: true,
+            smart_paste_json: true,
+            license: String::new(),
+            locale: i18n::DEFAULT_LOCALE.to_string(),
+            recent_files: Vec::new(),
+            recent_files_limit: DEFAULT_RECENT_FILES,
+            #[cfg(feature = "ambient-sound")]
+            ambient_volume: 0.5,
+        }
+    }
+}
+
+impl Settings {
+    /// Patrones de región compilados; `None` si falta alguno o no es una expresión válida.
+    pub fn fold_region_patterns(&self) -> Option<(Regex, Regex)> {
+        if self.fold_region_start.is_empty() || self.fold_region_end.is_empty() {
+            return None;
+        }
+        Some((Regex::new(&self.fold_region_start).ok()?, Regex::new(&self.fold_region_end).ok()?))
+    }
+
+    /// Pone `path` el primero de los archivos recientes.
+    pub fn add_recent_file(&mut self, path: &Path) {
+        self.recent_files.retain(|recent| recent != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(self.recent_files_limit);
+    }
+
+    /// Ajustes por defecto con lo guardado en `settings.toml` encima. De momento solo se guardan el ancho del panel
+    /// lateral, el idioma y los archivos recientes. Los errores al leer el archivo se añaden a `errors`.
+    pub fn load(errors: &mut Vec<String>) -> Self {
+        let mut settings = Self::default();
+        // Que no exista el archivo es lo normal la primera vez.
+        let Some(content) = settings_path().and_then(|path| fs::read_to_string(path).ok()) else {
+            return settings;
+        };
+        match content.parse::<toml::Table>() {
+            Ok(table) => {
+                if let Some(width) = table.get("sidebar_width").and_then(toml::Value::as_float) {
+                    settings.sidebar_width = (width as f32).clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH);
+                }
+                if let Some(locale) = table.get("locale").and_then(toml::Value::as_str) {
+                    settings.locale = locale.to_string();
+                }
+                if let Some(limit) = table.get("recent_files_limit").and_then(toml::Value::as_integer) {
+                    settings.recent_files_limit = (limit.max(1) as usize).min(MAX_RECENT_FILES);
+                }
+                if let Some(toml::Value::Array(paths)) = table.get("recent_files") {
+                    settings.recent_files = paths.iter().filter_map(toml::Value::as_str).map(PathBuf::from).collect();
+                    settings.recent_files.truncate(settings.recent_files_limit);
+                }
+            }
+            Err(e) => errors.push(format!("Unable to read settings: {}", e)),
+        }
+        settings
+    }
+
+    /// Escribe los ajustes persistentes conservando las demás claves que ya tuviera el archivo.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = settings_path() else {
+            return Ok(());
+        };
+        let mut table = fs::read_to_string(&path).ok().and_then(|content| content.parse::<toml::Table>().ok()).unwrap_or_default();
+        table.insert("sidebar_width".to_string(), toml::Value::Float(f64::from(self.sidebar_width.round())));
+        table.insert("locale".to_string(), toml::Value::String(self.locale.clone()));
+        table.insert("recent_files_limit".to_string(), toml::Value::Integer(self.recent_files_limit as i64));
+        let recent = self.recent_files.iter().map(|path| toml::Value::String(path.to_string_lossy().into_owned())).collect();
+        table.insert("recent_files".to_string(), toml::Value::Array(recent));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string_pretty(&table).map_err(io::Error::other)?)
+    }
+
+    pub fn is_high_contrast(&self) -> bool {
+        self.theme == ThemeName::HighContrast
+    }
+
+    pub fn set_theme(&mut self, theme: ThemeName) {
+        if theme == ThemeName::HighContrast {
+            apply_high_contrast(self);
+            return;
+        }
+        let (background, text, _) = theme.colors();
+        self.theme = theme;
+        self.background_color = background;
+        self.text_color = text;
+        self.syntax_colors = themes::syntax_colors(theme);
+    }
+
+    /// Aplica todos los ajustes de apariencia de `theme`. A diferencia de `set_theme`, también cambia la letra.
+    pub fn apply_theme(&mut self, theme: &Theme) {
+        self.font_size = theme.font_size;
+        self.line_spacing = theme.line_spacing;
+        self.set_theme(theme.base);
+        self.background_color = theme.background_color;
+        self.text_color = theme.text_color;
+        self.syntax_colors = theme.syntax_colors.clone();
+    }
+
+    /// Pasa al tema claro o al oscuro configurado, el contrario del actual; devuelve si el nuevo es oscuro.
+    pub fn toggle_dark_mode(&mut self) -> bool {
+        let target = if self.theme.is_dark() { self.light_theme } else { self.dark_theme };
+        self.set_theme(target);
+        self.theme.is_dark()
+    }
+
+    /// Estilo de egui para estos ajustes: el tema primero y luego la simulación de daltonismo.
+    pub fn visuals(&self) -> egui::Visuals {
+        let mut visuals = if self.theme.is_dark() { egui::Visuals::dark() } else { egui::Visuals::light() };
+        if self.is_high_contrast() {
+            high_contrast_visuals(&mut visuals);
+        } else {
+            let (_, _, panel) = self.theme.colors();
+            visuals.panel_fill = panel;
+            visuals.window_fill = panel;
+            visuals.extreme_bg_color = self.background_color;
+        }
+        accessibility::apply_to_visuals(&mut visuals, self.colorblind_mode);
+        visuals
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
+
+/// Fondo negro, texto blanco, letra de al menos 16 px, interlineado 2.0 y cursor fijo.
+pub fn apply_high_contrast(settings: &mut Settings) {
+    settings.theme = ThemeName::HighContrast;
+    settings.background_color = egui::Color32::BLACK;
+    settings.text_color = egui::Color32::WHITE;
+    settings.syntax_colors = themes::syntax_colors(ThemeName::HighContrast);
+    settings.font_size = settings.font_size.max(HIGH_CONTRAST_FONT_SIZE);
+    settings.line_spacing = settings.line_spacing.max(HIGH_CONTRAST_LINE_SPACING);
+    settings.cursor_blink_rate = 0.0;
+}
+
+// Todos los widgets en blanco sobre negro, con bordes blancos para que se distingan sin depender del color.
+fn high_contrast_visuals(visuals: &mut egui::Visuals) {
+    let stroke = egui::Stroke::new(1.5, egui::Color32::WHITE);
+    *visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(egui::Color32::WHITE);
+    visuals.hyperlink_color = egui::Color32::from_rgb(255, 255, 0);
+    visuals.window_fill = egui::Color32::BLACK;
+    visuals.panel_fill = egui::Color32::BLACK;
+    visuals.faint_bg_color = egui::Color32::BLACK;
+    visuals.extreme_bg_color = egui::Color32::BLACK;
+    visuals.window_stroke = stroke;
+    // egui pinta el cursor del TextEdit con `selection.stroke`: en negro no se vería sobre el fondo.
+    visuals.selection.bg_fill = egui::Color32::from_rgb(0, 60, 200);
+    visuals.selection.stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 255, 0));
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        widget.bg_fill = egui::Color32::BLACK;
+        widget.weak_bg_fill = egui::Color32::BLACK;
+        widget.fg_stroke = stroke;
+        widget.bg_stroke = stroke;
+    }
+    visuals.widgets.hovered.bg_fill = egui::Color32::from_gray(60);
+    visuals.widgets.hovered.weak_bg_fill = egui::Color32::from_gray(60);
+}
@@ -0,0 +1,286 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config;
+use crate::json::{self, Value};
+
+pub const SHORTCUTS_FILE: &str = "shortcuts.json";
+
+// egui 0.22 no tiene `Key::from_name`; las teclas se guardan por su `name()` y se buscan aquí al leerlas.
+const KEYS: [egui::Key; 73] = {
+    use egui::Key::*;
+    [
+        ArrowDown, ArrowLeft, ArrowRight, ArrowUp, Escape, Tab, Backspace, Enter, Space, Insert, Delete, Home, End, PageUp,
+        PageDown, Minus, PlusEquals, Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9, A, B, C, D, E, F, G, H,
+        I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14,
+        F15, F16, F17, F18, F19, F20,
+    ]
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Action {
+    Save,
+    OpenFile,
+    OpenDirectory,
+    NewFile,
+    ToggleSettings,
+    Find,
+    NextTab,
+    PreviousTab,
+    ToggleComment,
+    PasteAsNewFile,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Save,
+        Action::OpenFile,
+        Action::OpenDirectory,
+        Action::NewFile,
+        Action::ToggleSettings,
+        Action::Find,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::ToggleComment,
+        Action::PasteAsNewFile,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Save => "Save",
+            Action::OpenFile => "Open File",
+            Action::OpenDirectory => "Open Directory",
+            Action::NewFile => "New File",
+            Action::ToggleSettings => "Toggle Settings",
+            Action::Find => "Find",
+            Action::NextTab => "Next Tab",
+            Action::PreviousTab => "Previous Tab",
+            Action::ToggleComment => "Toggle Comment",
+            Action::PasteAsNewFile => "Paste as New File",
+        }
+    }
+
+    // Clave en `shortcuts.json`; no cambia aunque cambie el texto de `label`.
+    fn id(self) -> &'static str {
+        match self {
+            Action::Save => "save",
+            Action::OpenFile => "open_file",
+            Action::OpenDirectory => "open_directory",
+            Action::NewFile => "new_file",
+            Action::ToggleSettings => "toggle_settings",
+            Action::Find => "find",
+            Action::NextTab => "next_tab",
+            Action::PreviousTab => "previous_tab",
+            Action::ToggleComment => "toggle_comment",
+            Action::PasteAsNewFile => "paste_as_new_file",
+        }
+    }
+}
+
+/// Combinación de teclas de una acción. Los modificadores se normalizan para que la misma combinación grabada en el
+/// teclado y leída del archivo sea igual.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Shortcut(pub egui::KeyboardShortcut);
+
+impl Shortcut {
+    fn new(ctrl: bool, shift: bool, alt: bool, mac_cmd: bool, key: egui::Key) -> Self {
+        // `command` es Cmd en macOS y Ctrl en el resto, como en los eventos de egui.
+        let command = if cfg!(target_os = "macos") { mac_cmd } else { ctrl };
+        Self(egui::KeyboardShortcut::new(egui::Modifiers { alt, ctrl, shift, mac_cmd, command }, key))
+    }
+
+    // Cmd en macOS y Ctrl en el resto.
+    fn primary(shift: bool, key: egui::Key) -> Self {
+        let mac = cfg!(target_os = "macos");
+        Self::new(!mac, shift, false, mac, key)
+    }
+
+    fn to_text(self) -> String {
+        let modifiers = self.0.modifiers;
+        let mut parts = Vec::new();
+        for (held, name) in [(modifiers.ctrl, "Ctrl"), (modifiers.mac_cmd, "Cmd"), (modifiers.alt, "Alt"), (modifiers.shift, "Shift")] {
+            if held {
+                parts.push(name);
+            }
+        }
+        parts.push(self.0.key.name());
+        parts.join("+")
+    }
+
+    fn from_text(text: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = text.split('+').collect();
+        let key_name = parts.pop()?;
+        let key = KEYS.into_iter().find(|key| key.name() == key_name)?;
+        let held = |name: &str| parts.contains(&name);
+        Some(Self::new(held("Ctrl"), held("Shift"), held("Alt"), held("Cmd"), key))
+    }
+}
+
+fn default_bindings() -> HashMap<Action, Shortcut> {
+    use egui::Key;
+    // Ctrl+, y Ctrl+/ no se pueden asignar: egui 0.22 no tiene teclas para la coma ni la barra, así que "Toggle Settings"
+    // y "Toggle Comment" empiezan sin atajo.
+    HashMap::from([
+        (Action::Save, Shortcut::primary(false, Key::S)),
+        (Action::OpenFile, Shortcut::primary(false, Key::O)),
+        (Action::OpenDirectory, Shortcut::primary(false, Key::K)),
+        (Action::NewFile, Shortcut::primary(false, Key::N)),
+        (Action::Find, Shortcut::primary(false, Key::F)),
+        (Action::NextTab, Shortcut::new(true, false, false, false, Key::Tab)),
+        (Action::PreviousTab, Shortcut::new(true, true, false, false, Key::Tab)),
+    ])
+}
+
+// Combinaciones que el editor lee directamente en lugar de pasar por `Action`; grabarlas para una acción haría que
+// la tecla hiciera dos cosas.
+fn reserved_bindings() -> Vec<(Shortcut, &'static str)> {
+    use egui::Key;
+    let mac = cfg!(target_os = "macos");
+    let command_alt = |shift: bool, key: Key| Shortcut::new(!mac, shift, true, mac, key);
+    vec![
+        (Shortcut::primary(false, Key::Z), "Undo"),
+        (Shortcut::primary(true, Key::Z), "Redo"),
+        (Shortcut::primary(false, Key::Y), "Redo"),
+        (Shortcut::primary(false, Key::Space), "Completion"),
+        (Shortcut::primary(false, Key::T), "Symbol Search"),
+        (Shortcut::primary(true, Key::T), "Toggle Dark Mode"),
+        (Shortcut::primary(true, Key::O), "Toggle Outline"),
+        (Shortcut::primary(true, Key::K), "Delete Line"),
+        (Shortcut::primary(false, Key::F3), "Next Word Occurrence"),
+        (Shortcut::primary(true, Key::F3), "Previous Word Occurrence"),
+        (Shortcut::new(false, false, false, false, Key::F2), "Rename"),
+        (Shortcut::new(false, false, false, false, Key::F3), "Find Next"),
+        (Shortcut::new(false, true, false, false, Key::F3), "Find Previous"),
+        (Shortcut::new(false, false, true, false, Key::PageDown), "Next Function"),
+        (Shortcut::new(false, false, true, false, Key::PageUp), "Previous Function"),
+        (Shortcut::new(false, true, true, false, Key::ArrowDown), "Duplicate Line Down"),
+        (Shortcut::new(false, true, true, false, Key::ArrowUp), "Duplicate Line Up"),
+        (command_alt(false, Key::N), "Annotate Line"),
+        (command_alt(true, Key::V), "Paste Without Formatting"),
+    ]
+}
+
+/// Atajos de las acciones globales: los de serie con los cambios guardados en `shortcuts.json` encima.
+pub struct ShortcutMap {
+    bindings: HashMap<Action, Shortcut>,
+    // Acción cuyo atajo se está grabando con "Record", y el aviso del último conflicto.
+    recording: Option<Action>,
+    conflict: Option<String>,
+    // Error al guardar `shortcuts.json`, para que el editor lo muestre.
+    pub save_error: Option<String>,
+}
+
+impl ShortcutMap {
+    /// Los errores al leer `shortcuts.json` se añaden a `errors`.
+    pub fn load(errors: &mut Vec<String>) -> Self {
+        let mut bindings = default_bindings();
+        let content = shortcuts_path().and_then(|path| fs::read_to_string(path).ok());
+        match content.as_deref().map(json::parse) {
+            Some(Ok(Value::Object(entries))) => {
+                for (id, value) in entries {
+                    let action = Action::ALL.into_iter().find(|action| action.id() == id);
+                    let shortcut = match value {
+                        Value::String(text) => Shortcut::from_text(&text),
+                        _ => None,
+                    };
+                    if let (Some(action), Some(shortcut)) = (action, shortcut) {
+                        bindings.insert(action, shortcut);
+                    }
+                }
+            }
+            Some(Err(e)) => errors.push(format!("Unable to read shortcuts: {}", e)),
+            _ => {}
+        }
+        Self { bindings, recording: None, conflict: None, save_error: None }
+    }
+
+    /// Consume los atajos pulsados en este fotograma y devuelve sus acciones. Mientras se graba, la siguiente
+    /// combinación se asigna en lugar de ejecutarse (Escape cancela).
+    pub fn process(&mut self, ctx: &egui::Context) -> Vec<Action> {
+        if let Some(action) = self.recording {
+            let pressed = ctx.input_mut(|i| {
+                let index = i.events.iter().position(|event| matches!(event, egui::Event::Key { pressed: true, .. }))?;
+                match i.events.remove(index) {
+                    egui::Event::Key { key, modifiers, .. } => Some((key, modifiers)),
+                    _ => None,
+                }
+            });
+            match pressed {
+                Some((egui::Key::Escape, _)) => self.recording = None,
+                Some((key, modifiers)) => {
+                    self.recording = None;
+                    self.assign(action, Shortcut::new(modifiers.ctrl, modifiers.shift, modifiers.alt, modifiers.mac_cmd, key));
+                }
+                None => {}
+            }
+            return Vec::new();
+        }
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.bindings.get(action).is_some_and(|shortcut| ctx.input_mut(|i| i.consume_shortcut(&shortcut.0))))
+            .collect()
+    }
+
+    // Una combinación que ya usa otra acción, o una de las fijas del editor, no se asigna ni se guarda.
+    fn assign(&mut self, action: Action, shortcut: Shortcut) {
+        let taken = Action::ALL
+            .into_iter()
+            .find(|other| *other != action && self.bindings.get(other) == Some(&shortcut))
+            .map(Action::label)
+            .or_else(|| reserved_bindings().into_iter().find(|(reserved, _)| *reserved == shortcut).map(|(_, label)| label));
+        if let Some(other) = taken {
+            self.conflict = Some(format!("{} is already used by {}", shortcut.to_text(), other));
+            return;
+        }
+        self.conflict = None;
+        self.bindings.insert(action, shortcut);
+        if let Err(e) = self.save() {
+            self.save_error = Some(format!("Unable to save shortcuts: {}", e));
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = shortcuts_path() else {
+            return Ok(());
+        };
+        let entries = Action::ALL
+            .into_iter()
+            .filter_map(|action| Some((action.id().to_string(), Value::String(self.bindings.get(&action)?.to_text()))))
+            .collect();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, Value::Object(entries).to_pretty_string("  ") + "\n")
+    }
+
+    /// Tabla de acciones con su atajo y un botón para grabar otro.
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("shortcuts").num_columns(3).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.label());
+                let binding = self.bindings.get(&action).map_or_else(|| "—".to_string(), |shortcut| ui.ctx().format_shortcut(&shortcut.0));
+                ui.monospace(binding);
+                if self.recording == Some(action) {
+                    if ui.button("Press keys…").on_hover_text("Escape cancels").clicked() {
+                        self.recording = None;
+                    }
+                } else if ui.button("Record").clicked() {
+                    self.recording = Some(action);
+                    self.conflict = None;
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(conflict) = &self.conflict {
+            ui.colored_label(ui.visuals().error_fg_color, conflict);
+        }
+    }
+}
+
+fn shortcuts_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(SHORTCUTS_FILE))
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config;
+use crate::text;
+
+pub const SNIPPETS_FILE: &str = "snippets.toml";
+
+// Cada cuánto se comprueba si los archivos de snippets cambiaron en disco.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+// `$0` marca dónde queda el cursor tras expandir el snippet.
+const CURSOR_PLACEHOLDER: &str = "$0";
+
+const PROJECT_TEMPLATE: &str = "# Project snippets: type the trigger word and press Tab to expand it.
+# These take precedence over your user snippets with the same trigger.
+# `$0` marks where the cursor is placed after expansion.
+
+[snippets]
+# test = \"#[test]\\nfn $0() {\\n}\"
+";
+
+#[derive(Default)]
+struct SnippetFile {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    snippets: HashMap<String, String>,
+    error: Option<String>,
+}
+
+impl SnippetFile {
+    fn set_path(&mut self, path: Option<PathBuf>) {
+        if self.path != path {
+            *self = SnippetFile { path, ..Default::default() };
+            self.reload_if_changed();
+        }
+    }
+
+    fn reload_if_changed(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        self.snippets.clear();
+        self.error = None;
+        if modified.is_none() {
+            return;
+        }
+        match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|content| parse(&content)) {
+            Ok(snippets) => self.snippets = snippets,
+            Err(e) => self.error = Some(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
+
+// Formato: una tabla `[snippets]` con `disparador = "cuerpo"`.
+fn parse(content: &str) -> Result<HashMap<String, String>, String> {
+    let table = content.parse::<toml::Table>().map_err(|e| e.to_string())?;
+    let Some(snippets) = table.get("snippets") else {
+        return Ok(HashMap::new());
+    };
+    let snippets = snippets.as_table().ok_or("`snippets` must be a table")?;
+    snippets
+        .iter()
+        .map(|(trigger, body)| match body.as_str() {
+            Some(body) => Ok((trigger.clone(), body.to_string())),
+            None => Err(format!("snippet `{}` must be a string", trigger)),
+        })
+        .collect()
+}
+
+/// Snippets del usuario (`snippets.toml` en el directorio de configuración) y del proyecto
+/// (`.rbeditor/snippets.toml`). Los del proyecto tienen prioridad y ambos se recargan al cambiar en disco.
+pub struct SnippetRegistry {
+    user: SnippetFile,
+    project: SnippetFile,
+    last_check: Instant,
+}
+
+impl SnippetRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            user: SnippetFile::default(),
+            project: SnippetFile::default(),
+            last_check: Instant::now(),
+        };
+        registry.user.set_path(config::config_dir().map(|dir| dir.join(SNIPPETS_FILE)));
+        registry
+    }
+
+    pub fn set_project_root(&mut self, root: Option<&Path>) {
+        self.project.set_path(root.map(|root| config::project_dir(root).join(SNIPPETS_FILE)));
+    }
+
+    pub fn reload_if_changed(&mut self) {
+        if self.last_check.elapsed() < RELOAD_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+        self.user.reload_if_changed();
+        self.project.reload_if_changed();
+    }
+
+    pub fn lookup(&self, trigger: &str) -> Option<&str> {
+        self.project
+            .snippets
+            .get(trigger)
+            .or_else(|| self.user.snippets.get(trigger))
+            .map(String::as_str)
+    }
+
+    pub fn user_path(&self) -> Option<&Path> {
+        self.user.path.as_deref()
+    }
+
+    pub fn project_path(&self) -> Option<&Path> {
+        self.project.path.as_deref()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &str> {
+        self.user.error.iter().chain(self.project.error.iter()).map(String::as_str)
+    }
+
+    /// Crea `.rbeditor/snippets.toml` con una plantilla comentada.
+    pub fn create_project_file(&mut self) -> io::Result<()> {
+        let Some(path) = self.project.path.clone() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, PROJECT_TEMPLATE)?;
+        self.project.reload_if_changed();
+        Ok(())
+    }
+}
+
+/// Si la palabra que acaba en `cursor` es un disparador, la sustituye por el snippet (con la sangría de la línea
+/// en las líneas siguientes) y devuelve el nuevo cursor.
+pub fn expand(registry: &SnippetRegistry, content: &mut String, cursor: usize) -> Option<usize> {
+    let start = content[..cursor]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| text::is_identifier_char(*c))
+        .last()
+        .map(|(i, _)| i)?;
+    let body = registry.lookup(&content[start..cursor])?;
+    let indent = text::line_indent(content, start).to_string();
+    let body = body.replace('\n', &format!("\n{}", indent));
+    let (expansion, cursor_offset) = match body.find(CURSOR_PLACEHOLDER) {
+        Some(i) => (body.replacen(CURSOR_PLACEHOLDER, "", 1), i),
+        None => (body.clone(), body.len()),
+    };
+    content.replace_range(start..cursor, &expansion);
+    Some(start + cursor_offset)
+}
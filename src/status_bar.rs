@@ -0,0 +1,97 @@
+use tree_sitter::{Node, Tree};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StatusBarItem {
+    CursorPosition,
+    Language,
+    Encoding,
+    LineEnding,
+    FileSize,
+    DirtyFlag,
+    WordCount,
+    TypingSpeed,
+    LongLines,
+    IndentHistogram,
+    FindMatches,
+    LspStatus,
+    SyntaxNode,
+}
+
+impl StatusBarItem {
+    pub const ALL: [StatusBarItem; 13] = [
+        StatusBarItem::CursorPosition,
+        StatusBarItem::Language,
+        StatusBarItem::Encoding,
+        StatusBarItem::LineEnding,
+        StatusBarItem::FileSize,
+        StatusBarItem::DirtyFlag,
+        StatusBarItem::WordCount,
+        StatusBarItem::TypingSpeed,
+        StatusBarItem::LongLines,
+        StatusBarItem::IndentHistogram,
+        StatusBarItem::FindMatches,
+        StatusBarItem::LspStatus,
+        StatusBarItem::SyntaxNode,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatusBarItem::CursorPosition => "Cursor Position",
+            StatusBarItem::Language => "Language",
+            StatusBarItem::Encoding => "Encoding",
+            StatusBarItem::LineEnding => "Line Ending",
+            StatusBarItem::FileSize => "File Size",
+            StatusBarItem::DirtyFlag => "Dirty Flag",
+            StatusBarItem::WordCount => "Word Count",
+            StatusBarItem::TypingSpeed => "Typing Speed",
+            StatusBarItem::LongLines => "Long Lines",
+            StatusBarItem::IndentHistogram => "Indentation Histogram",
+            StatusBarItem::FindMatches => "Find Matches",
+            StatusBarItem::LspStatus => "LSP Status",
+            StatusBarItem::SyntaxNode => "Syntax Node",
+        }
+    }
+}
+
+/// Indicadores visibles por defecto, en el orden en que aparecían antes de poder configurarlos.
+pub fn default_items() -> Vec<StatusBarItem> {
+    vec![
+        StatusBarItem::CursorPosition,
+        StatusBarItem::TypingSpeed,
+        StatusBarItem::LongLines,
+        StatusBarItem::IndentHistogram,
+        StatusBarItem::FindMatches,
+    ]
+}
+
+pub fn line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "CRLF"
+    } else {
+        "LF"
+    }
+}
+
+pub fn human_size(bytes: usize) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// Tipos de los nodos con nombre desde la raíz (sin incluirla) hasta el más pequeño que contiene `byte`,
+/// como `function_item > block > let_declaration > integer_literal`.
+pub fn node_path_at(tree: &Tree, byte: usize) -> String {
+    let root = tree.root_node();
+    let Some(node) = root.named_descendant_for_byte_range(byte, byte) else {
+        return String::new();
+    };
+    let mut kinds: Vec<&str> = std::iter::successors(Some(node), Node::parent)
+        .take_while(|node| *node != root)
+        .filter(Node::is_named)
+        .map(|node| node.kind())
+        .collect();
+    kinds.reverse();
+    kinds.join(" > ")
+}
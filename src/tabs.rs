@@ -0,0 +1,72 @@
+use eframe::egui;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::column::RectSelection;
+use crate::follow::FollowState;
+use crate::mirror::MirrorPair;
+use crate::track_changes::ChangeSet;
+use crate::undo::UndoTree;
+
+/// Un archivo abierto. La pestaña activa se edita directamente en `TextEditor` (su texto, cursor, cambios
+/// registrados y zonas en espejo están en el editor y aquí quedan vacíos); al cambiar de pestaña se guardan aquí y se
+/// cargan los de la nueva. El seguimiento (`follow`) sí vive siempre aquí, porque se sondea también en segundo plano.
+#[derive(Default)]
+pub struct Tab {
+    pub content: String,
+    pub file_path: Option<PathBuf>,
+    // El contenido no está en disco: búfer sin archivo o fallo al guardar.
+    pub is_modified: bool,
+    pub undo_tree: UndoTree,
+    pub follow: Option<FollowState>,
+    pub cursor: usize,
+    pub selection: Range<usize>,
+    pub changes: ChangeSet,
+    pub mirror: Option<MirrorPair>,
+    pub mirror_pending: Option<Range<usize>>,
+    pub rect_selection: Option<RectSelection>,
+}
+
+impl Tab {
+    pub fn title(&self) -> String {
+        let name = self
+            .file_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(|| "Untitled".to_string(), |name| name.to_string_lossy().into_owned());
+        if self.is_modified {
+            format!("● {}", name)
+        } else {
+            name
+        }
+    }
+}
+
+// Los índices son posiciones en `tabs`; reordenar con arrastre sería otra variante más.
+pub enum TabAction {
+    Activate(usize),
+    Close(usize),
+}
+
+/// Tira de pestañas sobre el editor: clic para activar y `×` para cerrar.
+pub fn show_tab_bar(ui: &mut egui::Ui, tabs: &[Tab], active: usize) -> Option<TabAction> {
+    let mut action = None;
+    egui::ScrollArea::horizontal().id_source("tab_bar").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            for (index, tab) in tabs.iter().enumerate() {
+                let response = ui.selectable_label(index == active, tab.title());
+                if let Some(path) = &tab.file_path {
+                    response.clone().on_hover_text(path.to_string_lossy());
+                }
+                if response.clicked() {
+                    action = Some(TabAction::Activate(index));
+                }
+                if ui.small_button("×").on_hover_text("Close").clicked() {
+                    action = Some(TabAction::Close(index));
+                }
+                ui.separator();
+            }
+        });
+    });
+    action
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::expand;
+use crate::git;
+
+pub struct FileTemplate {
+    pub language: &'static str,
+    pub body: &'static str,
+}
+
+const RUST_TEMPLATE: &str = "//! {filename}\n//! Created on {date}\n\nfn main() {\n    println!(\"Hello, world!\");\n}\n";
+const PYTHON_TEMPLATE: &str = "\"\"\"{filename} — created on {date}.\"\"\"\n\n\ndef main():\n    pass\n\n\nif __name__ == \"__main__\":\n    main()\n";
+const JAVASCRIPT_TEMPLATE: &str = "// {filename} — created on {date}\n\n'use strict';\n\nfunction main() {\n}\n\nmain();\n";
+const HTML_TEMPLATE: &str = "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n    <meta charset=\"UTF-8\">\n    <title>{filename}</title>\n</head>\n<body>\n</body>\n</html>\n";
+const CSS_TEMPLATE: &str = "/* {filename} — created on {date} */\n\nbody {\n    margin: 0;\n}\n";
+const MARKDOWN_TEMPLATE: &str = "# {filename}\n\n_Created on {date}_\n";
+const TOML_TEMPLATE: &str = "# {filename} — created on {date}\n\n[package]\nname = \"\"\n";
+const JSON_TEMPLATE: &str = "{\n}\n";
+
+pub fn template_for_extension(extension: &str) -> Option<FileTemplate> {
+    let (language, body) = match extension {
+        "rs" => ("Rust", RUST_TEMPLATE),
+        "py" => ("Python", PYTHON_TEMPLATE),
+        "js" | "ts" => ("JavaScript", JAVASCRIPT_TEMPLATE),
+        "html" | "htm" => ("HTML", HTML_TEMPLATE),
+        "css" | "scss" => ("CSS", CSS_TEMPLATE),
+        "md" => ("Markdown", MARKDOWN_TEMPLATE),
+        "toml" => ("TOML", TOML_TEMPLATE),
+        "json" => ("JSON", JSON_TEMPLATE),
+        _ => return None,
+    };
+    Some(FileTemplate { language, body })
+}
+
+/// Valores de las variables `{nombre}` de las plantillas; las que no se conocen quedan en `None`.
+pub struct TemplateVars {
+    pub filename: String,
+    pub date: String,
+    pub year: String,
+    // `git config user.name` en la carpeta del archivo.
+    pub author: Option<String>,
+    // El ajuste de licencia; vacío cuenta como no definido.
+    pub license: Option<String>,
+    // Nombre del archivo sin extensión en snake_case y en PascalCase.
+    pub module_name: String,
+    pub camel_module_name: String,
+    // `[package].name` del `Cargo.toml` más cercano.
+    pub crate_name: Option<String>,
+}
+
+impl TemplateVars {
+    /// Variables para crear `path`; `name` es como lo escribió el usuario, relativo a la carpeta abierta.
+    pub fn for_file(path: &Path, name: &str, license: &str) -> Self {
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let dir = path.parent().filter(|dir| dir.is_dir());
+        let author = dir
+            .and_then(|dir| git::run_git_command(&["config", "user.name"], dir).ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let date = today();
+        Self {
+            filename: name.to_string(),
+            year: date[..4].to_string(),
+            date,
+            author,
+            license: Some(license.trim().to_string()).filter(|license| !license.is_empty()),
+            module_name: snake_case(&stem),
+            camel_module_name: pascal_case(&stem),
+            crate_name: expand::project_root(path).and_then(|root| crate_name(&root)),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "filename" => Some(&self.filename),
+            "date" => Some(&self.date),
+            "year" => Some(&self.year),
+            "author" => self.author.as_deref(),
+            "license" => self.license.as_deref(),
+            "module_name" => Some(&self.module_name),
+            "CamelModuleName" => Some(&self.camel_module_name),
+            "crate_name" => self.crate_name.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Sustituye cada `{variable}` de la plantilla. Las que no tienen valor se dejan tal cual, con un aviso; las llaves que
+/// no rodean un identificador (como las de un bloque `{\n}`) no son variables.
+pub fn substitute_template_vars(template: &str, vars: &TemplateVars) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        result.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let name_len = after.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(after.len());
+        let name = &after[..name_len];
+        if name.is_empty() || !after[name_len..].starts_with('}') {
+            result.push('{');
+            rest = after;
+            continue;
+        }
+        match vars.get(name) {
+            Some(value) => result.push_str(value),
+            None => {
+                eprintln!("Template variable {{{}}} has no value; leaving it as is", name);
+                result.push_str(&rest[open..open + name_len + 2]);
+            }
+        }
+        rest = &after[name_len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// `[package].name` del `Cargo.toml` de `root`.
+pub fn crate_name(root: &Path) -> Option<String> {
+    let manifest = fs::read_to_string(root.join("Cargo.toml")).ok()?.parse::<toml::Table>().ok()?;
+    Some(manifest.get("package")?.get("name")?.as_str()?.to_string())
+}
+
+// Palabras de un nombre como `my-file`, `my_file` o `MyFile`, en minúsculas.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            previous_lower = false;
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn snake_case(name: &str) -> String {
+    words(name).join("_")
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect()
+}
+
+fn unix_seconds() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+// Fecha actual (UTC) en formato AAAA-MM-DD, sin depender de crates de fechas.
+pub fn today() -> String {
+    let (year, month, day) = civil_from_days((unix_seconds() / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Fecha y hora actuales (UTC) en formato AAAA-MM-DD HH:MM.
+pub fn now() -> String {
+    let seconds = unix_seconds() % 86_400;
+    format!("{} {:02}:{:02} UTC", today(), seconds / 3600, seconds % 3600 / 60)
+}
+
+// Algoritmo de Howard Hinnant para convertir días desde 1970-01-01 en una fecha del calendario gregoriano.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
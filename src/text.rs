@@ -0,0 +1,205 @@
+// Conversiones entre índices de caracteres (los que usa egui) y offsets en bytes (los que usa tree-sitter).
+
+pub fn char_to_byte(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(byte, _)| byte)
+        .unwrap_or(text.len())
+}
+
+pub fn byte_to_char(text: &str, byte_index: usize) -> usize {
+    text[..byte_index.min(text.len())].chars().count()
+}
+
+// Sin el crate unicode-segmentation, un grupo de grafemas se aproxima como un carácter seguido de marcas combinantes,
+// selectores de variante, modificadores de tono, etiquetas y lo que se une con ZWJ; dos indicadores regionales (una
+// bandera) y `\r\n` también son un grupo. Cubre los acentos combinantes, los emoji compuestos y el jamo coreano.
+fn extends_grapheme(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0900..=0x0903
+            | 0x093A..=0x094F
+            | 0x0E31
+            | 0x0E34..=0x0E3A
+            | 0x0E47..=0x0E4E
+            | 0x1160..=0x11FF
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C..=0x200D
+            | 0x20D0..=0x20FF
+            | 0x3099..=0x309A
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0020..=0xE007F
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+/// Final del grupo de grafemas que empieza en `byte_index`.
+pub fn next_grapheme_boundary(text: &str, byte_index: usize) -> usize {
+    let byte_index = byte_index.min(text.len());
+    let mut chars = text[byte_index..].chars();
+    let Some(first) = chars.next() else {
+        return byte_index;
+    };
+    let mut end = byte_index + first.len_utf8();
+    if first == '\r' && text[end..].starts_with('\n') {
+        return end + 1;
+    }
+    let (mut previous, mut regional) = (first, usize::from(is_regional_indicator(first)));
+    for c in chars {
+        let pairs_flag = is_regional_indicator(c) && regional == 1;
+        if !(extends_grapheme(c) || previous == '\u{200D}' || pairs_flag) {
+            break;
+        }
+        regional += usize::from(is_regional_indicator(c));
+        end += c.len_utf8();
+        previous = c;
+    }
+    end
+}
+
+/// Principio del grupo de grafemas que acaba en `byte_index`.
+pub fn previous_grapheme_boundary(text: &str, byte_index: usize) -> usize {
+    let byte_index = byte_index.min(text.len());
+    let before = &text[..byte_index];
+    if before.ends_with("\r\n") {
+        return byte_index - 2;
+    }
+    if before.ends_with('\n') || before.is_empty() {
+        return byte_index.saturating_sub(1);
+    }
+    // Los grupos no cruzan saltos de línea, así que basta con recorrer la línea desde su principio.
+    let mut boundary = line_start(text, byte_index);
+    loop {
+        let next = next_grapheme_boundary(text, boundary);
+        if next >= byte_index {
+            return boundary;
+        }
+        boundary = next;
+    }
+}
+
+// Cuántos caracteres (de egui) ocupa `range`.
+pub fn char_len(text: &str, range: std::ops::Range<usize>) -> usize {
+    text[range].chars().count()
+}
+
+pub fn line_start(text: &str, byte_index: usize) -> usize {
+    text[..byte_index].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+pub fn line_indent(text: &str, byte_index: usize) -> &str {
+    let start = line_start(text, byte_index);
+    let line = &text[start..];
+    let len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    &line[..len]
+}
+
+/// Offset en bytes del inicio de cada línea (el primero siempre es 0).
+pub fn line_offsets(text: &str) -> Vec<usize> {
+    std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DuplicateDir {
+    Up,
+    Down,
+}
+
+/// Duplica las líneas que toca la selección (o la línea del cursor) encima o debajo del original.
+/// Devuelve la selección equivalente dentro de la copia.
+pub fn duplicate_selection(content: &mut String, selection: std::ops::Range<usize>, direction: DuplicateDir) -> std::ops::Range<usize> {
+    let selection = selection.start.min(content.len())..selection.end.clamp(selection.start, content.len());
+    let start = line_start(content, selection.start);
+    // Una selección que acaba justo al inicio de una línea no incluye esa línea.
+    let last = if selection.end > selection.start && content[..selection.end].ends_with('\n') {
+        selection.end - 1
+    } else {
+        selection.end
+    };
+    let end = content[last..].find('\n').map_or(content.len(), |i| last + i);
+    let block = content[start..end].to_string();
+    match direction {
+        DuplicateDir::Down => {
+            content.insert_str(end, &format!("\n{}", block));
+            let shift = block.len() + 1;
+            selection.start + shift..selection.end + shift
+        }
+        DuplicateDir::Up => {
+            content.insert_str(start, &format!("{}\n", block));
+            selection
+        }
+    }
+}
+
+// Cambia la sangría de las líneas de continuación de `body` de `from` a `to`.
+pub fn reindent(body: &str, from: &str, to: &str) -> String {
+    body.split('\n')
+        .enumerate()
+        .map(|(i, line)| match line.strip_prefix(from) {
+            Some(rest) if i > 0 => format!("{}{}", to, rest),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Rango en bytes de la palabra (identificador) que contiene o toca `byte_index`.
+pub fn word_at(text: &str, byte_index: usize) -> Option<std::ops::Range<usize>> {
+    word_around(text, byte_index, is_identifier_char)
+}
+
+// Como `word_at`, pero para seleccionar: las marcas combinantes se quedan con la letra que acompañan.
+pub fn word_selection_at(text: &str, byte_index: usize) -> Option<std::ops::Range<usize>> {
+    word_around(text, byte_index, |c| is_identifier_char(c) || extends_grapheme(c))
+}
+
+fn word_around(text: &str, byte_index: usize, is_word_char: impl Fn(char) -> bool) -> Option<std::ops::Range<usize>> {
+    let byte_index = byte_index.min(text.len());
+    let start = text[..byte_index]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map_or(byte_index, |(i, _)| i);
+    let end = text[byte_index..]
+        .char_indices()
+        .find(|(_, c)| !is_word_char(*c))
+        .map_or(text.len(), |(i, _)| byte_index + i);
+    (start < end).then_some(start..end)
+}
+
+/// Zona que cambió entre `before` y `after`: `(inicio, fin en before, fin en after)`, en límites de carácter.
+pub fn changed_range(before: &str, after: &str) -> (usize, usize, usize) {
+    let mut start = before.bytes().zip(after.bytes()).take_while(|(a, b)| a == b).count();
+    while !before.is_char_boundary(start) || !after.is_char_boundary(start) {
+        start -= 1;
+    }
+    let max_suffix = before.len().min(after.len()) - start;
+    let mut suffix = before
+        .bytes()
+        .rev()
+        .zip(after.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !before.is_char_boundary(before.len() - suffix) || !after.is_char_boundary(after.len() - suffix) {
+        suffix -= 1;
+    }
+    (start, before.len() - suffix, after.len() - suffix)
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::PathBuf;
+
+// Resultados como máximo por búsqueda; con más, la lista deja de ser útil y la ventana se vuelve lenta.
+pub const RESULT_LIMIT: usize = 500;
+// Archivos más grandes se saltan: seguramente son generados o no son texto.
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SearchScope {
+    Project,
+    RecentFiles,
+}
+
+impl SearchScope {
+    pub const ALL: [SearchScope; 2] = [SearchScope::Project, SearchScope::RecentFiles];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchScope::Project => "Search in Project",
+            SearchScope::RecentFiles => "Search Recent Files",
+        }
+    }
+}
+
+/// Línea que contiene el texto buscado; `byte` es dónde empieza la coincidencia en el archivo.
+pub struct SearchMatch {
+    pub path: PathBuf,
+    pub line: usize,
+    pub byte: usize,
+    pub text: String,
+}
+
+/// Estado de la ventana "Search": lo escrito, el ámbito elegido y los resultados de la última búsqueda.
+pub struct TextSearch {
+    pub query: String,
+    pub scope: SearchScope,
+    pub results: Vec<SearchMatch>,
+    // Los resultados llegaron al límite y hay más.
+    pub truncated: bool,
+}
+
+impl TextSearch {
+    pub fn new(query: String) -> Self {
+        Self { query, scope: SearchScope::Project, results: Vec::new(), truncated: false }
+    }
+
+    /// Busca `query` en `files` sin distinguir mayúsculas; los archivos que no se pueden leer como texto se saltan.
+    pub fn run(&mut self, files: &[PathBuf]) {
+        self.results.clear();
+        self.truncated = false;
+        let needle = self.query.to_lowercase();
+        if needle.is_empty() {
+            return;
+        }
+        for path in files {
+            if fs::metadata(path).map_or(true, |meta| meta.len() > MAX_FILE_BYTES) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            let mut line_start = 0;
+            for (line, text) in content.split('\n').enumerate() {
+                if let Some(column) = text.to_lowercase().find(&needle).filter(|column| text.is_char_boundary(*column)) {
+                    if self.results.len() == RESULT_LIMIT {
+                        self.truncated = true;
+                        return;
+                    }
+                    self.results.push(SearchMatch { path: path.clone(), line, byte: line_start + column, text: text.trim().to_string() });
+                }
+                line_start += text.len() + 1;
+            }
+        }
+    }
+}
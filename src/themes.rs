@@ -0,0 +1,223 @@
+use eframe::egui::Color32;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config;
+use crate::json::{self, Value};
+use crate::settings::{Settings, ThemeName};
+
+pub const THEMES_FILE: &str = "themes.json";
+
+/// Conjunto de ajustes de apariencia que se aplica de una vez desde el selector de temas.
+/// `base` decide el estilo de egui (claro, oscuro, alto contraste) y los colores de los paneles.
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    pub base: ThemeName,
+    pub background_color: Color32,
+    pub text_color: Color32,
+    pub font_size: f32,
+    pub line_spacing: f32,
+    // Color por captura de resaltado (`keyword`, `string`…); las que faltan usan `text_color`.
+    pub syntax_colors: HashMap<String, Color32>,
+    // Los temas de serie no se guardan en disco ni se pueden borrar.
+    pub builtin: bool,
+}
+
+impl Theme {
+    pub fn builtin(base: ThemeName) -> Self {
+        let mut settings = Settings::default();
+        settings.set_theme(base);
+        Self::from_settings(base.label(), &settings, true)
+    }
+
+    /// La apariencia actual con otro nombre, para guardarla como tema propio.
+    pub fn from_settings(name: &str, settings: &Settings, builtin: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            base: settings.theme,
+            background_color: settings.background_color,
+            text_color: settings.text_color,
+            font_size: settings.font_size,
+            line_spacing: settings.line_spacing,
+            syntax_colors: settings.syntax_colors.clone(),
+            builtin,
+        }
+    }
+}
+
+/// Colores de resaltado de cada tema de serie.
+pub fn syntax_colors(theme: ThemeName) -> HashMap<String, Color32> {
+    let rgb = Color32::from_rgb;
+    let palette = match theme {
+        ThemeName::Light => [
+            rgb(170, 13, 145),
+            rgb(196, 26, 22),
+            rgb(0, 116, 0),
+            rgb(50, 109, 116),
+            rgb(28, 70, 173),
+            rgb(28, 0, 207),
+            rgb(100, 56, 32),
+            rgb(63, 110, 116),
+        ],
+        ThemeName::Dark => [
+            rgb(198, 120, 221),
+            rgb(152, 195, 121),
+            rgb(106, 153, 85),
+            rgb(97, 175, 239),
+            rgb(229, 192, 123),
+            rgb(209, 154, 102),
+            rgb(220, 220, 170),
+            rgb(156, 220, 254),
+        ],
+        ThemeName::SolarizedLight | ThemeName::SolarizedDark => {
+            let comment = if theme == ThemeName::SolarizedDark { rgb(88, 110, 117) } else { rgb(147, 161, 161) };
+            [
+                rgb(133, 153, 0),
+                rgb(42, 161, 152),
+                comment,
+                rgb(38, 139, 210),
+                rgb(181, 137, 0),
+                rgb(211, 54, 130),
+                rgb(203, 75, 22),
+                rgb(108, 113, 196),
+            ]
+        }
+        ThemeName::Monokai => [
+            rgb(249, 38, 114),
+            rgb(230, 219, 116),
+            rgb(117, 113, 94),
+            rgb(166, 226, 46),
+            rgb(102, 217, 239),
+            rgb(174, 129, 255),
+            rgb(253, 151, 31),
+            rgb(248, 248, 242),
+        ],
+        ThemeName::HighContrast => [
+            rgb(255, 255, 0),
+            rgb(0, 255, 255),
+            rgb(200, 200, 200),
+            rgb(255, 255, 255),
+            rgb(0, 255, 0),
+            rgb(255, 128, 255),
+            rgb(255, 200, 0),
+            rgb(255, 255, 255),
+        ],
+    };
+    ["keyword", "string", "comment", "function", "type", "constant", "attribute", "property"]
+        .into_iter()
+        .map(str::to_string)
+        .zip(palette)
+        .collect()
+}
+
+/// Temas de serie seguidos de los guardados por el usuario; los errores al leer estos se añaden a `errors`.
+pub fn all_themes(errors: &mut Vec<String>) -> Vec<Theme> {
+    let mut themes: Vec<Theme> = ThemeName::ALL.into_iter().map(Theme::builtin).collect();
+    themes.extend(load_custom(errors));
+    themes
+}
+
+fn themes_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(THEMES_FILE))
+}
+
+fn load_custom(errors: &mut Vec<String>) -> Vec<Theme> {
+    let Some(content) = themes_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    match json::parse(&content) {
+        Ok(Value::Array(items)) => items.iter().filter_map(theme_from_json).collect(),
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            errors.push(format!("Unable to read themes: {}", e));
+            Vec::new()
+        }
+    }
+}
+
+/// Escribe los temas propios de `themes`; sin ninguno se borra el archivo.
+pub fn save_custom(themes: &[Theme]) -> io::Result<()> {
+    let Some(path) = themes_path() else {
+        return Ok(());
+    };
+    let custom: Vec<Value> = themes.iter().filter(|theme| !theme.builtin).map(theme_to_json).collect();
+    if custom.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, Value::Array(custom).to_pretty_string("  ") + "\n")
+}
+
+fn theme_to_json(theme: &Theme) -> Value {
+    let mut syntax: Vec<(String, Value)> = theme.syntax_colors.iter().map(|(name, color)| (name.clone(), color_to_json(*color))).collect();
+    syntax.sort_by(|a, b| a.0.cmp(&b.0));
+    Value::Object(vec![
+        ("name".to_string(), Value::String(theme.name.clone())),
+        ("base".to_string(), Value::String(theme.base.label().to_string())),
+        ("background_color".to_string(), color_to_json(theme.background_color)),
+        ("text_color".to_string(), color_to_json(theme.text_color)),
+        ("font_size".to_string(), Value::Number(theme.font_size.to_string())),
+        ("line_spacing".to_string(), Value::Number(theme.line_spacing.to_string())),
+        ("syntax_colors".to_string(), Value::Object(syntax)),
+    ])
+}
+
+fn theme_from_json(value: &Value) -> Option<Theme> {
+    let Value::Object(entries) = value else {
+        return None;
+    };
+    let field = |key: &str| entries.iter().find(|(name, _)| name == key).map(|(_, value)| value);
+    let number = |key: &str| match field(key)? {
+        Value::Number(number) => number.parse::<f32>().ok(),
+        _ => None,
+    };
+    let Some(Value::String(name)) = field("name") else {
+        return None;
+    };
+    let base = match field("base") {
+        Some(Value::String(label)) => ThemeName::ALL.into_iter().find(|theme| theme.label() == label)?,
+        _ => return None,
+    };
+    let syntax_colors = match field("syntax_colors") {
+        Some(Value::Object(colors)) => colors.iter().filter_map(|(name, color)| Some((name.clone(), color_from_json(color)?))).collect(),
+        _ => syntax_colors(base),
+    };
+    Some(Theme {
+        name: name.clone(),
+        base,
+        background_color: color_from_json(field("background_color")?)?,
+        text_color: color_from_json(field("text_color")?)?,
+        font_size: number("font_size")?,
+        line_spacing: number("line_spacing")?,
+        syntax_colors,
+        builtin: false,
+    })
+}
+
+// Los colores se guardan como `[r, g, b, a]`, los mismos cuatro bytes que `Color32`.
+fn color_to_json(color: Color32) -> Value {
+    Value::Array(color.to_array().iter().map(|channel| Value::Number(channel.to_string())).collect())
+}
+
+fn color_from_json(value: &Value) -> Option<Color32> {
+    let Value::Array(channels) = value else {
+        return None;
+    };
+    let channels: Vec<u8> = channels
+        .iter()
+        .map(|channel| match channel {
+            Value::Number(number) => number.parse().ok(),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b, a]: [u8; 4] = channels.try_into().ok()?;
+    Some(Color32::from_rgba_premultiplied(r, g, b, a))
+}
@@ -0,0 +1,25 @@
+use std::ops::Range;
+use std::time::Duration;
+
+/// Se valida tras esta pausa al escribir, no con cada tecla.
+pub const VALIDATION_DELAY: Duration = Duration::from_millis(300);
+
+/// Primer error de sintaxis de un documento TOML. `range` es la línea entera donde está, sin el salto de línea.
+pub struct TomlError {
+    pub line: usize,
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+pub fn validate(content: &str) -> Option<TomlError> {
+    let error = toml::from_str::<toml::Value>(content).err()?;
+    // Sin posición, el error se marca en la última línea.
+    let at = error.span().map_or(content.len(), |span| span.start.min(content.len()));
+    let start = content[..at].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[at..].find('\n').map_or(content.len(), |i| at + i);
+    Some(TomlError {
+        line: content[..start].matches('\n').count(),
+        range: start..end,
+        message: error.message().trim().replace('\n', "; "),
+    })
+}
@@ -0,0 +1,231 @@
+use std::ops::Range;
+use std::time::Instant;
+
+use crate::text;
+
+// Longitud máxima del extracto que se muestra en la lista de cambios.
+const LABEL_PREVIEW_CHARS: usize = 40;
+
+pub enum ChangeKind {
+    Insertion,
+    // El texto borrado ya no está en `content`; se conserva aquí hasta aceptar o rechazar el cambio.
+    Deletion { text: String },
+}
+
+pub struct TrackedChange {
+    pub id: usize,
+    pub kind: ChangeKind,
+    // Inserción: rango del texto insertado en `content`. Borrado: rango vacío en el punto donde estaba el texto.
+    pub range: Range<usize>,
+    pub author: String,
+    pub timestamp: Instant,
+}
+
+impl TrackedChange {
+    pub fn label(&self, content: &str) -> String {
+        let (verb, text) = match &self.kind {
+            ChangeKind::Insertion => ("Inserted", content.get(self.range.clone()).unwrap_or("")),
+            ChangeKind::Deletion { text } => ("Deleted", text.as_str()),
+        };
+        let mut preview: String = text.chars().take(LABEL_PREVIEW_CHARS).collect();
+        if text.chars().count() > LABEL_PREVIEW_CHARS {
+            preview.push('…');
+        }
+        format!(
+            "{} '{}' — {} ({}s ago)",
+            verb,
+            preview.replace('\n', "⏎"),
+            self.author,
+            self.timestamp.elapsed().as_secs()
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct ChangeSet {
+    pub changes: Vec<TrackedChange>,
+    next_id: usize,
+    // Texto con el que cuadran los rangos; `None` hasta la primera sincronización.
+    synced: Option<String>,
+}
+
+impl ChangeSet {
+    fn push(&mut self, kind: ChangeKind, range: Range<usize>, author: &str) {
+        self.changes.push(TrackedChange {
+            id: self.next_id,
+            kind,
+            range,
+            author: author.to_string(),
+            timestamp: Instant::now(),
+        });
+        self.next_id += 1;
+    }
+
+    /// Registra la edición que convirtió `before` en `after`. Borrar texto que ya era una inserción pendiente
+    /// simplemente la recorta; el resto se guarda como borrado para poder mostrarlo tachado.
+    pub fn record_edit(&mut self, before: &str, after: &str, author: &str) {
+        let (start, removed_end, inserted_end) = text::changed_range(before, after);
+        if removed_end > start {
+            self.record_removal(before, start..removed_end, author);
+        }
+        if inserted_end > start {
+            self.record_insertion(start, inserted_end - start, author);
+        }
+        self.changes.retain(|change| match change.kind {
+            ChangeKind::Insertion => !change.range.is_empty(),
+            ChangeKind::Deletion { ref text } => !text.is_empty(),
+        });
+        self.synced = Some(after.to_string());
+    }
+
+    /// Registra como cambio lo que haya pasado `content` por otro camino que el teclado (refactorizaciones,
+    /// deshacer, fragmentos, pegado en columna…) desde la última vez, para que los rangos sigan apuntando a su texto.
+    pub fn sync(&mut self, content: &str, author: &str) {
+        match self.synced.take() {
+            Some(previous) if previous != content => self.record_edit(&previous, content, author),
+            _ => self.synced = Some(content.to_string()),
+        }
+    }
+
+    /// Si `content` ya no es el texto con el que cuadran los rangos.
+    pub fn is_stale(&self, content: &str) -> bool {
+        self.synced.as_deref().is_some_and(|synced| synced != content)
+    }
+
+    fn record_removal(&mut self, before: &str, removed: Range<usize>, author: &str) {
+        // Partes del rango borrado que no pertenecían a inserciones pendientes.
+        let mut untracked = String::new();
+        let mut pos = removed.start;
+        let mut insertions: Vec<Range<usize>> = self
+            .changes
+            .iter()
+            .filter(|change| matches!(change.kind, ChangeKind::Insertion))
+            .map(|change| change.range.clone())
+            .collect();
+        insertions.sort_by_key(|range| range.start);
+        for range in insertions {
+            if range.end <= pos || range.start >= removed.end {
+                continue;
+            }
+            if range.start > pos {
+                untracked.push_str(&before[pos..range.start]);
+            }
+            pos = range.end.min(removed.end);
+        }
+        if pos < removed.end {
+            untracked.push_str(&before[pos..removed.end]);
+        }
+
+        let len = removed.len();
+        let shift = |offset: usize| {
+            if offset >= removed.end {
+                offset - len
+            } else {
+                offset.min(removed.start)
+            }
+        };
+        // Borrados consecutivos (Backspace o Supr repetidos) se fusionan con el borrado adyacente.
+        let mut merged = untracked.is_empty();
+        for change in &mut self.changes {
+            let original = change.range.start;
+            change.range = shift(change.range.start)..shift(change.range.end);
+            if let ChangeKind::Deletion { text } = &mut change.kind {
+                if !merged && change.author == author {
+                    if original == removed.end {
+                        text.insert_str(0, &untracked);
+                        merged = true;
+                    } else if original == removed.start {
+                        text.push_str(&untracked);
+                        merged = true;
+                    }
+                }
+            }
+        }
+        if !merged {
+            self.push(ChangeKind::Deletion { text: untracked }, removed.start..removed.start, author);
+        }
+    }
+
+    fn record_insertion(&mut self, at: usize, len: usize, author: &str) {
+        let mut extended = false;
+        for change in &mut self.changes {
+            match change.kind {
+                ChangeKind::Insertion if !extended && change.author == author && change.range.start < at && at <= change.range.end => {
+                    change.range.end += len;
+                    extended = true;
+                }
+                _ => {
+                    if change.range.start > at || (change.range.start == at && matches!(change.kind, ChangeKind::Insertion)) {
+                        change.range.start += len;
+                    }
+                    if change.range.end > at || (change.range.end == at && matches!(change.kind, ChangeKind::Insertion)) {
+                        change.range.end += len;
+                    }
+                }
+            }
+        }
+        if !extended {
+            self.push(ChangeKind::Insertion, at..at + len, author);
+        }
+    }
+
+    fn remove(&mut self, change_id: usize) -> Option<TrackedChange> {
+        let index = self.changes.iter().position(|change| change.id == change_id)?;
+        Some(self.changes.remove(index))
+    }
+
+    // Desplaza los cambios restantes tras modificar `content` en `at` (`delta` bytes añadidos o quitados).
+    fn shift_after(&mut self, at: usize, delta: isize) {
+        let shift = |offset: usize| offset.saturating_add_signed(delta);
+        for change in &mut self.changes {
+            // Un rango que termina justo en `at` no debe crecer con el texto restaurado.
+            let end_moves = change.range.end > at || change.range.start >= at;
+            if change.range.start >= at {
+                change.range.start = shift(change.range.start);
+            }
+            if end_moves {
+                change.range.end = shift(change.range.end);
+            }
+        }
+    }
+}
+
+/// Acepta el cambio: la inserción se queda en el texto y el borrado se descarta definitivamente.
+pub fn accept_change(cs: &mut ChangeSet, change_id: usize) {
+    cs.remove(change_id);
+}
+
+/// Rechaza el cambio: quita el texto insertado o vuelve a poner el borrado.
+pub fn reject_change(cs: &mut ChangeSet, content: &mut String, change_id: usize) {
+    let Some(change) = cs.remove(change_id) else {
+        return;
+    };
+    match change.kind {
+        ChangeKind::Insertion => {
+            if content.get(change.range.clone()).is_some() {
+                content.replace_range(change.range.clone(), "");
+                cs.shift_after(change.range.end, -(change.range.len() as isize));
+            }
+        }
+        ChangeKind::Deletion { text } => {
+            if content.is_char_boundary(change.range.start) {
+                content.insert_str(change.range.start, &text);
+                cs.shift_after(change.range.start, text.len() as isize);
+            }
+        }
+    }
+    cs.synced = Some(content.clone());
+}
+
+pub fn accept_all(cs: &mut ChangeSet) {
+    cs.changes.clear();
+}
+
+pub fn reject_all(cs: &mut ChangeSet, content: &mut String) {
+    // De atrás hacia delante para que los offsets de los cambios pendientes sigan siendo válidos.
+    let mut ids: Vec<(usize, usize)> = cs.changes.iter().map(|change| (change.range.start, change.id)).collect();
+    ids.sort();
+    for (_, id) in ids.into_iter().rev() {
+        reject_change(cs, content, id);
+    }
+}
@@ -0,0 +1,238 @@
+use eframe::egui;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::json;
+
+pub const TODO_VALUE: &str = "TODO: translate";
+
+const MISSING_COLOR: egui::Color32 = egui::Color32::from_rgb(250, 230, 120);
+const EXTRA_COLOR: egui::Color32 = egui::Color32::from_rgb(245, 150, 150);
+
+#[derive(Clone, Copy, PartialEq)]
+enum LocaleFormat {
+    Json,
+    Toml,
+}
+
+pub struct LocaleFile {
+    pub path: PathBuf,
+    format: LocaleFormat,
+    // Claves aplanadas con puntos (`menu.file.open`) y su valor como texto.
+    pub entries: Vec<(String, String)>,
+}
+
+impl LocaleFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let format = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => LocaleFormat::Json,
+            Some("toml") => LocaleFormat::Toml,
+            _ => return Err(format!("{} is not a JSON or TOML file", path.display())),
+        };
+        let content = fs::read_to_string(path).map_err(|e| format!("Unable to read file: {}", e))?;
+        let mut entries = Vec::new();
+        match format {
+            LocaleFormat::Json => {
+                let value = json::parse(&content).map_err(|e| e.to_string())?;
+                flatten_json(&value, "", &mut entries);
+            }
+            LocaleFormat::Toml => {
+                let table = content.parse::<toml::Table>().map_err(|e| e.to_string())?;
+                flatten_toml(&table, "", &mut entries);
+            }
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            entries,
+        })
+    }
+
+    fn value(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Añade `keys` al archivo en disco con el valor `TODO_VALUE`, creando los objetos/tablas intermedios.
+    pub fn add_keys(&self, keys: &[&str]) -> Result<(), String> {
+        let content = fs::read_to_string(&self.path).map_err(|e| format!("Unable to read file: {}", e))?;
+        let updated = match self.format {
+            LocaleFormat::Json => {
+                let mut value = json::parse(&content).map_err(|e| e.to_string())?;
+                for key in keys {
+                    insert_json(&mut value, &key.split('.').collect::<Vec<_>>());
+                }
+                value.to_pretty_string("  ") + "\n"
+            }
+            LocaleFormat::Toml => {
+                let mut table = content.parse::<toml::Table>().map_err(|e| e.to_string())?;
+                for key in keys {
+                    insert_toml(&mut table, &key.split('.').collect::<Vec<_>>());
+                }
+                toml::to_string_pretty(&table).map_err(|e| e.to_string())?
+            }
+        };
+        fs::write(&self.path, updated).map_err(|e| format!("Unable to save file: {}", e))
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn flatten_json(value: &json::Value, prefix: &str, out: &mut Vec<(String, String)>) {
+    match value {
+        json::Value::Object(entries) => {
+            for (key, value) in entries {
+                flatten_json(value, &join_key(prefix, key), out);
+            }
+        }
+        json::Value::String(s) => out.push((prefix.to_string(), s.clone())),
+        other => out.push((prefix.to_string(), other.to_compact_string())),
+    }
+}
+
+fn flatten_toml(table: &toml::Table, prefix: &str, out: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = join_key(prefix, key);
+        match value {
+            toml::Value::Table(inner) => flatten_toml(inner, &key, out),
+            toml::Value::String(s) => out.push((key, s.clone())),
+            other => out.push((key, other.to_string())),
+        }
+    }
+}
+
+fn insert_json(value: &mut json::Value, path: &[&str]) {
+    let json::Value::Object(entries) = value else {
+        return;
+    };
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    let index = match entries.iter().position(|(k, _)| k == first) {
+        Some(index) => index,
+        None => {
+            let new_value = if rest.is_empty() {
+                json::Value::String(TODO_VALUE.to_string())
+            } else {
+                json::Value::Object(Vec::new())
+            };
+            entries.push((first.to_string(), new_value));
+            entries.len() - 1
+        }
+    };
+    if !rest.is_empty() {
+        insert_json(&mut entries[index].1, rest);
+    }
+}
+
+fn insert_toml(table: &mut toml::Table, path: &[&str]) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        table
+            .entry(first.to_string())
+            .or_insert_with(|| toml::Value::String(TODO_VALUE.to_string()));
+        return;
+    }
+    let entry = table
+        .entry(first.to_string())
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(inner) = entry {
+        insert_toml(inner, rest);
+    }
+}
+
+pub struct TranslationView {
+    pub reference: LocaleFile,
+    pub target: LocaleFile,
+    pub error: Option<String>,
+}
+
+impl TranslationView {
+    fn missing_keys(&self) -> Vec<&str> {
+        self.reference
+            .entries
+            .iter()
+            .filter(|(key, _)| self.target.value(key).is_none())
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    /// Dibuja ambas traducciones en una sola rejilla para que el desplazamiento vertical vaya sincronizado.
+    /// Devuelve `false` cuando el usuario cierra la ventana.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        let mut copy_missing = false;
+        let title = format!(
+            "Translation — {} ↔ {}",
+            file_name(&self.reference.path),
+            file_name(&self.target.path)
+        );
+
+        egui::Window::new(title)
+            .id(egui::Id::new("translation_view"))
+            .open(&mut open)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                let missing = self.missing_keys();
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} missing keys", missing.len()));
+                    if ui.add_enabled(!missing.is_empty(), egui::Button::new("Copy Missing Keys")).clicked() {
+                        copy_missing = true;
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 50, 50), error);
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                    egui::Grid::new("translation_grid").striped(true).num_columns(3).show(ui, |ui| {
+                        ui.strong("Key");
+                        ui.strong(file_name(&self.reference.path));
+                        ui.strong(file_name(&self.target.path));
+                        ui.end_row();
+
+                        for (key, reference_value) in &self.reference.entries {
+                            let target_value = self.target.value(key);
+                            let key_text = egui::RichText::new(key).monospace();
+                            ui.label(if target_value.is_none() { key_text.background_color(MISSING_COLOR) } else { key_text });
+                            ui.label(reference_value);
+                            ui.label(target_value.unwrap_or("—"));
+                            ui.end_row();
+                        }
+                        for (key, target_value) in &self.target.entries {
+                            if self.reference.value(key).is_none() {
+                                ui.label(egui::RichText::new(key).monospace().background_color(EXTRA_COLOR));
+                                ui.label("—");
+                                ui.label(target_value);
+                                ui.end_row();
+                            }
+                        }
+                    });
+                });
+            });
+
+        if copy_missing {
+            let missing: Vec<String> = self.missing_keys().into_iter().map(str::to_string).collect();
+            let keys: Vec<&str> = missing.iter().map(String::as_str).collect();
+            self.error = self.target.add_keys(&keys).err();
+            match LocaleFile::load(&self.target.path) {
+                Ok(target) => self.target = target,
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        open
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
@@ -0,0 +1,106 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const WPM_WINDOW: Duration = Duration::from_secs(60);
+const GRAPH_SECONDS: usize = 300;
+const DISPLAY_INTERVAL: Duration = Duration::from_secs(5);
+// Convención habitual: una "palabra" son cinco caracteres.
+const CHARS_PER_WORD: f32 = 5.0;
+
+pub struct TypingStats {
+    // Caracteres tecleados en el último minuto.
+    events: VecDeque<(Instant, usize)>,
+    // Caracteres escritos en cada segundo de los últimos cinco minutos; el último es el segundo actual.
+    per_second: VecDeque<usize>,
+    current_second: Instant,
+    session_chars: usize,
+    peak_wpm: f32,
+    displayed_wpm: f32,
+    last_display: Instant,
+}
+
+impl TypingStats {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            events: VecDeque::new(),
+            per_second: VecDeque::from(vec![0; GRAPH_SECONDS]),
+            current_second: now,
+            session_chars: 0,
+            peak_wpm: 0.0,
+            displayed_wpm: 0.0,
+            last_display: now,
+        }
+    }
+
+    pub fn record(&mut self, typed: usize) {
+        self.tick();
+        self.events.push_back((Instant::now(), typed));
+        self.session_chars += typed;
+        if let Some(last) = self.per_second.back_mut() {
+            *last += typed;
+        }
+        self.peak_wpm = self.peak_wpm.max(self.wpm());
+    }
+
+    /// Avanza los cubos por segundo, descarta eventos viejos y refresca cada 5 s el valor de la barra de estado.
+    pub fn tick(&mut self) {
+        while self.current_second.elapsed() >= Duration::from_secs(1) {
+            self.current_second += Duration::from_secs(1);
+            self.per_second.push_back(0);
+            if self.per_second.len() > GRAPH_SECONDS {
+                self.per_second.pop_front();
+            }
+        }
+        while self.events.front().is_some_and(|(at, _)| at.elapsed() > WPM_WINDOW) {
+            self.events.pop_front();
+        }
+        if self.last_display.elapsed() >= DISPLAY_INTERVAL {
+            self.displayed_wpm = self.wpm();
+            self.last_display = Instant::now();
+        }
+    }
+
+    pub fn cpm(&self) -> f32 {
+        self.events.iter().map(|(_, typed)| *typed as f32).sum()
+    }
+
+    pub fn wpm(&self) -> f32 {
+        self.cpm() / CHARS_PER_WORD
+    }
+
+    pub fn displayed_wpm(&self) -> f32 {
+        self.displayed_wpm
+    }
+
+    /// Ventana con una barra por segundo de los últimos cinco minutos, el pico de WPM y el total de la sesión.
+    /// Devuelve `false` cuando el usuario la cierra.
+    pub fn show_window(&self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        egui::Window::new("Typing Statistics").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(format!("Current: {:.0} WPM ({:.0} CPM)", self.wpm(), self.cpm()));
+            ui.label(format!("Session peak: {:.0} WPM", self.peak_wpm));
+            ui.label(format!("Characters typed this session: {}", self.session_chars));
+            ui.separator();
+
+            let bar_width = 2.0;
+            let height = 80.0;
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(bar_width * GRAPH_SECONDS as f32, height), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+            let max = self.per_second.iter().copied().max().unwrap_or(0).max(1) as f32;
+            for (i, &chars) in self.per_second.iter().enumerate() {
+                if chars == 0 {
+                    continue;
+                }
+                let bar_height = height * chars as f32 / max;
+                let x = rect.left() + i as f32 * bar_width;
+                let bar = egui::Rect::from_min_max(egui::pos2(x, rect.bottom() - bar_height), egui::pos2(x + bar_width, rect.bottom()));
+                painter.rect_filled(bar, 0.0, ui.visuals().selection.bg_fill);
+            }
+            ui.weak("Last 5 minutes, one bar per second");
+        });
+        open
+    }
+}
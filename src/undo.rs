@@ -0,0 +1,298 @@
+use std::ops::Range;
+use std::time::Instant;
+
+use crate::text;
+
+// Longitud máxima del extracto de la línea que se muestra en el historial.
+const LABEL_PREVIEW_CHARS: usize = 40;
+
+pub enum UndoEntry {
+    DeletedLine {
+        content: String,
+        line_index: usize,
+        timestamp: Instant,
+    },
+    DuplicatedLines {
+        range: Range<usize>,
+        line_index: usize,
+        line_count: usize,
+        timestamp: Instant,
+    },
+    // Renombrado en línea: `ranges` son las apariciones de `to` tras confirmarlo.
+    Renamed {
+        from: String,
+        to: String,
+        ranges: Vec<Range<usize>>,
+        timestamp: Instant,
+    },
+}
+
+impl UndoEntry {
+    pub fn label(&self) -> String {
+        match self {
+            UndoEntry::DeletedLine { content, line_index, timestamp } => {
+                let mut preview: String = content.trim_end_matches('\n').chars().take(LABEL_PREVIEW_CHARS).collect();
+                if content.trim_end_matches('\n').chars().count() > LABEL_PREVIEW_CHARS {
+                    preview.push('…');
+                }
+                format!(
+                    "Deleted line {}: '{}' ({}s ago)",
+                    line_index + 1,
+                    preview,
+                    timestamp.elapsed().as_secs()
+                )
+            }
+            UndoEntry::DuplicatedLines { line_index, line_count, timestamp, .. } => format!(
+                "Duplicated {} line(s) at line {} ({}s ago)",
+                line_count,
+                line_index + 1,
+                timestamp.elapsed().as_secs()
+            ),
+            UndoEntry::Renamed { from, to, ranges, timestamp } => format!(
+                "Renamed '{}' to '{}' ({} occurrences, {}s ago)",
+                from,
+                to,
+                ranges.len(),
+                timestamp.elapsed().as_secs()
+            ),
+        }
+    }
+
+    /// Deshace esta entrada sobre `content` y devuelve el offset en bytes donde quedó el texto restaurado.
+    pub fn restore(&self, content: &mut String) -> usize {
+        match self {
+            UndoEntry::DeletedLine { content: line, line_index, .. } => {
+                let offset = line_offset(content, *line_index);
+                if offset == content.len() && !content.is_empty() && !content.ends_with('\n') {
+                    content.push('\n');
+                    let offset = content.len();
+                    content.push_str(line.trim_end_matches('\n'));
+                    return offset;
+                }
+                content.insert_str(offset, line);
+                offset
+            }
+            UndoEntry::DuplicatedLines { range, .. } => {
+                if content.get(range.clone()).is_some() {
+                    content.drain(range.clone());
+                }
+                range.start.min(content.len())
+            }
+            UndoEntry::Renamed { from, to, ranges, .. } => {
+                // De atrás hacia delante para que los rangos anteriores sigan siendo válidos; se saltan los que ya no dicen `to`.
+                for range in ranges.iter().rev() {
+                    if content.get(range.clone()) == Some(to.as_str()) {
+                        content.replace_range(range.clone(), from);
+                    }
+                }
+                ranges.first().map_or(0, |range| range.start.min(content.len()))
+            }
+        }
+    }
+}
+
+/// Borra la línea que contiene `byte_index` (con su salto de línea) y devuelve la entrada para deshacerlo.
+pub fn delete_line(content: &mut String, byte_index: usize) -> Option<UndoEntry> {
+    if content.is_empty() {
+        return None;
+    }
+    let byte_index = byte_index.min(content.len());
+    let start = content[..byte_index].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = content[byte_index..].find('\n').map_or(content.len(), |i| byte_index + i + 1);
+    let line_index = content[..start].matches('\n').count();
+
+    let removed: String = content.drain(start..end).collect();
+    Some(UndoEntry::DeletedLine {
+        content: removed,
+        line_index,
+        timestamp: Instant::now(),
+    })
+}
+
+/// Entrada que deshace una duplicación de líneas: el texto insertado entre `before` y `after`.
+pub fn duplicated_lines(before: &str, after: &str) -> Option<UndoEntry> {
+    let (start, _, end) = text::changed_range(before, after);
+    (end > start).then(|| UndoEntry::DuplicatedLines {
+        range: start..end,
+        line_index: after[..start].matches('\n').count(),
+        line_count: after[start..end].matches('\n').count(),
+        timestamp: Instant::now(),
+    })
+}
+
+pub fn renamed(from: &str, to: &str, ranges: Vec<Range<usize>>) -> UndoEntry {
+    UndoEntry::Renamed { from: from.to_string(), to: to.to_string(), ranges, timestamp: Instant::now() }
+}
+
+/// Detecta si el paso de `before` a `after` eliminó exactamente una línea completa (p. ej. Backspace al inicio de una línea vacía).
+pub fn detect_deleted_line(before: &str, after: &str) -> Option<UndoEntry> {
+    if after.len() >= before.len() {
+        return None;
+    }
+    let (start, end, _) = text::changed_range(before, after);
+    let removed = &before[start..end];
+    let at_line_start = start == 0 || before.as_bytes()[start - 1] == b'\n';
+    if !at_line_start || !removed.ends_with('\n') || removed[..removed.len() - 1].contains('\n') {
+        return None;
+    }
+
+    Some(UndoEntry::DeletedLine {
+        content: removed.to_string(),
+        line_index: before[..start].matches('\n').count(),
+        timestamp: Instant::now(),
+    })
+}
+
+/// Un estado del texto en el árbol de deshacer. `children` va del más antiguo al más reciente.
+pub struct UndoNode {
+    pub content: String,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub timestamp: Instant,
+}
+
+/// Historial de deshacer en árbol, como el de Vim: editar tras deshacer abre una rama nueva en lugar de descartar lo
+/// que se podía rehacer. `current` es el último estado guardado por el que ha pasado el texto; lo escrito después
+/// aún no está en el árbol y se añade como hijo de `current` al deshacer o saltar a otro estado.
+#[derive(Default)]
+pub struct UndoTree {
+    pub nodes: Vec<UndoNode>,
+    pub current: usize,
+}
+
+impl UndoTree {
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.current = 0;
+    }
+
+    /// Guarda `before`, el texto antes de una racha de edición, como estado al que se puede volver.
+    pub fn record(&mut self, before: String) {
+        if self.nodes.get(self.current).is_some_and(|node| node.content == before) {
+            return;
+        }
+        self.add_child(before);
+    }
+
+    /// Vuelve al estado anterior a `live`: a `current` si hay cambios sin guardar en el árbol, o a su padre.
+    pub fn undo(&mut self, live: &str) -> Option<String> {
+        let node = self.nodes.get(self.current)?;
+        if node.content != live {
+            let target = self.current;
+            self.add_child(live.to_string());
+            self.current = target;
+        } else {
+            self.current = node.parent?;
+        }
+        Some(self.nodes[self.current].content.clone())
+    }
+
+    /// Avanza por la rama más reciente; con cambios sin guardar no hay nada que rehacer, ya son una rama nueva.
+    pub fn redo(&mut self, live: &str) -> Option<String> {
+        let node = self.nodes.get(self.current).filter(|node| node.content == live)?;
+        self.current = *node.children.last()?;
+        Some(self.nodes[self.current].content.clone())
+    }
+
+    /// Salta a `target` en cualquier rama; lo que hubiera sin guardar en el árbol se conserva como rama propia.
+    pub fn jump(&mut self, target: usize, live: &str) -> Option<String> {
+        if target >= self.nodes.len() {
+            return None;
+        }
+        if self.nodes[self.current].content != live {
+            self.add_child(live.to_string());
+        }
+        self.current = target;
+        Some(self.nodes[target].content.clone())
+    }
+
+    /// Deja a lo sumo `max` estados quitando primero los más antiguos: hojas de otras ramas o la raíz cuando solo
+    /// tiene un hijo. Nunca se quita `current` ni un estado del que cuelguen dos ramas.
+    pub fn truncate(&mut self, max: usize) {
+        while self.nodes.len() > max.max(1) {
+            let removable = (0..self.nodes.len()).find(|&index| {
+                let node = &self.nodes[index];
+                index != self.current && (node.children.is_empty() || (node.parent.is_none() && node.children.len() == 1))
+            });
+            match removable {
+                Some(index) => self.remove(index),
+                None => break,
+            }
+        }
+    }
+
+    /// Filas para dibujar el árbol de arriba abajo como `(profundidad, nodo)`: la rama más reciente sigue en la misma
+    /// columna y las anteriores se sangran un nivel bajo el estado del que salen.
+    pub fn rows(&self) -> Vec<(usize, usize)> {
+        let mut rows = Vec::new();
+        for root in (0..self.nodes.len()).filter(|&index| self.nodes[index].parent.is_none()) {
+            self.push_rows(root, 0, &mut rows);
+        }
+        rows
+    }
+
+    fn push_rows(&self, mut index: usize, depth: usize, rows: &mut Vec<(usize, usize)>) {
+        loop {
+            rows.push((depth, index));
+            let Some((&newest, older)) = self.nodes[index].children.split_last() else {
+                return;
+            };
+            for &child in older {
+                self.push_rows(child, depth + 1, rows);
+            }
+            index = newest;
+        }
+    }
+
+    /// Texto de un estado en el historial: la línea donde difiere de su padre y hace cuánto se guardó.
+    pub fn label(&self, index: usize) -> String {
+        let node = &self.nodes[index];
+        let ago = node.timestamp.elapsed().as_secs();
+        let Some(parent) = node.parent else {
+            return format!("Oldest state ({}s ago)", ago);
+        };
+        let (start, _, _) = text::changed_range(&self.nodes[parent].content, &node.content);
+        let line = node.content[..start].matches('\n').count() + 1;
+        let branches = if node.children.len() > 1 { format!(" ⎇ {} branches", node.children.len()) } else { String::new() };
+        format!("Edit at line {} ({}s ago){}", line, ago, branches)
+    }
+
+    fn add_child(&mut self, content: String) {
+        let index = self.nodes.len();
+        let parent = (index > 0).then_some(self.current);
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+        self.nodes.push(UndoNode { content, parent, children: Vec::new(), timestamp: Instant::now() });
+        self.current = index;
+    }
+
+    // Quita el nodo y corrige los índices de los que iban detrás; una raíz quitada deja a su hijo como raíz.
+    fn remove(&mut self, index: usize) {
+        let node = self.nodes.remove(index);
+        if let Some(parent) = node.parent {
+            self.nodes[if parent > index { parent - 1 } else { parent }].children.retain(|&child| child != index);
+        }
+        for &child in &node.children {
+            self.nodes[if child > index { child - 1 } else { child }].parent = None;
+        }
+        let shift = |i: usize| if i > index { i - 1 } else { i };
+        for node in &mut self.nodes {
+            node.parent = node.parent.map(shift);
+            for child in &mut node.children {
+                *child = shift(*child);
+            }
+        }
+        self.current = shift(self.current);
+    }
+}
+
+pub fn line_offset(content: &str, line_index: usize) -> usize {
+    if line_index == 0 {
+        return 0;
+    }
+    content
+        .match_indices('\n')
+        .nth(line_index - 1)
+        .map_or(content.len(), |(i, _)| i + 1)
+}
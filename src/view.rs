@@ -0,0 +1,24 @@
+use eframe::egui;
+
+use crate::text::byte_to_char;
+
+/// Rectángulo en pantalla del carácter que empieza en `byte_index`, según la galería del TextEdit.
+pub fn byte_rect(galley: &egui::Galley, origin: egui::Pos2, content: &str, byte_index: usize) -> egui::Rect {
+    let cursor = galley.from_ccursor(egui::text::CCursor::new(byte_to_char(content, byte_index)));
+    galley.pos_from_cursor(&cursor).translate(origin.to_vec2())
+}
+
+// Cuánto se aparta el fondo del margen de números del fondo del editor.
+const GUTTER_SHADE: f32 = 0.1;
+
+/// Fondo del margen de números: un 10 % más oscuro que `background`, o más claro si el fondo ya es oscuro.
+pub fn gutter_color(background: egui::Color32) -> egui::Color32 {
+    let [r, g, b, a] = background.to_array();
+    let dark = (u32::from(r) + u32::from(g) + u32::from(b)) < 3 * 128;
+    let shade = |channel: u8| {
+        let channel = f32::from(channel);
+        let shaded = if dark { channel + (255.0 - channel) * GUTTER_SHADE } else { channel * (1.0 - GUTTER_SHADE) };
+        shaded.round() as u8
+    };
+    egui::Color32::from_rgba_premultiplied(shade(r), shade(g), shade(b), a)
+}
@@ -0,0 +1,35 @@
+use eframe::egui;
+
+/// Dónde se dibuja un texto virtual respecto a su línea.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VtPosition {
+    /// Detrás del final de la línea, o de la columna `col` si se indica.
+    InlineAfterEol,
+    /// Pegado por la izquierda al primer carácter que no es espacio (o a `col`), encima de la sangría.
+    InlineBeforeContent,
+    /// En letra pequeña sobre la línea, alineado con su contenido (o con `col`).
+    AboveLine,
+}
+
+/// Texto que se pinta sobre el editor sin formar parte del contenido: no se selecciona, no se copia y no mueve el
+/// texto real. `line` y `col` cuentan desde 0, y `col` va en caracteres.
+#[derive(Clone)]
+pub struct VirtualText {
+    pub line: usize,
+    pub col: Option<usize>,
+    pub text: String,
+    pub color: egui::Color32,
+    pub position: VtPosition,
+}
+
+/// Byte de `content` en el que se apoya `virtual_text`, o `None` si su línea ya no existe.
+pub fn anchor_byte(content: &str, line_offsets: &[usize], virtual_text: &VirtualText) -> Option<usize> {
+    let start = *line_offsets.get(virtual_text.line)?;
+    let line = content.get(start..)?.split('\n').next().unwrap_or("");
+    let column = match (virtual_text.col, virtual_text.position) {
+        (Some(col), _) => line.char_indices().nth(col).map_or(line.len(), |(byte, _)| byte),
+        (None, VtPosition::InlineAfterEol) => line.len(),
+        (None, _) => line.len() - line.trim_start().len(),
+    };
+    Some(start + column)
+}
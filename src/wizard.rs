@@ -0,0 +1,233 @@
+use eframe::egui;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::templates::{self, FileTemplate, TemplateVars};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WizardStep {
+    Name,
+    Template,
+    Content,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InitialContent {
+    Blank,
+    Template,
+    Clipboard,
+}
+
+pub enum WizardAction {
+    Create,
+    Cancel,
+}
+
+const MAX_SUGGESTIONS: usize = 8;
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*', '\\'];
+
+pub struct NewFileWizard {
+    pub step: WizardStep,
+    pub name: String,
+    pub initial: InitialContent,
+    pub error: Option<String>,
+    // Directorio abierto, para autocompletar nombres existentes.
+    dir: Option<PathBuf>,
+    suggestions: Vec<String>,
+    // Valor de `{license}` en la plantilla.
+    license: String,
+    // Plantilla ya sustituida para el nombre que la acompaña; `{author}` llama a git, así que no se rehace en cada fotograma.
+    preview: Option<(String, String)>,
+}
+
+/// Comprueba un nombre relativo como `src/nuevo_modulo.rs`: sin caracteres prohibidos, sin `..` ni rutas absolutas.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("The file name is empty".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| ILLEGAL_CHARS.contains(c) || c.is_control()) {
+        return Err(format!("'{}' is not allowed in file names", c.escape_default()));
+    }
+    if name.starts_with('/') {
+        return Err("Use a path relative to the open directory".to_string());
+    }
+    if name.ends_with('/') {
+        return Err("The name ends in '/': add a file name".to_string());
+    }
+    if name.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+        return Err("Empty, '.' and '..' path segments are not allowed".to_string());
+    }
+    Ok(())
+}
+
+impl NewFileWizard {
+    pub fn new(dir: Option<PathBuf>, license: String) -> Self {
+        let mut wizard = Self {
+            step: WizardStep::Name,
+            name: String::new(),
+            initial: InitialContent::Blank,
+            error: None,
+            dir,
+            suggestions: Vec::new(),
+            license,
+            preview: None,
+        };
+        wizard.update_suggestions();
+        wizard
+    }
+
+    // Entradas del subdirectorio escrito cuyo nombre empieza por lo último que se ha tecleado; los directorios acaban en `/`.
+    fn update_suggestions(&mut self) {
+        self.suggestions.clear();
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let (parent, prefix) = self.name.rsplit_once('/').unwrap_or(("", self.name.as_str()));
+        let Ok(entries) = fs::read_dir(dir.join(parent)) else {
+            return;
+        };
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_str()?.to_string();
+                if !name.starts_with(prefix) || name == prefix {
+                    return None;
+                }
+                let slash = if entry.path().is_dir() { "/" } else { "" };
+                Some(if parent.is_empty() { format!("{}{}", name, slash) } else { format!("{}/{}{}", parent, name, slash) })
+            })
+            .collect();
+        names.sort();
+        names.truncate(MAX_SUGGESTIONS);
+        self.suggestions = names;
+    }
+
+    pub fn template(&self) -> Option<FileTemplate> {
+        let extension = Path::new(self.name.trim()).extension()?.to_str()?;
+        templates::template_for_extension(extension)
+    }
+
+    /// Variables de la plantilla para el archivo que se va a crear.
+    pub fn template_vars(&self) -> TemplateVars {
+        let name = self.name.trim();
+        let path = self.dir.as_deref().map_or_else(|| PathBuf::from(name), |dir| dir.join(name));
+        TemplateVars::for_file(&path, name, &self.license)
+    }
+
+    fn next(&mut self) {
+        self.step = match self.step {
+            WizardStep::Name if self.template().is_some() => WizardStep::Template,
+            WizardStep::Name | WizardStep::Template | WizardStep::Content => WizardStep::Content,
+        };
+    }
+
+    fn back(&mut self) {
+        self.step = match self.step {
+            WizardStep::Content if self.template().is_some() => WizardStep::Template,
+            WizardStep::Name | WizardStep::Template | WizardStep::Content => WizardStep::Name,
+        };
+    }
+
+    /// Dibuja el asistente centrado; Tab/Shift+Tab avanzan o retroceden entre pasos.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<WizardAction> {
+        let mut action = None;
+
+        let (forward, backward) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+                i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab),
+            )
+        });
+        if forward && validate_name(&self.name).is_ok() {
+            self.next();
+        }
+        if backward {
+            self.back();
+        }
+
+        egui::Window::new("New File")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                match self.step {
+                    WizardStep::Name => {
+                        ui.label("Step 1 of 3 — file name (with extension, subdirectories allowed):");
+                        let response = ui.text_edit_singleline(&mut self.name);
+                        response.request_focus();
+                        if response.changed() {
+                            self.update_suggestions();
+                        }
+                        let valid = validate_name(&self.name);
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) && valid.is_ok() {
+                            self.next();
+                        }
+                        match valid {
+                            Err(e) if !self.name.is_empty() => {
+                                ui.colored_label(egui::Color32::from_rgb(220, 50, 50), e);
+                            }
+                            _ => {}
+                        }
+                        let mut picked = None;
+                        for suggestion in &self.suggestions {
+                            if ui.selectable_label(false, egui::RichText::new(suggestion).monospace()).clicked() {
+                                picked = Some(suggestion.clone());
+                            }
+                        }
+                        if let Some(suggestion) = picked {
+                            self.name = suggestion;
+                            self.update_suggestions();
+                        }
+                    }
+                    WizardStep::Template => {
+                        if let Some(template) = self.template() {
+                            ui.label(format!("Step 2 of 3 — {} file detected.", template.language));
+                            let mut use_template = self.initial == InitialContent::Template;
+                            if ui.checkbox(&mut use_template, "Apply template").changed() {
+                                self.initial = if use_template { InitialContent::Template } else { InitialContent::Blank };
+                            }
+                            if self.preview.as_ref().is_none_or(|(name, _)| *name != self.name) {
+                                let preview = templates::substitute_template_vars(template.body, &self.template_vars());
+                                self.preview = Some((self.name.clone(), preview));
+                            }
+                            if let Some((_, preview)) = &self.preview {
+                                ui.label(egui::RichText::new(preview).monospace().weak());
+                            }
+                        }
+                    }
+                    WizardStep::Content => {
+                        ui.label("Step 3 of 3 — initial content:");
+                        ui.radio_value(&mut self.initial, InitialContent::Blank, "Blank");
+                        ui.add_enabled_ui(self.template().is_some(), |ui| {
+                            ui.radio_value(&mut self.initial, InitialContent::Template, "Template");
+                        });
+                        ui.radio_value(&mut self.initial, InitialContent::Clipboard, "Copy from clipboard");
+                    }
+                }
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 50, 50), error);
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.step != WizardStep::Name, egui::Button::new("Back")).clicked() {
+                        self.back();
+                    }
+                    if self.step == WizardStep::Content {
+                        if ui.button("Create").clicked() {
+                            action = Some(WizardAction::Create);
+                        }
+                    } else if ui.add_enabled(validate_name(&self.name).is_ok(), egui::Button::new("Next")).clicked() {
+                        self.next();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = Some(WizardAction::Cancel);
+                    }
+                });
+            });
+
+        action
+    }
+}
@@ -0,0 +1,86 @@
+// YAML sin gramática de tree-sitter: anclas, alias y el comentario del esquema se buscan en el texto, línea a línea.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+// Comentario con el que yaml-language-server asocia un esquema al documento.
+const SCHEMA_MODELINE: &str = "yaml-language-server: $schema=";
+
+/// Alias `*nombre` y el ancla `&nombre` a la que se refiere, como rangos en bytes que incluyen `*` y `&`.
+pub struct YamlLink {
+    pub alias: Range<usize>,
+    pub anchor: Range<usize>,
+}
+
+/// Anclas `&nombre` y alias `*nombre` fuera de comentarios y comillas. `true` marca las anclas.
+pub fn markers(content: &str) -> Vec<(bool, Range<usize>)> {
+    let mut markers = Vec::new();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let mut quote = None;
+        let mut previous = ' ';
+        let mut chars = line.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            match (quote, c) {
+                (Some(open), _) if c == open => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') if is_separator(previous) => quote = Some(c),
+                (None, '#') if previous.is_whitespace() => break,
+                (None, '&' | '*') if is_separator(previous) => {
+                    let mut end = index + 1;
+                    while let Some(&(next, n)) = chars.peek() {
+                        if n.is_whitespace() || ",[]{}".contains(n) {
+                            break;
+                        }
+                        end = next + n.len_utf8();
+                        chars.next();
+                    }
+                    if end > index + 1 {
+                        markers.push((c == '&', line_start + index..line_start + end));
+                    }
+                }
+                _ => {}
+            }
+            previous = c;
+        }
+        line_start += line.len();
+    }
+    markers
+}
+
+// Un ancla, un alias o una cadena entre comillas solo empiezan un valor: tras un espacio, `-`, `:` o un indicador de
+// colección de flujo, no en mitad de un escalar como `a*b`.
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || "[{,".contains(c)
+}
+
+/// Byte donde empieza (el `&` de) cada ancla por nombre; si un nombre se repite vale la última definición.
+pub fn find_yaml_anchors(content: &str) -> HashMap<String, usize> {
+    markers(content)
+        .into_iter()
+        .filter(|(anchor, _)| *anchor)
+        .map(|(_, range)| (content[range.start + 1..range.end].to_string(), range.start))
+        .collect()
+}
+
+/// Cada alias unido a su ancla de `anchors`; los alias sin ancla se omiten.
+pub fn find_yaml_aliases(content: &str, anchors: &HashMap<String, usize>) -> Vec<YamlLink> {
+    markers(content)
+        .into_iter()
+        .filter(|(anchor, _)| !*anchor)
+        .filter_map(|(_, alias)| {
+            let name = &content[alias.start + 1..alias.end];
+            let start = *anchors.get(name)?;
+            Some(YamlLink { anchor: start..start + alias.len(), alias })
+        })
+        .collect()
+}
+
+/// Esquema declarado con `# yaml-language-server: $schema=<url>` en cualquier línea de comentario.
+pub fn schema_reference(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let comment = line.trim_start().strip_prefix('#')?.trim_start();
+        let reference = comment.strip_prefix(SCHEMA_MODELINE)?.trim();
+        (!reference.is_empty()).then(|| reference.to_string())
+    })
+}